@@ -76,6 +76,22 @@ fn convert(cx: &mut AcceptContext<'_, '_>, args: &ArgParser) -> Option<Attribute
     }
 }
 
+pub(crate) struct TestTimeoutParser;
+
+impl SingleAttributeParser for TestTimeoutParser {
+    const PATH: &[Symbol] = &[sym::test_timeout];
+    const ON_DUPLICATE: OnDuplicate = OnDuplicate::Error;
+    const ALLOWED_TARGETS: AllowedTargets =
+        AllowedTargets::AllowListWarnRest(&[Allow(Target::Fn), Error(Target::WherePredicate)]);
+    const TEMPLATE: AttributeTemplate = template!(NameValueStr: "seconds");
+
+    fn convert(cx: &mut AcceptContext<'_, '_>, args: &ArgParser) -> Option<AttributeKind> {
+        let nv = cx.expect_name_value(args, cx.attr_span, Some(sym::test_timeout))?;
+
+        Some(AttributeKind::TestTimeout { seconds: cx.parse_limit_int(nv)? })
+    }
+}
+
 pub(crate) struct ReexportTestHarnessMainParser;
 
 impl SingleAttributeParser for ReexportTestHarnessMainParser {
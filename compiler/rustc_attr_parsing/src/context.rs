@@ -223,6 +223,7 @@ macro_rules! attribute_parsers {
         Single<SanitizeParser>,
         Single<ShouldPanicParser>,
         Single<TestRunnerParser>,
+        Single<TestTimeoutParser>,
         Single<TypeLengthLimitParser>,
         Single<WindowsSubsystemParser>,
         Single<WithoutArgs<AllowInternalUnsafeParser>>,
@@ -348,6 +348,15 @@ pub(crate) fn expand_test_or_bench(
                                         thin_vec![cx.expr_str(sp, sym)],
                                     ),
                                 },),
+                                // timeout_secs: Some(<seconds>) | None
+                                field(
+                                    "timeout_secs",
+                                    if let Some(seconds) = test_timeout(cx, &item) {
+                                        cx.expr_some(sp, cx.expr_u64(sp, seconds))
+                                    } else {
+                                        cx.expr_none(sp)
+                                    },
+                                ),
                                 // test_type: ...
                                 field("test_type", match test_type(cx) {
                                     // test::TestType::UnitTest
@@ -488,6 +497,16 @@ fn should_panic(cx: &ExtCtxt<'_>, i: &ast::Item) -> ShouldPanic {
     }
 }
 
+fn test_timeout(cx: &ExtCtxt<'_>, i: &ast::Item) -> Option<u64> {
+    if let Some(Attribute::Parsed(AttributeKind::TestTimeout { seconds })) =
+        AttributeParser::parse_limited(cx.sess, &i.attrs, &[sym::test_timeout])
+    {
+        Some(seconds.0 as u64)
+    } else {
+        None
+    }
+}
+
 enum TestType {
     UnitTest,
     IntegrationTest,
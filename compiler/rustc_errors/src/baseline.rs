@@ -0,0 +1,143 @@
+//! Diagnostic baselines, which let a project adopt new lints incrementally.
+//!
+//! With `-Z diagnostic-baseline=<file> -Z record-diagnostic-baseline`, every
+//! lint warning that is emitted is appended to the file. Later compilations
+//! with just `-Z diagnostic-baseline=<file>` suppress the warnings found in
+//! the file, so that only new ones are reported.
+//!
+//! Each line of the file records one warning as the name of its lint, the file
+//! of its primary span and a hash of the source text of that span, separated
+//! by tabs. The text is normalized by collapsing whitespace, so the warning is
+//! still recognized when the code around it moves or is reformatted. Every
+//! line suppresses a single warning: if a file has the same warning twice,
+//! both are recorded.
+
+use std::fs::{self, OpenOptions};
+use std::hash::Hash;
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+use rustc_data_structures::fx::FxHashMap;
+use rustc_data_structures::stable_hash::StableHasher;
+use rustc_hashes::Hash128;
+use rustc_span::source_map::SourceMap;
+
+use crate::diagnostic::IsLint;
+use crate::{DiagInner, Level};
+
+#[cfg(test)]
+mod tests;
+
+pub struct DiagnosticBaseline {
+    path: PathBuf,
+    mode: Mode,
+}
+
+enum Mode {
+    /// The number of warnings to suppress for each entry.
+    Suppress(FxHashMap<Entry, usize>),
+    /// The entries to append to the file.
+    Record(Vec<Entry>),
+}
+
+#[derive(PartialEq, Eq, Hash)]
+struct Entry {
+    lint: String,
+    file: String,
+    /// The hash of the normalized source text of the primary span.
+    text_hash: String,
+}
+
+impl DiagnosticBaseline {
+    /// Loads the baseline at `path`, to suppress the warnings it records.
+    pub fn load(path: PathBuf) -> io::Result<DiagnosticBaseline> {
+        let mut entries = FxHashMap::default();
+        for (i, line) in fs::read_to_string(&path)?.lines().enumerate() {
+            if line.is_empty() {
+                continue;
+            }
+            let mut fields = line.split('\t');
+            let (Some(lint), Some(file), Some(text_hash), None) =
+                (fields.next(), fields.next(), fields.next(), fields.next())
+            else {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("line {} is not of the form `<lint>\\t<file>\\t<hash>`", i + 1),
+                ));
+            };
+            let entry = Entry {
+                lint: lint.to_owned(),
+                file: file.to_owned(),
+                text_hash: text_hash.to_owned(),
+            };
+            *entries.entry(entry).or_default() += 1;
+        }
+        Ok(DiagnosticBaseline { path, mode: Mode::Suppress(entries) })
+    }
+
+    /// Creates a baseline that records the warnings emitted, to append them to
+    /// the file at `path` with [`DiagnosticBaseline::finish`].
+    pub fn record(path: PathBuf) -> DiagnosticBaseline {
+        DiagnosticBaseline { path, mode: Mode::Record(Vec::new()) }
+    }
+
+    pub fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    /// Returns whether `diag` is in the baseline, in which case it shouldn't be
+    /// emitted. When recording, records `diag` and returns `false`.
+    pub(crate) fn suppresses(&mut self, diag: &DiagInner, sm: Option<&SourceMap>) -> bool {
+        let Some(entry) = Entry::new(diag, sm) else { return false };
+        match &mut self.mode {
+            Mode::Suppress(entries) => match entries.get_mut(&entry) {
+                Some(count) if *count > 0 => {
+                    *count -= 1;
+                    true
+                }
+                _ => false,
+            },
+            Mode::Record(entries) => {
+                entries.push(entry);
+                false
+            }
+        }
+    }
+
+    /// When recording, appends the recorded warnings to the file.
+    pub(crate) fn finish(&mut self) -> io::Result<()> {
+        let Mode::Record(entries) = &mut self.mode else { return Ok(()) };
+        let mut buf = String::new();
+        for Entry { lint, file, text_hash } in entries.drain(..) {
+            buf.push_str(&format!("{lint}\t{file}\t{text_hash}\n"));
+        }
+        // Append everything at once, so that compilations recording into the
+        // same file at the same time don't interleave their lines.
+        OpenOptions::new().create(true).append(true).open(&self.path)?.write_all(buf.as_bytes())
+    }
+}
+
+impl Entry {
+    /// Returns the entry for `diag`, if it is a lint warning.
+    fn new(diag: &DiagInner, sm: Option<&SourceMap>) -> Option<Entry> {
+        let Some(IsLint { name, .. }) = &diag.is_lint else { return None };
+        if diag.level != Level::Warning {
+            return None;
+        }
+
+        let (file, text) = match (sm, diag.span.primary_span()) {
+            (Some(sm), Some(span)) if !span.is_dummy() => {
+                let file = sm.lookup_source_file(span.lo());
+                let file = sm.filename_for_diagnostics(&file.name).to_string();
+                (file, sm.span_to_snippet(span).unwrap_or_default())
+            }
+            _ => (String::new(), String::new()),
+        };
+        let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        let mut hasher = StableHasher::new();
+        text.hash(&mut hasher);
+        let text_hash: Hash128 = hasher.finish();
+
+        Some(Entry { lint: name.clone(), file, text_hash: format!("{text_hash:x}") })
+    }
+}
@@ -0,0 +1,121 @@
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use rustc_span::source_map::FilePathMapping;
+use rustc_span::{BytePos, FileName, Span};
+
+use super::*;
+use crate::emitter::{ColorConfig, HumanReadableErrorType};
+use crate::json::JsonEmitter;
+use crate::{DiagCtxt, DiagCtxtHandle};
+
+struct Shared<T> {
+    data: Arc<Mutex<T>>,
+}
+
+impl<T: Write> Write for Shared<T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.data.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.data.lock().unwrap().flush()
+    }
+}
+
+fn lint(dcx: DiagCtxtHandle<'_>, name: &str, lo: u32, hi: u32) {
+    let span = Span::with_root_ctxt(BytePos(lo), BytePos(hi));
+    let mut diag = dcx.struct_span_warn(span, format!("{name} warning"));
+    diag.is_lint(name.to_owned(), false);
+    diag.emit();
+}
+
+/// Compiles `code` with `baseline`, emitting the lints from `f`, and returns
+/// the number of diagnostics that were emitted.
+fn emitted(code: &str, baseline: DiagnosticBaseline, f: impl FnOnce(DiagCtxtHandle<'_>)) -> usize {
+    rustc_span::create_default_session_globals_then(|| {
+        let sm = Arc::new(SourceMap::new(FilePathMapping::empty()));
+        let path = PathBuf::from("test.rs");
+        let filename = FileName::Real(sm.path_mapping().to_real_filename(sm.working_dir(), path));
+        sm.new_source_file(filename, code.to_owned());
+
+        let output = Arc::new(Mutex::new(Vec::new()));
+        let je = JsonEmitter::new(
+            Box::new(Shared { data: output.clone() }),
+            Some(sm),
+            false,
+            HumanReadableErrorType { short: true, unicode: false },
+            ColorConfig::Never,
+        );
+        let dcx = DiagCtxt::new(Box::new(je)).with_baseline(baseline);
+        f(dcx.handle());
+        drop(dcx);
+
+        let output = output.lock().unwrap();
+        output.split(|&b| b == b'\n').filter(|line| !line.is_empty()).count()
+    })
+}
+
+fn baseline_file(name: &str) -> PathBuf {
+    let path = std::env::temp_dir()
+        .join(format!("rustc-diagnostic-baseline-{}-{name}", std::process::id()));
+    let _ = fs::remove_file(&path);
+    path
+}
+
+#[test]
+fn record_and_suppress() {
+    let path = baseline_file("record");
+    let code = "fn a() {}\nfn b() {}\nfn b() {}\n";
+
+    let count = emitted(code, DiagnosticBaseline::record(path.clone()), |dcx| {
+        lint(dcx, "dead_code", 3, 4);
+        lint(dcx, "dead_code", 13, 14);
+        lint(dcx, "non_snake_case", 13, 14);
+    });
+    assert_eq!(count, 3);
+    assert_eq!(fs::read_to_string(&path).unwrap().lines().count(), 3);
+
+    // The code moved and was reformatted, and there's a new warning.
+    let code = "\nfn  a() {}\nfn b() {}\nfn b() {}\n";
+    let count = emitted(code, DiagnosticBaseline::load(path.clone()).unwrap(), |dcx| {
+        lint(dcx, "dead_code", 5, 6);
+        lint(dcx, "non_snake_case", 15, 16);
+        lint(dcx, "dead_code", 15, 16);
+        // Only one of the `fn b` was recorded for `dead_code`.
+        lint(dcx, "dead_code", 25, 26);
+    });
+    assert_eq!(count, 1);
+
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn errors_are_not_suppressed() {
+    let path = baseline_file("errors");
+    let code = "fn a() {}\n";
+
+    emitted(code, DiagnosticBaseline::record(path.clone()), |dcx| {
+        lint(dcx, "dead_code", 3, 4);
+    });
+    let count = emitted(code, DiagnosticBaseline::load(path.clone()).unwrap(), |dcx| {
+        let span = Span::with_root_ctxt(BytePos(3), BytePos(4));
+        let mut diag = dcx.struct_span_err(span, "dead_code error");
+        diag.is_lint("dead_code".to_owned(), false);
+        diag.emit();
+    });
+    assert_eq!(count, 1);
+
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn invalid_baseline() {
+    let path = baseline_file("invalid");
+    fs::write(&path, "dead_code\ttest.rs\n").unwrap();
+    let err = DiagnosticBaseline::load(path.clone()).err().unwrap();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    fs::remove_file(&path).unwrap();
+
+    assert!(DiagnosticBaseline::load(Path::new("/nonexistent/baseline").to_owned()).is_err());
+}
@@ -0,0 +1,376 @@
+//! Exporting machine-applicable suggestions as patches.
+//!
+//! With `-Z emit-fixes=<dir>`, the suggestions of the emitted diagnostics that
+//! are [`Applicability::MachineApplicable`] are collected, and a unified diff
+//! is written to the directory for every source file that they change. This
+//! gives the same result as `rustfix`, without having to go through the JSON
+//! output and recompile:
+//!
+//! - Only suggestions with a single alternative are applied, as there's no way
+//!   to pick one of several.
+//! - All parts of a suggestion are applied together, or not at all.
+//! - Suggestions are applied in the order they are emitted. A suggestion that
+//!   overlaps one that was already applied is skipped, unless it makes the
+//!   exact same replacement, in which case it was already applied.
+//!
+//! The diffs refer to the files by their path relative to the working
+//! directory, so that they apply with `patch -p1` or `git apply` from there.
+//! Suggestions for files outside of the working directory are left out, as
+//! neither tool would change those.
+//!
+//! Every compilation only knows its own suggestions, so when a file is part of
+//! several compilations, each of their diffs may contain the same hunks. Only
+//! one of them can be applied at a time.
+
+use std::fs;
+use std::io;
+use std::ops::Range;
+use std::path::{Component, Path, PathBuf};
+
+use rustc_data_structures::fx::FxIndexMap;
+use rustc_lint_defs::Applicability;
+use rustc_span::source_map::SourceMap;
+use rustc_span::{BytePos, FileName, Span};
+
+use crate::{CodeSuggestion, DiagInner, Suggestions};
+
+#[cfg(test)]
+mod tests;
+
+/// The number of unchanged lines around the changes in a hunk.
+const CONTEXT: usize = 3;
+
+pub struct FixExport {
+    dir: PathBuf,
+    /// The fixes for every file, indexed by the path to read it from.
+    files: FxIndexMap<PathBuf, FileFixes>,
+}
+
+struct FileFixes {
+    /// The path of the file relative to the working directory, with `/`
+    /// separators, which the diff refers to.
+    name: String,
+    edits: Vec<Edit>,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+struct Edit {
+    /// The bytes of the file on disk to replace.
+    range: Range<usize>,
+    text: String,
+}
+
+impl Edit {
+    /// Whether applying both `self` and `other` would be ambiguous.
+    fn overlaps(&self, other: &Edit) -> bool {
+        if self.range.is_empty() && other.range.is_empty() {
+            // Two insertions at the same place can't be ordered.
+            self.range.start == other.range.start
+        } else {
+            // An insertion overlaps a replacement around it, but not one that
+            // starts or ends where it is.
+            self.range.start.max(other.range.start) < self.range.end.min(other.range.end)
+                || self.range.start > other.range.start && self.range.end < other.range.end
+                || other.range.start > self.range.start && other.range.end < self.range.end
+        }
+    }
+}
+
+/// Returns whether some of `edits` overlap each other or the edits of
+/// `applied`. Edits identical to another one don't count as overlapping it.
+fn conflicts(applied: &[Edit], edits: &[Edit]) -> bool {
+    edits.iter().enumerate().any(|(i, edit)| {
+        applied.iter().chain(&edits[..i]).any(|other| other != edit && other.overlaps(edit))
+    })
+}
+
+impl FixExport {
+    /// Creates an export that writes the diffs to `dir` on [`FixExport::finish`].
+    pub fn new(dir: PathBuf) -> FixExport {
+        FixExport { dir, files: FxIndexMap::default() }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Collects the machine-applicable suggestions of `diag`.
+    pub(crate) fn add(&mut self, diag: &DiagInner, sm: Option<&SourceMap>) {
+        let Some(sm) = sm else { return };
+        let suggestions = match &diag.suggestions {
+            Suggestions::Enabled(suggestions) => &suggestions[..],
+            Suggestions::Sealed(suggestions) => &suggestions[..],
+            Suggestions::Disabled => &[],
+        };
+        for suggestion in suggestions {
+            self.add_suggestion(suggestion, sm);
+        }
+    }
+
+    fn add_suggestion(&mut self, suggestion: &CodeSuggestion, sm: &SourceMap) {
+        if suggestion.applicability != Applicability::MachineApplicable {
+            return;
+        }
+        let [substitution] = &suggestion.substitutions[..] else { return };
+
+        // Suggestions only change a single file in practice, but they could
+        // change several, in which case they must apply to all of them.
+        let mut edits: FxIndexMap<PathBuf, (String, Vec<Edit>)> = FxIndexMap::default();
+        for part in &substitution.parts {
+            let Some((path, name, edit)) = edit(sm, part.span, &part.snippet) else { return };
+            edits.entry(path).or_insert_with(|| (name, Vec::new())).1.push(edit);
+        }
+        if edits.iter().any(|(path, (_, edits))| {
+            conflicts(self.files.get(path).map_or(&[], |file| &file.edits[..]), edits)
+        }) {
+            return;
+        }
+        for (path, (name, edits)) in edits {
+            let file =
+                self.files.entry(path).or_insert_with(|| FileFixes { name, edits: Vec::new() });
+            for edit in edits {
+                if !file.edits.contains(&edit) {
+                    file.edits.push(edit);
+                }
+            }
+        }
+    }
+
+    /// Writes the diffs of the files with fixes, after removing the diffs that
+    /// an earlier compilation left in the directory, which may be stale.
+    pub(crate) fn finish(&mut self) -> io::Result<()> {
+        match fs::read_dir(&self.dir) {
+            Ok(entries) => {
+                for entry in entries {
+                    let path = entry?.path();
+                    if path.extension().is_some_and(|ext| ext == "diff") {
+                        fs::remove_file(path)?;
+                    }
+                }
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+        if self.files.is_empty() {
+            return Ok(());
+        }
+        fs::create_dir_all(&self.dir)?;
+        for (path, file) in self.files.drain(..) {
+            let src = fs::read_to_string(&path)?;
+            if file.edits.iter().any(|edit| {
+                !src.is_char_boundary(edit.range.start) || !src.is_char_boundary(edit.range.end)
+            }) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("`{}` changed during the compilation", path.display()),
+                ));
+            }
+            let diff = unified_diff(&file.name, &src, file.edits);
+            fs::write(self.dir.join(diff_file_name(&file.name)), diff)?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the name of the file to write the diff of the source file `name` to.
+/// The bytes of `name` other than ASCII letters, digits, `-`, `_` and `.` are
+/// percent-encoded, so that every source file gets a diff of its own.
+fn diff_file_name(name: &str) -> String {
+    let mut file_name = String::with_capacity(name.len() + 5);
+    for &b in name.as_bytes() {
+        if b.is_ascii_alphanumeric() || b"-_.".contains(&b) {
+            file_name.push(b as char);
+        } else {
+            file_name.push_str(&format!("%{b:02X}"));
+        }
+    }
+    file_name.push_str(".diff");
+    file_name
+}
+
+/// Returns the path of the file at `path` relative to the working directory,
+/// with `/` separators, or `None` if the file is outside of it.
+fn relative_name(sm: &SourceMap, path: &Path) -> Option<String> {
+    let path = if path.is_absolute() {
+        path.strip_prefix(sm.working_dir().local_path()?).ok()?
+    } else {
+        path
+    };
+    let mut name = String::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => {
+                if !name.is_empty() {
+                    name.push('/');
+                }
+                name.push_str(part.to_str()?);
+            }
+            Component::CurDir => {}
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+    (!name.is_empty()).then_some(name)
+}
+
+/// Returns the edit that replaces `span` by `snippet`, with the path to read
+/// the file from and its path relative to the working directory.
+fn edit(sm: &SourceMap, mut span: Span, snippet: &str) -> Option<(PathBuf, String, Edit)> {
+    if span.is_dummy() || span.from_expansion() {
+        return None;
+    }
+    let file = sm.lookup_source_file(span.lo());
+    let FileName::Real(real) = &file.name else { return None };
+    let path = real.local_path()?.to_owned();
+    let name = relative_name(sm, &path)?;
+    if !file.contains(span.hi()) {
+        return None;
+    }
+    // Like the JSON output, remove the whole line when the suggestion removes
+    // all of its contents, so no empty line is left behind.
+    if snippet.is_empty()
+        && sm.lookup_char_pos(span.lo()).col.0 == 0
+        && sm.span_to_next_source(span).is_ok_and(|after| after.starts_with('\n'))
+    {
+        span = span.with_hi(span.hi() + BytePos(1));
+    }
+    let start = file.original_relative_byte_pos(span.lo()).0 as usize;
+    let end = file.original_relative_byte_pos(span.hi()).0 as usize;
+    Some((path, name, Edit { range: start..end, text: snippet.to_owned() }))
+}
+
+/// Returns the unified diff that applies `edits` to the file `name`, whose
+/// contents are `src`.
+fn unified_diff(name: &str, src: &str, mut edits: Vec<Edit>) -> String {
+    edits.sort_by_key(|edit| (edit.range.start, edit.range.end));
+
+    // The byte offset of the start of every line, and of the end of the file.
+    let mut line_starts: Vec<usize> = Some(0)
+        .into_iter()
+        .chain(src.match_indices('\n').map(|(i, _)| i + 1).filter(|&i| i < src.len()))
+        .collect();
+    let line_count = if src.is_empty() { 0 } else { line_starts.len() };
+    line_starts.push(src.len());
+    let line_of = |pos: usize| {
+        let line = line_starts.partition_point(|&start| start <= pos).saturating_sub(1);
+        line.min(line_count.saturating_sub(1))
+    };
+    let lines = |range: Range<usize>| &src[line_starts[range.start]..line_starts[range.end]];
+
+    // Group the edits that change the same lines, as the lines they change and
+    // the new text of those lines.
+    let mut changes: Vec<(Range<usize>, Vec<Edit>)> = Vec::new();
+    for edit in edits {
+        let first = line_of(edit.range.start);
+        let last = if edit.range.is_empty() { first } else { line_of(edit.range.end - 1) };
+        let end = (last + 1).min(line_count);
+        match changes.last_mut() {
+            Some((changed, edits)) if first < changed.end => {
+                changed.end = changed.end.max(end);
+                edits.push(edit);
+            }
+            _ => changes.push((first..end, vec![edit])),
+        }
+    }
+    let apply = |changed: &Range<usize>, edits: &[Edit]| {
+        let mut new = String::new();
+        let mut pos = line_starts[changed.start];
+        for edit in edits {
+            new.push_str(&src[pos..edit.range.start]);
+            new.push_str(&edit.text);
+            pos = edit.range.end;
+        }
+        new.push_str(&src[pos..line_starts[changed.end]]);
+        new
+    };
+    let mut joined: Vec<(Range<usize>, String)> = Vec::new();
+    let mut i = 0;
+    while i < changes.len() {
+        let (mut changed, mut edits) = changes[i].clone();
+        i += 1;
+        // An edit that removes the newline at the end of the lines joins them
+        // with the next line, which is then changed as well.
+        loop {
+            let new = apply(&changed, &edits);
+            if new.is_empty() || new.ends_with('\n') || changed.end == line_count {
+                joined.push(trim_unchanged(changed.clone(), lines(changed), &new));
+                break;
+            }
+            if let Some((next, next_edits)) = changes.get(i)
+                && next.start == changed.end
+            {
+                changed.end = next.end;
+                edits.extend_from_slice(next_edits);
+                i += 1;
+            } else {
+                changed.end += 1;
+            }
+        }
+    }
+
+    // Group the changes into hunks, merging them when their context overlaps.
+    let mut hunks: Vec<Vec<(Range<usize>, String)>> = Vec::new();
+    for change in joined {
+        match hunks.last_mut() {
+            Some(hunk) if change.0.start <= hunk.last().unwrap().0.end + 2 * CONTEXT => {
+                hunk.push(change)
+            }
+            _ => hunks.push(vec![change]),
+        }
+    }
+
+    let mut diff = format!("--- a/{name}\n+++ b/{name}\n");
+    // The number of lines added by the hunks so far.
+    let mut offset = 0isize;
+    for hunk in hunks {
+        let start = hunk[0].0.start.saturating_sub(CONTEXT);
+        let end = (hunk.last().unwrap().0.end + CONTEXT).min(line_count);
+        let mut body = String::new();
+        let mut old_lines = 0;
+        let mut new_lines = 0;
+        let mut pos = start;
+        for (changed, new) in &hunk {
+            push_lines(&mut body, ' ', lines(pos..changed.start));
+            push_lines(&mut body, '-', lines(changed.clone()));
+            push_lines(&mut body, '+', new);
+            old_lines += changed.start - pos + changed.len();
+            new_lines += changed.start - pos + new.split_inclusive('\n').count();
+            pos = changed.end;
+        }
+        push_lines(&mut body, ' ', lines(pos..end));
+        old_lines += end - pos;
+        new_lines += end - pos;
+
+        // Empty ranges start at the line before them.
+        let old_start = start + usize::from(old_lines > 0);
+        let new_start = (start as isize + offset) as usize + usize::from(new_lines > 0);
+        offset += new_lines as isize - old_lines as isize;
+        diff.push_str(&format!("@@ -{old_start},{old_lines} +{new_start},{new_lines} @@\n"));
+        diff.push_str(&body);
+    }
+    diff
+}
+
+/// Removes the lines that `old` and `new` start or end with from the change
+/// of the lines `changed` from `old` to `new`.
+fn trim_unchanged(changed: Range<usize>, old: &str, new: &str) -> (Range<usize>, String) {
+    let old: Vec<&str> = old.split_inclusive('\n').collect();
+    let new: Vec<&str> = new.split_inclusive('\n').collect();
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    (changed.start + prefix..changed.end - suffix, new[prefix..new.len() - suffix].concat())
+}
+
+fn push_lines(diff: &mut String, prefix: char, text: &str) {
+    for line in text.split_inclusive('\n') {
+        diff.push(prefix);
+        diff.push_str(line);
+        if !line.ends_with('\n') {
+            diff.push_str("\n\\ No newline at end of file\n");
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use std::sync::Arc;
+
+use rustc_span::SourceFileHashAlgorithm;
+use rustc_span::source_map::{FileLoader, FilePathMapping, RealFileLoader, SourceMapInputs};
+
+use super::*;
+use crate::emitter::{ColorConfig, HumanReadableErrorType};
+use crate::json::JsonEmitter;
+use crate::{DiagCtxt, DiagCtxtHandle};
+
+fn edit(start: usize, end: usize, text: &str) -> Edit {
+    Edit { range: start..end, text: text.to_owned() }
+}
+
+#[test]
+fn overlapping_edits() {
+    let applied = [edit(4, 8, "x"), edit(12, 12, "y")];
+
+    assert!(!conflicts(&applied, &[edit(0, 4, "a"), edit(8, 12, "b")]));
+    assert!(!conflicts(&applied, &[edit(4, 8, "x")]));
+    assert!(!conflicts(&applied, &[edit(12, 14, "z")]));
+
+    assert!(conflicts(&applied, &[edit(6, 10, "a")]));
+    assert!(conflicts(&applied, &[edit(4, 8, "z")]));
+    assert!(conflicts(&applied, &[edit(5, 5, "a")]));
+    assert!(conflicts(&applied, &[edit(12, 12, "z")]));
+    assert!(conflicts(&applied, &[edit(10, 14, "z")]));
+    assert!(conflicts(&[], &[edit(0, 2, "a"), edit(1, 3, "b")]));
+}
+
+#[test]
+fn diff() {
+    let src = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\nk\nl\nm\nn\no\np\nq\nr\ns\n";
+    let a = src.find('a').unwrap();
+    let c = src.find('c').unwrap();
+    let j = src.find('j').unwrap();
+    let r = src.find('r').unwrap();
+
+    let diff = unified_diff(
+        "src/lib.rs",
+        src,
+        vec![edit(r, r + 2, ""), edit(c, c + 1, "C"), edit(j, j, "i2\n"), edit(a, a, "//\n")],
+    );
+    assert_eq!(
+        diff,
+        "--- a/src/lib.rs\n+++ b/src/lib.rs\n\
+         @@ -1,12 +1,14 @@\n+//\n a\n b\n-c\n+C\n d\n e\n f\n g\n h\n i\n+i2\n j\n k\n l\n\
+         @@ -15,5 +17,4 @@\n o\n p\n q\n-r\n s\n"
+    );
+}
+
+#[test]
+fn diff_joined_lines() {
+    let src = "fn f(\n    x: u8,\n) {}";
+    let diff = unified_diff("f.rs", src, vec![edit(5, 17, "")]);
+    assert_eq!(
+        diff,
+        "--- a/f.rs\n+++ b/f.rs\n@@ -1,3 +1,1 @@\n-fn f(\n-    x: u8,\n-) {}\n\
+         \\ No newline at end of file\n+fn f() {}\n\\ No newline at end of file\n"
+    );
+}
+
+#[test]
+fn diff_file_names() {
+    assert_eq!(diff_file_name("lib.rs"), "lib.rs.diff");
+    assert_eq!(diff_file_name("a/b_c.rs"), "a%2Fb_c.rs.diff");
+    assert_eq!(diff_file_name("a_b/c.rs"), "a_b%2Fc.rs.diff");
+    assert_eq!(diff_file_name("a b/ü.rs"), "a%20b%2F%C3%BC.rs.diff");
+}
+
+/// Loads files from disk, with `dir` as the working directory.
+struct WorkingDir {
+    dir: PathBuf,
+}
+
+impl FileLoader for WorkingDir {
+    fn file_exists(&self, path: &Path) -> bool {
+        RealFileLoader.file_exists(path)
+    }
+
+    fn read_file(&self, path: &Path) -> io::Result<String> {
+        RealFileLoader.read_file(path)
+    }
+
+    fn read_binary_file(&self, path: &Path) -> io::Result<Arc<[u8]>> {
+        RealFileLoader.read_binary_file(path)
+    }
+
+    fn current_directory(&self) -> io::Result<PathBuf> {
+        Ok(self.dir.clone())
+    }
+}
+
+#[test]
+fn export() {
+    let dir = std::env::temp_dir().join(format!("rustc-emit-fixes-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let work = dir.join("work");
+    fs::create_dir_all(work.join("src")).unwrap();
+    let path = work.join("src").join("lib.rs");
+    let code = "use std::io;\nfn main() {\n    let x = 1;\n}\n";
+    fs::write(&path, code).unwrap();
+    let outside = dir.join("outside.rs");
+    fs::write(&outside, code).unwrap();
+    let out = dir.join("fixes");
+    // A diff that an earlier compilation left behind.
+    fs::create_dir_all(&out).unwrap();
+    fs::write(out.join("old.rs.diff"), "").unwrap();
+
+    rustc_span::create_default_session_globals_then(|| {
+        let sm = Arc::new(SourceMap::with_inputs(SourceMapInputs {
+            file_loader: Box::new(WorkingDir { dir: work.clone() }),
+            path_mapping: FilePathMapping::empty(),
+            hash_kind: SourceFileHashAlgorithm::Md5,
+            checksum_hash_kind: None,
+        }));
+        let outside_file = sm.load_file(&outside).unwrap();
+        let file = sm.load_file(&path).unwrap();
+        let span = |text: &str, len: usize| {
+            let lo = file.start_pos + BytePos(code.find(text).unwrap() as u32);
+            Span::with_root_ctxt(lo, lo + BytePos(len as u32))
+        };
+        let suggest = |dcx: DiagCtxtHandle<'_>, parts: Vec<(Span, &str)>, app| {
+            let parts = parts.into_iter().map(|(span, text)| (span, text.to_owned())).collect();
+            dcx.struct_warn("warning").with_multipart_suggestion("fix", parts, app).emit();
+        };
+
+        let je = JsonEmitter::new(
+            Box::new(io::sink()),
+            Some(sm.clone()),
+            false,
+            HumanReadableErrorType { short: true, unicode: false },
+            ColorConfig::Never,
+        );
+        let dcx = DiagCtxt::new(Box::new(je)).with_fix_export(FixExport::new(out.clone()));
+        let handle = dcx.handle();
+        // Removes the whole line.
+        suggest(handle, vec![(span("use", 12), "")], Applicability::MachineApplicable);
+        suggest(handle, vec![(span("x", 1), "_x")], Applicability::MachineApplicable);
+        // Overlaps the previous suggestion.
+        suggest(handle, vec![(span("let", 5), "let y")], Applicability::MachineApplicable);
+        suggest(handle, vec![(span("1", 1), "2")], Applicability::MaybeIncorrect);
+        // Outside of the working directory.
+        let outside_span = Span::with_root_ctxt(outside_file.start_pos, outside_file.start_pos);
+        suggest(handle, vec![(outside_span, "//\n")], Applicability::MachineApplicable);
+        drop(dcx);
+    });
+
+    let mut diffs: Vec<_> =
+        fs::read_dir(&out).unwrap().map(|entry| entry.unwrap().file_name()).collect();
+    diffs.sort();
+    assert_eq!(diffs, ["src%2Flib.rs.diff"]);
+    let diff = fs::read_to_string(out.join("src%2Flib.rs.diff")).unwrap();
+    assert_eq!(
+        diff,
+        "--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1,4 +1,3 @@\n-use std::io;\n fn main() {\n\
+         -    let x = 1;\n+    let _x = 1;\n }\n"
+    );
+    fs::remove_dir_all(&dir).unwrap();
+}
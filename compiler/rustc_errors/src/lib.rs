@@ -33,6 +33,7 @@
 pub use anstyle::{
     Ansi256Color, AnsiColor, Color, EffectIter, Effects, Reset, RgbColor, Style as Anstyle,
 };
+use baseline::DiagnosticBaseline;
 pub use codes::*;
 pub use decorate_diag::{BufferedEarlyLint, DecorateDiagCompat, LintBuffer};
 pub use diagnostic::{
@@ -45,6 +46,7 @@
 };
 pub use emitter::ColorConfig;
 use emitter::{DynEmitter, Emitter};
+use fixes::FixExport;
 use rustc_data_structures::AtomicRef;
 use rustc_data_structures::fx::{FxHashSet, FxIndexMap, FxIndexSet};
 use rustc_data_structures::stable_hash::StableHasher;
@@ -70,16 +72,19 @@
 use crate::timings::TimingRecord;
 
 pub mod annotate_snippet_emitter_writer;
+pub mod baseline;
 pub mod codes;
 mod decorate_diag;
 mod diagnostic;
 mod diagnostic_impls;
 pub mod emitter;
+pub mod fixes;
 pub mod formatting;
 pub mod json;
 pub mod lints;
 mod lock;
 pub mod markdown;
+pub mod sarif;
 pub mod timings;
 
 pub type PResult<'a, T> = Result<T, Diag<'a>>;
@@ -363,6 +368,14 @@ struct DiagCtxtInner {
     /// The file where the ICE information is stored. This allows delayed_span_bug backtraces to be
     /// stored along side the main panic backtrace.
     ice_file: Option<PathBuf>,
+
+    /// The baseline of lint warnings to suppress or record.
+    /// (rustc: see `-Z diagnostic-baseline`)
+    baseline: Option<DiagnosticBaseline>,
+
+    /// The machine-applicable suggestions to export as patches.
+    /// (rustc: see `-Z emit-fixes`)
+    fixes: Option<FixExport>,
 }
 
 /// A key denoting where from a diagnostic was stashed.
@@ -431,6 +444,23 @@ fn drop(&mut self) {
         // errors because they are guaranteed to be emitted here or earlier.
         self.emit_stashed_diagnostics();
 
+        if let Some(mut baseline) = self.baseline.take()
+            && let Err(e) = baseline.finish()
+        {
+            let msg = format!(
+                "failed to write the diagnostic baseline `{}`: {e}",
+                baseline.path().display()
+            );
+            self.emitter.emit_diagnostic(DiagInner::new(Warning, msg));
+        }
+
+        if let Some(mut fixes) = self.fixes.take()
+            && let Err(e) = fixes.finish()
+        {
+            let msg = format!("failed to write the fixes to `{}`: {e}", fixes.dir().display());
+            self.emitter.emit_diagnostic(DiagInner::new(Warning, msg));
+        }
+
         // Important: it is sound to produce an `ErrorGuaranteed` when emitting
         // delayed bugs because they are guaranteed to be emitted here if
         // necessary.
@@ -477,6 +507,16 @@ pub fn with_ice_file(mut self, ice_file: PathBuf) -> Self {
         self
     }
 
+    pub fn with_baseline(mut self, baseline: DiagnosticBaseline) -> Self {
+        self.inner.get_mut().baseline = Some(baseline);
+        self
+    }
+
+    pub fn with_fix_export(mut self, fixes: FixExport) -> Self {
+        self.inner.get_mut().fixes = Some(fixes);
+        self
+    }
+
     pub fn new(emitter: Box<DynEmitter>) -> Self {
         Self { inner: Lock::new(DiagCtxtInner::new(emitter)) }
     }
@@ -524,6 +564,8 @@ pub fn reset_err_count(&self) {
             future_breakage_diagnostics,
             fulfilled_expectations,
             ice_file: _,
+            baseline: _,
+            fixes: _,
         } = inner.deref_mut();
 
         // For the `Vec`s and `HashMap`s, we overwrite with an empty container to free the
@@ -1170,6 +1212,8 @@ fn new(emitter: Box<DynEmitter>) -> Self {
             future_breakage_diagnostics: Vec::new(),
             fulfilled_expectations: Default::default(),
             ice_file: None,
+            baseline: None,
+            fixes: None,
         }
     }
 
@@ -1261,6 +1305,16 @@ fn emit_diagnostic(
                     }
                     return None;
                 }
+                if let Some(baseline) = &mut self.baseline
+                    && baseline.suppresses(&diagnostic, self.emitter.source_map())
+                {
+                    // The warning is in the baseline.
+                    if diagnostic.has_future_breakage() {
+                        // The side-effect is at the top of this method.
+                        TRACK_DIAGNOSTIC(diagnostic, &mut |_| None);
+                    }
+                    return None;
+                }
             }
             Note | Help | FailureNote => {}
             OnceNote | OnceHelp => panic!("bad level: {:?}", diagnostic.level),
@@ -1329,6 +1383,9 @@ fn emit_diagnostic(
                 }
                 self.has_printed = true;
 
+                if let Some(fixes) = &mut self.fixes {
+                    fixes.add(&diagnostic, self.emitter.source_map());
+                }
                 self.emitter.emit_diagnostic(diagnostic);
             }
 
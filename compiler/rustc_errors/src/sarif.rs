@@ -0,0 +1,425 @@
+//! A SARIF emitter for errors.
+//!
+//! [SARIF] (Static Analysis Results Interchange Format) is the format that
+//! code scanning services ingest. Unlike the JSON output, which prints every
+//! diagnostic as it is emitted, a SARIF log is a single document: diagnostics
+//! are collected as SARIF `result`s and the log is written when the emitter is
+//! dropped. Only the emitter of the session writes SARIF, so that there's a
+//! single log: diagnostics from before there is a session are rendered for
+//! humans.
+//!
+//! Diagnostics are mapped onto the format as follows:
+//!
+//! - The error code, or the lint name for lints without one, is the `ruleId`
+//!   of the result, and every rule is described once in the `rules` of the tool.
+//! - Primary spans become the `locations` of the result. Secondary spans, and
+//!   the primary spans of notes and helps, become its `relatedLocations`.
+//!   Notes and helps without a span are appended to the message.
+//! - Every alternative of a suggestion becomes a `fix`.
+//!
+//! [SARIF]: https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html
+
+use std::io::Write;
+use std::sync::Arc;
+
+use rustc_data_structures::fx::FxIndexMap;
+use rustc_data_structures::sync::IntoDynSyncSend;
+use rustc_error_messages::DiagArgMap;
+use rustc_lint_defs::Applicability;
+use rustc_span::Span;
+use rustc_span::source_map::SourceMap;
+use serde::Serialize;
+
+use crate::diagnostic::IsLint;
+use crate::emitter::Emitter;
+use crate::formatting::{format_diag_message, format_diag_messages};
+use crate::{CodeSuggestion, DiagInner, Level, Suggestions};
+
+#[cfg(test)]
+mod tests;
+
+const SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const VERSION: &str = "2.1.0";
+
+pub struct SarifEmitter {
+    dst: IntoDynSyncSend<Box<dyn Write + Send>>,
+    sm: Option<Arc<SourceMap>>,
+    pretty: bool,
+    /// The rules of the results so far, indexed by their id.
+    rules: FxIndexMap<String, Rule>,
+    results: Vec<SarifResult>,
+    saw_error: bool,
+}
+
+impl SarifEmitter {
+    pub fn new(
+        dst: Box<dyn Write + Send>,
+        sm: Option<Arc<SourceMap>>,
+        pretty: bool,
+    ) -> SarifEmitter {
+        SarifEmitter {
+            dst: IntoDynSyncSend(dst),
+            sm,
+            pretty,
+            rules: FxIndexMap::default(),
+            results: Vec::new(),
+            saw_error: false,
+        }
+    }
+
+    /// Returns the index of the rule for the code or lint of `diag`, if it has one.
+    fn rule_index(&mut self, diag: &DiagInner) -> Option<(String, usize)> {
+        let (id, rule) = if let Some(code) = diag.code {
+            let id = code.to_string();
+            let rule = Rule {
+                id: id.clone(),
+                help_uri: Some(format!("https://doc.rust-lang.org/error_codes/{id}.html")),
+                full_description: crate::codes::try_find_description(code)
+                    .ok()
+                    .map(|explanation| Message { text: explanation.to_owned() }),
+            };
+            (id, rule)
+        } else if let Some(IsLint { name, .. }) = &diag.is_lint {
+            (name.clone(), Rule { id: name.clone(), help_uri: None, full_description: None })
+        } else {
+            return None;
+        };
+        let entry = self.rules.entry(id.clone());
+        let index = entry.index();
+        entry.or_insert(rule);
+        Some((id, index))
+    }
+
+    fn location(&self, span: Span, message: Option<String>) -> Option<Location> {
+        let sm = self.sm.as_ref()?;
+        if span.is_dummy() {
+            return None;
+        }
+        let start = sm.lookup_char_pos(span.lo());
+        let end = sm.lookup_char_pos(span.hi());
+        let byte_start = start.file.original_relative_byte_pos(span.lo()).0;
+        let byte_end = start.file.original_relative_byte_pos(span.hi()).0;
+        Some(Location {
+            physical_location: PhysicalLocation {
+                artifact_location: ArtifactLocation {
+                    uri: path_to_uri(&sm.filename_for_diagnostics(&start.file.name).to_string()),
+                },
+                region: Region {
+                    start_line: start.line,
+                    start_column: start.col.0 + 1,
+                    end_line: end.line,
+                    end_column: end.col.0 + 1,
+                    byte_offset: byte_start,
+                    byte_length: byte_end.saturating_sub(byte_start),
+                },
+            },
+            message: message.map(|text| Message { text }),
+        })
+    }
+
+    fn fix(&self, sugg: &CodeSuggestion, args: &DiagArgMap) -> Vec<Fix> {
+        let description = format_diag_message(&sugg.msg, args).to_string();
+        sugg.substitutions
+            .iter()
+            .filter_map(|substitution| {
+                let mut changes: FxIndexMap<String, Vec<Replacement>> = FxIndexMap::default();
+                for part in &substitution.parts {
+                    // Fixes always have a location, even when the span is empty.
+                    let location = self.location(part.span, None)?.physical_location;
+                    changes.entry(location.artifact_location.uri).or_default().push(Replacement {
+                        deleted_region: location.region,
+                        inserted_content: InsertedContent { text: part.snippet.clone() },
+                    });
+                }
+                Some(Fix {
+                    description: Message { text: description.clone() },
+                    artifact_changes: changes
+                        .into_iter()
+                        .map(|(uri, replacements)| ArtifactChange {
+                            artifact_location: ArtifactLocation { uri },
+                            replacements,
+                        })
+                        .collect(),
+                    properties: FixProperties { applicability: sugg.applicability },
+                })
+            })
+            .collect()
+    }
+
+    fn write_log(&mut self) -> std::io::Result<()> {
+        let log = SarifLog {
+            schema: SCHEMA,
+            version: VERSION,
+            runs: [Run {
+                tool: Tool {
+                    driver: Driver {
+                        name: "rustc",
+                        information_uri: "https://www.rust-lang.org/",
+                        rules: self.rules.values().collect(),
+                    },
+                },
+                results: &self.results,
+                invocations: [Invocation { execution_successful: !self.saw_error }],
+                column_kind: "unicodeCodePoints",
+            }],
+        };
+        if self.pretty {
+            serde_json::to_writer_pretty(&mut *self.dst, &log)?
+        } else {
+            serde_json::to_writer(&mut *self.dst, &log)?
+        };
+        self.dst.write_all(b"\n")?;
+        self.dst.flush()
+    }
+}
+
+/// Converts a file name as it appears in diagnostics to a URI reference.
+fn path_to_uri(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    let mut uri = String::with_capacity(path.len());
+    // An absolute Windows path would otherwise be read as a URI with the
+    // drive letter as its scheme.
+    if path.as_bytes().get(1) == Some(&b':') {
+        uri.push_str("file:///");
+    }
+    for c in path.chars() {
+        match c {
+            ' ' => uri.push_str("%20"),
+            '%' => uri.push_str("%25"),
+            '#' => uri.push_str("%23"),
+            '?' => uri.push_str("%3F"),
+            c => uri.push(c),
+        }
+    }
+    uri
+}
+
+fn sarif_level(level: Level) -> &'static str {
+    match level {
+        Level::Bug | Level::DelayedBug | Level::Fatal | Level::Error => "error",
+        Level::ForceWarning | Level::Warning => "warning",
+        Level::Note | Level::OnceNote | Level::Help | Level::OnceHelp | Level::FailureNote => {
+            "note"
+        }
+        Level::Allow | Level::Expect => "none",
+    }
+}
+
+impl Emitter for SarifEmitter {
+    fn emit_diagnostic(&mut self, diag: DiagInner) {
+        // Messages like "aborting due to 2 previous errors" are not results.
+        if diag.level.is_failure_note() {
+            return;
+        }
+        self.saw_error |= diag.is_error();
+
+        let rule = self.rule_index(&diag);
+        let mut message = format_diag_messages(&diag.messages, &diag.args).to_string();
+
+        let mut locations = Vec::new();
+        let mut related_locations = Vec::new();
+        for label in diag.span.span_labels() {
+            let text = label.label.as_ref().map(|m| format_diag_message(m, &diag.args).to_string());
+            let Some(location) = self.location(label.span, text) else { continue };
+            if label.is_primary {
+                locations.push(location);
+            } else {
+                related_locations.push(location);
+            }
+        }
+        for child in &diag.children {
+            let text = format!(
+                "{}: {}",
+                child.level.to_str(),
+                format_diag_messages(&child.messages, &diag.args)
+            );
+            let mut child_locations = child
+                .span
+                .primary_spans()
+                .iter()
+                .filter_map(|&span| self.location(span, Some(text.clone())))
+                .peekable();
+            if child_locations.peek().is_none() {
+                message.push_str("\n= ");
+                message.push_str(&text);
+            }
+            related_locations.extend(child_locations);
+        }
+
+        let suggestions = match &diag.suggestions {
+            Suggestions::Enabled(suggestions) => &suggestions[..],
+            Suggestions::Sealed(suggestions) => &suggestions[..],
+            Suggestions::Disabled => &[],
+        };
+        let fixes = suggestions.iter().flat_map(|sugg| self.fix(sugg, &diag.args)).collect();
+
+        let (rule_id, rule_index) = rule.unzip();
+        self.results.push(SarifResult {
+            rule_id,
+            rule_index,
+            level: sarif_level(diag.level),
+            message: Message { text: message },
+            locations,
+            related_locations,
+            fixes,
+        });
+    }
+
+    fn source_map(&self) -> Option<&SourceMap> {
+        self.sm.as_deref()
+    }
+}
+
+impl Drop for SarifEmitter {
+    fn drop(&mut self) {
+        // The emitter may be dropped while unwinding, where panicking would
+        // abort the process. There is nowhere left to report the error but
+        // stderr, which may well be where the log was supposed to go.
+        if let Err(e) = self.write_log() {
+            let _ = writeln!(std::io::stderr(), "error: failed to write the SARIF log: {e}");
+        }
+    }
+}
+
+// The following data types are provided just for serialisation. Optional
+// properties that are empty are left out, as the SARIF schema recommends.
+
+#[derive(Serialize)]
+struct SarifLog<'a> {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    version: &'static str,
+    runs: [Run<'a>; 1],
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Run<'a> {
+    tool: Tool<'a>,
+    results: &'a [SarifResult],
+    invocations: [Invocation; 1],
+    column_kind: &'static str,
+}
+
+#[derive(Serialize)]
+struct Tool<'a> {
+    driver: Driver<'a>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Driver<'a> {
+    name: &'static str,
+    information_uri: &'static str,
+    rules: Vec<&'a Rule>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Rule {
+    /// The error code or lint name.
+    id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    help_uri: Option<String>,
+    /// The explanation of the error code, in Markdown.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    full_description: Option<Message>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Invocation {
+    /// Whether the compilation finished without errors.
+    execution_successful: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifResult {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rule_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rule_index: Option<usize>,
+    /// "error", "warning", "note" or "none".
+    level: &'static str,
+    message: Message,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    locations: Vec<Location>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    related_locations: Vec<Location>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    fixes: Vec<Fix>,
+}
+
+#[derive(Serialize)]
+struct Message {
+    text: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Location {
+    physical_location: PhysicalLocation,
+    /// The label of the span, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<Message>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PhysicalLocation {
+    artifact_location: ArtifactLocation,
+    region: Region,
+}
+
+#[derive(Serialize)]
+struct ArtifactLocation {
+    uri: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Region {
+    /// 1-based.
+    start_line: usize,
+    /// 1-based, character offset.
+    start_column: usize,
+    end_line: usize,
+    end_column: usize,
+    /// 0-based.
+    byte_offset: u32,
+    byte_length: u32,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Fix {
+    description: Message,
+    artifact_changes: Vec<ArtifactChange>,
+    properties: FixProperties,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ArtifactChange {
+    artifact_location: ArtifactLocation,
+    replacements: Vec<Replacement>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Replacement {
+    deleted_region: Region,
+    inserted_content: InsertedContent,
+}
+
+#[derive(Serialize)]
+struct InsertedContent {
+    text: String,
+}
+
+#[derive(Serialize)]
+struct FixProperties {
+    /// How confident rustc is that applying the fix gives the intended code.
+    applicability: Applicability,
+}
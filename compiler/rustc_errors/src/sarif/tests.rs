@@ -0,0 +1,146 @@
+use std::io;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use rustc_span::source_map::FilePathMapping;
+use rustc_span::{BytePos, FileName};
+use serde_json::Value;
+
+use super::*;
+use crate::{DiagCtxt, E0308};
+
+struct Shared<T> {
+    data: Arc<Mutex<T>>,
+}
+
+impl<T: Write> Write for Shared<T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.data.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.data.lock().unwrap().flush()
+    }
+}
+
+/// Runs `f` with a `DiagCtxt` that emits SARIF for a file `test.rs` containing
+/// `code`, and returns the log.
+fn sarif_log(code: &str, f: impl FnOnce(&DiagCtxt)) -> Value {
+    rustc_span::create_default_session_globals_then(|| {
+        let sm = Arc::new(SourceMap::new(FilePathMapping::empty()));
+        let path = PathBuf::from("test dir/test.rs");
+        let filename = FileName::Real(sm.path_mapping().to_real_filename(sm.working_dir(), path));
+        sm.new_source_file(filename, code.to_owned());
+
+        let output = Arc::new(Mutex::new(Vec::new()));
+        let emitter = SarifEmitter::new(Box::new(Shared { data: output.clone() }), Some(sm), true);
+        let dcx = DiagCtxt::new(Box::new(emitter));
+        f(&dcx);
+        drop(dcx);
+
+        serde_json::from_slice(&output.lock().unwrap()).unwrap()
+    })
+}
+
+fn span(lo: u32, hi: u32) -> Span {
+    Span::with_root_ctxt(BytePos(lo), BytePos(hi))
+}
+
+#[test]
+fn result() {
+    let log = sarif_log("let x: u8 = \"a\";\n", |dcx| {
+        dcx.handle()
+            .struct_span_err(span(12, 15), "mismatched types")
+            .with_code(E0308)
+            .with_span_label(span(12, 15), "expected `u8`, found `&str`")
+            .with_span_label(span(7, 9), "expected due to this")
+            .with_note("a note without a span")
+            .with_span_suggestion(span(12, 15), "use a number", "1", Applicability::MaybeIncorrect)
+            .emit();
+    });
+
+    assert_eq!(log["version"], "2.1.0");
+    let run = &log["runs"][0];
+    assert_eq!(run["invocations"][0]["executionSuccessful"], false);
+    let rule = &run["tool"]["driver"]["rules"][0];
+    assert_eq!(rule["id"], "E0308");
+    assert_eq!(rule["helpUri"], "https://doc.rust-lang.org/error_codes/E0308.html");
+
+    let result = &run["results"][0];
+    assert_eq!(result["ruleId"], "E0308");
+    assert_eq!(result["ruleIndex"], 0);
+    assert_eq!(result["level"], "error");
+    assert_eq!(result["message"]["text"], "mismatched types\n= note: a note without a span");
+
+    let location = &result["locations"][0];
+    assert_eq!(location["message"]["text"], "expected `u8`, found `&str`");
+    let physical = &location["physicalLocation"];
+    assert_eq!(physical["artifactLocation"]["uri"], "test%20dir/test.rs");
+    assert_eq!(physical["region"]["startLine"], 1);
+    assert_eq!(physical["region"]["startColumn"], 13);
+    assert_eq!(physical["region"]["endColumn"], 16);
+    assert_eq!(physical["region"]["byteOffset"], 12);
+    assert_eq!(physical["region"]["byteLength"], 3);
+    assert_eq!(result["relatedLocations"][0]["message"]["text"], "expected due to this");
+
+    let fix = &result["fixes"][0];
+    assert_eq!(fix["description"]["text"], "use a number");
+    assert_eq!(fix["properties"]["applicability"], "MaybeIncorrect");
+    let replacement = &fix["artifactChanges"][0]["replacements"][0];
+    assert_eq!(replacement["deletedRegion"]["byteOffset"], 12);
+    assert_eq!(replacement["insertedContent"]["text"], "1");
+}
+
+#[test]
+fn warnings_only() {
+    let log = sarif_log("fn main() {}\n", |dcx| {
+        dcx.handle().struct_span_warn(span(3, 7), "a warning").emit();
+        dcx.handle().struct_span_warn(span(3, 7), "another warning").emit();
+    });
+
+    let run = &log["runs"][0];
+    assert_eq!(run["invocations"][0]["executionSuccessful"], true);
+    assert_eq!(run["tool"]["driver"]["rules"].as_array().unwrap().len(), 0);
+    let results = run["results"].as_array().unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[1]["level"], "warning");
+    assert_eq!(results[1]["message"]["text"], "another warning");
+    assert!(results[1].get("ruleId").is_none());
+}
+
+#[test]
+fn empty_log() {
+    let log = sarif_log("", |_| {});
+    assert_eq!(log["runs"][0]["invocations"][0]["executionSuccessful"], true);
+    assert_eq!(log["runs"][0]["results"].as_array().unwrap().len(), 0);
+}
+
+#[test]
+fn write_error() {
+    struct Broken;
+
+    impl Write for Broken {
+        fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+            Err(std::io::ErrorKind::BrokenPipe.into())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    // Failing to write the log must not panic, as the emitter may be dropped
+    // while unwinding.
+    rustc_span::create_default_session_globals_then(|| {
+        let dcx = DiagCtxt::new(Box::new(SarifEmitter::new(Box::new(Broken), None, false)));
+        dcx.handle().struct_warn("a warning").emit();
+        drop(dcx);
+    });
+}
+
+#[test]
+fn uris() {
+    assert_eq!(path_to_uri("src/main.rs"), "src/main.rs");
+    assert_eq!(path_to_uri("/home/a b/100%.rs"), "/home/a%20b/100%25.rs");
+    assert_eq!(path_to_uri(r"C:\src\main.rs"), "file:///C:/src/main.rs");
+}
@@ -445,6 +445,12 @@ pub fn expr_u32(&self, span: Span, n: u32) -> Box<ast::Expr> {
         self.expr(span, ast::ExprKind::Lit(lit))
     }
 
+    pub fn expr_u64(&self, span: Span, n: u64) -> Box<ast::Expr> {
+        let suffix = Some(ast::UintTy::U64.name());
+        let lit = token::Lit::new(token::Integer, sym::integer(n), suffix);
+        self.expr(span, ast::ExprKind::Lit(lit))
+    }
+
     pub fn expr_bool(&self, span: Span, value: bool) -> Box<ast::Expr> {
         let lit = token::Lit::new(token::Bool, if value { kw::True } else { kw::False }, None);
         self.expr(span, ast::ExprKind::Lit(lit))
@@ -358,6 +358,7 @@ pub struct BuiltinAttribute {
     gated!(test_runner, custom_test_frameworks, "custom test frameworks are an unstable feature"),
 
     gated!(reexport_test_harness_main, custom_test_frameworks, "custom test frameworks are an unstable feature"),
+    gated!(test_timeout, experimental!(test_timeout)),
 
     // RFC #1268
     gated!(marker, marker_trait_attr, experimental!(marker)),
@@ -288,6 +288,8 @@ pub fn internal(&self, feature: Symbol) -> bool {
     (internal, staged_api, "1.0.0", None),
     /// Perma-unstable, only used to test the `incomplete_features` lint.
     (incomplete, test_incomplete_feature, "1.96.0", None),
+    /// Allows using `#[test_timeout]` to set a hard time limit on a `#[test]` function.
+    (unstable, test_timeout, "CURRENT_RUSTC_VERSION", None),
     /// Added for testing unstable lints; perma-unstable.
     (internal, test_unstable_lint, "1.60.0", None),
     /// Use for stable + negative coherence and strict coherence depending on trait's
@@ -1604,6 +1604,11 @@ pub enum AttributeKind {
     /// Represents `#![test_runner(path)]`
     TestRunner(Path),
 
+    /// Represents `#[test_timeout = "seconds"]`
+    TestTimeout {
+        seconds: Limit,
+    },
+
     /// Represents `#[thread_local]`
     ThreadLocal,
 
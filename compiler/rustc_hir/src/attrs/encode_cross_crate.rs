@@ -194,6 +194,7 @@ pub fn encode_cross_crate(&self) -> EncodeCrossCrate {
             Stability { .. } => Yes,
             TargetFeature { .. } => No,
             TestRunner(..) => Yes,
+            TestTimeout { .. } => No,
             ThreadLocal => No,
             TrackCaller(..) => Yes,
             TypeLengthLimit { .. } => No,
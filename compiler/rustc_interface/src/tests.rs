@@ -686,6 +686,7 @@ macro_rules! untracked {
     untracked!(assert_incr_state, Some(IncrementalStateAssertion::Loaded));
     untracked!(codegen_source_order, true);
     untracked!(deduplicate_diagnostics, false);
+    untracked!(diagnostic_baseline, Some(PathBuf::from("baseline.txt")));
     untracked!(dump_dep_graph, true);
     untracked!(dump_mir, Some(String::from("abc")));
     untracked!(dump_mir_dataflow, true);
@@ -693,9 +694,11 @@ macro_rules! untracked {
     untracked!(dump_mir_exclude_alloc_bytes, true);
     untracked!(dump_mir_exclude_pass_number, true);
     untracked!(dump_mir_graphviz, true);
+    untracked!(dump_mono_graph, Some(PathBuf::from("mono-graph.json")));
     untracked!(dump_mono_stats, SwitchWithOptPath::Enabled(Some("mono-items-dir/".into())));
     untracked!(dump_mono_stats_format, DumpMonoStatsFormat::Json);
     untracked!(dylib_lto, true);
+    untracked!(emit_fixes, Some(PathBuf::from("fixes")));
     untracked!(emit_stack_sizes, true);
     untracked!(future_incompat_test, true);
     untracked!(identify_regions, true);
@@ -725,6 +728,7 @@ macro_rules! untracked {
     untracked!(proc_macro_execution_strategy, ProcMacroExecutionStrategy::CrossThread);
     untracked!(profile_closures, true);
     untracked!(query_dep_graph, true);
+    untracked!(record_diagnostic_baseline, true);
     untracked!(self_profile, SwitchWithOptPath::Enabled(None));
     untracked!(self_profile_events, Some(vec![String::new()]));
     untracked!(shell_argfiles, true);
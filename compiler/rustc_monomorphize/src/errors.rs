@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use rustc_macros::Diagnostic;
 use rustc_middle::ty::{Instance, Ty};
 use rustc_span::{Span, Symbol};
@@ -50,6 +52,13 @@ pub(crate) struct CouldntDumpMonoStats {
     pub error: String,
 }
 
+#[derive(Diagnostic)]
+#[diag("couldn't write the mono item graph to `{$path}`: {$error}")]
+pub(crate) struct CouldntDumpMonoGraph {
+    pub path: PathBuf,
+    pub error: String,
+}
+
 #[derive(Diagnostic)]
 #[diag("the above error was encountered while instantiating `{$kind} {$instance}`")]
 pub(crate) struct EncounteredErrorWhileInstantiating<'tcx> {
@@ -93,12 +93,13 @@
 //! inlining, even when they are not marked `#[inline]`.
 
 use std::cmp;
+use std::collections::VecDeque;
 use std::collections::hash_map::Entry;
 use std::fs::{self, File};
 use std::io::Write;
 use std::path::{Path, PathBuf};
 
-use rustc_data_structures::fx::{FxIndexMap, FxIndexSet};
+use rustc_data_structures::fx::{FxHashMap, FxIndexMap, FxIndexSet};
 use rustc_data_structures::sync::par_join;
 use rustc_data_structures::unord::{UnordMap, UnordSet};
 use rustc_hir::LangItem;
@@ -124,7 +125,7 @@
 use tracing::debug;
 
 use crate::collector::{self, MonoItemCollectionStrategy, UsageMap};
-use crate::errors::{CouldntDumpMonoStats, SymbolAlreadyDefined};
+use crate::errors::{CouldntDumpMonoGraph, CouldntDumpMonoStats, SymbolAlreadyDefined};
 use crate::graph_checks::target_specific_checks;
 
 struct PartitioningCx<'a, 'tcx> {
@@ -1183,6 +1184,13 @@ fn collect_and_partition_mono_items(tcx: TyCtxt<'_>, (): ()) -> MonoItemPartitio
         tcx.dcx().emit_fatal(CouldntDumpMonoStats { error: err.to_string() });
     }
 
+    // Output the mono item graph, along with where each item ended up
+    if let Some(ref path) = tcx.sess.opts.unstable_opts.dump_mono_graph
+        && let Err(err) = dump_mono_item_graph(tcx, &items, &usage_map, codegen_units, path)
+    {
+        tcx.dcx().emit_fatal(CouldntDumpMonoGraph { path: path.clone(), error: err.to_string() });
+    }
+
     if tcx.sess.opts.unstable_opts.print_mono_items {
         let mut item_to_cgus: UnordMap<_, Vec<_>> = Default::default();
 
@@ -1312,6 +1320,109 @@ struct MonoItem {
     Ok(())
 }
 
+/// Outputs the graph of mono items as JSON to the given file: what each item uses, its
+/// estimated size, the codegen units it was placed in, and the chain of users that caused it
+/// to be instantiated.
+fn dump_mono_item_graph<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    items: &[MonoItem<'tcx>],
+    usage_map: &UsageMap<'tcx>,
+    codegen_units: &[CodegenUnit<'tcx>],
+    path: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // Items are referred to by their index in `items`, which is sorted deterministically.
+    let indices: FxHashMap<_, _> = items.iter().enumerate().map(|(i, &item)| (item, i)).collect();
+    let mut uses: Vec<Vec<usize>> = items
+        .iter()
+        .map(|item| match usage_map.used_map.get(item) {
+            Some(used) => used.iter().filter_map(|used| indices.get(used).copied()).collect(),
+            None => Vec::new(),
+        })
+        .collect();
+
+    // The roots are the local items that aren't generic: they are instantiated because they
+    // exist, everything else is instantiated because something uses it. A breadth-first walk
+    // from the roots attributes each item to one of its users on a shortest path from a root.
+    let is_root = |item: &MonoItem<'tcx>| item.krate() == LOCAL_CRATE && !item.is_generic_fn();
+    let mut user: Vec<Option<usize>> = vec![None; items.len()];
+    let mut reached: Vec<bool> = items.iter().map(is_root).collect();
+    let mut queue: VecDeque<usize> = (0..items.len()).filter(|&i| reached[i]).collect();
+    while let Some(i) = queue.pop_front() {
+        for &used in &uses[i] {
+            if !reached[used] {
+                reached[used] = true;
+                user[used] = Some(i);
+                queue.push_back(used);
+            }
+        }
+    }
+
+    #[derive(Clone, serde::Serialize)]
+    struct CguPlacement {
+        name: String,
+        linkage: String,
+    }
+
+    let mut placements: Vec<Vec<CguPlacement>> = vec![Vec::new(); items.len()];
+    for cgu in codegen_units {
+        for (mono_item, data) in cgu.items() {
+            if let Some(&i) = indices.get(mono_item) {
+                placements[i].push(CguPlacement {
+                    name: cgu.name().to_string(),
+                    linkage: format!("{:?}", data.linkage),
+                });
+            }
+        }
+    }
+
+    #[derive(serde::Serialize)]
+    struct MonoItemNode {
+        name: String,
+        def_path: String,
+        generic: bool,
+        size_estimate: usize,
+        cgus: Vec<CguPlacement>,
+        uses: Vec<usize>,
+        /// The item's user, that user's own user, and so on up to a root. Empty for the roots
+        /// themselves, and for the items that no root reaches.
+        root_cause: Vec<usize>,
+    }
+
+    #[derive(serde::Serialize)]
+    struct MonoItemGraph {
+        crate_name: String,
+        items: Vec<MonoItemNode>,
+    }
+
+    let nodes = items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| {
+            let mut root_cause = Vec::new();
+            let mut current = user[i];
+            while let Some(user_index) = current {
+                root_cause.push(user_index);
+                current = user[user_index];
+            }
+            MonoItemNode {
+                name: with_no_trimmed_paths!(item.to_string()),
+                def_path: with_no_trimmed_paths!(tcx.def_path_str(item.def_id())),
+                generic: item.is_generic_fn(),
+                size_estimate: item.size_estimate(tcx),
+                cgus: std::mem::take(&mut placements[i]),
+                uses: std::mem::take(&mut uses[i]),
+                root_cause,
+            }
+        })
+        .collect();
+
+    let graph = MonoItemGraph { crate_name: tcx.crate_name(LOCAL_CRATE).to_string(), items: nodes };
+    let file = File::create_buffered(path)?;
+    serde_json::to_writer(file, &graph)?;
+
+    Ok(())
+}
+
 pub(crate) fn provide(providers: &mut Providers) {
     providers.queries.collect_and_partition_mono_items = collect_and_partition_mono_items;
 
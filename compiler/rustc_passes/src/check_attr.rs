@@ -404,6 +404,7 @@ fn check_one_parsed_attribute(
             AttributeKind::ShouldPanic { .. } => (),
             AttributeKind::Stability { .. } => (),
             AttributeKind::TestRunner(..) => (),
+            AttributeKind::TestTimeout { .. } => (),
             AttributeKind::ThreadLocal => (),
             AttributeKind::TypeLengthLimit { .. } => (),
             AttributeKind::UnstableFeatureBound(..) => (),
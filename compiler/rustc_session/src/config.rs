@@ -812,6 +812,8 @@ pub enum ErrorOutputType {
         json_rendered: HumanReadableErrorType,
         color_config: ColorConfig,
     },
+    /// A SARIF log, which is consumed by code scanning tools.
+    Sarif,
 }
 
 #[derive(Clone, Hash, Debug)]
@@ -2048,6 +2050,7 @@ pub fn parse_error_format(
             Some("pretty-json") => {
                 ErrorOutputType::Json { pretty: true, json_rendered, color_config: json_color }
             }
+            Some("sarif") => ErrorOutputType::Sarif,
             Some("short") => ErrorOutputType::HumanReadable {
                 kind: HumanReadableErrorType { short: true, unicode: false },
                 color_config,
@@ -2063,7 +2066,7 @@ pub fn parse_error_format(
                 });
                 early_dcx.early_fatal(format!(
                     "argument for `--error-format` must be `human`, `human-unicode`, \
-                    `json`, `pretty-json` or `short` (instead was `{arg}`)"
+                    `json`, `pretty-json`, `sarif` or `short` (instead was `{arg}`)"
                 ))
             }
         }
@@ -2124,6 +2127,7 @@ fn check_error_format_stability(
     }
     let format = match format {
         ErrorOutputType::Json { pretty: true, .. } => "pretty-json",
+        ErrorOutputType::Sarif => "sarif",
         ErrorOutputType::HumanReadable { kind, .. } => match kind {
             HumanReadableErrorType { unicode: true, .. } => "human-unicode",
             _ => return,
@@ -2267,6 +2267,8 @@ pub(crate) fn parse_assert_incr_state(
     dep_info_omit_d_target: bool = (false, parse_bool, [TRACKED],
         "in dep-info output, omit targets for tracking dependencies of the dep-info files \
         themselves (default: no)"),
+    diagnostic_baseline: Option<PathBuf> = (None, parse_opt_pathbuf, [UNTRACKED],
+        "suppress the lint warnings recorded in this file, so that only new ones are reported"),
     direct_access_external_data: Option<bool> = (None, parse_opt_bool, [TRACKED],
         "Direct or use GOT indirect to reference external data symbols"),
     disable_fast_paths: bool = (false, parse_bool, [TRACKED],
@@ -2294,6 +2296,9 @@ pub(crate) fn parse_assert_incr_state(
         "exclude the pass number when dumping MIR (used in tests) (default: no)"),
     dump_mir_graphviz: bool = (false, parse_bool, [UNTRACKED],
         "in addition to `.mir` files, create graphviz `.dot` files (default: no)"),
+    dump_mono_graph: Option<PathBuf> = (None, parse_opt_pathbuf, [UNTRACKED],
+        "write the mono item graph as JSON to the given file, with what caused each item to be \
+        instantiated, its estimated size and its codegen units"),
     dump_mono_stats: SwitchWithOptPath = (SwitchWithOptPath::Disabled,
         parse_switch_with_opt_path, [UNTRACKED],
         "output statistics about monomorphization collection"),
@@ -2313,6 +2318,9 @@ pub(crate) fn parse_assert_incr_state(
         "embed metadata in rlibs and dylibs (default: yes)"),
     embed_source: bool = (false, parse_bool, [TRACKED],
         "embed source text in DWARF debug sections (default: no)"),
+    emit_fixes: Option<PathBuf> = (None, parse_opt_pathbuf, [UNTRACKED],
+        "write the machine-applicable suggestions as a diff for each source file \
+        to this directory"),
     emit_stack_sizes: bool = (false, parse_bool, [UNTRACKED],
         "emit a section containing stack size metadata (default: no)"),
     emscripten_wasm_eh: bool = (true, parse_bool, [TRACKED],
@@ -2567,6 +2575,9 @@ pub(crate) fn parse_assert_incr_state(
         "enable queries of the dependency graph for regression testing (default: no)"),
     randomize_layout: bool = (false, parse_bool, [TRACKED],
         "randomize the layout of types (default: no)"),
+    record_diagnostic_baseline: bool = (false, parse_bool, [UNTRACKED],
+        "append the lint warnings that are emitted to the `-Z diagnostic-baseline` file \
+        instead of suppressing the ones it contains (default: no)"),
     reg_struct_return: bool = (false, parse_bool, [TRACKED] { TARGET_MODIFIER: RegStructReturn },
         "On x86-32 targets, it overrides the default ABI to return small structs in registers.
         It is UNSOUND to link together crates that use different values for this flag!"),
@@ -12,13 +12,16 @@
     AppendOnlyVec, DynSend, DynSync, Lock, MappedReadGuard, ReadGuard, RwLock,
 };
 use rustc_errors::annotate_snippet_emitter_writer::AnnotateSnippetEmitter;
+use rustc_errors::baseline::DiagnosticBaseline;
 use rustc_errors::codes::*;
 use rustc_errors::emitter::{DynEmitter, HumanReadableErrorType, OutputTheme, stderr_destination};
+use rustc_errors::fixes::FixExport;
 use rustc_errors::json::JsonEmitter;
+use rustc_errors::sarif::SarifEmitter;
 use rustc_errors::timings::TimingSectionHandler;
 use rustc_errors::{
-    Diag, DiagCtxt, DiagCtxtHandle, DiagMessage, Diagnostic, ErrorGuaranteed, FatalAbort,
-    TerminalUrl,
+    ColorConfig, Diag, DiagCtxt, DiagCtxtHandle, DiagMessage, Diagnostic, ErrorGuaranteed,
+    FatalAbort, TerminalUrl,
 };
 use rustc_feature::UnstableFeatures;
 use rustc_hir::limit::Limit;
@@ -993,6 +996,11 @@ fn default_emitter(sopts: &config::Options, source_map: Arc<SourceMap>) -> Box<D
             .track_diagnostics(track_diagnostics)
             .terminal_url(terminal_url),
         ),
+        config::ErrorOutputType::Sarif => Box::new(SarifEmitter::new(
+            Box::new(io::BufWriter::new(io::stderr())),
+            source_map,
+            false,
+        )),
     }
 }
 
@@ -1026,6 +1034,28 @@ pub fn build_session(
     if let Some(ice_file) = ice_file {
         dcx = dcx.with_ice_file(ice_file);
     }
+    if let Some(path) = &sopts.unstable_opts.diagnostic_baseline {
+        let baseline = if sopts.unstable_opts.record_diagnostic_baseline {
+            DiagnosticBaseline::record(path.clone())
+        } else {
+            DiagnosticBaseline::load(path.clone()).unwrap_or_else(|e| {
+                dcx.handle().fatal(format!(
+                    "failed to read the diagnostic baseline `{}`: {e}",
+                    path.display()
+                ))
+            })
+        };
+        dcx = dcx.with_baseline(baseline);
+    } else if sopts.unstable_opts.record_diagnostic_baseline {
+        dcx.handle().warn("`-Z record-diagnostic-baseline` requires `-Z diagnostic-baseline`");
+    }
+    if let Some(dir) = &sopts.unstable_opts.emit_fixes {
+        // Every compilation has a directory of its own, named like its outputs, so that the
+        // crates of a build don't overwrite each other's diffs.
+        let crate_name = sopts.crate_name.as_deref().unwrap_or_else(|| io.input.filestem());
+        let dir = dir.join(format!("{crate_name}{}", sopts.cg.extra_filename));
+        dcx = dcx.with_fix_export(FixExport::new(dir));
+    }
 
     let host_triple = TargetTuple::from_tuple(config::host_tuple());
     let (host, target_warnings) =
@@ -1469,6 +1499,12 @@ fn mk_emitter(output: ErrorOutputType) -> Box<DynEmitter> {
                 color_config,
             ))
         }
+        // A SARIF log is a single document, which the session writes for the whole
+        // compilation. Diagnostics from before there is a session, or from outside of it, are
+        // rendered for humans instead of adding a second document.
+        config::ErrorOutputType::Sarif => {
+            Box::new(AnnotateSnippetEmitter::new(stderr_destination(ColorConfig::Never)))
+        }
     };
     emitter
 }
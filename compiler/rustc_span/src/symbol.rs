@@ -2044,6 +2044,7 @@
         test_incomplete_feature,
         test_removed_feature,
         test_runner,
+        test_timeout,
         test_unstable_lint,
         thread,
         thread_local,
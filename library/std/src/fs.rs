@@ -166,6 +166,16 @@ pub enum TryLockError {
 /// On other systems, it stores an absolute path (see [`canonicalize()`]). In the latter case, no
 /// [TOCTOU] guarantees are made.
 ///
+/// On Unix, the methods of `Dir` use the `*at` family of functions, such as `openat` and
+/// `unlinkat`. On Linux, relative paths are additionally resolved with `openat2` and
+/// `RESOLVE_BENEATH`, which fails if the path would escape the directory, for example through `..`
+/// or a symbolic link to an absolute path. This requires Linux 5.6 or later: on older kernels,
+/// the methods fail with [`io::ErrorKind::Unsupported`] for relative paths instead of resolving
+/// them without this guarantee. On Windows, the methods open relative paths with `NtCreateFile`
+/// relative to the directory handle, and [`Dir::symlink`] and [`Dir::read_dir`] are not supported
+/// yet. Absolute paths are never resolved relative to the directory. This may change in the
+/// future.
+///
 /// # Examples
 ///
 /// Opens a directory and then a file inside it.
@@ -1598,6 +1608,256 @@ pub fn open_file<P: AsRef<Path>>(&self, path: P) -> io::Result<File> {
             .open_file(path.as_ref(), &OpenOptions::new().read(true).0)
             .map(|f| File { inner: f })
     }
+
+    /// Attempts to open a directory relative to this directory.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `path` does not point to an existing directory.
+    /// Other errors may also be returned according to [`OpenOptions::open`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(dirfd)]
+    /// use std::fs::Dir;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     let dir = Dir::open("foo")?;
+    ///     let subdir = dir.open_dir("bar")?;
+    ///     let f = subdir.open_file("baz.txt")?;
+    ///     Ok(())
+    /// }
+    /// ```
+    #[unstable(feature = "dirfd", issue = "120426")]
+    pub fn open_dir<P: AsRef<Path>>(&self, path: P) -> io::Result<Dir> {
+        self.inner.open_dir(path.as_ref()).map(|inner| Dir { inner })
+    }
+
+    /// Creates a new, empty directory relative to this directory.
+    ///
+    /// This is the equivalent of [`create_dir`] for a path relative to this directory.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(dirfd)]
+    /// use std::fs::Dir;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     let dir = Dir::open("foo")?;
+    ///     dir.create_dir("bar")?;
+    ///     Ok(())
+    /// }
+    /// ```
+    #[unstable(feature = "dirfd", issue = "120426")]
+    pub fn create_dir<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        self.inner.create_dir(path.as_ref())
+    }
+
+    /// Removes a file relative to this directory.
+    ///
+    /// This is the equivalent of [`remove_file`] for a path relative to this directory.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(dirfd)]
+    /// use std::fs::Dir;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     let dir = Dir::open("foo")?;
+    ///     dir.remove_file("bar.txt")?;
+    ///     Ok(())
+    /// }
+    /// ```
+    #[unstable(feature = "dirfd", issue = "120426")]
+    pub fn remove_file<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        self.inner.remove_file(path.as_ref())
+    }
+
+    /// Removes an empty directory relative to this directory.
+    ///
+    /// This is the equivalent of [`remove_dir`] for a path relative to this directory.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(dirfd)]
+    /// use std::fs::Dir;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     let dir = Dir::open("foo")?;
+    ///     dir.remove_dir("bar")?;
+    ///     Ok(())
+    /// }
+    /// ```
+    #[unstable(feature = "dirfd", issue = "120426")]
+    pub fn remove_dir<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        self.inner.remove_dir(path.as_ref())
+    }
+
+    /// Renames a file or directory relative to this directory to `to`, relative to `to_dir`.
+    ///
+    /// This is the equivalent of [`rename`] for paths relative to the two directories, which may
+    /// be the same.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(dirfd)]
+    /// use std::fs::Dir;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     let from = Dir::open("foo")?;
+    ///     let to = Dir::open("bar")?;
+    ///     from.rename("a.txt", &to, "b.txt")?;
+    ///     Ok(())
+    /// }
+    /// ```
+    #[unstable(feature = "dirfd", issue = "120426")]
+    pub fn rename<P: AsRef<Path>, Q: AsRef<Path>>(
+        &self,
+        from: P,
+        to_dir: &Dir,
+        to: Q,
+    ) -> io::Result<()> {
+        self.inner.rename(from.as_ref(), &to_dir.inner, to.as_ref())
+    }
+
+    /// Creates a new symbolic link relative to this directory.
+    ///
+    /// The `link` path will be a symbolic link pointing to the `original` path. `original` is
+    /// stored as is, and is resolved relative to the directory containing `link` when the link is
+    /// followed.
+    ///
+    /// # Platform-specific behavior
+    ///
+    /// This is not supported on Windows yet, and returns an error of kind
+    /// [`io::ErrorKind::Unsupported`] there.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(dirfd)]
+    /// use std::fs::Dir;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     let dir = Dir::open("foo")?;
+    ///     dir.symlink("a.txt", "b.txt")?;
+    ///     Ok(())
+    /// }
+    /// ```
+    #[unstable(feature = "dirfd", issue = "120426")]
+    pub fn symlink<P: AsRef<Path>, Q: AsRef<Path>>(&self, original: P, link: Q) -> io::Result<()> {
+        self.inner.symlink(original.as_ref(), link.as_ref())
+    }
+
+    /// Queries the metadata of a file or directory relative to this directory, following
+    /// symbolic links.
+    ///
+    /// This is the equivalent of [`metadata`] for a path relative to this directory.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(dirfd)]
+    /// use std::fs::Dir;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     let dir = Dir::open("foo")?;
+    ///     let metadata = dir.metadata("bar.txt")?;
+    ///     println!("{}", metadata.len());
+    ///     Ok(())
+    /// }
+    /// ```
+    #[unstable(feature = "dirfd", issue = "120426")]
+    pub fn metadata<P: AsRef<Path>>(&self, path: P) -> io::Result<Metadata> {
+        self.inner.metadata(path.as_ref()).map(Metadata)
+    }
+
+    /// Queries the metadata of a file or directory relative to this directory, without
+    /// following symbolic links.
+    ///
+    /// This is the equivalent of [`symlink_metadata`] for a path relative to this directory.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(dirfd)]
+    /// use std::fs::Dir;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     let dir = Dir::open("foo")?;
+    ///     let metadata = dir.symlink_metadata("bar.txt")?;
+    ///     println!("{:?}", metadata.file_type());
+    ///     Ok(())
+    /// }
+    /// ```
+    #[unstable(feature = "dirfd", issue = "120426")]
+    pub fn symlink_metadata<P: AsRef<Path>>(&self, path: P) -> io::Result<Metadata> {
+        self.inner.symlink_metadata(path.as_ref()).map(Metadata)
+    }
+
+    /// Returns an iterator over the entries of a directory relative to this directory.
+    ///
+    /// This is the equivalent of [`read_dir`] for a path relative to this directory. The paths
+    /// of the returned entries are `path` joined with their file names.
+    ///
+    /// # Platform-specific behavior
+    ///
+    /// This is not supported on Windows yet, and returns an error of kind
+    /// [`io::ErrorKind::Unsupported`] there.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(dirfd)]
+    /// use std::fs::Dir;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     let dir = Dir::open("foo")?;
+    ///     for entry in dir.read_dir("bar")? {
+    ///         println!("{:?}", entry?.file_name());
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    #[unstable(feature = "dirfd", issue = "120426")]
+    pub fn read_dir<P: AsRef<Path>>(&self, path: P) -> io::Result<ReadDir> {
+        self.inner.read_dir(path.as_ref()).map(ReadDir)
+    }
+
+    /// Changes the permissions of a file or directory relative to this directory, following
+    /// symbolic links.
+    ///
+    /// This is the equivalent of [`set_permissions`] for a path relative to this directory.
+    ///
+    /// # Platform-specific behavior
+    ///
+    /// On Linux, symbolic links are only followed as long as they stay beneath this directory,
+    /// like for the other methods, and the mode is changed with `fchmodat2`. This requires
+    /// Linux 6.6 or later: on older kernels, this method fails with
+    /// [`io::ErrorKind::Unsupported`] for relative paths.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(dirfd)]
+    /// use std::fs::Dir;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     let dir = Dir::open("foo")?;
+    ///     let mut perms = dir.metadata("bar.txt")?.permissions();
+    ///     perms.set_readonly(true);
+    ///     dir.set_permissions("bar.txt", perms)?;
+    ///     Ok(())
+    /// }
+    /// ```
+    #[unstable(feature = "dirfd", issue = "120426")]
+    pub fn set_permissions<P: AsRef<Path>>(&self, path: P, perm: Permissions) -> io::Result<()> {
+        self.inner.set_permissions(path.as_ref(), perm.0)
+    }
 }
 
 impl AsInner<fs_imp::Dir> for Dir {
@@ -2516,3 +2516,141 @@ fn test_dir_read_file() {
     let buf = check!(io::read_to_string(f));
     assert_eq!("bar", &buf);
 }
+
+#[test]
+fn test_dir_create_remove() {
+    let tmpdir = tmpdir();
+    let dir = check!(Dir::open(tmpdir.path()));
+    check!(dir.create_dir("a"));
+    check!(dir.create_dir("a/b"));
+    assert!(tmpdir.join("a/b").is_dir());
+    assert_eq!(dir.create_dir("a").unwrap_err().kind(), ErrorKind::AlreadyExists);
+
+    check!(File::create(tmpdir.join("a/b/foo.txt")));
+    check!(dir.remove_file("a/b/foo.txt"));
+    assert!(!tmpdir.join("a/b/foo.txt").exists());
+    assert_eq!(dir.remove_file("a/b/foo.txt").unwrap_err().kind(), ErrorKind::NotFound);
+
+    check!(dir.remove_dir("a/b"));
+    check!(dir.remove_dir("a"));
+    assert!(!tmpdir.join("a").exists());
+}
+
+#[test]
+fn test_dir_open_dir() {
+    let tmpdir = tmpdir();
+    check!(fs::create_dir_all(tmpdir.join("a/b")));
+    check!(fs::write(tmpdir.join("a/b/foo.txt"), "bar"));
+    let dir = check!(Dir::open(tmpdir.path()));
+    let sub = check!(dir.open_dir("a"));
+    let sub = check!(sub.open_dir("b"));
+    let f = check!(sub.open_file("foo.txt"));
+    assert_eq!(check!(io::read_to_string(f)), "bar");
+    assert!(check!(sub.metadata("foo.txt")).is_file());
+    assert!(sub.open_dir("foo.txt").is_err());
+    check!(dir.open_dir(tmpdir.join("a")));
+}
+
+#[test]
+fn test_dir_rename() {
+    let tmpdir = tmpdir();
+    check!(fs::create_dir(tmpdir.join("from")));
+    check!(fs::create_dir(tmpdir.join("to")));
+    check!(fs::write(tmpdir.join("from/foo.txt"), "bar"));
+    let from = check!(Dir::open(tmpdir.join("from")));
+    let to = check!(Dir::open(tmpdir.join("to")));
+
+    check!(from.rename("foo.txt", &from, "baz.txt"));
+    assert!(!tmpdir.join("from/foo.txt").exists());
+    check!(from.rename("baz.txt", &to, "qux.txt"));
+    assert!(!tmpdir.join("from/baz.txt").exists());
+    assert_eq!(check!(fs::read_to_string(tmpdir.join("to/qux.txt"))), "bar");
+}
+
+#[test]
+fn test_dir_metadata_and_symlink() {
+    let tmpdir = tmpdir();
+    if !got_symlink_permission(&tmpdir) {
+        return;
+    };
+    check!(fs::write(tmpdir.join("foo.txt"), "bar"));
+    let dir = check!(Dir::open(tmpdir.path()));
+    if cfg!(windows) {
+        assert_eq!(dir.symlink("foo.txt", "link").unwrap_err().kind(), ErrorKind::Unsupported);
+        check!(symlink_file("foo.txt", tmpdir.join("link")));
+    } else {
+        check!(dir.symlink("foo.txt", "link"));
+    }
+
+    let metadata = check!(dir.metadata("link"));
+    assert!(metadata.is_file());
+    assert_eq!(metadata.len(), 3);
+    assert!(check!(dir.symlink_metadata("link")).file_type().is_symlink());
+    assert_eq!(check!(fs::read_link(tmpdir.join("link"))), Path::new("foo.txt"));
+    assert!(check!(dir.metadata(".")).is_dir());
+    assert_eq!(dir.metadata("missing").unwrap_err().kind(), ErrorKind::NotFound);
+}
+
+#[test]
+#[cfg(not(windows))]
+fn test_dir_read_dir() {
+    let tmpdir = tmpdir();
+    check!(fs::create_dir(tmpdir.join("sub")));
+    check!(fs::write(tmpdir.join("sub/a"), ""));
+    check!(fs::write(tmpdir.join("sub/b"), ""));
+    let dir = check!(Dir::open(tmpdir.path()));
+    let mut names = check!(dir.read_dir("sub"))
+        .map(|entry| check!(entry).file_name().into_string().unwrap())
+        .collect::<Vec<_>>();
+    names.sort();
+    assert_eq!(names, ["a", "b"]);
+}
+
+#[test]
+fn test_dir_set_permissions() {
+    let tmpdir = tmpdir();
+    check!(fs::write(tmpdir.join("foo.txt"), "bar"));
+    let dir = check!(Dir::open(tmpdir.path()));
+    let mut perm = check!(dir.metadata("foo.txt")).permissions();
+    perm.set_readonly(true);
+    match dir.set_permissions("foo.txt", perm.clone()) {
+        // Kernels prior to 6.6 don't have `fchmodat2`.
+        Err(err) if cfg!(target_os = "linux") && err.kind() == ErrorKind::Unsupported => return,
+        result => check!(result),
+    }
+    assert!(check!(fs::metadata(tmpdir.join("foo.txt"))).permissions().readonly());
+    perm.set_readonly(false);
+    check!(dir.set_permissions("foo.txt", perm));
+    assert!(!check!(fs::metadata(tmpdir.join("foo.txt"))).permissions().readonly());
+}
+
+#[test]
+#[cfg(all(target_os = "linux", any(target_env = "gnu", target_env = "musl")))]
+fn test_dir_resolve_beneath() {
+    let tmpdir = tmpdir();
+    check!(fs::create_dir(tmpdir.join("sub")));
+    check!(fs::write(tmpdir.join("foo.txt"), "bar"));
+    check!(symlink_file("../foo.txt", tmpdir.join("sub/escape")));
+    let dir = check!(Dir::open(tmpdir.join("sub")));
+
+    // Absolute paths are opened as is.
+    check!(dir.open_file(tmpdir.join("foo.txt")));
+    // Relative paths may not escape the directory. Without `openat2`, they aren't resolved at all.
+    let err = dir.open_file("../foo.txt").unwrap_err();
+    if err.kind() == ErrorKind::Unsupported {
+        assert_eq!(dir.open_file("escape").unwrap_err().kind(), ErrorKind::Unsupported);
+        return;
+    }
+    assert_eq!(dir.open_file("escape").unwrap_err().raw_os_error(), Some(libc::EXDEV));
+    assert!(dir.metadata("../foo.txt").is_err());
+    assert!(dir.open_dir("..").is_err());
+    assert!(dir.remove_file("../foo.txt").is_err());
+    assert!(tmpdir.join("foo.txt").exists());
+
+    // Symlinks are not followed outside of the directory when changing permissions either.
+    let mut perm = check!(fs::metadata(tmpdir.join("foo.txt"))).permissions();
+    perm.set_readonly(true);
+    let err = dir.set_permissions("escape", perm).unwrap_err();
+    assert!(err.raw_os_error() == Some(libc::EXDEV) || err.kind() == ErrorKind::Unsupported);
+    assert!(!check!(fs::metadata(tmpdir.join("foo.txt"))).permissions().readonly());
+}
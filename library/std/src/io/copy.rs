@@ -55,6 +55,12 @@
 /// `sendfile(2)` or `splice(2)` syscalls to move data directly between file
 /// descriptors if possible.
 ///
+/// Copying from a regular file (or a [`BufReader`] of one) into a socket or a
+/// pipe does not use these syscalls and copies the data through userspace
+/// instead: the kernel may still send the file's pages after the call
+/// returns, so later writes to the file could change the data the peer
+/// receives.
+///
 /// Note that platform-specific behavior [may change in the future][changes].
 ///
 /// [changes]: crate::io#platform-specific-behavior
@@ -64,17 +70,89 @@ pub fn copy<R: ?Sized, W: ?Sized>(reader: &mut R, writer: &mut W) -> Result<u64>
     R: Read,
     W: Write,
 {
-    match kernel_copy(reader, writer)? {
+    copy_reporting(reader, writer, &mut |_| {})
+}
+
+/// Copies the entire contents of a reader into a writer, reporting the number
+/// of bytes copied as it goes.
+///
+/// This function behaves like [`copy`], including its use of
+/// platform-specific syscalls, but calls `progress` with the total number of
+/// bytes copied so far each time more data has been written to `writer`. How
+/// often that happens depends on the reader, the writer and the platform: for
+/// example, a single syscall may copy gigabytes between two files.
+///
+/// On success, the total number of bytes that were copied from `reader` to
+/// `writer` is returned, which is also the last value passed to `progress`.
+///
+/// # Errors
+///
+/// This function returns an error in the same cases as [`copy`]. Bytes that
+/// were reported to `progress` before the error have been written to `writer`.
+///
+/// # Examples
+///
+/// ```
+/// #![feature(copy_with_progress)]
+/// use std::io;
+///
+/// fn main() -> io::Result<()> {
+///     let mut reader: &[u8] = b"hello";
+///     let mut writer: Vec<u8> = vec![];
+///     let mut reported = 0;
+///
+///     let copied = io::copy_with_progress(&mut reader, &mut writer, |total| reported = total)?;
+///
+///     assert_eq!(copied, 5);
+///     assert_eq!(reported, 5);
+///     assert_eq!(&b"hello"[..], &writer[..]);
+///     Ok(())
+/// }
+/// ```
+#[unstable(feature = "copy_with_progress", issue = "none")]
+pub fn copy_with_progress<R: ?Sized, W: ?Sized, F>(
+    reader: &mut R,
+    writer: &mut W,
+    mut progress: F,
+) -> Result<u64>
+where
+    R: Read,
+    W: Write,
+    F: FnMut(u64),
+{
+    let mut total = 0;
+    copy_reporting(reader, writer, &mut |bytes| {
+        total += bytes;
+        progress(total);
+    })
+}
+
+/// The implementation of `io::copy` and `io::copy_with_progress`. `progress`
+/// is called with the number of bytes written by each step of the copy.
+fn copy_reporting<R: ?Sized, W: ?Sized>(
+    reader: &mut R,
+    writer: &mut W,
+    progress: &mut dyn FnMut(u64),
+) -> Result<u64>
+where
+    R: Read,
+    W: Write,
+{
+    match kernel_copy(reader, writer, progress)? {
         CopyState::Ended(copied) => Ok(copied),
         CopyState::Fallback(copied) => {
-            generic_copy(reader, writer).map(|additional| copied + additional)
+            generic_copy(reader, writer, progress).map(|additional| copied + additional)
         }
     }
 }
 
 /// The userspace read-write-loop implementation of `io::copy` that is used when
 /// OS-specific specializations for copy offloading are not available or not applicable.
-fn generic_copy<R: ?Sized, W: ?Sized>(reader: &mut R, writer: &mut W) -> Result<u64>
+fn generic_copy<R: ?Sized, W: ?Sized>(
+    reader: &mut R,
+    writer: &mut W,
+    progress: &mut dyn FnMut(u64),
+) -> Result<u64>
 where
     R: Read,
     W: Write,
@@ -83,10 +161,10 @@ fn generic_copy<R: ?Sized, W: ?Sized>(reader: &mut R, writer: &mut W) -> Result<
     let write_buf = BufferedWriterSpec::buffer_size(writer);
 
     if read_buf >= DEFAULT_BUF_SIZE && read_buf >= write_buf {
-        return BufferedReaderSpec::copy_to(reader, writer);
+        return BufferedReaderSpec::copy_to(reader, writer, progress);
     }
 
-    BufferedWriterSpec::copy_from(writer, reader)
+    BufferedWriterSpec::copy_from(writer, reader, progress)
 }
 
 /// Specialization of the read-write loop that reuses the internal
@@ -95,7 +173,11 @@ fn generic_copy<R: ?Sized, W: ?Sized>(reader: &mut R, writer: &mut W) -> Result<
 trait BufferedReaderSpec {
     fn buffer_size(&self) -> usize;
 
-    fn copy_to(&mut self, to: &mut (impl Write + ?Sized)) -> Result<u64>;
+    fn copy_to(
+        &mut self,
+        to: &mut (impl Write + ?Sized),
+        progress: &mut dyn FnMut(u64),
+    ) -> Result<u64>;
 }
 
 impl<T> BufferedReaderSpec for T
@@ -108,7 +190,11 @@ impl<T> BufferedReaderSpec for T
         0
     }
 
-    default fn copy_to(&mut self, _to: &mut (impl Write + ?Sized)) -> Result<u64> {
+    default fn copy_to(
+        &mut self,
+        _to: &mut (impl Write + ?Sized),
+        _progress: &mut dyn FnMut(u64),
+    ) -> Result<u64> {
         unreachable!("only called from specializations")
     }
 }
@@ -120,10 +206,17 @@ fn buffer_size(&self) -> usize {
         usize::MAX
     }
 
-    fn copy_to(&mut self, to: &mut (impl Write + ?Sized)) -> Result<u64> {
+    fn copy_to(
+        &mut self,
+        to: &mut (impl Write + ?Sized),
+        progress: &mut dyn FnMut(u64),
+    ) -> Result<u64> {
         let len = self.len();
         to.write_all(self)?;
         *self = &self[len..];
+        if len > 0 {
+            progress(len as u64);
+        }
         Ok(len as u64)
     }
 }
@@ -135,12 +228,19 @@ fn buffer_size(&self) -> usize {
         usize::MAX
     }
 
-    fn copy_to(&mut self, to: &mut (impl Write + ?Sized)) -> Result<u64> {
+    fn copy_to(
+        &mut self,
+        to: &mut (impl Write + ?Sized),
+        progress: &mut dyn FnMut(u64),
+    ) -> Result<u64> {
         let len = self.len();
         let (front, back) = self.as_slices();
         let bufs = &mut [IoSlice::new(front), IoSlice::new(back)];
         to.write_all_vectored(bufs)?;
         self.clear();
+        if len > 0 {
+            progress(len as u64);
+        }
         Ok(len as u64)
     }
 }
@@ -154,7 +254,11 @@ fn buffer_size(&self) -> usize {
         self.capacity()
     }
 
-    fn copy_to(&mut self, to: &mut (impl Write + ?Sized)) -> Result<u64> {
+    fn copy_to(
+        &mut self,
+        to: &mut (impl Write + ?Sized),
+        progress: &mut dyn FnMut(u64),
+    ) -> Result<u64> {
         let mut len = 0;
 
         loop {
@@ -179,6 +283,7 @@ fn copy_to(&mut self, to: &mut (impl Write + ?Sized)) -> Result<u64> {
             // a copy between buffered inputs and outputs.
             to.write_all(buf)?;
             len += buf.len() as u64;
+            progress(buf.len() as u64);
             self.discard_buffer();
         }
     }
@@ -189,7 +294,11 @@ fn copy_to(&mut self, to: &mut (impl Write + ?Sized)) -> Result<u64> {
 trait BufferedWriterSpec: Write {
     fn buffer_size(&self) -> usize;
 
-    fn copy_from<R: Read + ?Sized>(&mut self, reader: &mut R) -> Result<u64>;
+    fn copy_from<R: Read + ?Sized>(
+        &mut self,
+        reader: &mut R,
+        progress: &mut dyn FnMut(u64),
+    ) -> Result<u64>;
 }
 
 impl<W: Write + ?Sized> BufferedWriterSpec for W {
@@ -198,8 +307,12 @@ impl<W: Write + ?Sized> BufferedWriterSpec for W {
         0
     }
 
-    default fn copy_from<R: Read + ?Sized>(&mut self, reader: &mut R) -> Result<u64> {
-        stack_buffer_copy(reader, self)
+    default fn copy_from<R: Read + ?Sized>(
+        &mut self,
+        reader: &mut R,
+        progress: &mut dyn FnMut(u64),
+    ) -> Result<u64> {
+        stack_buffer_copy(reader, self, progress)
     }
 }
 
@@ -208,9 +321,13 @@ fn buffer_size(&self) -> usize {
         self.capacity()
     }
 
-    fn copy_from<R: Read + ?Sized>(&mut self, reader: &mut R) -> Result<u64> {
+    fn copy_from<R: Read + ?Sized>(
+        &mut self,
+        reader: &mut R,
+        progress: &mut dyn FnMut(u64),
+    ) -> Result<u64> {
         if self.capacity() < DEFAULT_BUF_SIZE {
-            return stack_buffer_copy(reader, self);
+            return stack_buffer_copy(reader, self, progress);
         }
 
         let mut len = 0;
@@ -238,6 +355,7 @@ fn copy_from<R: Read + ?Sized>(&mut self, reader: &mut R) -> Result<u64> {
 
                         init = read_buf.is_init();
                         len += bytes_read as u64;
+                        progress(bytes_read as u64);
 
                         // SAFETY: BorrowedBuf guarantees all of its filled bytes are init
                         unsafe { buf.set_len(buf.len() + bytes_read) };
@@ -262,14 +380,24 @@ fn buffer_size(&self) -> usize {
         cmp::max(DEFAULT_BUF_SIZE, self.capacity() - self.len())
     }
 
-    fn copy_from<R: Read + ?Sized>(&mut self, reader: &mut R) -> Result<u64> {
-        reader.read_to_end(self).map(|bytes| u64::try_from(bytes).expect("usize overflowed u64"))
+    fn copy_from<R: Read + ?Sized>(
+        &mut self,
+        reader: &mut R,
+        progress: &mut dyn FnMut(u64),
+    ) -> Result<u64> {
+        let bytes = reader.read_to_end(self)?;
+        let bytes = u64::try_from(bytes).expect("usize overflowed u64");
+        if bytes > 0 {
+            progress(bytes);
+        }
+        Ok(bytes)
     }
 }
 
 fn stack_buffer_copy<R: Read + ?Sized, W: Write + ?Sized>(
     reader: &mut R,
     writer: &mut W,
+    progress: &mut dyn FnMut(u64),
 ) -> Result<u64> {
     let buf: &mut [_] = &mut [MaybeUninit::uninit(); DEFAULT_BUF_SIZE];
     let mut buf: BorrowedBuf<'_> = buf.into();
@@ -289,6 +417,7 @@ fn stack_buffer_copy<R: Read + ?Sized, W: Write + ?Sized>(
 
         len += buf.filled().len() as u64;
         writer.write_all(buf.filled())?;
+        progress(buf.filled().len() as u64);
         buf.clear();
     }
 
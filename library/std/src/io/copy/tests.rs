@@ -118,6 +118,34 @@ fn copy_specializes_from_slice() {
     assert_eq!(60 * 1024, sink.observed_buffer);
 }
 
+#[test]
+fn copy_with_progress_reports_totals() {
+    let cap = 117 * 1024;
+    let mut r = ShortReader { cap, observed_buffer: 0, read_size: 1337 };
+    let mut w = WriteObserver { observed_buffer: 0 };
+    let mut reports = Vec::new();
+    let copied = copy_with_progress(&mut r, &mut w, |total| reports.push(total)).unwrap();
+    assert_eq!(copied, cap as u64);
+    assert_eq!(reports.last(), Some(&copied));
+    assert!(reports.len() > 1, "expected a report for every chunk, got {reports:?}");
+    assert!(reports.is_sorted_by(|a, b| a < b), "expected increasing totals, got {reports:?}");
+
+    let mut r = BufReader::with_capacity(
+        16 * 1024,
+        ShortReader { cap, observed_buffer: 0, read_size: 1337 },
+    );
+    let mut w = BufWriter::with_capacity(8 * 1024, WriteObserver { observed_buffer: 0 });
+    let mut last = 0;
+    assert_eq!(copy_with_progress(&mut r, &mut w, |total| last = total).unwrap(), cap as u64);
+    assert_eq!(last, cap as u64);
+
+    let mut reports = Vec::new();
+    let copied =
+        copy_with_progress(&mut empty(), &mut sink(), |total| reports.push(total)).unwrap();
+    assert_eq!(copied, 0);
+    assert!(reports.is_empty(), "nothing was copied, got {reports:?}");
+}
+
 #[cfg(unix)]
 mod io_benches {
     use test::Bencher;
@@ -305,6 +305,8 @@
 
 #[stable(feature = "bufwriter_into_parts", since = "1.56.0")]
 pub use self::buffered::WriterPanicked;
+#[unstable(feature = "copy_with_progress", issue = "none")]
+pub use self::copy::copy_with_progress;
 #[unstable(feature = "raw_os_error_ty", issue = "107792")]
 pub use self::error::RawOsError;
 #[doc(hidden)]
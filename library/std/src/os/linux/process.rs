@@ -207,6 +207,37 @@ pub trait CommandExt: Sealed {
     /// [`pidfd`]: fn@ChildExt::pidfd
     /// [`into_pidfd`]: ChildExt::into_pidfd
     fn create_pidfd(&mut self, val: bool) -> &mut process::Command;
+
+    /// Places the [`Child`] spawned by this [`Command`] in a cgroup.
+    ///
+    /// `cgroup` must be a file descriptor that refers to a cgroup v2 directory, such as one
+    /// opened with `O_PATH` or `O_DIRECTORY` under `/sys/fs/cgroup`. The child is in the cgroup
+    /// before it executes a single instruction of the new program, so it is subject to the
+    /// limits of the cgroup from the start, without the race of moving it there after spawning.
+    ///
+    /// Where the C library supports it (glibc 2.41 and later), this uses `posix_spawn` with
+    /// `clone3`'s `CLONE_INTO_CGROUP`, which requires Linux 5.7 or later. Otherwise the child is
+    /// forked and moves itself into the cgroup, by writing to its `cgroup.procs` file, before
+    /// it executes the program. With [`exec`], the current process is moved into the cgroup.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(process_cgroup)]
+    /// use std::fs::File;
+    /// use std::os::linux::process::CommandExt;
+    /// use std::process::Command;
+    ///
+    /// let cgroup = File::open("/sys/fs/cgroup/build.slice")?;
+    /// let status = Command::new("make").cgroup(cgroup.into()).status()?;
+    /// # std::io::Result::Ok(())
+    /// ```
+    ///
+    /// [`Command`]: process::Command
+    /// [`Child`]: process::Child
+    /// [`exec`]: crate::os::unix::process::CommandExt::exec
+    #[unstable(feature = "process_cgroup", issue = "none")]
+    fn cgroup(&mut self, cgroup: OwnedFd) -> &mut process::Command;
 }
 
 impl CommandExt for process::Command {
@@ -214,4 +245,9 @@ fn create_pidfd(&mut self, val: bool) -> &mut process::Command {
         self.as_inner_mut().create_pidfd(val);
         self
     }
+
+    fn cgroup(&mut self, cgroup: OwnedFd) -> &mut process::Command {
+        self.as_inner_mut().cgroup(cgroup);
+        self
+    }
 }
@@ -219,6 +219,43 @@ fn arg0<S>(&mut self, arg: S) -> &mut process::Command
 
     #[unstable(feature = "process_setsid", issue = "105376")]
     fn setsid(&mut self, setsid: bool) -> &mut process::Command;
+
+    /// Sets a resource limit of the child process. Equivalent to a `setrlimit` call in the child
+    /// process, before it drops privileges with [`uid`] or [`gid`].
+    ///
+    /// `soft` is the limit that the kernel enforces, and `hard` is the ceiling up to which the
+    /// child may raise the soft limit itself. A value of `u64::MAX`, or any value that the
+    /// platform can't represent, means no limit (`RLIM_INFINITY`). Setting the same resource
+    /// again replaces the previous limit.
+    ///
+    /// `posix_spawn` has no way to set resource limits in the child, so setting any of them
+    /// makes [`spawn`] fall back to `fork` and `exec`, like [`pre_exec`] does. This is
+    /// noticeably slower for a parent process with a lot of mapped memory. To cap the memory of
+    /// many children on Linux, spawning them into a cgroup with a `memory.max` limit using the
+    /// Linux-specific `CommandExt::cgroup` keeps the `posix_spawn` path where the C library
+    /// supports it.
+    ///
+    /// [`uid`]: CommandExt::uid
+    /// [`gid`]: CommandExt::gid
+    /// [`spawn`]: process::Command::spawn
+    /// [`pre_exec`]: CommandExt::pre_exec
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(process_rlimit)]
+    /// use std::os::unix::process::{CommandExt, Resource};
+    /// use std::process::Command;
+    ///
+    /// // Limit the address space of the child to 1 GiB.
+    /// let status = Command::new("cc")
+    ///     .arg("main.c")
+    ///     .rlimit(Resource::AddressSpace, 1 << 30, 1 << 30)
+    ///     .status()?;
+    /// # std::io::Result::Ok(())
+    /// ```
+    #[unstable(feature = "process_rlimit", issue = "none")]
+    fn rlimit(&mut self, resource: Resource, soft: u64, hard: u64) -> &mut process::Command;
 }
 
 #[stable(feature = "rust1", since = "1.0.0")]
@@ -274,6 +311,34 @@ fn setsid(&mut self, setsid: bool) -> &mut process::Command {
         self.as_inner_mut().setsid(setsid);
         self
     }
+
+    fn rlimit(&mut self, resource: Resource, soft: u64, hard: u64) -> &mut process::Command {
+        self.as_inner_mut().rlimit(resource, soft, hard);
+        self
+    }
+}
+
+/// A resource whose consumption can be limited with [`CommandExt::rlimit`].
+///
+/// These are the resources that POSIX defines for `setrlimit`.
+#[unstable(feature = "process_rlimit", issue = "none")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Resource {
+    /// The maximum size of a core file in bytes (`RLIMIT_CORE`).
+    Core,
+    /// The maximum CPU time in seconds (`RLIMIT_CPU`).
+    Cpu,
+    /// The maximum size of the data segment in bytes (`RLIMIT_DATA`).
+    Data,
+    /// The maximum size of a file that may be created, in bytes (`RLIMIT_FSIZE`).
+    FileSize,
+    /// One more than the highest file descriptor number that may be opened (`RLIMIT_NOFILE`).
+    OpenFiles,
+    /// The maximum size of the stack of the main thread in bytes (`RLIMIT_STACK`).
+    Stack,
+    /// The maximum size of the virtual address space in bytes (`RLIMIT_AS`).
+    AddressSpace,
 }
 
 /// Unix-specific extensions to [`process::ExitStatus`] and
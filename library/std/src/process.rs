@@ -168,6 +168,7 @@
 use crate::num::NonZero;
 use crate::path::Path;
 use crate::sys::{AsInner, AsInnerMut, FromInner, IntoInner, process as imp};
+use crate::time::Duration;
 use crate::{fmt, format_args_nl, fs, str};
 
 /// Representation of a running or exited child process.
@@ -1114,6 +1115,45 @@ pub fn output(&mut self) -> io::Result<Output> {
         Ok(Output { status: ExitStatus(status), stdout, stderr })
     }
 
+    /// Executes the command as a child process like [`output`], but kills it if it is still
+    /// running after `timeout`.
+    ///
+    /// Returns the collected output, and whether the timeout elapsed. If it did, the output holds
+    /// whatever the child wrote to stdout and stderr until then, and the exit status of the
+    /// killed child.
+    ///
+    /// # Platform-specific behavior
+    ///
+    /// Only the child itself is killed. If it started processes of its own that inherited its
+    /// stdout or stderr, their output after the timeout is discarded.
+    ///
+    /// This function is currently not supported on UEFI and Motor OS.
+    ///
+    /// [`output`]: Command::output
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(child_wait_timeout)]
+    /// use std::process::Command;
+    /// use std::time::Duration;
+    ///
+    /// let (output, timed_out) = Command::new("sh")
+    ///     .arg("-c")
+    ///     .arg("echo started; sleep 60")
+    ///     .output_with_timeout(Duration::from_secs(1))?;
+    ///
+    /// assert!(timed_out);
+    /// assert_eq!(output.stdout, b"started\n");
+    /// # std::io::Result::Ok(())
+    /// ```
+    #[unstable(feature = "child_wait_timeout", issue = "none")]
+    pub fn output_with_timeout(&mut self, timeout: Duration) -> io::Result<(Output, bool)> {
+        let (status, stdout, stderr, timed_out) =
+            imp::output_with_timeout(&mut self.inner, timeout)?;
+        Ok((Output { status: ExitStatus(status), stdout, stderr }, timed_out))
+    }
+
     /// Executes a command as a child process, waiting for it to finish and
     /// collecting its status.
     ///
@@ -2402,6 +2442,50 @@ pub fn try_wait(&mut self) -> io::Result<Option<ExitStatus>> {
         Ok(self.handle.try_wait()?.map(ExitStatus))
     }
 
+    /// Waits for the child to exit for at most `timeout`.
+    ///
+    /// If the child exits in time, `Ok(Some(status))` is returned, and the child is reaped like
+    /// by [`wait`]. If it is still running after `timeout`, `Ok(None)` is returned, and the child
+    /// keeps running.
+    ///
+    /// The stdin handle to the child process, if any, will be closed
+    /// before waiting, as in [`wait`].
+    ///
+    /// # Platform-specific behavior
+    ///
+    /// On Linux, this waits on a pidfd of the child with `poll`, opening one if the child was not
+    /// spawned with [`create_pidfd`]. On Windows, it uses `WaitForSingleObject`. On other
+    /// platforms, and on Linux kernels without pidfd support, it checks whether the child has
+    /// exited at increasing intervals of up to 100 milliseconds, so it may return slightly later
+    /// than the child exits. This may change in the future.
+    ///
+    /// [`wait`]: Child::wait
+    /// [`create_pidfd`]: crate::os::linux::process::CommandExt::create_pidfd
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(child_wait_timeout)]
+    /// use std::process::Command;
+    /// use std::time::Duration;
+    ///
+    /// let mut child = Command::new("sleep").arg("60").spawn()?;
+    ///
+    /// match child.wait_timeout(Duration::from_secs(1))? {
+    ///     Some(status) => println!("exited with: {status}"),
+    ///     None => {
+    ///         child.kill()?;
+    ///         child.wait()?;
+    ///     }
+    /// }
+    /// # std::io::Result::Ok(())
+    /// ```
+    #[unstable(feature = "child_wait_timeout", issue = "none")]
+    pub fn wait_timeout(&mut self, timeout: Duration) -> io::Result<Option<ExitStatus>> {
+        drop(self.stdin.take());
+        Ok(self.handle.wait_timeout(timeout)?.map(ExitStatus))
+    }
+
     /// Simultaneously waits for the child to exit and collect all remaining
     /// output on the stdout/stderr handles, returning an `Output`
     /// instance.
@@ -3,6 +3,7 @@
 use crate::io::{BorrowedBuf, ErrorKind};
 use crate::mem::MaybeUninit;
 use crate::str;
+use crate::time::{Duration, Instant};
 
 fn known_command() -> Command {
     if cfg!(windows) {
@@ -139,6 +140,61 @@ fn set_current_dir_works() {
     }
 }
 
+#[test]
+#[cfg(unix)]
+#[cfg_attr(
+    any(target_os = "vxworks", all(target_vendor = "apple", not(target_os = "macos"))),
+    ignore = "no shell available"
+)]
+fn rlimit_works() {
+    use crate::os::unix::process::{CommandExt, Resource};
+
+    let mut cmd = shell_cmd();
+    cmd.arg("-c").arg("ulimit -n; ulimit -Hn").stdout(Stdio::piped());
+    cmd.rlimit(Resource::OpenFiles, 32, 64);
+    assert_eq!(run_output(cmd), "32\n64\n");
+
+    // The last limit set for a resource wins.
+    let mut cmd = shell_cmd();
+    cmd.arg("-c").arg("ulimit -n").stdout(Stdio::piped());
+    cmd.rlimit(Resource::OpenFiles, 32, 64).rlimit(Resource::OpenFiles, 48, 64);
+    assert_eq!(run_output(cmd), "48\n");
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn cgroup_works() {
+    use crate::os::linux::process::CommandExt;
+
+    // Spawn the child into our own cgroup, which needs cgroup v2 to be mounted
+    // at the usual place.
+    if !crate::fs::exists("/sys/fs/cgroup/cgroup.controllers").unwrap_or(false) {
+        return;
+    }
+    let ours = crate::fs::read_to_string("/proc/self/cgroup").unwrap();
+    let Some(path) = ours.lines().find_map(|line| line.strip_prefix("0::")) else {
+        return;
+    };
+    let Ok(cgroup) = crate::fs::File::open(format!("/sys/fs/cgroup{path}")) else {
+        return;
+    };
+
+    let output = Command::new("cat").arg("/proc/self/cgroup").cgroup(cgroup.into()).output();
+    match output {
+        Ok(output) => {
+            assert!(output.status.success());
+            assert_eq!(String::from_utf8(output.stdout).unwrap(), ours);
+        }
+        // Old kernels, sandboxes and restrictive cgroup hierarchies.
+        Err(e)
+            if matches!(
+                e.kind(),
+                ErrorKind::Unsupported | ErrorKind::PermissionDenied | ErrorKind::ResourceBusy
+            ) => {}
+        Err(e) => panic!("failed to spawn into a cgroup: {e}"),
+    }
+}
+
 #[test]
 #[cfg_attr(windows, ignore)]
 #[cfg_attr(
@@ -309,6 +365,69 @@ fn test_wait_with_output_once() {
     assert_eq!(stderr, Vec::new());
 }
 
+#[test]
+#[cfg_attr(
+    any(target_os = "vxworks", all(target_vendor = "apple", not(target_os = "macos"))),
+    ignore = "no shell available"
+)]
+fn test_wait_timeout() {
+    let mut prog = if cfg!(target_os = "windows") {
+        Command::new("cmd").args(&["/C", "ping -n 60 127.0.0.1 > nul"]).spawn().unwrap()
+    } else {
+        shell_cmd().arg("-c").arg("exec sleep 60").spawn().unwrap()
+    };
+    let start = Instant::now();
+    assert!(prog.wait_timeout(Duration::from_millis(100)).unwrap().is_none());
+    assert!(start.elapsed() >= Duration::from_millis(100));
+    prog.kill().unwrap();
+    let status = prog.wait_timeout(Duration::from_secs(60)).unwrap().unwrap();
+    assert!(!status.success());
+    assert_eq!(prog.wait_timeout(Duration::ZERO).unwrap(), Some(status));
+
+    let mut prog = if cfg!(target_os = "windows") {
+        Command::new("cmd").args(&["/C", "exit 1"]).spawn().unwrap()
+    } else {
+        shell_cmd().arg("-c").arg("false").spawn().unwrap()
+    };
+    assert_eq!(prog.wait_timeout(Duration::from_secs(60)).unwrap().unwrap().code(), Some(1));
+    assert_eq!(prog.wait().unwrap().code(), Some(1));
+}
+
+#[test]
+#[cfg_attr(
+    any(target_os = "vxworks", all(target_vendor = "apple", not(target_os = "macos"))),
+    ignore = "no shell available"
+)]
+fn test_output_with_timeout() {
+    let (output, timed_out) = if cfg!(target_os = "windows") {
+        Command::new("cmd").args(&["/C", "echo hello"]).output_with_timeout(Duration::from_secs(60))
+    } else {
+        shell_cmd().arg("-c").arg("echo hello").output_with_timeout(Duration::from_secs(60))
+    }
+    .unwrap();
+    assert!(!timed_out);
+    assert!(output.status.success());
+    assert_eq!(str::from_utf8(&output.stdout).unwrap().trim(), "hello");
+
+    let (output, timed_out) = if cfg!(target_os = "windows") {
+        Command::new("cmd")
+            .args(&["/C", "echo started & ping -n 60 127.0.0.1 > nul"])
+            .output_with_timeout(Duration::from_millis(500))
+    } else {
+        shell_cmd()
+            .arg("-c")
+            .arg("echo started; echo error >&2; exec sleep 60")
+            .output_with_timeout(Duration::from_millis(500))
+    }
+    .unwrap();
+    assert!(timed_out);
+    assert!(!output.status.success());
+    assert_eq!(str::from_utf8(&output.stdout).unwrap().trim(), "started");
+    if cfg!(unix) {
+        assert_eq!(output.stderr, b"error\n");
+    }
+}
+
 #[cfg(all(unix, not(target_os = "android")))]
 pub fn env_cmd() -> Command {
     Command::new("env")
@@ -2,7 +2,7 @@
 
 use super::context::Context;
 use super::error::*;
-use super::select::{Operation, Selected, Token};
+use super::select::{Operation, SelectHandle, Selected, Token};
 use super::utils::{Backoff, CachePadded};
 use super::waker::SyncWaker;
 use crate::cell::UnsafeCell;
@@ -171,6 +171,16 @@ pub(crate) fn new() -> Self {
         }
     }
 
+    /// Returns a receiver handle to the channel.
+    pub(crate) fn receiver(&self) -> Receiver<'_, T> {
+        Receiver(self)
+    }
+
+    /// Returns a sender handle to the channel.
+    pub(crate) fn sender(&self) -> Sender<'_, T> {
+        Sender(self)
+    }
+
     /// Attempts to reserve a slot for sending a message.
     fn start_send(&self, token: &mut Token) -> bool {
         let backoff = Backoff::new();
@@ -666,3 +676,45 @@ fn drop(&mut self) {
         }
     }
 }
+
+/// Receiver handle to a channel.
+pub(crate) struct Receiver<'a, T>(&'a Channel<T>);
+
+/// Sender handle to a channel.
+pub(crate) struct Sender<'a, T>(&'a Channel<T>);
+
+impl<T> SelectHandle for Receiver<'_, T> {
+    fn try_select(&self, token: &mut Token) -> bool {
+        self.0.start_recv(token)
+    }
+
+    fn register(&self, oper: Operation, cx: &Context) -> bool {
+        self.0.receivers.register(oper, cx);
+        !self.0.is_empty() || self.0.is_disconnected()
+    }
+
+    fn unregister(&self, oper: Operation) {
+        self.0.receivers.unregister(oper);
+    }
+
+    fn accept(&self, token: &mut Token, _cx: &Context) -> bool {
+        self.try_select(token)
+    }
+}
+
+impl<T> SelectHandle for Sender<'_, T> {
+    fn try_select(&self, token: &mut Token) -> bool {
+        self.0.start_send(token)
+    }
+
+    fn register(&self, _oper: Operation, _cx: &Context) -> bool {
+        // The channel is unbounded, so sending is always ready.
+        true
+    }
+
+    fn unregister(&self, _oper: Operation) {}
+
+    fn accept(&self, token: &mut Token, _cx: &Context) -> bool {
+        self.try_select(token)
+    }
+}
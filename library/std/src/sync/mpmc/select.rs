@@ -1,3 +1,13 @@
+//! Waiting on several channel operations at once.
+
+use super::context::Context;
+use super::error::{RecvError, SelectTimeoutError, SendError, TrySelectError};
+use super::utils;
+use super::{Receiver, Sender};
+use crate::fmt;
+use crate::marker::PhantomData;
+use crate::time::{Duration, Instant};
+
 /// Temporary data that gets initialized during a blocking operation, and is consumed by
 /// `read` or `write`.
 ///
@@ -69,3 +79,909 @@ fn into(self) -> usize {
         }
     }
 }
+
+/// A receiver or a sender that can participate in select.
+///
+/// This is a handle that assists select in executing an operation, registration, deciding on the
+/// appropriate deadline for blocking, etc.
+pub(crate) trait SelectHandle {
+    /// Attempts to select an operation and returns `true` on success.
+    fn try_select(&self, token: &mut Token) -> bool;
+
+    /// Registers an operation for execution and returns `true` if it is ready.
+    fn register(&self, oper: Operation, cx: &Context) -> bool;
+
+    /// Unregisters an operation for execution.
+    fn unregister(&self, oper: Operation);
+
+    /// Attempts to select an operation the thread got woken up for and returns `true` on success.
+    fn accept(&self, token: &mut Token, cx: &Context) -> bool;
+}
+
+/// Determines when a select operation should time out.
+#[derive(Clone, Copy, Eq, PartialEq)]
+enum Timeout {
+    /// No blocking.
+    Now,
+
+    /// Block forever.
+    Never,
+
+    /// Time out after the time instant.
+    At(Instant),
+}
+
+/// Runs until one of the operations is selected, potentially blocking the current thread.
+///
+/// Successful receive operations will have to be followed up by `read` and successful send
+/// operations by `write`.
+fn run_select(
+    handles: &mut [(&dyn SelectHandle, usize, *const u8)],
+    timeout: Timeout,
+) -> Option<(Token, usize, *const u8)> {
+    if handles.is_empty() {
+        // Wait until the timeout and return.
+        match timeout {
+            Timeout::Now => return None,
+            Timeout::Never => {
+                utils::sleep_until(None);
+                unreachable!();
+            }
+            Timeout::At(when) => {
+                utils::sleep_until(Some(when));
+                return None;
+            }
+        }
+    }
+
+    // Shuffle the operations for fairness.
+    utils::shuffle(handles);
+
+    // Create a token, which serves as a temporary variable that gets initialized in this function
+    // and is later used by a call to `read` or `write` that completes the selected operation.
+    let mut token = Token::default();
+
+    // Try selecting one of the operations without blocking.
+    for &(handle, i, ptr) in handles.iter() {
+        if handle.try_select(&mut token) {
+            return Some((token, i, ptr));
+        }
+    }
+
+    loop {
+        // Prepare for blocking.
+        let res = Context::with(|cx| {
+            let mut sel = Selected::Waiting;
+            let mut registered_count = 0;
+            let mut index_ready = None;
+
+            if let Timeout::Now = timeout {
+                cx.try_select(Selected::Aborted).unwrap();
+            }
+
+            // Register all operations.
+            for entry in handles.iter_mut() {
+                registered_count += 1;
+                let handle = entry.0;
+                let i = entry.1;
+
+                // If registration returns `false`, that means the operation has just become ready.
+                if handle.register(Operation::hook(entry), cx) {
+                    // Try aborting select.
+                    sel = match cx.try_select(Selected::Aborted) {
+                        Ok(()) => {
+                            index_ready = Some(i);
+                            Selected::Aborted
+                        }
+                        Err(s) => s,
+                    };
+                    break;
+                }
+
+                // If another thread has already selected one of the operations, stop registration.
+                sel = cx.selected();
+                if sel != Selected::Waiting {
+                    break;
+                }
+            }
+
+            if sel == Selected::Waiting {
+                let deadline = match timeout {
+                    Timeout::Now => unreachable!(),
+                    Timeout::Never => None,
+                    Timeout::At(when) => Some(when),
+                };
+
+                // Block the current thread.
+                // SAFETY: the context belongs to the current thread.
+                sel = unsafe { cx.wait_until(deadline) };
+            }
+
+            // Unregister all registered operations.
+            for entry in handles.iter_mut().take(registered_count) {
+                let handle = entry.0;
+                handle.unregister(Operation::hook(entry));
+            }
+
+            match sel {
+                Selected::Waiting => unreachable!(),
+                Selected::Aborted => {
+                    // If an operation became ready during registration, try selecting it.
+                    if let Some(index_ready) = index_ready {
+                        for &(handle, i, ptr) in handles.iter() {
+                            if i == index_ready && handle.try_select(&mut token) {
+                                return Some((i, ptr));
+                            }
+                        }
+                    }
+                }
+                Selected::Disconnected => {}
+                Selected::Operation(_) => {
+                    // Find the selected operation.
+                    for entry in handles.iter_mut() {
+                        let (handle, i, ptr) = *entry;
+                        // Is this the selected operation?
+                        if sel == Selected::Operation(Operation::hook(entry)) {
+                            // Try selecting this operation.
+                            if handle.accept(&mut token, cx) {
+                                return Some((i, ptr));
+                            }
+                        }
+                    }
+                }
+            }
+
+            None
+        });
+
+        // Return if an operation was selected.
+        if let Some((i, ptr)) = res {
+            return Some((token, i, ptr));
+        }
+
+        // Try selecting one of the operations without blocking.
+        for &(handle, i, ptr) in handles.iter() {
+            if handle.try_select(&mut token) {
+                return Some((token, i, ptr));
+            }
+        }
+
+        match timeout {
+            Timeout::Now => return None,
+            Timeout::Never => {}
+            Timeout::At(when) => {
+                if Instant::now() >= when {
+                    return None;
+                }
+            }
+        }
+    }
+}
+
+/// Selects from a set of channel operations.
+///
+/// `Select` allows you to define a set of channel operations, wait until any one of them becomes
+/// ready, and finally execute it. If multiple operations are ready at the same time, a random one
+/// among them is selected.
+///
+/// An operation is considered to be ready if it doesn't have to block. Note that it is ready even
+/// when it will simply return an error because the channel is disconnected.
+///
+/// The [`select!`] macro is a convenience wrapper around `Select`. However, it cannot select over a
+/// dynamically created list of channel operations.
+///
+/// Once a list of operations has been built with `Select`, there are two different ways of
+/// proceeding:
+///
+/// * Select an operation with [`try_select`], [`select`], [`select_timeout`] or
+///   [`select_deadline`]. If successful, the returned selected operation has already begun and
+///   **must** be completed. If we don't complete it, a panic will occur.
+///
+/// * Wait for an operation to become ready with the same methods, and then complete it by calling
+///   [`SelectedOperation::send`] or [`SelectedOperation::recv`] with the sender or receiver that
+///   was added for it.
+///
+/// [`try_select`]: Select::try_select
+/// [`select`]: Select::select
+/// [`select_timeout`]: Select::select_timeout
+/// [`select_deadline`]: Select::select_deadline
+///
+/// # Examples
+///
+/// Use [`select`] to receive a message from a list of receivers:
+///
+/// ```
+/// #![feature(mpmc_channel)]
+///
+/// use std::sync::mpmc::{self, Receiver, RecvError, Select};
+///
+/// fn recv_multiple<T>(rs: &[Receiver<T>]) -> Result<T, RecvError> {
+///     // Build a list of operations.
+///     let mut sel = Select::new();
+///     for r in rs {
+///         sel.recv(r);
+///     }
+///
+///     // Complete the selected operation.
+///     let oper = sel.select();
+///     let index = oper.index();
+///     oper.recv(&rs[index])
+/// }
+///
+/// let (s1, r1) = mpmc::channel();
+/// let (_s2, r2) = mpmc::channel::<i32>();
+/// s1.send(10).unwrap();
+///
+/// assert_eq!(recv_multiple(&[r1, r2]), Ok(10));
+/// ```
+#[unstable(feature = "mpmc_channel", issue = "126840")]
+pub struct Select<'a> {
+    /// A list of senders and receivers participating in selection.
+    handles: Vec<(&'a dyn SelectHandle, usize, *const u8)>,
+
+    /// The next index to assign to an operation.
+    next_index: usize,
+}
+
+#[unstable(feature = "mpmc_channel", issue = "126840")]
+unsafe impl Send for Select<'_> {}
+#[unstable(feature = "mpmc_channel", issue = "126840")]
+unsafe impl Sync for Select<'_> {}
+
+impl<'a> Select<'a> {
+    /// Creates an empty list of channel operations for selection.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(mpmc_channel)]
+    ///
+    /// use std::sync::mpmc::Select;
+    ///
+    /// let mut sel = Select::new();
+    ///
+    /// // The list of operations is empty, which means no operation can be selected.
+    /// assert!(sel.try_select().is_err());
+    /// ```
+    #[unstable(feature = "mpmc_channel", issue = "126840")]
+    pub fn new() -> Select<'a> {
+        Select { handles: Vec::with_capacity(4), next_index: 0 }
+    }
+
+    /// Adds a send operation.
+    ///
+    /// Returns the index of the added operation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(mpmc_channel)]
+    ///
+    /// use std::sync::mpmc::{self, Select};
+    ///
+    /// let (s, r) = mpmc::channel::<i32>();
+    ///
+    /// let mut sel = Select::new();
+    /// let index = sel.send(&s);
+    /// ```
+    #[unstable(feature = "mpmc_channel", issue = "126840")]
+    pub fn send<T>(&mut self, s: &'a Sender<T>) -> usize {
+        let i = self.next_index;
+        let ptr = s as *const Sender<_> as *const u8;
+        self.handles.push((s, i, ptr));
+        self.next_index += 1;
+        i
+    }
+
+    /// Adds a receive operation.
+    ///
+    /// Returns the index of the added operation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(mpmc_channel)]
+    ///
+    /// use std::sync::mpmc::{self, Select};
+    ///
+    /// let (s, r) = mpmc::channel::<i32>();
+    ///
+    /// let mut sel = Select::new();
+    /// let index = sel.recv(&r);
+    /// ```
+    #[unstable(feature = "mpmc_channel", issue = "126840")]
+    pub fn recv<T>(&mut self, r: &'a Receiver<T>) -> usize {
+        let i = self.next_index;
+        let ptr = r as *const Receiver<_> as *const u8;
+        self.handles.push((r, i, ptr));
+        self.next_index += 1;
+        i
+    }
+
+    /// Removes a previously added operation.
+    ///
+    /// This is useful when an operation is selected because the channel got disconnected and we
+    /// want to try again to select a different operation instead.
+    ///
+    /// If new operations are added after removing some, the indices of removed operations will not
+    /// be reused.
+    ///
+    /// # Panics
+    ///
+    /// An attempt to remove a non-existing or already removed operation will panic.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(mpmc_channel)]
+    ///
+    /// use std::sync::mpmc::{self, Select};
+    ///
+    /// let (s1, r1) = mpmc::channel::<i32>();
+    /// let (_, r2) = mpmc::channel::<i32>();
+    ///
+    /// let mut sel = Select::new();
+    /// let oper1 = sel.recv(&r1);
+    /// let oper2 = sel.recv(&r2);
+    ///
+    /// // Only the second operation is ready, because its channel is disconnected.
+    /// let oper = sel.select();
+    /// assert_eq!(oper.index(), oper2);
+    /// assert!(oper.recv(&r2).is_err());
+    /// sel.remove(oper2);
+    ///
+    /// s1.send(10).unwrap();
+    ///
+    /// let oper = sel.select();
+    /// assert_eq!(oper.index(), oper1);
+    /// assert_eq!(oper.recv(&r1), Ok(10));
+    /// ```
+    #[unstable(feature = "mpmc_channel", issue = "126840")]
+    pub fn remove(&mut self, index: usize) {
+        assert!(index < self.next_index, "index out of bounds; {} >= {}", index, self.next_index);
+
+        let i = self
+            .handles
+            .iter()
+            .position(|&(_, i, _)| i == index)
+            .expect("no operation with this index");
+
+        self.handles.swap_remove(i);
+    }
+
+    /// Attempts to select one of the operations without blocking.
+    ///
+    /// If an operation is ready, it is selected and returned. If multiple operations are ready at
+    /// the same time, a random one among them is selected. If none of the operations are ready, an
+    /// error is returned.
+    ///
+    /// An operation is considered to be ready if it doesn't have to block. Note that it is ready
+    /// even when it will simply return an error because the channel is disconnected.
+    ///
+    /// The selected operation must be completed with [`SelectedOperation::send`]
+    /// or [`SelectedOperation::recv`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(mpmc_channel)]
+    ///
+    /// use std::sync::mpmc::{self, Select};
+    ///
+    /// let (s1, r1) = mpmc::channel();
+    /// let (s2, r2) = mpmc::channel();
+    ///
+    /// s1.send(10).unwrap();
+    /// s2.send(20).unwrap();
+    ///
+    /// let mut sel = Select::new();
+    /// let oper1 = sel.recv(&r1);
+    /// let oper2 = sel.recv(&r2);
+    ///
+    /// // Both operations are initially ready, so a random one will be executed.
+    /// let oper = sel.try_select();
+    /// match oper {
+    ///     Err(_) => panic!("both operations should be ready"),
+    ///     Ok(oper) => match oper.index() {
+    ///         i if i == oper1 => assert_eq!(oper.recv(&r1), Ok(10)),
+    ///         i if i == oper2 => assert_eq!(oper.recv(&r2), Ok(20)),
+    ///         _ => unreachable!(),
+    ///     }
+    /// }
+    /// ```
+    #[unstable(feature = "mpmc_channel", issue = "126840")]
+    pub fn try_select(&mut self) -> Result<SelectedOperation<'a>, TrySelectError> {
+        self.run(Timeout::Now).ok_or(TrySelectError)
+    }
+
+    /// Blocks until one of the operations becomes ready and selects it.
+    ///
+    /// Once an operation becomes ready, it is selected and returned. If multiple operations are
+    /// ready at the same time, a random one among them is selected.
+    ///
+    /// An operation is considered to be ready if it doesn't have to block. Note that it is ready
+    /// even when it will simply return an error because the channel is disconnected.
+    ///
+    /// The selected operation must be completed with [`SelectedOperation::send`]
+    /// or [`SelectedOperation::recv`].
+    ///
+    /// If the list of operations is empty, this method blocks forever.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(mpmc_channel)]
+    ///
+    /// use std::sync::mpmc::{self, Select};
+    /// use std::thread;
+    /// use std::time::Duration;
+    ///
+    /// let (s1, r1) = mpmc::channel();
+    /// let (s2, r2) = mpmc::channel();
+    ///
+    /// thread::spawn(move || {
+    ///     thread::sleep(Duration::from_millis(100));
+    ///     s1.send(10).unwrap();
+    /// });
+    /// thread::spawn(move || {
+    ///     thread::sleep(Duration::from_millis(200));
+    ///     s2.send(20).unwrap();
+    /// });
+    ///
+    /// let mut sel = Select::new();
+    /// let oper1 = sel.recv(&r1);
+    /// let oper2 = sel.recv(&r2);
+    ///
+    /// // The first operation will be selected because it becomes ready first.
+    /// let oper = sel.select();
+    /// match oper.index() {
+    ///     i if i == oper1 => assert_eq!(oper.recv(&r1), Ok(10)),
+    ///     i if i == oper2 => assert_eq!(oper.recv(&r2), Ok(20)),
+    ///     _ => unreachable!(),
+    /// }
+    /// ```
+    #[unstable(feature = "mpmc_channel", issue = "126840")]
+    pub fn select(&mut self) -> SelectedOperation<'a> {
+        self.run(Timeout::Never).unwrap()
+    }
+
+    /// Blocks for a limited time until one of the operations becomes ready and selects it.
+    ///
+    /// If an operation becomes ready, it is selected and returned. If multiple operations are
+    /// ready at the same time, a random one among them is selected. If none of the operations
+    /// become ready for the specified duration, an error is returned.
+    ///
+    /// An operation is considered to be ready if it doesn't have to block. Note that it is ready
+    /// even when it will simply return an error because the channel is disconnected.
+    ///
+    /// The selected operation must be completed with [`SelectedOperation::send`]
+    /// or [`SelectedOperation::recv`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(mpmc_channel)]
+    ///
+    /// use std::sync::mpmc::{self, Select};
+    /// use std::thread;
+    /// use std::time::Duration;
+    ///
+    /// let (s, r) = mpmc::channel::<i32>();
+    ///
+    /// thread::spawn(move || {
+    ///     thread::sleep(Duration::from_millis(100));
+    ///     s.send(10).unwrap();
+    /// });
+    ///
+    /// let mut sel = Select::new();
+    /// sel.recv(&r);
+    ///
+    /// // No message arrives within 10 milliseconds.
+    /// assert!(sel.select_timeout(Duration::from_millis(10)).is_err());
+    /// ```
+    #[unstable(feature = "mpmc_channel", issue = "126840")]
+    pub fn select_timeout(
+        &mut self,
+        timeout: Duration,
+    ) -> Result<SelectedOperation<'a>, SelectTimeoutError> {
+        match Instant::now().checked_add(timeout) {
+            Some(deadline) => self.select_deadline(deadline),
+            None => Ok(self.select()),
+        }
+    }
+
+    /// Blocks until a given deadline, or until one of the operations becomes ready and selects it.
+    ///
+    /// If an operation becomes ready, it is selected and returned. If multiple operations are
+    /// ready at the same time, a random one among them is selected. If none of the operations
+    /// become ready before the given deadline, an error is returned.
+    ///
+    /// An operation is considered to be ready if it doesn't have to block. Note that it is ready
+    /// even when it will simply return an error because the channel is disconnected.
+    ///
+    /// The selected operation must be completed with [`SelectedOperation::send`]
+    /// or [`SelectedOperation::recv`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(mpmc_channel)]
+    ///
+    /// use std::sync::mpmc::{self, Select};
+    /// use std::time::{Duration, Instant};
+    ///
+    /// let (s, r) = mpmc::sync_channel::<i32>(1);
+    /// s.send(10).unwrap();
+    ///
+    /// let mut sel = Select::new();
+    /// let oper = sel.send(&s);
+    ///
+    /// // The channel stays full, so sending times out.
+    /// let deadline = Instant::now() + Duration::from_millis(10);
+    /// assert!(sel.select_deadline(deadline).is_err());
+    /// # let _ = (oper, r);
+    /// ```
+    #[unstable(feature = "mpmc_channel", issue = "126840")]
+    pub fn select_deadline(
+        &mut self,
+        deadline: Instant,
+    ) -> Result<SelectedOperation<'a>, SelectTimeoutError> {
+        self.run(Timeout::At(deadline)).ok_or(SelectTimeoutError)
+    }
+
+    fn run(&mut self, timeout: Timeout) -> Option<SelectedOperation<'a>> {
+        run_select(&mut self.handles, timeout).map(|(token, index, ptr)| SelectedOperation {
+            token,
+            index,
+            ptr,
+            _marker: PhantomData,
+        })
+    }
+}
+
+#[unstable(feature = "mpmc_channel", issue = "126840")]
+impl<'a> Default for Select<'a> {
+    fn default() -> Select<'a> {
+        Select::new()
+    }
+}
+
+#[unstable(feature = "mpmc_channel", issue = "126840")]
+impl fmt::Debug for Select<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Select").finish_non_exhaustive()
+    }
+}
+
+/// A selected operation that needs to be completed.
+///
+/// To complete the operation, call [`send`] or [`recv`].
+///
+/// # Panics
+///
+/// Forgetting to complete the operation is an error and might lead to deadlocks. If a
+/// `SelectedOperation` is dropped without completion, a panic occurs.
+///
+/// [`send`]: SelectedOperation::send
+/// [`recv`]: SelectedOperation::recv
+#[must_use]
+#[unstable(feature = "mpmc_channel", issue = "126840")]
+pub struct SelectedOperation<'a> {
+    /// Token needed to complete the operation.
+    token: Token,
+
+    /// The index of the selected operation.
+    index: usize,
+
+    /// The address of the selected `Sender` or `Receiver`.
+    ptr: *const u8,
+
+    /// Indicates that `Sender`s and `Receiver`s are borrowed.
+    _marker: PhantomData<&'a ()>,
+}
+
+impl SelectedOperation<'_> {
+    /// Returns the index of the selected operation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(mpmc_channel)]
+    ///
+    /// use std::sync::mpmc::{self, Select};
+    ///
+    /// let (s1, r1) = mpmc::channel::<()>();
+    /// let (s2, r2) = mpmc::channel::<()>();
+    /// let (s3, r3) = mpmc::channel::<()>();
+    ///
+    /// let mut sel = Select::new();
+    /// let oper1 = sel.send(&s1);
+    /// let oper2 = sel.recv(&r2);
+    /// let oper3 = sel.send(&s3);
+    ///
+    /// // Only the first operation can proceed.
+    /// let oper = sel.select();
+    /// assert_eq!(oper.index(), oper1);
+    ///
+    /// // Complete the operation.
+    /// oper.send(&s1, ()).unwrap();
+    /// # let _ = (r1, s2, r3, oper2, oper3);
+    /// ```
+    #[unstable(feature = "mpmc_channel", issue = "126840")]
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Completes the send operation.
+    ///
+    /// The passed [`Sender`] reference must be the same one that was used in [`Select::send`]
+    /// when the operation was added.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an incorrect [`Sender`] reference is passed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(mpmc_channel)]
+    ///
+    /// use std::sync::mpmc::{self, Select, SendError};
+    ///
+    /// let (s, r) = mpmc::sync_channel::<i32>(1);
+    /// drop(r);
+    ///
+    /// let mut sel = Select::new();
+    /// sel.send(&s);
+    ///
+    /// let oper = sel.select();
+    /// assert_eq!(oper.send(&s, 10), Err(SendError(10)));
+    /// ```
+    #[unstable(feature = "mpmc_channel", issue = "126840")]
+    pub fn send<T>(mut self, s: &Sender<T>, msg: T) -> Result<(), SendError<T>> {
+        assert!(
+            s as *const Sender<T> as *const u8 == self.ptr,
+            "passed a sender that wasn't selected",
+        );
+        // SAFETY: the token was prepared by selecting a send operation on `s`.
+        let res = unsafe { s.write(&mut self.token, msg) };
+        crate::mem::forget(self);
+        res.map_err(SendError)
+    }
+
+    /// Completes the receive operation.
+    ///
+    /// The passed [`Receiver`] reference must be the same one that was used in [`Select::recv`]
+    /// when the operation was added.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an incorrect [`Receiver`] reference is passed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(mpmc_channel)]
+    ///
+    /// use std::sync::mpmc::{self, Select, RecvError};
+    ///
+    /// let (s, r) = mpmc::sync_channel::<i32>(1);
+    /// drop(s);
+    ///
+    /// let mut sel = Select::new();
+    /// sel.recv(&r);
+    ///
+    /// let oper = sel.select();
+    /// assert_eq!(oper.recv(&r), Err(RecvError));
+    /// ```
+    #[unstable(feature = "mpmc_channel", issue = "126840")]
+    pub fn recv<T>(mut self, r: &Receiver<T>) -> Result<T, RecvError> {
+        assert!(
+            r as *const Receiver<T> as *const u8 == self.ptr,
+            "passed a receiver that wasn't selected",
+        );
+        // SAFETY: the token was prepared by selecting a receive operation on `r`.
+        let res = unsafe { r.read(&mut self.token) };
+        crate::mem::forget(self);
+        res.map_err(|_| RecvError)
+    }
+}
+
+#[unstable(feature = "mpmc_channel", issue = "126840")]
+impl fmt::Debug for SelectedOperation<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SelectedOperation").field("index", &self.index).finish_non_exhaustive()
+    }
+}
+
+#[unstable(feature = "mpmc_channel", issue = "126840")]
+impl Drop for SelectedOperation<'_> {
+    fn drop(&mut self) {
+        // Don't turn a panic while completing the operation (say, in `send` or `recv`) into an
+        // abort.
+        if !crate::thread::panicking() {
+            panic!("dropped `SelectedOperation` without completing the operation");
+        }
+    }
+}
+
+/// Selects from a set of channel operations.
+///
+/// This macro allows you to define a set of channel operations, wait until any one of them becomes
+/// ready, and finally execute it. If multiple operations are ready at the same time, a random one
+/// among them is selected.
+///
+/// It is also possible to define a `default` case that gets executed if none of the operations
+/// are ready, either right away or for a certain duration of time.
+///
+/// An operation is considered to be ready if it doesn't have to block. Note that it is ready even
+/// when it will simply return an error because the channel is disconnected.
+///
+/// The operations are written as:
+///
+/// * `recv(r) -> res => body`: receives from `r`, with `res` bound to a `Result<T, RecvError>`.
+/// * `send(s, msg) -> res => body`: sends `msg` into `s`, with `res` bound to a
+///   `Result<(), SendError<T>>`. The `msg` expression is only evaluated if this operation is
+///   selected.
+/// * `default => body`: runs if none of the operations are ready right away.
+/// * `default(timeout) => body`: runs if none of the operations become ready within `timeout`,
+///   a [`Duration`].
+///
+/// A `default` case must come last. The `select!` macro is a convenience wrapper around
+/// [`Select`], which can be used to select over a dynamically created list of operations.
+///
+/// # Examples
+///
+/// Receive a message from whichever channel gets one first, or give up after a second:
+///
+/// ```
+/// #![feature(mpmc_channel)]
+///
+/// use std::sync::mpmc::{self, select};
+/// use std::thread;
+/// use std::time::Duration;
+///
+/// let (s1, r1) = mpmc::channel();
+/// let (_s2, r2) = mpmc::channel::<i32>();
+///
+/// thread::spawn(move || s1.send(10).unwrap());
+///
+/// select! {
+///     recv(r1) -> msg => assert_eq!(msg, Ok(10)),
+///     recv(r2) -> msg => panic!("unexpected message {msg:?}"),
+///     default(Duration::from_secs(1)) => println!("timed out"),
+/// }
+/// ```
+///
+/// Send a message if the channel has room for it, without blocking:
+///
+/// ```
+/// #![feature(mpmc_channel)]
+///
+/// use std::sync::mpmc::{self, select};
+///
+/// let (s, r) = mpmc::sync_channel(1);
+///
+/// let sent = select! {
+///     send(s, 1) -> res => res.is_ok(),
+///     default => false,
+/// };
+/// assert!(sent);
+///
+/// let sent = select! {
+///     send(s, 2) -> res => res.is_ok(),
+///     default => false,
+/// };
+/// assert!(!sent);
+/// assert_eq!(r.recv(), Ok(1));
+/// ```
+///
+/// [`Duration`]: crate::time::Duration
+#[rustc_macro_transparency = "semiopaque"]
+#[unstable(feature = "mpmc_channel", issue = "126840")]
+#[allow_internal_unstable(mpmc_channel)]
+pub macro select {
+    // Parse the arms into `(recv r (res) {body})` and `(send s (msg) (res) {body})`, and the
+    // default case into `()`, `(default {body})` or `(default (timeout) {body})`.
+    (@parse [$($arms:tt)*]) => {
+        $crate::sync::mpmc::select!(@bind [$($arms)*] [] ())
+    },
+    (@parse [$($arms:tt)*] recv($r:expr) -> $res:pat => $body:expr $(, $($rest:tt)*)?) => {
+        $crate::sync::mpmc::select!(@parse [$($arms)* (recv $r ($res) { $body })] $($($rest)*)?)
+    },
+    (@parse [$($arms:tt)*] recv($r:expr) -> $res:pat => $body:block $($rest:tt)*) => {
+        $crate::sync::mpmc::select!(@parse [$($arms)* (recv $r ($res) $body)] $($rest)*)
+    },
+    (
+        @parse [$($arms:tt)*]
+        send($s:expr, $msg:expr) -> $res:pat => $body:expr $(, $($rest:tt)*)?
+    ) => {
+        $crate::sync::mpmc::select!(
+            @parse [$($arms)* (send $s ($msg) ($res) { $body })] $($($rest)*)?
+        )
+    },
+    (@parse [$($arms:tt)*] send($s:expr, $msg:expr) -> $res:pat => $body:block $($rest:tt)*) => {
+        $crate::sync::mpmc::select!(@parse [$($arms)* (send $s ($msg) ($res) $body)] $($rest)*)
+    },
+    (@parse [$($arms:tt)*] default => $body:expr $(,)?) => {
+        $crate::sync::mpmc::select!(@bind [$($arms)*] [] (default { $body }))
+    },
+    (@parse [$($arms:tt)*] default($timeout:expr) => $body:expr $(,)?) => {
+        $crate::sync::mpmc::select!(@bind [$($arms)*] [] (default ($timeout) { $body }))
+    },
+    (@parse [$($arms:tt)*] $($rest:tt)+) => {
+        $crate::compile_error!(
+            "expected `recv(r) -> res => body`, `send(s, msg) -> res => body` \
+             or a final `default` case"
+        )
+    },
+
+    // Evaluate each sender and receiver expression once, binding it to a fresh handle.
+    (@bind [(recv $r:tt $($arm:tt)*) $($arms:tt)*] [$($bound:tt)*] $default:tt) => {
+        match $r {
+            ref handle => {
+                let handle: &$crate::sync::mpmc::Receiver<_> = handle;
+                $crate::sync::mpmc::select!(
+                    @bind [$($arms)*] [$($bound)* (recv handle index $($arm)*)] $default
+                )
+            }
+        }
+    },
+    (@bind [(send $s:tt $($arm:tt)*) $($arms:tt)*] [$($bound:tt)*] $default:tt) => {
+        match $s {
+            ref handle => {
+                let handle: &$crate::sync::mpmc::Sender<_> = handle;
+                $crate::sync::mpmc::select!(
+                    @bind [$($arms)*] [$($bound)* (send handle index $($arm)*)] $default
+                )
+            }
+        }
+    },
+    (@bind [] [$(($op:ident $handle:ident $index:ident $($arm:tt)*))*] $default:tt) => {{
+        let mut sel = $crate::sync::mpmc::Select::new();
+        $( let $index = sel.$op($handle); )*
+        $crate::sync::mpmc::select!(
+            @run sel $default [$(($op $handle $index $($arm)*))*]
+        )
+    }},
+
+    // Wait for one of the operations, or for the default case.
+    (@run $sel:ident () $arms:tt) => {{
+        let oper = $sel.select();
+        $crate::sync::mpmc::select!(@complete oper $arms)
+    }},
+    (@run $sel:ident (default $body:block) $arms:tt) => {
+        match $sel.try_select() {
+            $crate::result::Result::Ok(oper) => $crate::sync::mpmc::select!(@complete oper $arms),
+            $crate::result::Result::Err(_) => $body,
+        }
+    },
+    (@run $sel:ident (default ($timeout:expr) $body:block) $arms:tt) => {
+        match $sel.select_timeout($timeout) {
+            $crate::result::Result::Ok(oper) => $crate::sync::mpmc::select!(@complete oper $arms),
+            $crate::result::Result::Err(_) => $body,
+        }
+    },
+
+    // Complete the selected operation and run its body.
+    (@complete $oper:ident [$(($op:ident $handle:ident $index:ident $($arm:tt)*))*]) => {
+        match $oper.index() {
+            $(
+                i if i == $index => $crate::sync::mpmc::select!(@finish $oper $op $handle $($arm)*),
+            )*
+            _ => $crate::unreachable!(),
+        }
+    },
+    (@finish $oper:ident recv $handle:ident ($res:pat) $body:block) => {{
+        let $res = $oper.recv($handle);
+        $body
+    }},
+    (@finish $oper:ident send $handle:ident ($msg:expr) ($res:pat) $body:block) => {{
+        let $res = $oper.send($handle, $msg);
+        $body
+    }},
+
+    ($($tokens:tt)*) => {
+        $crate::sync::mpmc::select!(@parse [] $($tokens)*)
+    },
+}
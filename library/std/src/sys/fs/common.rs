@@ -2,7 +2,7 @@
 
 use crate::io::{self, Error, ErrorKind};
 use crate::path::{Path, PathBuf};
-use crate::sys::fs::{File, OpenOptions};
+use crate::sys::fs::{DirBuilder, File, FileAttr, FilePermissions, OpenOptions, ReadDir};
 use crate::sys::helpers::ignore_notfound;
 use crate::{fmt, fs};
 
@@ -72,6 +72,48 @@ pub fn open(path: &Path, _opts: &OpenOptions) -> io::Result<Self> {
     pub fn open_file(&self, path: &Path, opts: &OpenOptions) -> io::Result<File> {
         File::open(&self.path.join(path), &opts)
     }
+
+    pub fn open_dir(&self, path: &Path) -> io::Result<Self> {
+        let mut opts = OpenOptions::new();
+        opts.read(true);
+        Self::open(&self.path.join(path), &opts)
+    }
+
+    pub fn create_dir(&self, path: &Path) -> io::Result<()> {
+        DirBuilder::new().mkdir(&self.path.join(path))
+    }
+
+    pub fn remove_file(&self, path: &Path) -> io::Result<()> {
+        super::remove_file(&self.path.join(path))
+    }
+
+    pub fn remove_dir(&self, path: &Path) -> io::Result<()> {
+        super::remove_dir(&self.path.join(path))
+    }
+
+    pub fn rename(&self, from: &Path, to_dir: &Dir, to: &Path) -> io::Result<()> {
+        super::rename(&self.path.join(from), &to_dir.path.join(to))
+    }
+
+    pub fn symlink(&self, original: &Path, link: &Path) -> io::Result<()> {
+        super::symlink(original, &self.path.join(link))
+    }
+
+    pub fn metadata(&self, path: &Path) -> io::Result<FileAttr> {
+        super::metadata(&self.path.join(path))
+    }
+
+    pub fn symlink_metadata(&self, path: &Path) -> io::Result<FileAttr> {
+        super::symlink_metadata(&self.path.join(path))
+    }
+
+    pub fn read_dir(&self, path: &Path) -> io::Result<ReadDir> {
+        super::read_dir(&self.path.join(path))
+    }
+
+    pub fn set_permissions(&self, path: &Path, perm: FilePermissions) -> io::Result<()> {
+        super::set_permissions(&self.path.join(path), perm)
+    }
 }
 
 impl fmt::Debug for Dir {
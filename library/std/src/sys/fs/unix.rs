@@ -19,6 +19,23 @@
 use libc::dirfd;
 #[cfg(any(target_os = "fuchsia", target_os = "illumos", target_vendor = "apple"))]
 use libc::fstatat as fstatat64;
+#[cfg(not(any(
+    all(target_os = "linux", not(target_env = "musl")),
+    target_os = "android",
+    target_os = "fuchsia",
+    target_os = "hurd",
+    target_os = "illumos",
+    target_os = "l4re",
+    target_vendor = "apple",
+    // these don't have `Dir`, the only user
+    target_os = "redox",
+    target_os = "espidf",
+    target_os = "horizon",
+    target_os = "vita",
+    target_os = "nto",
+    target_os = "vxworks",
+)))]
+use libc::fstatat as fstatat64;
 #[cfg(any(all(target_os = "linux", not(target_env = "musl")), target_os = "hurd"))]
 use libc::fstatat64;
 #[cfg(any(
@@ -274,9 +291,10 @@ fn new(inner: InnerReadDir) -> Self {
 
 struct DirStream(*mut libc::DIR);
 
-// dir::Dir requires openat support
+// dir::Dir requires openat and fstatat support
 cfg_select! {
     any(
+        target_os = "l4re",
         target_os = "redox",
         target_os = "espidf",
         target_os = "horizon",
@@ -2197,6 +2215,33 @@ pub fn lstat(p: &CStr) -> io::Result<FileAttr> {
     Ok(FileAttr::from_stat64(stat))
 }
 
+/// Like [`stat`] and [`lstat`], but resolves `p` relative to the directory `dirfd`.
+#[cfg(not(any(
+    target_os = "l4re",
+    target_os = "redox",
+    target_os = "espidf",
+    target_os = "horizon",
+    target_os = "vita",
+    target_os = "nto",
+    target_os = "vxworks",
+)))]
+fn stat_at(dirfd: c_int, p: &CStr, flags: c_int) -> io::Result<FileAttr> {
+    cfg_has_statx! {
+        if let Some(ret) = unsafe { try_statx(
+            dirfd,
+            p.as_ptr(),
+            flags | libc::AT_STATX_SYNC_AS_STAT,
+            libc::STATX_BASIC_STATS | libc::STATX_BTIME,
+        ) } {
+            return ret;
+        }
+    }
+
+    let mut stat: stat64 = unsafe { mem::zeroed() };
+    cvt(unsafe { fstatat64(dirfd, p.as_ptr(), &mut stat, flags) })?;
+    Ok(FileAttr::from_stat64(stat))
+}
+
 pub fn canonicalize(path: &CStr) -> io::Result<PathBuf> {
     let r = unsafe { libc::realpath(path.as_ptr(), ptr::null_mut()) };
     if r.is_null() {
@@ -16,6 +16,7 @@
     }
 }
 
+use super::{DirStream, FileAttr, FilePermissions, InnerReadDir, ReadDir, stat_at};
 use crate::ffi::CStr;
 use crate::os::fd::{AsFd, BorrowedFd, IntoRawFd, OwnedFd, RawFd};
 #[cfg(target_family = "unix")]
@@ -27,7 +28,7 @@
 use crate::sys::fs::OpenOptions;
 use crate::sys::fs::unix::{File, debug_path_fd};
 use crate::sys::helpers::run_path_with_cstr;
-use crate::sys::{AsInner, FromInner, IntoInner, cvt_r};
+use crate::sys::{AsInner, FromInner, IntoInner, cvt, cvt_r};
 use crate::{fmt, fs, io};
 
 pub struct Dir(OwnedFd);
@@ -56,10 +57,207 @@ fn open_file_c(&self, path: &CStr, opts: &OpenOptions) -> io::Result<File> {
             | opts.get_access_mode()?
             | opts.get_creation_mode()?
             | (opts.custom_flags as c_int & !libc::O_ACCMODE);
-        let fd = cvt_r(|| unsafe {
-            openat64(self.0.as_raw_fd(), path.as_ptr(), flags, opts.mode as c_int)
+        let fd = self.open_at(path, flags, opts.mode as c_int)?;
+        Ok(File(FileDesc::from_inner(fd)))
+    }
+
+    pub fn open_dir(&self, path: &Path) -> io::Result<Self> {
+        run_path_with_cstr(path, &|path| {
+            self.open_at(path, libc::O_CLOEXEC | libc::O_DIRECTORY | libc::O_RDONLY, 0).map(Self)
+        })
+    }
+
+    pub fn create_dir(&self, path: &Path) -> io::Result<()> {
+        self.with_parent(path, &|dir, name| {
+            cvt(unsafe { libc::mkdirat(dir.as_raw_fd(), name.as_ptr(), 0o777) }).map(|_| ())
+        })
+    }
+
+    pub fn remove_file(&self, path: &Path) -> io::Result<()> {
+        self.with_parent(path, &|dir, name| {
+            cvt(unsafe { libc::unlinkat(dir.as_raw_fd(), name.as_ptr(), 0) }).map(|_| ())
+        })
+    }
+
+    pub fn remove_dir(&self, path: &Path) -> io::Result<()> {
+        self.with_parent(path, &|dir, name| {
+            cvt(unsafe { libc::unlinkat(dir.as_raw_fd(), name.as_ptr(), libc::AT_REMOVEDIR) })
+                .map(|_| ())
+        })
+    }
+
+    pub fn rename(&self, from: &Path, to_dir: &Dir, to: &Path) -> io::Result<()> {
+        self.with_parent(from, &|from_dir, from| {
+            to_dir.with_parent(to, &|to_dir, to| {
+                cvt(unsafe {
+                    libc::renameat(
+                        from_dir.as_raw_fd(),
+                        from.as_ptr(),
+                        to_dir.as_raw_fd(),
+                        to.as_ptr(),
+                    )
+                })
+                .map(|_| ())
+            })
+        })
+    }
+
+    pub fn symlink(&self, original: &Path, link: &Path) -> io::Result<()> {
+        run_path_with_cstr(original, &|original| {
+            self.with_parent(link, &|dir, link| {
+                cvt(unsafe { libc::symlinkat(original.as_ptr(), dir.as_raw_fd(), link.as_ptr()) })
+                    .map(|_| ())
+            })
+        })
+    }
+
+    pub fn metadata(&self, path: &Path) -> io::Result<FileAttr> {
+        cfg_select! {
+            all(target_os = "linux", any(target_env = "gnu", target_env = "musl")) => {
+                // `fstatat` would follow a symlink in the last component wherever it points to.
+                // Opening the file with `O_PATH` resolves it beneath this directory instead.
+                run_path_with_cstr(path, &|path| {
+                    let fd = self.open_at(path, libc::O_CLOEXEC | libc::O_PATH, 0)?;
+                    File(FileDesc::from_inner(fd)).file_attr()
+                })
+            }
+            _ => {
+                self.with_parent(path, &|dir, name| stat_at(dir.as_raw_fd(), name, 0))
+            }
+        }
+    }
+
+    pub fn symlink_metadata(&self, path: &Path) -> io::Result<FileAttr> {
+        self.with_parent(path, &|dir, name| {
+            stat_at(dir.as_raw_fd(), name, libc::AT_SYMLINK_NOFOLLOW)
+        })
+    }
+
+    pub fn read_dir(&self, path: &Path) -> io::Result<ReadDir> {
+        let fd = run_path_with_cstr(path, &|path| {
+            self.open_at(path, libc::O_CLOEXEC | libc::O_DIRECTORY | libc::O_RDONLY, 0)
         })?;
-        Ok(File(unsafe { FileDesc::from_raw_fd(fd) }))
+        let ptr = unsafe { libc::fdopendir(fd.as_raw_fd()) };
+        if ptr.is_null() {
+            return Err(io::Error::last_os_error());
+        }
+        // the file descriptor is closed by `closedir` now
+        let _ = fd.into_raw_fd();
+        let inner = InnerReadDir { dirp: DirStream(ptr), root: path.to_path_buf() };
+        Ok(ReadDir::new(inner))
+    }
+
+    pub fn set_permissions(&self, path: &Path, perm: FilePermissions) -> io::Result<()> {
+        cfg_select! {
+            all(target_os = "linux", any(target_env = "gnu", target_env = "musl")) => {
+                // Resolve `path` beneath this directory first, following symlinks only as long
+                // as they stay beneath it, and change the mode of what it refers to through the
+                // `O_PATH` descriptor. `fchmod` doesn't accept such descriptors, and `fchmodat`
+                // would resolve the last component again, so this needs `fchmodat2` and its
+                // `AT_EMPTY_PATH`, which kernels prior to 6.6 don't have.
+                run_path_with_cstr(path, &|path| {
+                    let fd = self.open_at(path, libc::O_CLOEXEC | libc::O_PATH, 0)?;
+                    match cvt_r(|| unsafe {
+                        libc::syscall(
+                            libc::SYS_fchmodat2,
+                            fd.as_raw_fd(),
+                            c"".as_ptr(),
+                            perm.mode,
+                            libc::AT_EMPTY_PATH,
+                        )
+                    }) {
+                        Ok(_) => Ok(()),
+                        Err(err) if err.raw_os_error() == Some(libc::ENOSYS) => {
+                            Err(io::const_error!(
+                                io::ErrorKind::Unsupported,
+                                "changing permissions relative to a directory requires Linux 6.6",
+                            ))
+                        }
+                        Err(err) => Err(err),
+                    }
+                })
+            }
+            target_os = "wasi" => {
+                let _ = (path, perm);
+                Err(io::Error::UNSUPPORTED_PLATFORM)
+            }
+            _ => {
+                self.with_parent(path, &|dir, name| {
+                    cvt_r(|| unsafe { libc::fchmodat(dir.as_raw_fd(), name.as_ptr(), perm.mode, 0) })
+                        .map(|_| ())
+                })
+            }
+        }
+    }
+
+    /// Opens `path` relative to this directory.
+    ///
+    /// On Linux, relative paths are resolved with `openat2` and `RESOLVE_BENEATH`, which fails
+    /// if `path` refers to anything outside of this directory, through `..` components or
+    /// symbolic links. Kernels prior to 5.6 don't have `openat2`, and relative paths fail with
+    /// [`io::ErrorKind::Unsupported`] there rather than being resolved without that guarantee.
+    /// Absolute paths are opened as given, as by `openat`.
+    fn open_at(&self, path: &CStr, flags: c_int, mode: c_int) -> io::Result<OwnedFd> {
+        #[cfg(all(target_os = "linux", any(target_env = "gnu", target_env = "musl")))]
+        if path.to_bytes().first() != Some(&b'/') {
+            // `open_how` is non-exhaustive
+            let mut how: libc::open_how = unsafe { crate::mem::zeroed() };
+            how.flags = flags as u64;
+            // openat2 rejects a mode unless a file may be created
+            if flags & (libc::O_CREAT | libc::O_TMPFILE) != 0 {
+                how.mode = mode as u64;
+            }
+            how.resolve = libc::RESOLVE_BENEATH;
+
+            return match cvt_r(|| unsafe {
+                libc::syscall(
+                    libc::SYS_openat2,
+                    self.0.as_raw_fd(),
+                    path.as_ptr(),
+                    &raw const how,
+                    size_of::<libc::open_how>(),
+                )
+            }) {
+                Ok(fd) => Ok(unsafe { OwnedFd::from_raw_fd(fd as c_int) }),
+                Err(err) if err.raw_os_error() == Some(libc::ENOSYS) => Err(io::const_error!(
+                    io::ErrorKind::Unsupported,
+                    "opening paths relative to a directory requires Linux 5.6",
+                )),
+                Err(err) => Err(err),
+            };
+        }
+
+        let fd = cvt_r(|| unsafe { openat64(self.0.as_raw_fd(), path.as_ptr(), flags, mode) })?;
+        Ok(unsafe { OwnedFd::from_raw_fd(fd) })
+    }
+
+    /// Calls `f` with a directory and the name of the last component of `path` in it, for the
+    /// syscalls that operate on a name relative to a directory file descriptor.
+    ///
+    /// The parent directories of `path` are opened with [`Dir::open_at`], so that `f` can't
+    /// escape this directory either.
+    fn with_parent<T>(
+        &self,
+        path: &Path,
+        f: &dyn Fn(BorrowedFd<'_>, &CStr) -> io::Result<T>,
+    ) -> io::Result<T> {
+        let dir_flags = libc::O_CLOEXEC | libc::O_DIRECTORY | libc::O_RDONLY;
+        match (path.parent(), path.file_name()) {
+            (Some(parent), Some(name)) if parent.as_os_str().is_empty() => {
+                run_path_with_cstr(name.as_ref(), &|name| f(self.0.as_fd(), name))
+            }
+            (Some(parent), Some(name)) => {
+                let parent =
+                    run_path_with_cstr(parent, &|parent| self.open_at(parent, dir_flags, 0))?;
+                run_path_with_cstr(name.as_ref(), &|name| f(parent.as_fd(), name))
+            }
+            // `path` is empty, the root directory, or ends in `.` or `..`, so it has no name in
+            // a parent directory. Open it and let `f` refer to it as `.` instead.
+            _ => {
+                let dir = run_path_with_cstr(path, &|path| self.open_at(path, dir_flags, 0))?;
+                f(dir.as_fd(), c".")
+            }
+        }
     }
 }
 
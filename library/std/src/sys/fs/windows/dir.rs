@@ -1,3 +1,6 @@
+use crate::alloc::{Layout, alloc, dealloc};
+use crate::ffi::c_void;
+use crate::mem::offset_of;
 use crate::os::windows::io::{
     AsHandle, AsRawHandle, BorrowedHandle, FromRawHandle, HandleOrInvalid, IntoRawHandle,
     OwnedHandle, RawHandle,
@@ -5,10 +8,10 @@
 use crate::path::Path;
 use crate::sys::api::{UnicodeStrRef, WinError};
 use crate::sys::fs::windows::debug_path_handle;
-use crate::sys::fs::{File, OpenOptions};
+use crate::sys::fs::{DirBuilder, File, FileAttr, FilePermissions, OpenOptions, ReadDir};
 use crate::sys::handle::Handle;
-use crate::sys::path::{WCStr, with_native_path};
-use crate::sys::{AsInner, FromInner, IntoInner, IoResult, c, to_u16s};
+use crate::sys::path::{WCStr, maybe_verbatim, with_native_path};
+use crate::sys::{AsInner, FromInner, IntoInner, IoResult, c, cvt, to_u16s};
 use crate::{fmt, fs, io, ptr};
 
 pub struct Dir {
@@ -52,6 +55,50 @@ unsafe fn nt_create_file(
     }
 }
 
+/// Renames the file or directory `file` to `to`, which is relative to the directory `root`, or
+/// absolute if `root` is null. An existing file at `to` is replaced.
+fn rename_relative(file: &File, root: c::HANDLE, to: &[u16]) -> io::Result<()> {
+    // `FILE_RENAME_INFO` ends in a dynamically sized file name, which isn't nul-terminated.
+    let name_len = u32::try_from(to.len() * 2)
+        .map_err(|_| io::const_error!(io::ErrorKind::InvalidFilename, "file name is too long"))?;
+    let offset: u32 = offset_of!(c::FILE_RENAME_INFO, FileName).try_into().unwrap();
+    let struct_size = offset + name_len;
+    let layout =
+        Layout::from_size_align(struct_size as usize, align_of::<c::FILE_RENAME_INFO>()).unwrap();
+
+    // SAFETY: We allocate enough memory for a full FILE_RENAME_INFO struct and the file name.
+    let info = unsafe { alloc(layout).cast::<c::FILE_RENAME_INFO>() };
+    if info.is_null() {
+        return Err(io::ErrorKind::OutOfMemory.into());
+    }
+    let set_info = |class, flags| unsafe {
+        (&raw mut (*info).Anonymous).write(flags);
+        (&raw mut (*info).RootDirectory).write(root);
+        (&raw mut (*info).FileNameLength).write(name_len);
+        to.as_ptr().copy_to_nonoverlapping((&raw mut (*info).FileName).cast::<u16>(), to.len());
+        cvt(c::SetFileInformationByHandle(
+            file.as_raw_handle(),
+            class,
+            info.cast::<c_void>(),
+            struct_size,
+        ))
+    };
+    let flags = c::FILE_RENAME_FLAG_REPLACE_IF_EXISTS | c::FILE_RENAME_FLAG_POSIX_SEMANTICS;
+    let result = match set_info(c::FileRenameInfoEx, c::FILE_RENAME_INFO_0 { Flags: flags }) {
+        // `FileRenameInfoEx` is only supported since Windows 10 1709, and not by every file
+        // system. Fall back to `FileRenameInfo` there.
+        Err(err)
+            if err.raw_os_error() == Some(c::ERROR_INVALID_PARAMETER as i32)
+                || err.raw_os_error() == Some(c::ERROR_NOT_SUPPORTED as i32) =>
+        {
+            set_info(c::FileRenameInfo, c::FILE_RENAME_INFO_0 { ReplaceIfExists: true })
+        }
+        result => result,
+    };
+    unsafe { dealloc(info.cast::<u8>(), layout) };
+    result.map(drop)
+}
+
 impl Dir {
     pub fn open(path: &Path, opts: &OpenOptions) -> io::Result<Self> {
         with_native_path(path, &|path| Self::open_with_native(path, opts))
@@ -62,9 +109,129 @@ pub fn open_file(&self, path: &Path, opts: &OpenOptions) -> io::Result<File> {
         if path.is_absolute() {
             return File::open(path, opts);
         }
+        self.open_relative(path, opts, c::FILE_NON_DIRECTORY_FILE).map(|handle| File { handle })
+    }
+
+    pub fn open_dir(&self, path: &Path) -> io::Result<Self> {
+        let mut opts = OpenOptions::new();
+        opts.read(true);
+        if path.is_absolute() {
+            return Self::open(path, &opts);
+        }
+        self.open_relative(path, &opts, c::FILE_DIRECTORY_FILE).map(|handle| Self { handle })
+    }
+
+    pub fn create_dir(&self, path: &Path) -> io::Result<()> {
+        if path.is_absolute() {
+            return DirBuilder::new().mkdir(path);
+        }
+        let mut opts = OpenOptions::new();
+        // `write` is only needed for `create_new`, the access mode takes precedence
+        opts.write(true);
+        opts.create_new(true);
+        opts.access_mode(c::FILE_LIST_DIRECTORY);
+        self.open_relative(path, &opts, c::FILE_DIRECTORY_FILE).map(drop)
+    }
+
+    pub fn remove_file(&self, path: &Path) -> io::Result<()> {
+        if path.is_absolute() {
+            return crate::sys::fs::remove_file(path);
+        }
+        self.delete(path, c::FILE_NON_DIRECTORY_FILE)
+    }
+
+    pub fn remove_dir(&self, path: &Path) -> io::Result<()> {
+        if path.is_absolute() {
+            return crate::sys::fs::remove_dir(path);
+        }
+        self.delete(path, c::FILE_DIRECTORY_FILE)
+    }
+
+    pub fn rename(&self, from: &Path, to_dir: &Dir, to: &Path) -> io::Result<()> {
+        let mut opts = OpenOptions::new();
+        opts.access_mode(c::DELETE);
+        let file = if from.is_absolute() {
+            opts.custom_flags(c::FILE_FLAG_OPEN_REPARSE_POINT | c::FILE_FLAG_BACKUP_SEMANTICS);
+            File::open(from, &opts)?
+        } else {
+            File { handle: self.open_relative(from, &opts, c::FILE_OPEN_REPARSE_POINT)? }
+        };
+        // An absolute `to` is resolved on its own, like when opening files.
+        let (root, to) = if to.is_absolute() {
+            (ptr::null_mut(), maybe_verbatim(to)?)
+        } else {
+            (to_dir.handle.as_raw_handle(), to_u16s(to)?)
+        };
+        let to = &to[..to.len() - 1]; // trim 0 byte
+        rename_relative(&file, root, to)
+    }
+
+    pub fn symlink(&self, _original: &Path, _link: &Path) -> io::Result<()> {
+        // FIXME: create the link with `NtCreateFile` and `FSCTL_SET_REPARSE_POINT`.
+        Err(io::const_error!(
+            io::ErrorKind::Unsupported,
+            "creating symbolic links relative to a directory is not supported on Windows yet",
+        ))
+    }
+
+    pub fn metadata(&self, path: &Path) -> io::Result<FileAttr> {
+        if path.is_absolute() {
+            return crate::sys::fs::metadata(path);
+        }
+        let mut opts = OpenOptions::new();
+        opts.access_mode(c::FILE_READ_ATTRIBUTES);
+        File { handle: self.open_relative(path, &opts, 0)? }.file_attr()
+    }
+
+    pub fn symlink_metadata(&self, path: &Path) -> io::Result<FileAttr> {
+        if path.is_absolute() {
+            return crate::sys::fs::symlink_metadata(path);
+        }
+        let mut opts = OpenOptions::new();
+        opts.access_mode(c::FILE_READ_ATTRIBUTES);
+        File { handle: self.open_relative(path, &opts, c::FILE_OPEN_REPARSE_POINT)? }.file_attr()
+    }
+
+    pub fn read_dir(&self, _path: &Path) -> io::Result<ReadDir> {
+        // FIXME: list the directory with `GetFileInformationByHandleEx`, like `remove_dir_all`.
+        Err(io::const_error!(
+            io::ErrorKind::Unsupported,
+            "reading a directory relative to a directory is not supported on Windows yet",
+        ))
+    }
+
+    pub fn set_permissions(&self, path: &Path, perm: FilePermissions) -> io::Result<()> {
+        if path.is_absolute() {
+            return crate::sys::fs::set_permissions(path, perm);
+        }
+        let mut opts = OpenOptions::new();
+        opts.access_mode(c::FILE_WRITE_ATTRIBUTES);
+        File { handle: self.open_relative(path, &opts, 0)? }.set_permissions(perm)
+    }
+
+    /// Opens the relative `path` with `NtCreateFile`, relative to this directory's handle.
+    fn open_relative(
+        &self,
+        path: &Path,
+        opts: &OpenOptions,
+        create_options: c::NTCREATEFILE_CREATE_OPTIONS,
+    ) -> io::Result<Handle> {
         let path = to_u16s(path)?;
         let path = &path[..path.len() - 1]; // trim 0 byte
-        self.open_file_native(&path, opts).map(|handle| File { handle })
+        self.open_file_native(&path, opts, create_options)
+    }
+
+    /// Deletes the relative `path` without following a symlink in its last component.
+    fn delete(
+        &self,
+        path: &Path,
+        create_options: c::NTCREATEFILE_CREATE_OPTIONS,
+    ) -> io::Result<()> {
+        let mut opts = OpenOptions::new();
+        opts.access_mode(c::DELETE);
+        let handle =
+            self.open_relative(path, &opts, create_options | c::FILE_OPEN_REPARSE_POINT)?;
+        File { handle }.delete().io_result()
     }
 
     fn open_with_native(path: &WCStr, opts: &OpenOptions) -> io::Result<Self> {
@@ -92,14 +259,19 @@ fn open_with_native(path: &WCStr, opts: &OpenOptions) -> io::Result<Self> {
         }
     }
 
-    fn open_file_native(&self, path: &[u16], opts: &OpenOptions) -> io::Result<Handle> {
+    fn open_file_native(
+        &self,
+        path: &[u16],
+        opts: &OpenOptions,
+        create_options: c::NTCREATEFILE_CREATE_OPTIONS,
+    ) -> io::Result<Handle> {
         let name = UnicodeStrRef::from_slice(path);
         let object_attributes = c::OBJECT_ATTRIBUTES {
             RootDirectory: self.handle.as_raw_handle(),
             ObjectName: name.as_ptr(),
             ..c::OBJECT_ATTRIBUTES::with_length()
         };
-        unsafe { nt_create_file(opts, &object_attributes, c::FILE_NON_DIRECTORY_FILE) }
+        unsafe { nt_create_file(opts, &object_attributes, create_options) }
     }
 }
 
@@ -17,6 +17,8 @@
 //! Once it has obtained all necessary pieces and brought any wrapper types into a state where they
 //! can be safely bypassed it will attempt to use the `copy_file_range(2)`,
 //! `sendfile(2)` or `splice(2)` syscalls to move data directly between file descriptors.
+//! `splice(2)` requires one of its file descriptors to be a pipe, so data arriving from a socket
+//! is spliced through an intermediate pipe when neither side is one.
 //! Since those syscalls have requirements that cannot be fully checked in advance it attempts
 //! to use them one after another (guided by hints) to figure out which one works and
 //! falls back to the generic read-write copy loop if none of them does.
@@ -52,7 +54,7 @@
 use crate::cmp::min;
 use crate::fs::{File, Metadata};
 use crate::io::{
-    BufRead, BufReader, BufWriter, Error, PipeReader, PipeWriter, Read, Result, StderrLock,
+    self, BufRead, BufReader, BufWriter, Error, PipeReader, PipeWriter, Read, Result, StderrLock,
     StdinLock, StdoutLock, Take, Write,
 };
 use crate::mem::ManuallyDrop;
@@ -73,8 +75,9 @@
 pub fn kernel_copy<R: Read + ?Sized, W: Write + ?Sized>(
     read: &mut R,
     write: &mut W,
+    progress: &mut dyn FnMut(u64),
 ) -> Result<CopyState> {
-    let copier = Copier { read, write };
+    let copier = Copier { read, write, progress };
     SpecCopy::copy(copier)
 }
 
@@ -107,6 +110,14 @@ fn maybe_fifo(&self) -> bool {
         }
     }
 
+    fn is_socket(&self) -> bool {
+        match self {
+            FdMeta::Metadata(meta) => meta.file_type().is_socket(),
+            FdMeta::Socket => true,
+            FdMeta::Pipe | FdMeta::NoneObtained => false,
+        }
+    }
+
     fn potential_sendfile_source(&self) -> bool {
         match self {
             // procfs erroneously shows 0 length on non-empty readable files.
@@ -170,24 +181,26 @@ fn safe_kernel_copy(source: &FdMeta, sink: &FdMeta) -> bool {
 
 struct CopyParams(FdMeta, Option<RawFd>);
 
-struct Copier<'a, 'b, R: Read + ?Sized, W: Write + ?Sized> {
+struct Copier<'a, 'b, 'c, R: Read + ?Sized, W: Write + ?Sized> {
     read: &'a mut R,
     write: &'b mut W,
+    /// Called with the number of bytes written to `write` after each successful syscall.
+    progress: &'c mut dyn FnMut(u64),
 }
 
 trait SpecCopy {
     fn copy(self) -> Result<CopyState>;
 }
 
-impl<R: Read + ?Sized, W: Write + ?Sized> SpecCopy for Copier<'_, '_, R, W> {
+impl<R: Read + ?Sized, W: Write + ?Sized> SpecCopy for Copier<'_, '_, '_, R, W> {
     default fn copy(self) -> Result<CopyState> {
         Ok(CopyState::Fallback(0))
     }
 }
 
-impl<R: CopyRead, W: CopyWrite> SpecCopy for Copier<'_, '_, R, W> {
+impl<R: CopyRead, W: CopyWrite> SpecCopy for Copier<'_, '_, '_, R, W> {
     fn copy(self) -> Result<CopyState> {
-        let (reader, writer) = (self.read, self.write);
+        let (reader, writer, progress) = (self.read, self.write, self.progress);
         let r_cfg = reader.properties();
         let w_cfg = writer.properties();
 
@@ -196,6 +209,9 @@ fn copy(self) -> Result<CopyState> {
             let bytes = reader.drain_to(writer, u64::MAX)?;
             // BufWriter buffered bytes have already been accounted for in earlier write() calls
             writer.flush()?;
+            if bytes > 0 {
+                progress(bytes);
+            }
             Ok(bytes)
         };
 
@@ -210,7 +226,7 @@ fn copy(self) -> Result<CopyState> {
             if input_meta.copy_file_range_candidate(FdHandle::Input)
                 && output_meta.copy_file_range_candidate(FdHandle::Output)
             {
-                let result = copy_regular_files(readfd, writefd, max_write);
+                let result = copy_regular_files(readfd, writefd, max_write, progress);
                 result.update_take(reader);
 
                 match result {
@@ -229,7 +245,8 @@ fn copy(self) -> Result<CopyState> {
             // fall back to the generic copy loop.
             if input_meta.potential_sendfile_source() && safe_kernel_copy(&input_meta, &output_meta)
             {
-                let result = sendfile_splice(SpliceMode::Sendfile, readfd, writefd, max_write);
+                let result =
+                    sendfile_splice(SpliceMode::Sendfile, readfd, writefd, max_write, progress);
                 result.update_take(reader);
 
                 match result {
@@ -244,7 +261,8 @@ fn copy(self) -> Result<CopyState> {
             if (input_meta.maybe_fifo() || output_meta.maybe_fifo())
                 && safe_kernel_copy(&input_meta, &output_meta)
             {
-                let result = sendfile_splice(SpliceMode::Splice, readfd, writefd, max_write);
+                let result =
+                    sendfile_splice(SpliceMode::Splice, readfd, writefd, max_write, progress);
                 result.update_take(reader);
 
                 match result {
@@ -258,6 +276,22 @@ fn copy(self) -> Result<CopyState> {
                     }
                 }
             }
+
+            // neither sendfile nor a direct splice can read from a socket into anything but a pipe,
+            // so splice through an intermediate pipe instead. This covers socket -> socket and
+            // socket -> file copies.
+            if input_meta.is_socket() && !output_meta.maybe_fifo() {
+                let result = splice_via_pipe(readfd, writefd, max_write, progress);
+                result.update_take(reader);
+
+                match result {
+                    CopyResult::Ended(bytes_copied) => {
+                        return Ok(CopyState::Ended(bytes_copied + written));
+                    }
+                    CopyResult::Error(e, _) => return Err(e),
+                    CopyResult::Fallback(bytes) => written += bytes,
+                }
+            }
         }
 
         // fallback if none of the more specialized syscalls wants to work with these file descriptors
@@ -590,7 +624,12 @@ fn update_take(&self, reader: &mut impl CopyRead) {
 /// Callers must handle fallback to a generic copy loop.
 /// `Fallback` may indicate non-zero number of bytes already written
 /// if one of the files' cursor +`max_len` would exceed u64::MAX (`EOVERFLOW`).
-fn copy_regular_files(reader: RawFd, writer: RawFd, max_len: u64) -> CopyResult {
+fn copy_regular_files(
+    reader: RawFd,
+    writer: RawFd,
+    max_len: u64,
+    progress: &mut dyn FnMut(u64),
+) -> CopyResult {
     use crate::cmp;
 
     const NOT_PROBED: u8 = 0;
@@ -663,7 +702,10 @@ fn probe_copy_file_range_support() -> u8 {
                 return CopyResult::Fallback(0);
             }
             Ok(0) => return CopyResult::Ended(written), // reached EOF
-            Ok(ret) => written += ret as u64,
+            Ok(ret) => {
+                written += ret as u64;
+                progress(ret as u64);
+            }
             Err(err) => {
                 return match err.raw_os_error() {
                     // when file offset + max_length > u64::MAX
@@ -721,28 +763,35 @@ enum SpliceMode {
     Splice,
 }
 
+// Android builds use feature level 14, but the libc wrapper for splice is
+// gated on feature level 21+, so we have to invoke the syscall directly.
+#[cfg(target_os = "android")]
+syscall!(
+    fn splice(
+        srcfd: libc::c_int,
+        src_offset: *const i64,
+        dstfd: libc::c_int,
+        dst_offset: *const i64,
+        len: libc::size_t,
+        flags: libc::c_int,
+    ) -> libc::ssize_t;
+);
+
+#[cfg(target_os = "linux")]
+use libc::splice;
+
+static HAS_SPLICE: Atomic<bool> = AtomicBool::new(true);
+
 /// performs splice or sendfile between file descriptors
 /// Does _not_ fall back to a generic copy loop.
-fn sendfile_splice(mode: SpliceMode, reader: RawFd, writer: RawFd, len: u64) -> CopyResult {
+fn sendfile_splice(
+    mode: SpliceMode,
+    reader: RawFd,
+    writer: RawFd,
+    len: u64,
+    progress: &mut dyn FnMut(u64),
+) -> CopyResult {
     static HAS_SENDFILE: Atomic<bool> = AtomicBool::new(true);
-    static HAS_SPLICE: Atomic<bool> = AtomicBool::new(true);
-
-    // Android builds use feature level 14, but the libc wrapper for splice is
-    // gated on feature level 21+, so we have to invoke the syscall directly.
-    #[cfg(target_os = "android")]
-    syscall!(
-        fn splice(
-            srcfd: libc::c_int,
-            src_offset: *const i64,
-            dstfd: libc::c_int,
-            dst_offset: *const i64,
-            len: libc::size_t,
-            flags: libc::c_int,
-        ) -> libc::ssize_t;
-    );
-
-    #[cfg(target_os = "linux")]
-    use libc::splice;
 
     match mode {
         SpliceMode::Sendfile if !HAS_SENDFILE.load(Ordering::Relaxed) => {
@@ -770,7 +819,10 @@ fn splice(
 
         match result {
             Ok(0) => break, // EOF
-            Ok(ret) => written += ret as u64,
+            Ok(ret) => {
+                written += ret as u64;
+                progress(ret as u64);
+            }
             Err(err) => {
                 return match err.raw_os_error() {
                     Some(ENOSYS | EPERM) => {
@@ -798,3 +850,114 @@ fn splice(
     }
     CopyResult::Ended(written)
 }
+
+/// Splices data from `reader` into an intermediate pipe and from there into `writer`, for file
+/// descriptor pairs where neither side is a pipe. This is mainly useful for sockets, which can't
+/// be the source of a sendfile.
+/// Does _not_ fall back to a generic copy loop.
+fn splice_via_pipe(
+    reader: RawFd,
+    writer: RawFd,
+    len: u64,
+    progress: &mut dyn FnMut(u64),
+) -> CopyResult {
+    if !HAS_SPLICE.load(Ordering::Relaxed) {
+        return CopyResult::Fallback(0);
+    }
+
+    let Ok((pipe_reader, pipe_writer)) = io::pipe() else {
+        // out of file descriptors, the generic copy loop doesn't need any
+        return CopyResult::Fallback(0);
+    };
+
+    let mut written = 0u64;
+    while written < len {
+        // the default capacity of a pipe, larger chunks wouldn't fit into it and leave us blocked
+        let chunk_size = min(len - written, 0x10000) as usize;
+
+        // a single call, rather than a loop filling the pipe, so that data is passed on as soon
+        // as it arrives, like a read-write loop would.
+        let result = cvt(unsafe {
+            splice(reader, ptr::null_mut(), pipe_writer.as_raw_fd(), ptr::null_mut(), chunk_size, 0)
+        });
+
+        let mut in_pipe = match result {
+            Ok(0) => break, // EOF
+            Ok(ret) => ret as usize,
+            Err(err) => {
+                return match err.raw_os_error() {
+                    Some(ENOSYS | EPERM) if written == 0 => {
+                        // syscall not supported (ENOSYS)
+                        // syscall is disallowed, e.g. by seccomp (EPERM)
+                        HAS_SPLICE.store(false, Ordering::Relaxed);
+                        CopyResult::Fallback(0)
+                    }
+                    // splice does not support reading from this file descriptor (EINVAL)
+                    Some(EINVAL) if written == 0 => CopyResult::Fallback(0),
+                    _ => CopyResult::Error(err, written),
+                };
+            }
+        };
+
+        while in_pipe > 0 {
+            let result = cvt(unsafe {
+                splice(
+                    pipe_reader.as_raw_fd(),
+                    ptr::null_mut(),
+                    writer,
+                    ptr::null_mut(),
+                    in_pipe,
+                    0,
+                )
+            });
+
+            match result {
+                Ok(0) => return CopyResult::Error(Error::WRITE_ALL_EOF, written),
+                Ok(ret) => {
+                    in_pipe -= ret as usize;
+                    written += ret as u64;
+                    progress(ret as u64);
+                }
+                // splice does not support writing to this file descriptor, e.g. because it was
+                // opened with O_APPEND (EINVAL). The data in the pipe has already been taken from
+                // `reader`, so it has to be written out before falling back.
+                Err(err) if matches!(err.raw_os_error(), Some(EINVAL | ENOSYS | EPERM)) => {
+                    return match write_from_pipe(
+                        &pipe_reader,
+                        writer,
+                        in_pipe,
+                        &mut written,
+                        progress,
+                    ) {
+                        Ok(()) => CopyResult::Fallback(written),
+                        Err(e) => CopyResult::Error(e, written),
+                    };
+                }
+                Err(err) => return CopyResult::Error(err, written),
+            }
+        }
+    }
+    CopyResult::Ended(written)
+}
+
+/// Moves `len` bytes from `pipe` to `writer` with read and write syscalls, adding them to
+/// `written`.
+fn write_from_pipe(
+    mut pipe: &PipeReader,
+    writer: RawFd,
+    mut len: usize,
+    written: &mut u64,
+    progress: &mut dyn FnMut(u64),
+) -> Result<()> {
+    let mut writer: ManuallyDrop<File> = ManuallyDrop::new(unsafe { File::from_raw_fd(writer) });
+    let mut buf = [0u8; 8192];
+    while len > 0 {
+        let chunk = min(len, buf.len());
+        let n = pipe.read(&mut buf[..chunk])?;
+        writer.write_all(&buf[..n])?;
+        len -= n;
+        *written += n as u64;
+        progress(n as u64);
+    }
+    Ok(())
+}
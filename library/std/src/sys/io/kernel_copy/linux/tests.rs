@@ -118,6 +118,80 @@ fn dont_splice_pipes_from_files() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn copies_from_sockets() -> Result<()> {
+    use crate::os::unix::net::UnixStream;
+
+    const BYTES: usize = 256 * 1024;
+    let data: Vec<u8> = (0..BYTES).map(|i| i as u8).collect();
+
+    let send_all = |data: Vec<u8>| -> Result<UnixStream> {
+        let (mut tx, rx) = UnixStream::pair()?;
+        // the copy may stop early, in which case the rest of the data is discarded
+        crate::thread::spawn(move || tx.write_all(&data));
+        Ok(rx)
+    };
+
+    // socket -> socket
+    let mut source = send_all(data.clone())?;
+    let (mut sink, mut sink_drainer) = UnixStream::pair()?;
+    let t = crate::thread::spawn(move || {
+        let mut received = Vec::new();
+        sink_drainer.read_to_end(&mut received).unwrap();
+        received
+    });
+    let mut total = 0;
+    let copied = io::copy_with_progress(&mut source, &mut sink, |t| total = t)?;
+    drop(sink);
+    assert_eq!(copied, BYTES as u64);
+    assert_eq!(total, BYTES as u64, "progress should report every byte");
+    assert!(t.join().unwrap() == data, "socket -> socket copy corrupted data");
+
+    let tmp_path = tmpdir();
+
+    // socket -> file, limited by Take
+    let mut source = send_all(data.clone())?.take(100_000);
+    let sink_path = tmp_path.join("copies_from_sockets.sink");
+    let mut sink = OpenOptions::new().create(true).write(true).read(true).open(&sink_path)?;
+    assert_eq!(io::copy(&mut source, &mut sink)?, 100_000);
+    assert_eq!(source.limit(), 0);
+    assert!(crate::fs::read(&sink_path)? == data[..100_000], "socket -> file copy corrupted data");
+
+    // socket -> append-mode file, which can't be spliced to
+    let sink_path = tmp_path.join("copies_from_sockets.append");
+    let mut sink = OpenOptions::new().create(true).append(true).open(&sink_path)?;
+    sink.write_all(b"header")?;
+    let mut source = send_all(data.clone())?;
+    assert_eq!(io::copy(&mut source, &mut sink)?, BYTES as u64);
+    let written = crate::fs::read(&sink_path)?;
+    assert!(written[..6] == *b"header" && written[6..] == data, "no data should be lost");
+
+    Ok(())
+}
+
+#[test]
+fn copy_with_progress_reports_kernel_copies() -> Result<()> {
+    const BYTES: usize = 128 * 1024;
+    let tmp_path = tmpdir();
+    let source_path = tmp_path.join("progress.source");
+    let sink_path = tmp_path.join("progress.sink");
+    crate::fs::write(&source_path, vec![0x42; BYTES])?;
+
+    let mut source = crate::io::BufReader::new(crate::fs::File::open(&source_path)?);
+    // partially fill the buffer, which has to be drained before the kernel takes over
+    source.fill_buf()?;
+    let mut sink = crate::fs::File::create(&sink_path)?;
+
+    let mut reports = Vec::new();
+    let copied = io::copy_with_progress(&mut source, &mut sink, |total| reports.push(total))?;
+    assert_eq!(copied, BYTES as u64);
+    assert_eq!(reports.last(), Some(&copied));
+    assert!(reports.is_sorted_by(|a, b| a < b), "expected increasing totals, got {reports:?}");
+    assert_eq!(crate::fs::read(&sink_path)?, vec![0x42; BYTES]);
+
+    Ok(())
+}
+
 #[bench]
 fn bench_file_to_file_copy(b: &mut test::Bencher) {
     const BYTES: usize = 128 * 1024;
@@ -279,6 +353,7 @@ fn bench_socket_pipe_socket_copy(b: &mut test::Bencher) {
         local_end.as_raw_fd(),
         write_end.as_raw_fd(),
         1,
+        &mut |_| {},
     );
 
     match probe {
@@ -299,6 +374,7 @@ fn bench_socket_pipe_socket_copy(b: &mut test::Bencher) {
                 local_source.as_raw_fd(),
                 write_end.as_raw_fd(),
                 u64::MAX,
+                &mut |_| {},
             );
         }
     });
@@ -12,7 +12,11 @@ pub enum CopyState {
     _ => {
         use crate::io::{Result, Read, Write};
 
-        pub fn kernel_copy<R: ?Sized, W: ?Sized>(_reader: &mut R, _writer: &mut W) -> Result<CopyState>
+        pub fn kernel_copy<R: ?Sized, W: ?Sized>(
+            _reader: &mut R,
+            _writer: &mut W,
+            _progress: &mut dyn FnMut(u64),
+        ) -> Result<CopyState>
         where
             R: Read,
             W: Write,
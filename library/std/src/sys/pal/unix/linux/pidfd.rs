@@ -3,6 +3,7 @@
 use crate::sys::fd::FileDesc;
 use crate::sys::process::ExitStatus;
 use crate::sys::{AsInner, FromInner, IntoInner, cvt};
+use crate::time::{Duration, Instant};
 
 #[cfg(test)]
 mod tests;
@@ -15,13 +16,17 @@ pub fn kill(&self) -> io::Result<()> {
         self.send_signal(libc::SIGKILL)
     }
 
-    #[cfg(any(test, target_env = "gnu", target_env = "musl"))]
-    pub(crate) fn current_process() -> io::Result<PidFd> {
-        let pid = crate::process::id();
+    /// Opens a pidfd for the process `pid`.
+    pub(crate) fn open(pid: libc::pid_t) -> io::Result<PidFd> {
         let pidfd = cvt(unsafe { libc::syscall(libc::SYS_pidfd_open, pid, 0) })?;
         Ok(unsafe { PidFd::from_raw_fd(pidfd as RawFd) })
     }
 
+    #[cfg(any(test, target_env = "gnu", target_env = "musl"))]
+    pub(crate) fn current_process() -> io::Result<PidFd> {
+        PidFd::open(crate::process::id() as libc::pid_t)
+    }
+
     #[cfg(any(test, target_env = "gnu", target_env = "musl"))]
     pub(crate) fn pid(&self) -> io::Result<u32> {
         use crate::sys::weak::weak;
@@ -106,6 +111,30 @@ pub fn wait(&self) -> io::Result<ExitStatus> {
     pub fn try_wait(&self) -> io::Result<Option<ExitStatus>> {
         self.waitid(libc::WEXITED | libc::WNOHANG)
     }
+
+    pub fn wait_timeout(&self, timeout: Duration) -> io::Result<Option<ExitStatus>> {
+        let Some(deadline) = Instant::now().checked_add(timeout) else {
+            return self.wait().map(Some);
+        };
+        // A pidfd becomes readable once the process has exited.
+        let mut pollfd = libc::pollfd { fd: self.0.as_raw_fd(), events: libc::POLLIN, revents: 0 };
+        loop {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            // Round up, so that `poll` doesn't time out before the deadline.
+            let timeout_ms = timeout.as_nanos().div_ceil(1_000_000).min(libc::c_int::MAX as u128);
+            match unsafe { libc::poll(&mut pollfd, 1, timeout_ms as libc::c_int) } {
+                -1 => {
+                    let err = io::Error::last_os_error();
+                    if !err.is_interrupted() {
+                        return Err(err);
+                    }
+                }
+                0 if timeout.is_zero() => return Ok(None),
+                0 => {}
+                _ => return self.try_wait(),
+            }
+        }
+    }
 }
 
 impl AsInner<FileDesc> for PidFd {
@@ -36,6 +36,33 @@
     getpid, read_output,
 };
 
+use crate::io;
+use crate::time::{Duration, Instant};
+
+/// Waits for a process to exit by calling `try_wait` at increasing intervals, on platforms that
+/// have no way to wait for a process with a timeout.
+#[allow(dead_code)]
+fn wait_timeout_by_polling(
+    try_wait: &mut dyn FnMut() -> io::Result<Option<ExitStatus>>,
+    timeout: Duration,
+) -> io::Result<Option<ExitStatus>> {
+    const MAX_INTERVAL: Duration = Duration::from_millis(100);
+
+    let start = Instant::now();
+    let mut interval = Duration::from_millis(1);
+    loop {
+        if let Some(status) = try_wait()? {
+            return Ok(Some(status));
+        }
+        let elapsed = start.elapsed();
+        if elapsed >= timeout {
+            return Ok(None);
+        }
+        crate::thread::sleep(interval.min(timeout - elapsed));
+        interval = (interval * 2).min(MAX_INTERVAL);
+    }
+}
+
 #[cfg(any(
     all(
         target_family = "unix",
@@ -74,6 +101,48 @@ pub fn output(cmd: &mut Command) -> crate::io::Result<(ExitStatus, Vec<u8>, Vec<
     Ok((status, stdout, stderr))
 }
 
+/// Like [`output`], but kills the process if it hasn't exited after `timeout`. Returns the output
+/// that was read until then, and whether the timeout elapsed.
+#[cfg(any(
+    all(
+        target_family = "unix",
+        not(any(
+            target_os = "espidf",
+            target_os = "horizon",
+            target_os = "vita",
+            target_os = "nuttx"
+        ))
+    ),
+    target_os = "windows",
+    target_os = "motor"
+))]
+pub fn output_with_timeout(
+    cmd: &mut Command,
+    timeout: Duration,
+) -> io::Result<(ExitStatus, Vec<u8>, Vec<u8>, bool)> {
+    let Some(deadline) = Instant::now().checked_add(timeout) else {
+        let (status, stdout, stderr) = output(cmd)?;
+        return Ok((status, stdout, stderr, false));
+    };
+    let (mut process, mut pipes) = cmd.spawn(Stdio::MakePipe, false)?;
+
+    drop(pipes.stdin.take());
+    let (mut stdout, mut stderr) = (Vec::new(), Vec::new());
+    let (out, err) = (pipes.stdout.take(), pipes.stderr.take());
+    if imp::read_output_until(out, &mut stdout, err, &mut stderr, deadline)? {
+        let timeout = deadline.saturating_duration_since(Instant::now());
+        if let Some(status) = process.wait_timeout(timeout)? {
+            return Ok((status, stdout, stderr, false));
+        }
+    }
+
+    // The output read so far is returned as is: once the process is killed, its pipes may still
+    // be kept open by its own children.
+    process.kill()?;
+    let status = process.wait()?;
+    Ok((status, stdout, stderr, true))
+}
+
 #[cfg(not(any(
     all(
         target_family = "unix",
@@ -87,4 +156,4 @@ pub fn output(cmd: &mut Command) -> crate::io::Result<(ExitStatus, Vec<u8>, Vec<
     target_os = "windows",
     target_os = "motor"
 )))]
-pub use imp::output;
+pub use imp::{output, output_with_timeout};
@@ -9,6 +9,7 @@
 use crate::process::StdioPipes;
 use crate::sys::fs::File;
 use crate::sys::{AsInner, FromInner, map_motor_error};
+use crate::time::{Duration, Instant};
 use crate::{fmt, io};
 
 pub enum Stdio {
@@ -286,6 +287,10 @@ pub fn try_wait(&mut self) -> io::Result<Option<ExitStatus>> {
         }
     }
 
+    pub fn wait_timeout(&mut self, timeout: Duration) -> io::Result<Option<ExitStatus>> {
+        crate::sys::process::wait_timeout_by_polling(&mut || self.try_wait(), timeout)
+    }
+
     #[allow(unused)]
     pub fn handle(&self) -> u64 {
         self.handle
@@ -332,6 +337,16 @@ pub fn read_output(
     Err(io::Error::from_raw_os_error(moto_rt::E_NOT_IMPLEMENTED.into()))
 }
 
+pub fn read_output_until(
+    _out: Option<ChildPipe>,
+    _stdout: &mut Vec<u8>,
+    _err: Option<ChildPipe>,
+    _stderr: &mut Vec<u8>,
+    _deadline: Instant,
+) -> io::Result<bool> {
+    Err(io::Error::from_raw_os_error(moto_rt::E_NOT_IMPLEMENTED.into()))
+}
+
 pub fn getpid() -> u32 {
     panic!("Pids on Motor OS are u64.")
 }
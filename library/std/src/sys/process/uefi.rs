@@ -11,6 +11,7 @@
 use crate::sys::io::error_string;
 use crate::sys::pal::helpers;
 use crate::sys::unsupported;
+use crate::time::Duration;
 use crate::{fmt, io};
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -209,6 +210,14 @@ pub fn output(command: &mut Command) -> io::Result<(ExitStatus, Vec<u8>, Vec<u8>
     Ok((ExitStatus(stat), stdout, stderr))
 }
 
+pub fn output_with_timeout(
+    _command: &mut Command,
+    _timeout: Duration,
+) -> io::Result<(ExitStatus, Vec<u8>, Vec<u8>, bool)> {
+    // Images are run to completion by `output`, so they can't be stopped after a timeout.
+    unsupported()
+}
+
 impl From<ChildPipe> for Stdio {
     fn from(pipe: ChildPipe) -> Stdio {
         pipe.diverge()
@@ -327,6 +336,10 @@ pub fn wait(&mut self) -> io::Result<ExitStatus> {
     pub fn try_wait(&mut self) -> io::Result<Option<ExitStatus>> {
         self.0
     }
+
+    pub fn wait_timeout(&mut self, _timeout: Duration) -> io::Result<Option<ExitStatus>> {
+        self.0
+    }
 }
 
 pub struct CommandArgs<'a> {
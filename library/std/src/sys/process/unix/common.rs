@@ -8,6 +8,7 @@
 use crate::collections::BTreeMap;
 use crate::ffi::{CStr, CString, OsStr, OsString};
 use crate::os::unix::prelude::*;
+use crate::os::unix::process::Resource;
 use crate::path::Path;
 use crate::process::StdioPipes;
 use crate::sys::fd::FileDesc;
@@ -17,7 +18,8 @@
 use crate::sys::pipe::pipe;
 use crate::sys::process::env::{CommandEnv, CommandEnvs, CommandResolvedEnvs};
 use crate::sys::{FromInner, IntoInner, cvt_r};
-use crate::{fmt, io, mem};
+use crate::time::Instant;
+use crate::{cmp, fmt, io, mem};
 
 mod cstring_array;
 
@@ -101,8 +103,11 @@ pub struct Command {
     stderr: Option<Stdio>,
     #[cfg(target_os = "linux")]
     create_pidfd: bool,
+    #[cfg(target_os = "linux")]
+    cgroup: Option<OwnedFd>,
     pgroup: Option<pid_t>,
     setsid: bool,
+    rlimits: Vec<(Resource, u64, u64)>,
 }
 
 // passed to do_exec() with configuration of what the child stdio should look
@@ -181,8 +186,11 @@ pub fn new(program: &OsStr) -> Command {
             stderr: None,
             #[cfg(target_os = "linux")]
             create_pidfd: false,
+            #[cfg(target_os = "linux")]
+            cgroup: None,
             pgroup: None,
             setsid: false,
+            rlimits: Vec::new(),
         }
     }
 
@@ -221,6 +229,12 @@ pub fn chroot(&mut self, dir: &Path) {
     pub fn setsid(&mut self, setsid: bool) {
         self.setsid = setsid;
     }
+    pub fn rlimit(&mut self, resource: Resource, soft: u64, hard: u64) {
+        match self.rlimits.iter_mut().find(|(r, ..)| *r == resource) {
+            Some(limit) => *limit = (resource, soft, hard),
+            None => self.rlimits.push((resource, soft, hard)),
+        }
+    }
 
     #[cfg(target_os = "linux")]
     pub fn create_pidfd(&mut self, val: bool) {
@@ -238,6 +252,16 @@ pub fn get_create_pidfd(&self) -> bool {
         self.create_pidfd
     }
 
+    #[cfg(target_os = "linux")]
+    pub fn cgroup(&mut self, cgroup: OwnedFd) {
+        self.cgroup = Some(cgroup);
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn get_cgroup(&self) -> Option<BorrowedFd<'_>> {
+        self.cgroup.as_ref().map(|fd| fd.as_fd())
+    }
+
     pub fn saw_nul(&self) -> bool {
         self.saw_nul
     }
@@ -311,6 +335,10 @@ pub fn get_chroot(&self) -> Option<&CStr> {
     pub fn get_setsid(&self) -> bool {
         self.setsid
     }
+    #[allow(dead_code)]
+    pub fn get_rlimits(&self) -> &[(Resource, u64, u64)] {
+        &self.rlimits
+    }
 
     pub fn get_closures(&mut self) -> &mut Vec<Box<dyn FnMut() -> io::Result<()> + Send + Sync>> {
         &mut self.closures
@@ -526,10 +554,16 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             if self.pgroup.is_some() {
                 debug_command.field("pgroup", &self.pgroup);
             }
+            if !self.rlimits.is_empty() {
+                debug_command.field("rlimits", &self.rlimits);
+            }
 
             #[cfg(target_os = "linux")]
             {
                 debug_command.field("create_pidfd", &self.create_pidfd);
+                if self.cgroup.is_some() {
+                    debug_command.field("cgroup", &self.cgroup);
+                }
             }
 
             debug_command.finish()
@@ -684,6 +718,67 @@ fn read(fd: &FileDesc, dst: &mut Vec<u8>) -> Result<bool, io::Error> {
     }
 }
 
+/// Reads from the pipes until both are at EOF, or until `deadline`. Returns whether all output was
+/// read.
+pub fn read_output_until(
+    out: Option<ChildPipe>,
+    stdout: &mut Vec<u8>,
+    err: Option<ChildPipe>,
+    stderr: &mut Vec<u8>,
+    deadline: Instant,
+) -> io::Result<bool> {
+    let mut pipes = [(out, stdout), (err, stderr)];
+    for (pipe, _) in &pipes {
+        if let Some(pipe) = pipe {
+            pipe.set_nonblocking(true)?;
+        }
+    }
+
+    loop {
+        let mut fds: [libc::pollfd; 2] = unsafe { mem::zeroed() };
+        for ((pipe, _), fd) in pipes.iter().zip(&mut fds) {
+            // `poll` ignores negative file descriptors.
+            fd.fd = pipe.as_ref().map_or(-1, |pipe| pipe.as_raw_fd());
+            fd.events = libc::POLLIN;
+        }
+        if fds.iter().all(|fd| fd.fd < 0) {
+            return Ok(true);
+        }
+
+        let timeout = deadline.saturating_duration_since(Instant::now());
+        // Round up, so that `poll` doesn't time out before the deadline.
+        let timeout_ms = cmp::min(timeout.as_nanos().div_ceil(1_000_000), c_int::MAX as u128);
+        match unsafe { libc::poll(fds.as_mut_ptr(), 2, timeout_ms as c_int) } {
+            -1 => {
+                let err = io::Error::last_os_error();
+                if !err.is_interrupted() {
+                    return Err(err);
+                }
+            }
+            0 if timeout.is_zero() => return Ok(false),
+            _ => {
+                for ((pipe, dst), fd) in pipes.iter_mut().zip(&fds) {
+                    if let Some(p) = pipe
+                        && fd.revents != 0
+                        && read(p, dst)?
+                    {
+                        *pipe = None;
+                    }
+                }
+            }
+        }
+    }
+
+    // Like in `read_output`, returns whether EOF was reached.
+    fn read(fd: &FileDesc, dst: &mut Vec<u8>) -> Result<bool, io::Error> {
+        match fd.read_to_end(dst) {
+            Ok(_) => Ok(true),
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+}
+
 pub fn getpid() -> u32 {
     unsafe { libc::getpid() as u32 }
 }
@@ -4,6 +4,7 @@
 use crate::num::NonZero;
 use crate::process::StdioPipes;
 use crate::sys::pal::fuchsia::*;
+use crate::time::Duration;
 use crate::{fmt, io, mem, ptr};
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -24,6 +25,12 @@ pub fn spawn(
                 "nul byte found in provided data",
             ));
         }
+        if !self.get_rlimits().is_empty() {
+            return Err(io::const_error!(
+                io::ErrorKind::Unsupported,
+                "resource limits not supported by fuchsia",
+            ));
+        }
 
         let (ours, theirs) = self.setup_io(default, needs_stdin)?;
 
@@ -222,6 +229,10 @@ pub fn try_wait(&mut self) -> io::Result<Option<ExitStatus>> {
         }
         Ok(Some(ExitStatus(proc_info.return_code)))
     }
+
+    pub fn wait_timeout(&mut self, timeout: Duration) -> io::Result<Option<ExitStatus>> {
+        crate::sys::process::wait_timeout_by_polling(&mut || self.try_wait(), timeout)
+    }
 }
 
 #[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
@@ -1,4 +1,7 @@
-#[cfg_attr(any(target_os = "espidf", target_os = "horizon", target_os = "nuttx"), allow(unused))]
+#[cfg_attr(
+    any(target_os = "espidf", target_os = "horizon", target_os = "vita", target_os = "nuttx"),
+    allow(unused)
+)]
 mod common;
 
 cfg_select! {
@@ -13,7 +16,7 @@
     any(target_os = "espidf", target_os = "horizon", target_os = "vita", target_os = "nuttx") => {
         mod unsupported;
         use unsupported as imp;
-        pub use unsupported::output;
+        pub use unsupported::{output, output_with_timeout};
     }
     _ => {
         mod unix;
@@ -25,5 +28,6 @@
 
 pub use self::common::{
     ChildPipe, Command, CommandArgs, ExitCode, Stdio, getpid, getppid, read_output,
+    read_output_until,
 };
 pub use crate::ffi::OsString as EnvKey;
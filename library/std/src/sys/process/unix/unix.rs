@@ -13,10 +13,12 @@
 use super::common::*;
 use crate::io::{self, Error, ErrorKind};
 use crate::num::NonZero;
+use crate::os::unix::process::Resource;
 use crate::process::StdioPipes;
 use crate::sys::cvt;
 #[cfg(target_os = "linux")]
 use crate::sys::pal::linux::pidfd::PidFd;
+use crate::time::Duration;
 use crate::{fmt, mem, sys};
 
 cfg_select! {
@@ -68,6 +70,12 @@ pub fn spawn(
             ));
         }
 
+        // Check the resource limits now, as errors from the child can only be
+        // reported as an errno.
+        for &(resource, ..) in self.get_rlimits() {
+            rlimit_resource(resource)?;
+        }
+
         let (ours, theirs) = self.setup_io(default, needs_stdin)?;
 
         if let Some(ret) = self.posix_spawn(&theirs, envp.as_ref())? {
@@ -227,6 +235,11 @@ pub fn exec(&mut self, default: Stdio) -> io::Error {
         if self.saw_nul() {
             return io::const_error!(ErrorKind::InvalidInput, "nul byte found in provided data");
         }
+        for &(resource, ..) in self.get_rlimits() {
+            if let Err(e) = rlimit_resource(resource) {
+                return e;
+            }
+        }
 
         match self.setup_io(default, true) {
             Ok((_, theirs)) => {
@@ -292,6 +305,20 @@ unsafe fn do_exec(
             cvt_r(|| libc::dup2(fd, libc::STDERR_FILENO))?;
         }
 
+        // Like the limits below, join the cgroup before dropping privileges,
+        // which may take away the permission to do so.
+        #[cfg(target_os = "linux")]
+        if let Some(cgroup) = self.get_cgroup() {
+            move_into_cgroup(cgroup)?;
+        }
+
+        // Set the limits before dropping privileges, which may prevent raising
+        // the hard limits.
+        for &(resource, soft, hard) in self.get_rlimits() {
+            let limit = libc::rlimit { rlim_cur: rlim(soft), rlim_max: rlim(hard) };
+            cvt(libc::setrlimit(rlimit_resource(resource)? as _, &limit))?;
+        }
+
         #[cfg(not(target_os = "l4re"))]
         {
             if let Some(_g) = self.get_groups() {
@@ -463,6 +490,9 @@ fn posix_spawn(
             || !self.get_closures().is_empty()
             || self.get_groups().is_some()
             || self.get_chroot().is_some()
+            // There is no spawn attribute for resource limits, and applying them
+            // with `prlimit` after spawning would race with the child.
+            || !self.get_rlimits().is_empty()
         {
             return Ok(None);
         }
@@ -534,6 +564,37 @@ fn pidfd_spawnp(
             }
         }
 
+        // glibc 2.41 can spawn the child directly into a cgroup, older versions
+        // and other libcs have to go through the fork/exec path, where the child
+        // moves itself into the cgroup before it executes the program.
+        cfg_select! {
+            all(target_os = "linux", target_env = "gnu") => {
+                use crate::os::fd::AsRawFd;
+
+                weak!(
+                    fn posix_spawnattr_setcgroup_np(
+                        attr: *mut libc::posix_spawnattr_t,
+                        cgroup: libc::c_int,
+                    ) -> libc::c_int;
+                );
+                const POSIX_SPAWN_SETCGROUP: libc::c_int = 0x100;
+
+                let cgroup = match self.get_cgroup() {
+                    Some(cgroup) => match posix_spawnattr_setcgroup_np.get() {
+                        Some(f) => Some((f, cgroup.as_raw_fd())),
+                        None => return Ok(None),
+                    },
+                    None => None,
+                };
+            }
+            target_os = "linux" => {
+                if self.get_cgroup().is_some() {
+                    return Ok(None);
+                }
+            }
+            _ => {}
+        }
+
         // Only glibc 2.24+ posix_spawn() supports returning ENOENT directly.
         #[cfg(all(target_os = "linux", target_env = "gnu"))]
         {
@@ -757,6 +818,12 @@ fn drop(&mut self) {
                 }
             }
 
+            #[cfg(all(target_os = "linux", target_env = "gnu"))]
+            if let Some((f, cgroup)) = cgroup {
+                flags |= POSIX_SPAWN_SETCGROUP;
+                cvt_nz(f(attrs.0.as_mut_ptr(), cgroup))?;
+            }
+
             cvt_nz(libc::posix_spawnattr_setflags(attrs.0.as_mut_ptr(), flags as _))?;
 
             // Make sure we synchronize access to the global `environ` resource
@@ -942,6 +1009,61 @@ union Cmsg {
     }
 }
 
+/// Returns the `RLIMIT_*` constant of `resource`.
+fn rlimit_resource(resource: Resource) -> io::Result<c_int> {
+    Ok(match resource {
+        Resource::Core => libc::RLIMIT_CORE as c_int,
+        Resource::Cpu => libc::RLIMIT_CPU as c_int,
+        Resource::Data => libc::RLIMIT_DATA as c_int,
+        Resource::FileSize => libc::RLIMIT_FSIZE as c_int,
+        Resource::OpenFiles => libc::RLIMIT_NOFILE as c_int,
+        Resource::Stack => libc::RLIMIT_STACK as c_int,
+        #[cfg(not(target_os = "openbsd"))]
+        Resource::AddressSpace => libc::RLIMIT_AS as c_int,
+        #[cfg(target_os = "openbsd")]
+        Resource::AddressSpace => {
+            return Err(io::const_error!(
+                ErrorKind::Unsupported,
+                "limiting the address space is not supported on this platform",
+            ));
+        }
+    })
+}
+
+/// Converts a limit to `rlim_t`, where `u64::MAX` and values too large to be
+/// represented mean no limit.
+#[cfg(not(any(target_os = "tvos", target_os = "watchos")))]
+fn rlim(limit: u64) -> libc::rlim_t {
+    if limit == u64::MAX {
+        return libc::RLIM_INFINITY;
+    }
+    libc::rlim_t::try_from(limit).unwrap_or(libc::RLIM_INFINITY)
+}
+
+/// Moves the calling process into the cgroup that `cgroup` refers to, by
+/// writing `0`, which stands for the writer, to the `cgroup.procs` file of the
+/// cgroup.
+///
+/// # Safety
+///
+/// This is called in the child between `fork` and `exec`, where another thread
+/// of the parent may have held a lock of malloc or stdio at the time of the
+/// fork. It must only make async-signal-safe calls: it opens, writes and closes
+/// a file with raw syscalls, doesn't allocate, and its errors are plain OS
+/// errors, which don't allocate either.
+#[cfg(target_os = "linux")]
+unsafe fn move_into_cgroup(cgroup: crate::os::fd::BorrowedFd<'_>) -> io::Result<()> {
+    use crate::os::fd::AsRawFd;
+    use crate::sys::cvt_r;
+
+    let procs = cvt_r(|| unsafe {
+        libc::openat(cgroup.as_raw_fd(), c"cgroup.procs".as_ptr(), libc::O_WRONLY | libc::O_CLOEXEC)
+    })?;
+    let written = cvt_r(|| unsafe { libc::write(procs, b"0".as_ptr().cast(), 1) });
+    unsafe { libc::close(procs) };
+    written.map(|_| ())
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 // Processes
 ////////////////////////////////////////////////////////////////////////////////
@@ -1040,6 +1162,33 @@ pub fn try_wait(&mut self) -> io::Result<Option<ExitStatus>> {
             Ok(Some(ExitStatus::new(status)))
         }
     }
+
+    pub fn wait_timeout(&mut self, timeout: Duration) -> io::Result<Option<ExitStatus>> {
+        if let Some(status) = self.status {
+            return Ok(Some(status));
+        }
+        #[cfg(target_os = "linux")]
+        {
+            // If the child wasn't spawned with a pidfd, try to open one just for waiting. This
+            // can't refer to another process, because the child hasn't been reaped yet.
+            let opened;
+            let pid_fd = match self.pidfd.as_ref() {
+                Some(pid_fd) => Some(pid_fd),
+                None => {
+                    opened = PidFd::open(self.pid).ok();
+                    opened.as_ref()
+                }
+            };
+            if let Some(pid_fd) = pid_fd {
+                let status = pid_fd.wait_timeout(timeout)?;
+                if let Some(status) = status {
+                    self.status = Some(status)
+                }
+                return Ok(status);
+            }
+        }
+        crate::sys::process::wait_timeout_by_polling(&mut || self.try_wait(), timeout)
+    }
 }
 
 /// Unix exit statuses
@@ -5,6 +5,7 @@
 use crate::num::NonZero;
 use crate::process::StdioPipes;
 use crate::sys::pal::unsupported::*;
+use crate::time::Duration;
 
 ////////////////////////////////////////////////////////////////////////////////
 // Command
@@ -28,6 +29,13 @@ pub fn output(_: &mut Command) -> io::Result<(ExitStatus, Vec<u8>, Vec<u8>)> {
     unsupported()
 }
 
+pub fn output_with_timeout(
+    _: &mut Command,
+    _: Duration,
+) -> io::Result<(ExitStatus, Vec<u8>, Vec<u8>, bool)> {
+    unsupported()
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 // Processes
 ////////////////////////////////////////////////////////////////////////////////
@@ -56,6 +64,10 @@ pub fn wait(&mut self) -> io::Result<ExitStatus> {
     pub fn try_wait(&mut self) -> io::Result<Option<ExitStatus>> {
         unsupported()
     }
+
+    pub fn wait_timeout(&mut self, _timeout: Duration) -> io::Result<Option<ExitStatus>> {
+        unsupported()
+    }
 }
 
 mod wait_status;
@@ -6,6 +6,7 @@
 use crate::num::NonZero;
 use crate::process::StdioPipes;
 use crate::sys::{cvt, thread};
+use crate::time::Duration;
 use crate::{fmt, sys};
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -33,6 +34,12 @@ pub fn spawn(
                 "chroot not supported by vxworks",
             ));
         }
+        if !self.get_rlimits().is_empty() {
+            return Err(io::const_error!(
+                ErrorKind::Unsupported,
+                "resource limits not supported by vxworks",
+            ));
+        }
         let (ours, theirs) = self.setup_io(default, needs_stdin)?;
         let mut p = Process { pid: 0, status: None };
 
@@ -185,6 +192,10 @@ pub fn try_wait(&mut self) -> io::Result<Option<ExitStatus>> {
             Ok(Some(ExitStatus::new(status)))
         }
     }
+
+    pub fn wait_timeout(&mut self, timeout: Duration) -> io::Result<Option<ExitStatus>> {
+        crate::sys::process::wait_timeout_by_polling(&mut || self.try_wait(), timeout)
+    }
 }
 
 /// Unix exit statuses
@@ -6,6 +6,7 @@
 use crate::process::StdioPipes;
 use crate::sys::fs::File;
 use crate::sys::unsupported;
+use crate::time::Duration;
 use crate::{fmt, io};
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -110,6 +111,13 @@ pub fn output(_cmd: &mut Command) -> io::Result<(ExitStatus, Vec<u8>, Vec<u8>)>
     unsupported()
 }
 
+pub fn output_with_timeout(
+    _cmd: &mut Command,
+    _timeout: Duration,
+) -> io::Result<(ExitStatus, Vec<u8>, Vec<u8>, bool)> {
+    unsupported()
+}
+
 impl From<ChildPipe> for Stdio {
     fn from(pipe: ChildPipe) -> Stdio {
         pipe.diverge()
@@ -290,6 +298,10 @@ pub fn wait(&mut self) -> io::Result<ExitStatus> {
     pub fn try_wait(&mut self) -> io::Result<Option<ExitStatus>> {
         self.0
     }
+
+    pub fn wait_timeout(&mut self, _timeout: Duration) -> io::Result<Option<ExitStatus>> {
+        self.0
+    }
 }
 
 pub struct CommandArgs<'a> {
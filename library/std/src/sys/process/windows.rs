@@ -22,13 +22,14 @@
 use crate::sys::fs::{File, OpenOptions};
 use crate::sys::handle::Handle;
 use crate::sys::pal::api::{self, WinError, utf16};
-use crate::sys::pal::{ensure_no_nuls, fill_utf16_buf};
+use crate::sys::pal::{dur2timeout, ensure_no_nuls, fill_utf16_buf};
 use crate::sys::{IntoInner, cvt, path, stdio};
+use crate::time::Duration;
 use crate::{cmp, env, fmt, ptr};
 
 mod child_pipe;
 
-pub use self::child_pipe::{ChildPipe, read_output};
+pub use self::child_pipe::{ChildPipe, read_output, read_output_until};
 
 ////////////////////////////////////////////////////////////////////////////////
 // Command
@@ -740,6 +741,16 @@ pub fn try_wait(&mut self) -> io::Result<Option<ExitStatus>> {
         }
     }
 
+    pub fn wait_timeout(&mut self, timeout: Duration) -> io::Result<Option<ExitStatus>> {
+        let res =
+            unsafe { c::WaitForSingleObject(self.handle.as_raw_handle(), dur2timeout(timeout)) };
+        match res {
+            c::WAIT_OBJECT_0 => self.try_wait(),
+            c::WAIT_TIMEOUT => Ok(None),
+            _ => Err(Error::last_os_error()),
+        }
+    }
+
     pub fn handle(&self) -> &Handle {
         &self.handle
     }
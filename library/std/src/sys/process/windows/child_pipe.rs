@@ -4,7 +4,9 @@
 use crate::sync::atomic::Atomic;
 use crate::sync::atomic::Ordering::Relaxed;
 use crate::sys::handle::Handle;
+use crate::sys::pal::dur2timeout;
 use crate::sys::{FromInner, IntoInner, api, c};
+use crate::time::Instant;
 use crate::{mem, ptr};
 
 pub struct ChildPipe {
@@ -449,6 +451,58 @@ pub fn read_output(
     }
 }
 
+/// Reads from the pipes until both are at EOF, or until `deadline`. Returns whether all output was
+/// read.
+pub fn read_output_until(
+    p1: Option<ChildPipe>,
+    v1: &mut Vec<u8>,
+    p2: Option<ChildPipe>,
+    v2: &mut Vec<u8>,
+    deadline: Instant,
+) -> io::Result<bool> {
+    let mut pipes = [
+        p1.map(|p1| AsyncPipe::new(p1.into_handle(), v1)).transpose()?,
+        p2.map(|p2| AsyncPipe::new(p2.into_handle(), v2)).transpose()?,
+    ];
+
+    // Like in `read_output`, but with a timeout, and with pipes that reached EOF removed from the
+    // set of pipes to wait for. Pipes that are still being read when the deadline passes have
+    // their pending read cancelled when they are dropped.
+    loop {
+        let mut objs = [ptr::null_mut(); 2];
+        let mut indices = [0; 2];
+        let mut count = 0;
+        for (i, pipe) in pipes.iter().enumerate() {
+            if let Some(pipe) = pipe {
+                objs[count] = pipe.event.as_raw_handle();
+                indices[count] = i;
+                count += 1;
+            }
+        }
+        if count == 0 {
+            return Ok(true);
+        }
+
+        let timeout = dur2timeout(deadline.saturating_duration_since(Instant::now()));
+        let res =
+            unsafe { c::WaitForMultipleObjects(count as u32, objs.as_ptr(), c::FALSE, timeout) };
+        if res == c::WAIT_TIMEOUT {
+            if Instant::now() >= deadline {
+                return Ok(false);
+            }
+        } else if res >= c::WAIT_OBJECT_0 && res < c::WAIT_OBJECT_0 + count as u32 {
+            let i = indices[(res - c::WAIT_OBJECT_0) as usize];
+            if let Some(pipe) = &mut pipes[i]
+                && (!pipe.result()? || !pipe.schedule_read()?)
+            {
+                pipes[i] = None;
+            }
+        } else {
+            return Err(io::Error::last_os_error());
+        }
+    }
+}
+
 struct AsyncPipe<'a> {
     pipe: Handle,
     event: Handle,
@@ -166,6 +166,7 @@
 mod id;
 mod join_handle;
 mod lifecycle;
+mod pool;
 mod scoped;
 mod spawnhook;
 mod thread;
@@ -200,6 +201,8 @@
 pub(crate) use lifecycle::ThreadInit;
 #[stable(feature = "rust1", since = "1.0.0")]
 pub use local::{AccessError, LocalKey};
+#[unstable(feature = "scoped_pool", issue = "none")]
+pub use pool::ScopedPool;
 #[stable(feature = "scoped_threads", since = "1.63.0")]
 pub use scoped::{Scope, ScopedJoinHandle, scope};
 #[unstable(feature = "thread_spawn_hook", issue = "132951")]
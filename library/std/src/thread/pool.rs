@@ -0,0 +1,242 @@
+use super::builder::Builder;
+use super::functions::available_parallelism;
+use super::scoped::Scope;
+use crate::collections::VecDeque;
+use crate::num::NonZero;
+use crate::panic::{AssertUnwindSafe, catch_unwind, resume_unwind};
+use crate::sync::{Arc, Condvar, Mutex};
+use crate::{fmt, io};
+
+type Job<'scope> = Box<dyn FnOnce() + Send + 'scope>;
+
+/// A fixed number of scoped threads that run the jobs submitted to them.
+///
+/// A `ScopedPool` is created with [`Scope::spawn_pool`] or
+/// [`Builder::spawn_scoped_pool`]. Jobs are submitted with [`execute`] and run
+/// in submission order by the first idle worker, so at most [`workers`] of
+/// them run at the same time.
+///
+/// `ScopedPool` is a handle to the pool: it can be cloned, for example to
+/// submit jobs from within other jobs. The workers exit once every handle has
+/// been dropped and all submitted jobs have run. Like the other threads of the
+/// scope, they are joined at the end of [`scope`].
+///
+/// # Panics
+///
+/// A job that panics doesn't stop its worker, which goes on running other
+/// jobs. Once the pool shuts down, the worker panics with the payload of the
+/// first panic it caught, which makes [`scope`] panic as it does for any
+/// scoped thread that panicked.
+///
+/// [`execute`]: ScopedPool::execute
+/// [`workers`]: ScopedPool::workers
+/// [`scope`]: super::scope
+///
+/// # Examples
+///
+/// ```
+/// #![feature(scoped_pool)]
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use std::thread;
+///
+/// let lines = ["a b", "c", "d e f"];
+/// let words = AtomicUsize::new(0);
+///
+/// thread::scope(|s| {
+///     let pool = s.spawn_pool();
+///     for line in &lines {
+///         let words = &words;
+///         pool.execute(move || {
+///             words.fetch_add(line.split_whitespace().count(), Ordering::Relaxed);
+///         });
+///     }
+/// });
+///
+/// assert_eq!(words.into_inner(), 6);
+/// ```
+#[unstable(feature = "scoped_pool", issue = "none")]
+pub struct ScopedPool<'scope> {
+    shared: Arc<Shared<'scope>>,
+    workers: NonZero<usize>,
+}
+
+struct Shared<'scope> {
+    state: Mutex<State<'scope>>,
+    /// Notified when a job is submitted or the last handle is dropped.
+    work_available: Condvar,
+}
+
+struct State<'scope> {
+    jobs: VecDeque<Job<'scope>>,
+    /// The number of `ScopedPool` handles. The workers exit once this is zero
+    /// and there are no more jobs.
+    handles: usize,
+}
+
+impl<'scope> Shared<'scope> {
+    /// Waits for the next job, or returns `None` once the pool has shut down.
+    fn next_job(&self) -> Option<Job<'scope>> {
+        let mut state = self.state.lock().unwrap();
+        loop {
+            if let Some(job) = state.jobs.pop_front() {
+                return Some(job);
+            }
+            if state.handles == 0 {
+                return None;
+            }
+            state = self.work_available.wait(state).unwrap();
+        }
+    }
+
+    /// The main function of the workers.
+    fn work(&self) {
+        let mut panic = None;
+        while let Some(job) = self.next_job() {
+            if let Err(payload) = catch_unwind(AssertUnwindSafe(job)) {
+                panic.get_or_insert(payload);
+            }
+        }
+        if let Some(payload) = panic {
+            resume_unwind(payload);
+        }
+    }
+}
+
+impl<'scope, 'env> Scope<'scope, 'env> {
+    /// Spawns a [`ScopedPool`] within the scope, with one worker thread for
+    /// each unit of [`available_parallelism`], or a single worker if it is
+    /// unknown.
+    ///
+    /// This function creates the workers with the default parameters of
+    /// [`Builder`]. To specify their names, stack size or number, use
+    /// [`Builder::spawn_scoped_pool`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the OS fails to create a thread; use
+    /// [`Builder::spawn_scoped_pool`] to recover from such errors.
+    #[unstable(feature = "scoped_pool", issue = "none")]
+    pub fn spawn_pool(&'scope self) -> ScopedPool<'scope> {
+        let workers = available_parallelism().unwrap_or(NonZero::<usize>::MIN);
+        Builder::new().spawn_scoped_pool(self, workers).expect("failed to spawn thread")
+    }
+}
+
+impl Builder {
+    /// Spawns a [`ScopedPool`] of `workers` threads within a scope, using the
+    /// settings set through this `Builder`.
+    ///
+    /// If a name was set, the workers are named after it, followed by `-` and
+    /// the index of the worker.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a thread name was set and it contained null bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(scoped_pool)]
+    /// use std::num::NonZero;
+    /// use std::sync::mpsc;
+    /// use std::thread;
+    ///
+    /// let (tx, rx) = mpsc::channel();
+    ///
+    /// thread::scope(|s| {
+    ///     let pool = thread::Builder::new()
+    ///         .name("worker".to_string())
+    ///         .spawn_scoped_pool(s, NonZero::new(2).unwrap())
+    ///         .unwrap();
+    ///     for i in 0..8 {
+    ///         let tx = tx.clone();
+    ///         pool.execute(move || {
+    ///             let name = thread::current().name().unwrap().to_string();
+    ///             tx.send((i, name)).unwrap();
+    ///         });
+    ///     }
+    /// });
+    /// drop(tx);
+    ///
+    /// let mut results: Vec<_> = rx.iter().collect();
+    /// results.sort();
+    /// assert_eq!(results.len(), 8);
+    /// assert!(results.iter().all(|(_, name)| name == "worker-0" || name == "worker-1"));
+    /// ```
+    #[unstable(feature = "scoped_pool", issue = "none")]
+    pub fn spawn_scoped_pool<'scope, 'env>(
+        self,
+        scope: &'scope Scope<'scope, 'env>,
+        workers: NonZero<usize>,
+    ) -> io::Result<ScopedPool<'scope>> {
+        let pool = ScopedPool {
+            shared: Arc::new(Shared {
+                state: Mutex::new(State { jobs: VecDeque::new(), handles: 1 }),
+                work_available: Condvar::new(),
+            }),
+            workers,
+        };
+
+        let Builder { name, stack_size, no_hooks } = self;
+        for i in 0..workers.get() {
+            let builder = Builder {
+                name: name.as_ref().map(|name| format!("{name}-{i}")),
+                stack_size,
+                no_hooks,
+            };
+            let shared = Arc::clone(&pool.shared);
+            // If this fails, dropping `pool` shuts down the workers spawned so far.
+            builder.spawn_scoped(scope, move || shared.work())?;
+        }
+        Ok(pool)
+    }
+}
+
+impl<'scope> ScopedPool<'scope> {
+    /// Submits a job to the pool.
+    ///
+    /// The job runs on the first worker that becomes idle, after the jobs
+    /// that were submitted before it.
+    #[unstable(feature = "scoped_pool", issue = "none")]
+    pub fn execute<F>(&self, f: F)
+    where
+        F: FnOnce() + Send + 'scope,
+    {
+        self.shared.state.lock().unwrap().jobs.push_back(Box::new(f));
+        self.shared.work_available.notify_one();
+    }
+
+    /// Returns the number of worker threads of the pool.
+    #[must_use]
+    #[unstable(feature = "scoped_pool", issue = "none")]
+    pub fn workers(&self) -> NonZero<usize> {
+        self.workers
+    }
+}
+
+#[unstable(feature = "scoped_pool", issue = "none")]
+impl Clone for ScopedPool<'_> {
+    fn clone(&self) -> Self {
+        self.shared.state.lock().unwrap().handles += 1;
+        ScopedPool { shared: Arc::clone(&self.shared), workers: self.workers }
+    }
+}
+
+#[unstable(feature = "scoped_pool", issue = "none")]
+impl Drop for ScopedPool<'_> {
+    fn drop(&mut self) {
+        let mut state = self.shared.state.lock().unwrap();
+        state.handles -= 1;
+        if state.handles == 0 {
+            drop(state);
+            self.shared.work_available.notify_all();
+        }
+    }
+}
+
+#[unstable(feature = "scoped_pool", issue = "none")]
+impl fmt::Debug for ScopedPool<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ScopedPool").field("workers", &self.workers).finish_non_exhaustive()
+    }
+}
@@ -1,7 +1,8 @@
 use crate::any::Any;
-use crate::panic::panic_any;
+use crate::num::NonZero;
+use crate::panic::{AssertUnwindSafe, catch_unwind, panic_any};
 use crate::result;
-use crate::sync::atomic::{AtomicBool, Ordering};
+use crate::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use crate::sync::mpsc::{Sender, channel};
 use crate::sync::{Arc, Barrier};
 use crate::thread::{self, Builder, Scope, ThreadId};
@@ -425,3 +426,64 @@ fn test_minimal_thread_stack() {
     assert_eq!(before, 0);
     assert_eq!(COUNT.load(Ordering::Relaxed), 1);
 }
+
+#[test]
+fn test_scoped_pool_bounded_parallelism() {
+    let running = AtomicUsize::new(0);
+    let max_running = AtomicUsize::new(0);
+    let done = AtomicUsize::new(0);
+    thread::scope(|s| {
+        let pool = Builder::new().spawn_scoped_pool(s, NonZero::new(3).unwrap()).unwrap();
+        assert_eq!(pool.workers().get(), 3);
+        for _ in 0..30 {
+            pool.execute(|| {
+                let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                max_running.fetch_max(now, Ordering::SeqCst);
+                thread::sleep(Duration::from_millis(1));
+                running.fetch_sub(1, Ordering::SeqCst);
+                done.fetch_add(1, Ordering::SeqCst);
+            });
+        }
+    });
+    assert_eq!(done.load(Ordering::SeqCst), 30);
+    assert!(max_running.load(Ordering::SeqCst) <= 3);
+}
+
+#[test]
+fn test_scoped_pool_nested_jobs() {
+    let count = AtomicUsize::new(0);
+    thread::scope(|s| {
+        let pool = s.spawn_pool();
+        for _ in 0..4 {
+            let inner = pool.clone();
+            let count = &count;
+            pool.execute(move || {
+                for _ in 0..4 {
+                    inner.execute(move || {
+                        count.fetch_add(1, Ordering::Relaxed);
+                    });
+                }
+            });
+        }
+    });
+    assert_eq!(count.load(Ordering::Relaxed), 16);
+}
+
+#[test]
+fn test_scoped_pool_panic() {
+    let done = AtomicUsize::new(0);
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        thread::scope(|s| {
+            let pool = Builder::new().spawn_scoped_pool(s, NonZero::<usize>::MIN).unwrap();
+            pool.execute(|| panic!("job panicked"));
+            for _ in 0..5 {
+                pool.execute(|| {
+                    done.fetch_add(1, Ordering::Relaxed);
+                });
+            }
+        })
+    }));
+    // The worker kept running jobs after the panic, and the scope panicked at its end.
+    assert!(result.is_err());
+    assert_eq!(done.load(Ordering::Relaxed), 5);
+}
@@ -20,6 +20,8 @@
 #[cfg(not(any(target_os = "emscripten", target_os = "wasi")))]
 mod mpmc;
 #[cfg(not(any(target_os = "emscripten", target_os = "wasi")))]
+mod mpmc_select;
+#[cfg(not(any(target_os = "emscripten", target_os = "wasi")))]
 mod mpsc;
 #[cfg(not(any(target_os = "emscripten", target_os = "wasi")))]
 mod mpsc_sync;
@@ -0,0 +1,280 @@
+use std::sync::mpmc::{
+    self, RecvError, Select, SelectTimeoutError, SendError, TrySelectError, select,
+};
+use std::thread;
+use std::time::{Duration, Instant};
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    let (s1, r1) = mpmc::channel::<i32>();
+    let (s2, r2) = mpmc::channel::<i32>();
+
+    s1.send(1).unwrap();
+    select! {
+        recv(r1) -> v => assert_eq!(v, Ok(1)),
+        recv(r2) -> _ => panic!(),
+    }
+
+    s2.send(2).unwrap();
+    select! {
+        recv(r1) -> _ => panic!(),
+        recv(r2) -> v => assert_eq!(v, Ok(2)),
+    }
+}
+
+#[test]
+fn disconnected() {
+    let (s1, r1) = mpmc::channel::<i32>();
+    let (s2, r2) = mpmc::channel::<i32>();
+
+    drop(s1);
+    select! {
+        recv(r1) -> v => assert_eq!(v, Err(RecvError)),
+        recv(r2) -> _ => panic!(),
+    }
+
+    drop(r2);
+    select! {
+        send(s2, 1) -> v => assert_eq!(v, Err(SendError(1))),
+    }
+}
+
+#[test]
+fn default() {
+    let (s1, r1) = mpmc::channel::<i32>();
+    let (_s2, r2) = mpmc::sync_channel::<i32>(0);
+
+    let ran_default = select! {
+        recv(r1) -> _ => false,
+        recv(r2) -> _ => false,
+        default => true,
+    };
+    assert!(ran_default);
+
+    s1.send(1).unwrap();
+    select! {
+        recv(r1) -> v => assert_eq!(v, Ok(1)),
+        recv(r2) -> _ => panic!(),
+        default => panic!(),
+    }
+}
+
+#[test]
+fn timeout() {
+    let (s, r) = mpmc::sync_channel::<i32>(0);
+
+    let start = Instant::now();
+    select! {
+        recv(r) -> _ => panic!(),
+        default(ms(100)) => {}
+    }
+    assert!(start.elapsed() >= ms(100));
+
+    let t = thread::spawn(move || {
+        thread::sleep(ms(100));
+        s.send(1).unwrap();
+    });
+    select! {
+        recv(r) -> v => assert_eq!(v, Ok(1)),
+        default(Duration::from_secs(60)) => panic!(),
+    }
+    t.join().unwrap();
+}
+
+#[test]
+fn blocking_recv() {
+    let (s1, r1) = mpmc::channel::<i32>();
+    let (s2, r2) = mpmc::sync_channel::<i32>(0);
+
+    let t = thread::spawn(move || {
+        thread::sleep(ms(50));
+        s2.send(2).unwrap();
+        thread::sleep(ms(50));
+        s1.send(1).unwrap();
+    });
+
+    select! {
+        recv(r1) -> _ => panic!(),
+        recv(r2) -> v => assert_eq!(v, Ok(2)),
+    }
+    select! {
+        recv(r1) -> v => assert_eq!(v, Ok(1)),
+        recv(r2) -> _ => panic!(),
+    }
+    t.join().unwrap();
+}
+
+#[test]
+fn blocking_send() {
+    let (s1, r1) = mpmc::sync_channel::<i32>(1);
+    let (s2, r2) = mpmc::sync_channel::<i32>(0);
+    s1.send(0).unwrap();
+
+    let t = thread::spawn(move || {
+        thread::sleep(ms(50));
+        assert_eq!(r2.recv(), Ok(2));
+        r1
+    });
+
+    select! {
+        send(s1, 1) -> _ => panic!(),
+        send(s2, 2) -> v => assert_eq!(v, Ok(())),
+    }
+    let r1 = t.join().unwrap();
+    assert_eq!(r1.recv(), Ok(0));
+}
+
+#[test]
+fn send_value_is_evaluated_only_when_selected() {
+    let (s1, _r1) = mpmc::sync_channel::<String>(0);
+    let (s2, r2) = mpmc::channel::<String>();
+
+    let mut evaluated = false;
+    select! {
+        send(s1, { evaluated = true; "unused".to_string() }) -> _ => panic!(),
+        send(s2, "hello".to_string()) -> v => assert!(v.is_ok()),
+    }
+    assert!(!evaluated);
+    assert_eq!(r2.recv().as_deref(), Ok("hello"));
+}
+
+#[test]
+fn select_returns_value() {
+    let (s, r) = mpmc::channel();
+    s.send(3).unwrap();
+
+    let doubled = select! {
+        recv(r) -> v => v.unwrap() * 2,
+    };
+    assert_eq!(doubled, 6);
+}
+
+#[test]
+fn zero_capacity_both_sides() {
+    let (s, r) = mpmc::sync_channel::<i32>(0);
+
+    let t = thread::spawn(move || {
+        select! {
+            recv(r) -> v => assert_eq!(v, Ok(7)),
+        }
+    });
+    select! {
+        send(s, 7) -> v => assert_eq!(v, Ok(())),
+    }
+    t.join().unwrap();
+}
+
+#[test]
+fn many_threads() {
+    const THREADS: usize = 4;
+    const MESSAGES: usize = 1000;
+
+    let (s1, r1) = mpmc::sync_channel::<usize>(0);
+    let (s2, r2) = mpmc::sync_channel::<usize>(3);
+
+    let senders: Vec<_> = (0..THREADS)
+        .map(|_| {
+            let (s1, s2) = (s1.clone(), s2.clone());
+            thread::spawn(move || {
+                for i in 0..MESSAGES {
+                    select! {
+                        send(s1, i) -> v => v.unwrap(),
+                        send(s2, i) -> v => v.unwrap(),
+                    }
+                }
+            })
+        })
+        .collect();
+    drop((s1, s2));
+
+    let receivers: Vec<_> = (0..THREADS)
+        .map(|_| {
+            let (r1, r2) = (r1.clone(), r2.clone());
+            thread::spawn(move || {
+                let mut sel = Select::new();
+                let oper1 = sel.recv(&r1);
+                sel.recv(&r2);
+
+                let mut sum = 0;
+                let mut remaining = 2;
+                while remaining > 0 {
+                    let oper = sel.select();
+                    let index = oper.index();
+                    let res = if index == oper1 { oper.recv(&r1) } else { oper.recv(&r2) };
+                    match res {
+                        Ok(i) => sum += i,
+                        Err(RecvError) => {
+                            // The channel is disconnected and empty.
+                            sel.remove(index);
+                            remaining -= 1;
+                        }
+                    }
+                }
+                sum
+            })
+        })
+        .collect();
+
+    for t in senders {
+        t.join().unwrap();
+    }
+    let total: usize = receivers.into_iter().map(|t| t.join().unwrap()).sum();
+    assert_eq!(total, THREADS * (0..MESSAGES).sum::<usize>());
+}
+
+#[test]
+fn select_api() {
+    let (s1, r1) = mpmc::channel::<i32>();
+    let (s2, r2) = mpmc::sync_channel::<i32>(1);
+
+    let mut sel = Select::new();
+    assert_eq!(sel.try_select().map(|_| ()), Err(TrySelectError));
+
+    let oper1 = sel.recv(&r1);
+    let oper2 = sel.send(&s2);
+
+    // The send operation is ready right away.
+    let oper = sel.try_select().unwrap();
+    assert_eq!(oper.index(), oper2);
+    oper.send(&s2, 2).unwrap();
+
+    // Now the buffered channel is full, and nothing was sent on the other one.
+    assert_eq!(sel.select_timeout(ms(10)).map(|_| ()), Err(SelectTimeoutError));
+    let deadline = Instant::now() + ms(50);
+    assert_eq!(sel.select_deadline(deadline).map(|_| ()), Err(SelectTimeoutError));
+    assert!(Instant::now() >= deadline);
+
+    s1.send(1).unwrap();
+    let oper = sel.select();
+    assert_eq!(oper.index(), oper1);
+    assert_eq!(oper.recv(&r1), Ok(1));
+    assert_eq!(r2.recv(), Ok(2));
+}
+
+#[test]
+#[should_panic(expected = "dropped `SelectedOperation` without completing the operation")]
+fn incomplete_operation() {
+    let (s, r) = mpmc::channel::<i32>();
+    drop(s);
+
+    let mut sel = Select::new();
+    sel.recv(&r);
+    let _ = sel.select();
+}
+
+#[test]
+#[should_panic(expected = "passed a receiver that wasn't selected")]
+fn wrong_receiver() {
+    let (s1, r1) = mpmc::channel::<i32>();
+    let (_s2, r2) = mpmc::channel::<i32>();
+    s1.send(1).unwrap();
+
+    let mut sel = Select::new();
+    sel.recv(&r1);
+    let oper = sel.select();
+    let _ = oper.recv(&r2);
+}
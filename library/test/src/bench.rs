@@ -30,6 +30,7 @@ pub fn black_box<T>(dummy: T) -> T {
 pub struct Bencher {
     mode: BenchMode,
     summary: Option<stats::Summary>,
+    samples: Vec<f64>,
     pub bytes: u64,
 }
 
@@ -44,7 +45,9 @@ pub fn iter<T, F>(&mut self, mut inner: F)
             return;
         }
 
-        self.summary = Some(iter(&mut inner));
+        let (summary, samples) = iter_samples(&mut inner);
+        self.summary = Some(summary);
+        self.samples = samples;
     }
 
     pub fn bench<F>(&mut self, mut f: F) -> Result<Option<stats::Summary>, String>
@@ -60,6 +63,8 @@ pub fn bench<F>(&mut self, mut f: F) -> Result<Option<stats::Summary>, String>
 pub struct BenchSamples {
     pub ns_iter_summ: stats::Summary,
     pub mb_s: usize,
+    /// The winsorized ns/iter samples `ns_iter_summ` was computed from.
+    pub samples: Vec<f64>,
 }
 
 pub fn fmt_bench_samples(bs: &BenchSamples) -> String {
@@ -121,6 +126,14 @@ fn ns_iter_inner<T, F>(inner: &mut F, k: u64) -> u64
 }
 
 pub fn iter<T, F>(inner: &mut F) -> stats::Summary
+where
+    F: FnMut() -> T,
+{
+    iter_samples(inner).0
+}
+
+/// Like [`iter`], but also returns the winsorized samples of the final round.
+fn iter_samples<T, F>(inner: &mut F) -> (stats::Summary, Vec<f64>)
 where
     F: FnMut() -> T,
 {
@@ -167,13 +180,13 @@ pub fn iter<T, F>(inner: &mut F) -> stats::Summary
             && summ.median_abs_dev_pct < 1.0
             && summ.median - summ5.median < summ5.median_abs_dev
         {
-            return summ5;
+            return (summ5, samples.to_vec());
         }
 
         total_run += loop_run;
         // Longest we ever run for is 3s.
         if total_run > Duration::from_secs(3) {
-            return summ5;
+            return (summ5, samples.to_vec());
         }
 
         // If we overflow here just return the results so far. We check a
@@ -183,7 +196,7 @@ pub fn iter<T, F>(inner: &mut F) -> stats::Summary
         n = match n.checked_mul(10) {
             Some(_) => n * 2,
             None => {
-                return summ5;
+                return (summ5, samples.to_vec());
             }
         };
     }
@@ -198,7 +211,7 @@ pub fn benchmark<F>(
 ) where
     F: FnMut(&mut Bencher) -> Result<(), String>,
 {
-    let mut bs = Bencher { mode: BenchMode::Auto, summary: None, bytes: 0 };
+    let mut bs = Bencher { mode: BenchMode::Auto, summary: None, samples: Vec::new(), bytes: 0 };
 
     let data = Arc::new(Mutex::new(Vec::new()));
 
@@ -216,14 +229,19 @@ pub fn benchmark<F>(
             let ns_iter = cmp::max(ns_iter_summ.median as u64, 1);
             let mb_s = bs.bytes * 1000 / ns_iter;
 
-            let bs = BenchSamples { ns_iter_summ, mb_s: mb_s as usize };
+            let samples = std::mem::take(&mut bs.samples);
+            let bs = BenchSamples { ns_iter_summ, mb_s: mb_s as usize, samples };
             TestResult::TrBench(bs)
         }
         Ok(Ok(None)) => {
             // iter not called, so no data.
             // FIXME: error in this case?
             let samples: &mut [f64] = &mut [0.0_f64; 1];
-            let bs = BenchSamples { ns_iter_summ: stats::Summary::new(samples), mb_s: 0 };
+            let bs = BenchSamples {
+                ns_iter_summ: stats::Summary::new(samples),
+                mb_s: 0,
+                samples: samples.to_vec(),
+            };
             TestResult::TrBench(bs)
         }
         Err(_) => TestResult::TrFailed,
@@ -239,6 +257,6 @@ pub fn run_once<F>(f: F) -> Result<(), String>
 where
     F: FnMut(&mut Bencher) -> Result<(), String>,
 {
-    let mut bs = Bencher { mode: BenchMode::Single, summary: None, bytes: 0 };
+    let mut bs = Bencher { mode: BenchMode::Single, summary: None, samples: Vec::new(), bytes: 0 };
     bs.bench(f).map(|_| ())
 }
@@ -2,9 +2,12 @@
 
 use std::env;
 use std::io::{self, IsTerminal, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
-use super::options::{ColorConfig, Options, OutputFormat, RunIgnored};
+use super::helpers::record;
+use super::options::{ColorConfig, Options, OutputFormat, RunIgnored, TestShard};
+use super::resources;
 use super::time::TestTimeOptions;
 
 #[derive(Debug)]
@@ -23,9 +26,29 @@ pub struct TestOpts {
     pub format: OutputFormat,
     pub shuffle: bool,
     pub shuffle_seed: Option<u64>,
+    /// Only run the part of the filtered tests assigned to this shard.
+    pub shard: Option<TestShard>,
     pub test_threads: Option<usize>,
     pub skip: Vec<String>,
     pub time_options: Option<TestTimeOptions>,
+    /// Hard time limit for each test. Tests that exceed it are reported as failed;
+    /// subprocess tests are killed and in-process tests are abandoned.
+    pub test_timeout: Option<Duration>,
+    /// Limit on the peak memory usage of each test, in bytes. Only enforced
+    /// for tests run in a subprocess, on platforms where it can be measured.
+    pub memory_limit: Option<u64>,
+    /// How many times to run a failing test again. A test that passes on one of
+    /// these retries is reported as flaky instead of failed.
+    pub retries: usize,
+    /// Save the samples of the benchmarks as the baseline with this name.
+    pub save_baseline: Option<String>,
+    /// Compare the benchmarks against the baseline with this name.
+    pub baseline: Option<String>,
+    /// Record the result of every test that is run in this file.
+    pub record: Option<PathBuf>,
+    /// Only run the tests with these names (sorted), which failed in an
+    /// earlier run according to the record passed to `--rerun-failed`.
+    pub rerun_failed: Option<Vec<String>>,
     /// Stop at first failing test.
     /// May run a few more tests due to threading, but will
     /// abort as soon as possible.
@@ -103,8 +126,9 @@ fn optgroups() -> getopts::Options {
             pretty = Print verbose output;
             terse  = Display one character per test;
             json   = Output a json document;
-            junit  = Output a JUnit document",
-            "pretty|terse|json|junit",
+            junit  = Output a JUnit document;
+            tap    = Output a TAP (Test Anything Protocol) stream",
+            "pretty|terse|json|junit|tap",
         )
         .optflag("", "show-output", "Show captured stdout of successful tests")
         .optopt(
@@ -143,12 +167,82 @@ fn optgroups() -> getopts::Options {
             `CRITICAL_TIME` here means the limit that should not be exceeded by test.
             ",
         )
+        .optopt(
+            "",
+            "ensure-memory",
+            "Fail tests whose peak resident set size exceeds SIZE bytes (or KiB,
+            MiB or GiB with a K, M or G suffix).
+
+            Memory usage is only measured for tests run in a subprocess
+            (panic=abort) on Linux.",
+            "SIZE",
+        )
+        .optopt(
+            "",
+            "test-timeout",
+            "Fail tests that run for longer than SECS seconds.
+
+            Tests run in a subprocess (panic=abort) are killed, tests run in-process
+            are reported as failed and abandoned. A limit set on an individual test
+            with `#[test_timeout = \"SECS\"]` takes precedence over this option.",
+            "SECS",
+        )
+        .optopt(
+            "",
+            "retries",
+            "Run failing tests up to N more times. Tests that pass on a retry are
+            reported as flaky rather than failed. Tests created at runtime (for
+            example doctests) can only be run once and are not retried.",
+            "N",
+        )
+        .optopt(
+            "",
+            "save-baseline",
+            "Save the samples of the benchmarks as the baseline NAME.
+
+            Baselines are stored in the directory given by `RUST_TEST_BASELINE_DIR`,
+            or else next to the test binary.",
+            "NAME",
+        )
+        .optopt(
+            "",
+            "baseline",
+            "Compare the benchmarks against the baseline NAME saved by an earlier
+            run, reporting whether they regressed, improved or didn't change.",
+            "NAME",
+        )
+        .optopt(
+            "",
+            "record",
+            "Record which tests passed, failed or were ignored in PATH, as one
+            JSON object per line",
+            "PATH",
+        )
+        .optopt(
+            "",
+            "rerun-failed",
+            "Only run the tests that failed according to the record in PATH,
+            written by --record or --format json",
+            "PATH",
+        )
         .optflag("", "shuffle", "Run tests in random order")
         .optopt(
             "",
             "shuffle-seed",
             "Run tests in random order; seed the random number generator with SEED",
             "SEED",
+        )
+        .optopt(
+            "",
+            "shard-index",
+            "Only run the tests assigned to shard N (zero-based); requires --shard-count",
+            "N",
+        )
+        .optopt(
+            "",
+            "shard-count",
+            "Split the filtered tests into M shards; requires --shard-index",
+            "M",
         );
     opts
 }
@@ -171,6 +265,12 @@ fn usage(binary: &str, options: &getopts::Options) {
 --shuffle and --shuffle-seed do not affect whether the tests are run in
 parallel.
 
+Use --shard-index and --shard-count to split the tests across several
+machines. The tests that remain after filtering are sorted by name and
+assigned to shards round-robin, so every shard gets the same tests each time,
+regardless of --shuffle. Combine them with --list to see which tests a shard
+would run.
+
 All tests have their standard output and standard error captured by default.
 This can be overridden with the --no-capture flag to a value other than "0".
 Logging is not captured by default.
@@ -263,8 +363,15 @@ fn parse_opts_impl(matches: getopts::Matches) -> OptRes {
     let exclude_should_panic = unstable_optflag!(matches, allow_unstable, "exclude-should-panic");
     let fail_fast = unstable_optflag!(matches, allow_unstable, "fail-fast");
     let time_options = get_time_options(&matches, allow_unstable)?;
+    let test_timeout = get_test_timeout(&matches, allow_unstable)?;
+    let memory_limit = get_memory_limit(&matches, allow_unstable)?;
+    let retries = get_retries(&matches, allow_unstable)?;
+    let (save_baseline, baseline) = get_baselines(&matches, allow_unstable)?;
     let shuffle = get_shuffle(&matches, allow_unstable)?;
     let shuffle_seed = get_shuffle_seed(&matches, allow_unstable)?;
+    let shard = get_shard(&matches, allow_unstable)?;
+    let record = get_record(&matches, allow_unstable)?;
+    let rerun_failed = get_rerun_failed(&matches, allow_unstable)?;
 
     let include_ignored = matches.opt_present("include-ignored");
     let quiet = matches.opt_present("quiet");
@@ -304,9 +411,17 @@ fn parse_opts_impl(matches: getopts::Matches) -> OptRes {
         format,
         shuffle,
         shuffle_seed,
+        shard,
         test_threads,
         skip,
         time_options,
+        test_timeout,
+        memory_limit,
+        retries,
+        save_baseline,
+        baseline,
+        record,
+        rerun_failed,
         options,
         fail_fast,
     };
@@ -343,6 +458,104 @@ fn get_time_options(
     Ok(options)
 }
 
+fn get_test_timeout(
+    matches: &getopts::Matches,
+    allow_unstable: bool,
+) -> OptPartRes<Option<Duration>> {
+    let test_timeout = match unstable_optopt!(matches, allow_unstable, "test-timeout") {
+        Some(n_str) => match n_str.parse::<u64>() {
+            Ok(0) => return Err("argument for --test-timeout must not be 0".to_string()),
+            Ok(n) => Some(Duration::from_secs(n)),
+            Err(e) => {
+                return Err(format!(
+                    "argument for --test-timeout must be a number > 0 \
+                     (error: {e})"
+                ));
+            }
+        },
+        None => None,
+    };
+
+    Ok(test_timeout)
+}
+
+fn get_memory_limit(matches: &getopts::Matches, allow_unstable: bool) -> OptPartRes<Option<u64>> {
+    let memory_limit = match unstable_optopt!(matches, allow_unstable, "ensure-memory") {
+        Some(size) => match resources::parse_memory_limit(&size) {
+            Some(0) => return Err("argument for --ensure-memory must not be 0".to_string()),
+            Some(limit) => Some(limit),
+            None => {
+                return Err(format!(
+                    "argument for --ensure-memory must be a number of bytes, \
+                     optionally followed by K, M or G (was {size})"
+                ));
+            }
+        },
+        None => None,
+    };
+
+    Ok(memory_limit)
+}
+
+fn get_retries(matches: &getopts::Matches, allow_unstable: bool) -> OptPartRes<usize> {
+    let retries = match unstable_optopt!(matches, allow_unstable, "retries") {
+        Some(n_str) => match n_str.parse::<usize>() {
+            Ok(n) => n,
+            Err(e) => {
+                return Err(format!(
+                    "argument for --retries must be a number \
+                     (error: {e})"
+                ));
+            }
+        },
+        None => 0,
+    };
+
+    Ok(retries)
+}
+
+fn get_baselines(
+    matches: &getopts::Matches,
+    allow_unstable: bool,
+) -> OptPartRes<(Option<String>, Option<String>)> {
+    let save_baseline = unstable_optopt!(matches, allow_unstable, "save-baseline");
+    let baseline = unstable_optopt!(matches, allow_unstable, "baseline");
+
+    for (option_name, name) in [("save-baseline", &save_baseline), ("baseline", &baseline)] {
+        if let Some(name) = name
+            && (name.is_empty() || name.contains(['/', '\\']))
+        {
+            return Err(format!(
+                "argument for --{option_name} must be a non-empty name without path \
+                 separators (was {name})"
+            ));
+        }
+    }
+
+    Ok((save_baseline, baseline))
+}
+
+fn get_record(matches: &getopts::Matches, allow_unstable: bool) -> OptPartRes<Option<PathBuf>> {
+    let record = unstable_optopt!(matches, allow_unstable, "record").map(PathBuf::from);
+
+    Ok(record)
+}
+
+fn get_rerun_failed(
+    matches: &getopts::Matches,
+    allow_unstable: bool,
+) -> OptPartRes<Option<Vec<String>>> {
+    let rerun_failed = match unstable_optopt!(matches, allow_unstable, "rerun-failed") {
+        Some(path) => match record::failed_tests(Path::new(&path)) {
+            Ok(failed) => Some(failed),
+            Err(e) => return Err(format!("failed to read the test record {path}: {e}")),
+        },
+        None => None,
+    };
+
+    Ok(rerun_failed)
+}
+
 fn get_shuffle(matches: &getopts::Matches, allow_unstable: bool) -> OptPartRes<bool> {
     let mut shuffle = unstable_optflag!(matches, allow_unstable, "shuffle");
     if !shuffle && allow_unstable {
@@ -382,6 +595,45 @@ fn get_shuffle_seed(matches: &getopts::Matches, allow_unstable: bool) -> OptPart
     Ok(shuffle_seed)
 }
 
+fn get_shard(matches: &getopts::Matches, allow_unstable: bool) -> OptPartRes<Option<TestShard>> {
+    let index = unstable_optopt!(matches, allow_unstable, "shard-index");
+    let count = unstable_optopt!(matches, allow_unstable, "shard-count");
+
+    let (index, count) = match (index, count) {
+        (Some(index), Some(count)) => (index, count),
+        (None, None) => return Ok(None),
+        (Some(_), None) => return Err("--shard-index requires --shard-count".to_string()),
+        (None, Some(_)) => return Err("--shard-count requires --shard-index".to_string()),
+    };
+
+    let count = match count.parse::<usize>() {
+        Ok(0) => return Err("argument for --shard-count must not be 0".to_string()),
+        Ok(n) => n,
+        Err(e) => {
+            return Err(format!(
+                "argument for --shard-count must be a number > 0 \
+                 (error: {e})"
+            ));
+        }
+    };
+    let index = match index.parse::<usize>() {
+        Ok(n) if n < count => n,
+        Ok(n) => {
+            return Err(format!(
+                "argument for --shard-index must be less than --shard-count ({n} >= {count})"
+            ));
+        }
+        Err(e) => {
+            return Err(format!(
+                "argument for --shard-index must be a number \
+                 (error: {e})"
+            ));
+        }
+    };
+
+    Ok(Some(TestShard { index, count }))
+}
+
 fn get_test_threads(matches: &getopts::Matches) -> OptPartRes<Option<usize>> {
     let test_threads = match matches.opt_str("test-threads") {
         Some(n_str) => match n_str.parse::<usize>() {
@@ -421,9 +673,15 @@ fn get_format(
             }
             OutputFormat::Junit
         }
+        Some("tap") => {
+            if !allow_unstable {
+                return Err("The \"tap\" format is only accepted on the nightly compiler with -Z unstable-options".into());
+            }
+            OutputFormat::Tap
+        }
         Some(v) => {
             return Err(format!(
-                "argument for --format must be pretty, terse, json or junit (was \
+                "argument for --format must be pretty, terse, json, junit or tap (was \
                  {v})"
             ));
         }
@@ -9,12 +9,14 @@
 use super::cli::TestOpts;
 use super::event::{CompletedTest, TestEvent};
 use super::formatters::{
-    JsonFormatter, JunitFormatter, OutputFormatter, PrettyFormatter, TerseFormatter,
+    JsonFormatter, JunitFormatter, OutputFormatter, PrettyFormatter, TapFormatter, TerseFormatter,
 };
+use super::helpers::baseline::Baseline;
 use super::helpers::concurrency::get_concurrency;
 use super::helpers::metrics::MetricMap;
+use super::helpers::record;
 use super::options::{Options, OutputFormat};
-use super::test_result::TestResult;
+use super::test_result::{FailedAttempt, TestResult};
 use super::time::{TestExecTime, TestSuiteExecTime};
 use super::types::{NamePadding, TestDesc, TestDescAndFn, TestList};
 use super::{filter_tests, run_tests, term};
@@ -78,6 +80,7 @@ pub(crate) struct ConsoleTestState {
     pub log_out: Option<File>,
     pub total: usize,
     pub passed: usize,
+    pub flaky: usize,
     pub failed: usize,
     pub ignored: usize,
     pub filtered_out: usize,
@@ -88,6 +91,13 @@ pub(crate) struct ConsoleTestState {
     pub not_failures: Vec<(TestDesc, Vec<u8>)>,
     pub ignores: Vec<(TestDesc, Vec<u8>)>,
     pub time_failures: Vec<(TestDesc, Vec<u8>)>,
+    pub flaky_tests: Vec<(TestDesc, Vec<u8>)>,
+    /// The samples of every benchmark that was run, to save them as a baseline.
+    pub bench_samples: Vec<(String, Vec<f64>)>,
+    /// The baseline to compare benchmarks against.
+    pub baseline: Option<Baseline>,
+    /// Where to record the result of every test (`--record`).
+    pub record_out: Option<File>,
     pub options: Options,
 }
 
@@ -97,11 +107,20 @@ pub(crate) fn new(opts: &TestOpts) -> io::Result<ConsoleTestState> {
             Some(ref path) => Some(File::create(path)?),
             None => None,
         };
+        let baseline = match opts.baseline {
+            Some(ref name) => Some(Baseline::load(name)?),
+            None => None,
+        };
+        let record_out = match opts.record {
+            Some(ref path) => Some(File::create(path)?),
+            None => None,
+        };
 
         Ok(ConsoleTestState {
             log_out,
             total: 0,
             passed: 0,
+            flaky: 0,
             failed: 0,
             ignored: 0,
             filtered_out: 0,
@@ -112,6 +131,10 @@ pub(crate) fn new(opts: &TestOpts) -> io::Result<ConsoleTestState> {
             not_failures: Vec::new(),
             ignores: Vec::new(),
             time_failures: Vec::new(),
+            flaky_tests: Vec::new(),
+            bench_samples: Vec::new(),
+            baseline,
+            record_out,
             options: opts.options,
         })
     }
@@ -154,6 +177,12 @@ pub(crate) fn write_log_result(
                     }
                     TestResult::TrBench(ref bs) => fmt_bench_samples(bs),
                     TestResult::TrTimedFail => "failed (time limit exceeded)".to_owned(),
+                    TestResult::TrTimedOut(ref limit) => {
+                        format!("failed (timed out after {}s)", limit.as_secs())
+                    }
+                    TestResult::TrFlaky(ref attempts) => {
+                        format!("ok (flaky, {} failed attempts)", attempts.len())
+                    }
                 },
                 name,
             )
@@ -164,8 +193,22 @@ pub(crate) fn write_log_result(
         self.write_log(|| "\n")
     }
 
+    pub(crate) fn write_record_result(
+        &mut self,
+        test: &TestDesc,
+        result: &TestResult,
+    ) -> io::Result<()> {
+        match self.record_out {
+            None => Ok(()),
+            Some(ref mut o) => {
+                let line = record::record_line(test.name.as_slice(), result);
+                writeln!(o, "{line}")
+            }
+        }
+    }
+
     fn current_test_count(&self) -> usize {
-        self.passed + self.failed + self.ignored + self.measured
+        self.passed + self.flaky + self.failed + self.ignored + self.measured
     }
 }
 
@@ -177,7 +220,7 @@ pub(crate) fn list_tests_console(opts: &TestOpts, tests: TestList) -> io::Result
     };
 
     let mut out: Box<dyn OutputFormatter> = match opts.format {
-        OutputFormat::Pretty | OutputFormat::Junit => {
+        OutputFormat::Pretty | OutputFormat::Junit | OutputFormat::Tap => {
             Box::new(PrettyFormatter::new(output, false, 0, false, None))
         }
         OutputFormat::Terse => Box::new(TerseFormatter::new(output, false, 0, false)),
@@ -230,6 +273,7 @@ fn handle_test_result(st: &mut ConsoleTestState, completed_test: CompletedTest)
                 bs.ns_iter_summ.median,
                 bs.ns_iter_summ.max - bs.ns_iter_summ.min,
             );
+            st.bench_samples.push((test.name.to_string(), bs.samples));
             st.measured += 1
         }
         TestResult::TrFailed => {
@@ -246,7 +290,42 @@ fn handle_test_result(st: &mut ConsoleTestState, completed_test: CompletedTest)
             st.failed += 1;
             st.time_failures.push((test, stdout));
         }
+        TestResult::TrTimedOut(limit) => {
+            st.failed += 1;
+            let mut stdout = stdout;
+            stdout.extend_from_slice(
+                format!("note: test timed out after {}s", limit.as_secs()).as_bytes(),
+            );
+            st.failures.push((test, stdout));
+        }
+        TestResult::TrFlaky(attempts) => {
+            st.flaky += 1;
+            st.flaky_tests.push((test, failed_attempts_output(&attempts)));
+        }
+    }
+}
+
+// Describes the failed attempts of a flaky test, to be printed next to its name.
+fn failed_attempts_output(attempts: &[FailedAttempt]) -> Vec<u8> {
+    let mut output = Vec::new();
+    for (i, attempt) in attempts.iter().enumerate() {
+        output.extend_from_slice(format!("attempt {} failed:\n", i + 1).as_bytes());
+        output.extend_from_slice(&attempt.stdout);
+        match attempt.result {
+            TestResult::TrFailedMsg(ref msg) => {
+                output.extend_from_slice(format!("note: {msg}\n").as_bytes());
+            }
+            TestResult::TrTimedFail => {
+                output.extend_from_slice(b"note: test exceeded its time limit\n");
+            }
+            TestResult::TrTimedOut(limit) => {
+                let note = format!("note: test timed out after {}s\n", limit.as_secs());
+                output.extend_from_slice(note.as_bytes());
+            }
+            _ => {}
+        }
     }
+    output
 }
 
 // Handler for events that occur during test execution.
@@ -270,10 +349,19 @@ fn on_test_event(
             let test = &completed_test.desc;
             let result = &completed_test.result;
             let exec_time = &completed_test.exec_time;
+            let resource_usage = &completed_test.resource_usage;
             let stdout = &completed_test.stdout;
 
             st.write_log_result(test, result, exec_time.as_ref())?;
-            out.write_result(test, result, exec_time.as_ref(), stdout, st)?;
+            st.write_record_result(test, result)?;
+            out.write_result(
+                test,
+                result,
+                exec_time.as_ref(),
+                resource_usage.as_ref(),
+                stdout,
+                st,
+            )?;
             handle_test_result(st, completed_test);
         }
     }
@@ -302,6 +390,7 @@ pub(crate) fn get_formatter(opts: &TestOpts, max_name_len: usize) -> Box<dyn Out
         }
         OutputFormat::Json => Box::new(JsonFormatter::new(output)),
         OutputFormat::Junit => Box::new(JunitFormatter::new(output)),
+        OutputFormat::Tap => Box::new(TapFormatter::new(output)),
     }
 }
 
@@ -328,6 +417,10 @@ pub fn run_tests_console(opts: &TestOpts, tests: TestList) -> io::Result<bool> {
     run_tests(opts, tests, |x| on_test_event(&x, &mut st, &mut *out))?;
     st.exec_time = start_time.map(|t| TestSuiteExecTime(t.elapsed()));
 
+    if let Some(ref name) = opts.save_baseline {
+        Baseline::save(name, &st.bench_samples)?;
+    }
+
     assert!(opts.fail_fast || st.current_test_count() == st.total);
 
     out.write_run_finish(&st)
@@ -1,6 +1,7 @@
 //! Module containing different events that can occur
 //! during tests execution process.
 
+use super::resources::TestResourceUsage;
 use super::test_result::TestResult;
 use super::time::TestExecTime;
 use super::types::{TestDesc, TestId};
@@ -12,6 +13,9 @@ pub struct CompletedTest {
     pub result: TestResult,
     pub exec_time: Option<TestExecTime>,
     pub stdout: Vec<u8>,
+    /// The resources used by the test, if it ran in a subprocess and they
+    /// could be measured.
+    pub resource_usage: Option<TestResourceUsage>,
 }
 
 impl CompletedTest {
@@ -22,7 +26,7 @@ pub fn new(
         exec_time: Option<TestExecTime>,
         stdout: Vec<u8>,
     ) -> Self {
-        Self { id, desc, result, exec_time, stdout }
+        Self { id, desc, result, exec_time, stdout, resource_usage: None }
     }
 }
 
@@ -4,7 +4,8 @@
 
 use super::OutputFormatter;
 use crate::console::{ConsoleTestDiscoveryState, ConsoleTestState, OutputLocation};
-use crate::test_result::TestResult;
+use crate::resources::TestResourceUsage;
+use crate::test_result::{FailedAttempt, TestResult};
 use crate::time;
 use crate::types::TestDesc;
 
@@ -32,6 +33,7 @@ fn write_event(
         name: &str,
         event: &str,
         exec_time: Option<&time::TestExecTime>,
+        resource_usage: Option<&TestResourceUsage>,
         stdout: Option<Cow<'_, str>>,
         extra: Option<&str>,
     ) -> io::Result<()> {
@@ -42,6 +44,16 @@ fn write_event(
         } else {
             String::from("")
         };
+        let resource_usage_json = if let Some(usage) = resource_usage {
+            format!(
+                r#", "max_rss": {}, "user_time": {}, "system_time": {}"#,
+                usage.max_rss,
+                usage.user_time.as_secs_f64(),
+                usage.system_time.as_secs_f64()
+            )
+        } else {
+            String::from("")
+        };
         let stdout_json = if let Some(stdout) = stdout {
             format!(r#", "stdout": "{}""#, EscapedString(stdout))
         } else {
@@ -52,7 +64,7 @@ fn write_event(
         let newline = "\n";
 
         self.writeln_message(&format!(
-                r#"{{ "type": "{ty}", "name": "{name}", "event": "{event}"{exec_time_json}{stdout_json}{extra_json} }}{newline}"#))
+                r#"{{ "type": "{ty}", "name": "{name}", "event": "{event}"{exec_time_json}{resource_usage_json}{stdout_json}{extra_json} }}{newline}"#))
     }
 }
 
@@ -119,47 +131,88 @@ fn write_result(
         desc: &TestDesc,
         result: &TestResult,
         exec_time: Option<&time::TestExecTime>,
+        resource_usage: Option<&TestResourceUsage>,
         stdout: &[u8],
         state: &ConsoleTestState,
     ) -> io::Result<()> {
-        let display_stdout = state.options.display_output || *result != TestResult::TrOk;
+        let display_stdout = state.options.display_output
+            || !matches!(*result, TestResult::TrOk | TestResult::TrFlaky(_));
         let stdout = if display_stdout && !stdout.is_empty() {
             Some(String::from_utf8_lossy(stdout))
         } else {
             None
         };
         match *result {
-            TestResult::TrOk => {
-                self.write_event("test", desc.name.as_slice(), "ok", exec_time, stdout, None)
-            }
+            TestResult::TrOk => self.write_event(
+                "test",
+                desc.name.as_slice(),
+                "ok",
+                exec_time,
+                resource_usage,
+                stdout,
+                None,
+            ),
 
-            TestResult::TrFailed => {
-                self.write_event("test", desc.name.as_slice(), "failed", exec_time, stdout, None)
-            }
+            TestResult::TrFailed => self.write_event(
+                "test",
+                desc.name.as_slice(),
+                "failed",
+                exec_time,
+                resource_usage,
+                stdout,
+                None,
+            ),
 
             TestResult::TrTimedFail => self.write_event(
                 "test",
                 desc.name.as_slice(),
                 "failed",
                 exec_time,
+                resource_usage,
                 stdout,
                 Some(r#""reason": "time limit exceeded""#),
             ),
 
+            TestResult::TrTimedOut(ref limit) => self.write_event(
+                "test",
+                desc.name.as_slice(),
+                "failed",
+                exec_time,
+                resource_usage,
+                stdout,
+                Some(&*format!(r#""reason": "timed out", "timeout": {}"#, limit.as_secs_f64())),
+            ),
+
             TestResult::TrFailedMsg(ref m) => self.write_event(
                 "test",
                 desc.name.as_slice(),
                 "failed",
                 exec_time,
+                resource_usage,
                 stdout,
                 Some(&*format!(r#""message": "{}""#, EscapedString(m))),
             ),
 
+            TestResult::TrFlaky(ref attempts) => {
+                let attempts_json =
+                    attempts.iter().map(failed_attempt_json).collect::<Vec<_>>().join(", ");
+                self.write_event(
+                    "test",
+                    desc.name.as_slice(),
+                    "flaky",
+                    exec_time,
+                    resource_usage,
+                    stdout,
+                    Some(&*format!(r#""failed_attempts": [{attempts_json}]"#)),
+                )
+            }
+
             TestResult::TrIgnored => self.write_event(
                 "test",
                 desc.name.as_slice(),
                 "ignored",
                 exec_time,
+                resource_usage,
                 stdout,
                 desc.ignore_message
                     .map(|msg| format!(r#""message": "{}""#, EscapedString(msg)))
@@ -175,13 +228,31 @@ fn write_result(
                 } else {
                     format!(r#", "mib_per_second": {}"#, bs.mb_s)
                 };
+                let baseline = match state
+                    .baseline
+                    .as_ref()
+                    .and_then(|b| b.compare(desc.name.as_slice(), &bs.samples))
+                {
+                    Some(comparison) => {
+                        let change = comparison.change;
+                        format!(
+                            r#", "baseline": "{}", "change": {}, "change_lower": {}, "change_upper": {}, "verdict": "{}""#,
+                            EscapedString(comparison.baseline),
+                            change.estimate,
+                            change.lower,
+                            change.upper,
+                            change.verdict.as_str()
+                        )
+                    }
+                    None => String::new(),
+                };
                 let name = EscapedString(desc.name.as_slice());
 
                 self.writeln_message(&format!(
                     "{{ \"type\": \"bench\", \
                      \"name\": \"{name}\", \
                      \"median\": {median}, \
-                     \"deviation\": {deviation}{mbps} }}\n",
+                     \"deviation\": {deviation}{mbps}{baseline} }}\n",
                 ))
             }
         }
@@ -202,6 +273,8 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
         let ignored = state.ignored;
         let measured = state.measured;
         let filtered_out = state.filtered_out;
+        let flaky_json =
+            if state.flaky > 0 { format!(r#", "flaky": {}"#, state.flaky) } else { String::new() };
         let exec_time_json = if let Some(ref exec_time) = state.exec_time {
             format!(r#", "exec_time": {}"#, exec_time.0.as_secs_f64())
         } else {
@@ -210,7 +283,7 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
         let newline = "\n";
 
         self.writeln_message(&format!(
-            r#"{{ "type": "suite", "event": "{event}", "passed": {passed}, "failed": {failed}, "ignored": {ignored}, "measured": {measured}, "filtered_out": {filtered_out}{exec_time_json} }}{newline}"#
+            r#"{{ "type": "suite", "event": "{event}", "passed": {passed}{flaky_json}, "failed": {failed}, "ignored": {ignored}, "measured": {measured}, "filtered_out": {filtered_out}{exec_time_json} }}{newline}"#
         ))?;
 
         Ok(state.failed == 0)
@@ -228,9 +301,28 @@ fn write_merged_doctests_times(
     }
 }
 
+/// Formats a failed attempt of a flaky test as a JSON object, with the same
+/// fields as the "failed" event of a test.
+fn failed_attempt_json(attempt: &FailedAttempt) -> String {
+    let stdout_json = if attempt.stdout.is_empty() {
+        String::new()
+    } else {
+        format!(r#", "stdout": "{}""#, EscapedString(String::from_utf8_lossy(&attempt.stdout)))
+    };
+    let extra_json = match attempt.result {
+        TestResult::TrFailedMsg(ref m) => format!(r#", "message": "{}""#, EscapedString(m)),
+        TestResult::TrTimedFail => String::from(r#", "reason": "time limit exceeded""#),
+        TestResult::TrTimedOut(ref limit) => {
+            format!(r#", "reason": "timed out", "timeout": {}"#, limit.as_secs_f64())
+        }
+        _ => String::new(),
+    };
+    format!(r#"{{ "event": "failed"{stdout_json}{extra_json} }}"#)
+}
+
 /// A formatting utility used to print strings with characters in need of escaping.
 /// Base code taken form `libserialize::json::escape_str`
-struct EscapedString<S: AsRef<str>>(S);
+pub(crate) struct EscapedString<S: AsRef<str>>(pub(crate) S);
 
 impl<S: AsRef<str>> std::fmt::Display for EscapedString<S> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> ::std::fmt::Result {
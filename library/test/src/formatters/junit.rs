@@ -4,13 +4,14 @@
 
 use super::OutputFormatter;
 use crate::console::{ConsoleTestDiscoveryState, ConsoleTestState, OutputLocation};
+use crate::resources::TestResourceUsage;
 use crate::test_result::TestResult;
 use crate::time;
 use crate::types::{TestDesc, TestType};
 
 pub(crate) struct JunitFormatter<T> {
     out: OutputLocation<T>,
-    results: Vec<(TestDesc, TestResult, Duration, Vec<u8>)>,
+    results: Vec<(TestDesc, TestResult, Duration, Option<TestResourceUsage>, Vec<u8>)>,
 }
 
 impl<T: Write> JunitFormatter<T> {
@@ -23,6 +24,21 @@ fn write_message(&mut self, s: &str) -> io::Result<()> {
 
         self.out.write_all(s.as_ref())
     }
+
+    /// Writes the resources used by a test as properties of its testcase.
+    fn write_properties(&mut self, resource_usage: Option<&TestResourceUsage>) -> io::Result<()> {
+        let Some(usage) = resource_usage else { return Ok(()) };
+        self.write_message(&format!(
+            "<properties>\
+             <property name=\"max_rss\" value=\"{}\"/>\
+             <property name=\"user_time\" value=\"{}\"/>\
+             <property name=\"system_time\" value=\"{}\"/>\
+             </properties>",
+            usage.max_rss,
+            usage.user_time.as_secs_f64(),
+            usage.system_time.as_secs_f64()
+        ))
+    }
 }
 
 fn str_to_cdata(s: &str) -> String {
@@ -74,6 +90,7 @@ fn write_result(
         desc: &TestDesc,
         result: &TestResult,
         exec_time: Option<&time::TestExecTime>,
+        resource_usage: Option<&TestResourceUsage>,
         stdout: &[u8],
         _state: &ConsoleTestState,
     ) -> io::Result<()> {
@@ -81,7 +98,13 @@ fn write_result(
         // until all of the tests have finished. Instead of writing every result as they come in, we add
         // them to a Vec and write them all at once when run is complete.
         let duration = exec_time.map(|t| t.0).unwrap_or_default();
-        self.results.push((desc.clone(), result.clone(), duration, stdout.to_vec()));
+        self.results.push((
+            desc.clone(),
+            result.clone(),
+            duration,
+            resource_usage.copied(),
+            stdout.to_vec(),
+        ));
         Ok(())
     }
     fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
@@ -96,7 +119,7 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
              >",
             state.failed, state.total, state.ignored
         ))?;
-        for (desc, result, duration, stdout) in std::mem::take(&mut self.results) {
+        for (desc, result, duration, usage, stdout) in std::mem::take(&mut self.results) {
             let (class_name, test_name) = parse_class_name(&desc);
             match result {
                 TestResult::TrIgnored => { /* no-op */ }
@@ -108,6 +131,7 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
                         test_name,
                         duration.as_secs_f64()
                     ))?;
+                    self.write_properties(usage.as_ref())?;
                     self.write_message("<failure type=\"assert\"/>")?;
                     if !stdout.is_empty() {
                         self.write_message("<system-out>")?;
@@ -125,6 +149,7 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
                         test_name,
                         duration.as_secs_f64()
                     ))?;
+                    self.write_properties(usage.as_ref())?;
                     self.write_message(&format!("<failure message=\"{m}\" type=\"assert\"/>"))?;
                     if !stdout.is_empty() {
                         self.write_message("<system-out>")?;
@@ -142,10 +167,74 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
                         test_name,
                         duration.as_secs_f64()
                     ))?;
+                    self.write_properties(usage.as_ref())?;
                     self.write_message("<failure type=\"timeout\"/>")?;
                     self.write_message("</testcase>")?;
                 }
 
+                TestResult::TrTimedOut(limit) => {
+                    self.write_message(&format!(
+                        "<testcase classname=\"{}\" \
+                         name=\"{}\" time=\"{}\">",
+                        class_name,
+                        test_name,
+                        duration.as_secs_f64()
+                    ))?;
+                    self.write_properties(usage.as_ref())?;
+                    self.write_message(&format!(
+                        "<failure message=\"test timed out after {}s\" type=\"timeout\"/>",
+                        limit.as_secs()
+                    ))?;
+                    if !stdout.is_empty() {
+                        self.write_message("<system-out>")?;
+                        self.write_message(&str_to_cdata(&String::from_utf8_lossy(&stdout)))?;
+                        self.write_message("</system-out>")?;
+                    }
+                    self.write_message("</testcase>")?;
+                }
+
+                TestResult::TrFlaky(ref attempts) => {
+                    self.write_message(&format!(
+                        "<testcase classname=\"{}\" \
+                         name=\"{}\" time=\"{}\">",
+                        class_name,
+                        test_name,
+                        duration.as_secs_f64()
+                    ))?;
+                    self.write_properties(usage.as_ref())?;
+                    for attempt in attempts {
+                        let (message, ty) = match attempt.result {
+                            TestResult::TrFailedMsg(ref m) => (Some(m.clone()), "assert"),
+                            TestResult::TrTimedFail => (None, "timeout"),
+                            TestResult::TrTimedOut(limit) => (
+                                Some(format!("test timed out after {}s", limit.as_secs())),
+                                "timeout",
+                            ),
+                            _ => (None, "assert"),
+                        };
+                        match message {
+                            Some(m) => self.write_message(&format!(
+                                "<flakyFailure message=\"{m}\" type=\"{ty}\">"
+                            ))?,
+                            None => self.write_message(&format!("<flakyFailure type=\"{ty}\">"))?,
+                        }
+                        if !attempt.stdout.is_empty() {
+                            self.write_message("<system-out>")?;
+                            self.write_message(&str_to_cdata(&String::from_utf8_lossy(
+                                &attempt.stdout,
+                            )))?;
+                            self.write_message("</system-out>")?;
+                        }
+                        self.write_message("</flakyFailure>")?;
+                    }
+                    if !stdout.is_empty() && state.options.display_output {
+                        self.write_message("<system-out>")?;
+                        self.write_message(&str_to_cdata(&String::from_utf8_lossy(&stdout)))?;
+                        self.write_message("</system-out>")?;
+                    }
+                    self.write_message("</testcase>")?;
+                }
+
                 TestResult::TrBench(ref b) => {
                     self.write_message(&format!(
                         "<testcase classname=\"benchmark::{}\" \
@@ -162,12 +251,17 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
                         test_name,
                         duration.as_secs_f64()
                     ))?;
-                    if stdout.is_empty() || !state.options.display_output {
+                    let display_stdout = !stdout.is_empty() && state.options.display_output;
+                    if !display_stdout && usage.is_none() {
                         self.write_message("/>")?;
                     } else {
-                        self.write_message("><system-out>")?;
-                        self.write_message(&str_to_cdata(&String::from_utf8_lossy(&stdout)))?;
-                        self.write_message("</system-out>")?;
+                        self.write_message(">")?;
+                        self.write_properties(usage.as_ref())?;
+                        if display_stdout {
+                            self.write_message("<system-out>")?;
+                            self.write_message(&str_to_cdata(&String::from_utf8_lossy(&stdout)))?;
+                            self.write_message("</system-out>")?;
+                        }
                         self.write_message("</testcase>")?;
                     }
                 }
@@ -2,6 +2,7 @@
 use std::io::prelude::Write;
 
 use crate::console::{ConsoleTestDiscoveryState, ConsoleTestState};
+use crate::resources::TestResourceUsage;
 use crate::test_result::TestResult;
 use crate::time;
 use crate::types::{TestDesc, TestName};
@@ -9,11 +10,13 @@
 mod json;
 mod junit;
 mod pretty;
+mod tap;
 mod terse;
 
-pub(crate) use self::json::JsonFormatter;
+pub(crate) use self::json::{EscapedString, JsonFormatter};
 pub(crate) use self::junit::JunitFormatter;
 pub(crate) use self::pretty::PrettyFormatter;
+pub(crate) use self::tap::TapFormatter;
 pub(crate) use self::terse::TerseFormatter;
 
 pub(crate) trait OutputFormatter {
@@ -29,6 +32,7 @@ fn write_result(
         desc: &TestDesc,
         result: &TestResult,
         exec_time: Option<&time::TestExecTime>,
+        resource_usage: Option<&TestResourceUsage>,
         stdout: &[u8],
         state: &ConsoleTestState,
     ) -> io::Result<()>;
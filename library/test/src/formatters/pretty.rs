@@ -4,6 +4,7 @@
 use super::OutputFormatter;
 use crate::bench::fmt_bench_samples;
 use crate::console::{ConsoleTestDiscoveryState, ConsoleTestState, OutputLocation};
+use crate::resources::TestResourceUsage;
 use crate::test_result::TestResult;
 use crate::types::TestDesc;
 use crate::{term, time};
@@ -55,6 +56,14 @@ pub(crate) fn write_time_failed(&mut self) -> io::Result<()> {
         self.write_short_result("FAILED (time limit exceeded)", term::color::RED)
     }
 
+    pub(crate) fn write_timed_out(&mut self) -> io::Result<()> {
+        self.write_short_result("FAILED (timed out)", term::color::RED)
+    }
+
+    pub(crate) fn write_flaky(&mut self) -> io::Result<()> {
+        self.write_short_result("ok (flaky)", term::color::YELLOW)
+    }
+
     pub(crate) fn write_bench(&mut self) -> io::Result<()> {
         self.write_pretty("bench", term::color::CYAN)
     }
@@ -166,6 +175,10 @@ pub(crate) fn write_time_failures(&mut self, state: &ConsoleTestState) -> io::Re
         self.write_results(&state.time_failures, "failures (time limit exceeded)")
     }
 
+    pub(crate) fn write_flaky_tests(&mut self, state: &ConsoleTestState) -> io::Result<()> {
+        self.write_results(&state.flaky_tests, "flaky tests")
+    }
+
     fn write_test_name(&mut self, desc: &TestDesc) -> io::Result<()> {
         let name = desc.padded_name(self.max_name_len, desc.name.padding());
         if let Some(test_mode) = desc.test_mode() {
@@ -233,8 +246,9 @@ fn write_result(
         desc: &TestDesc,
         result: &TestResult,
         exec_time: Option<&time::TestExecTime>,
+        _: Option<&TestResourceUsage>,
         _: &[u8],
-        _: &ConsoleTestState,
+        state: &ConsoleTestState,
     ) -> io::Result<()> {
         if self.is_multithreaded {
             self.write_test_name(desc)?;
@@ -247,8 +261,17 @@ fn write_result(
             TestResult::TrBench(ref bs) => {
                 self.write_bench()?;
                 self.write_plain(format!(": {}", fmt_bench_samples(bs)))?;
+                if let Some(comparison) = state
+                    .baseline
+                    .as_ref()
+                    .and_then(|b| b.compare(desc.name.as_slice(), &bs.samples))
+                {
+                    self.write_plain(format!(" ({comparison})"))?;
+                }
             }
             TestResult::TrTimedFail => self.write_time_failed()?,
+            TestResult::TrTimedOut(_) => self.write_timed_out()?,
+            TestResult::TrFlaky(_) => self.write_flaky()?,
         }
 
         self.write_time(desc, exec_time)?;
@@ -267,6 +290,9 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
         if state.options.display_output {
             self.write_successes(state)?;
         }
+        if !state.flaky_tests.is_empty() {
+            self.write_flaky_tests(state)?;
+        }
         let success = state.failed == 0;
         if !success {
             if !state.failures.is_empty() {
@@ -287,8 +313,10 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
             self.write_pretty("FAILED", term::color::RED)?;
         }
 
+        let flaky =
+            if state.flaky > 0 { format!("{} flaky; ", state.flaky) } else { String::new() };
         let s = format!(
-            ". {} passed; {} failed; {} ignored; {} measured; {} filtered out",
+            ". {} passed; {flaky}{} failed; {} ignored; {} measured; {} filtered out",
             state.passed, state.failed, state.ignored, state.measured, state.filtered_out
         );
 
@@ -0,0 +1,223 @@
+use std::io;
+use std::io::prelude::Write;
+
+use super::{EscapedString, OutputFormatter};
+use crate::console::{ConsoleTestDiscoveryState, ConsoleTestState, OutputLocation};
+use crate::resources::TestResourceUsage;
+use crate::test_result::TestResult;
+use crate::time;
+use crate::types::TestDesc;
+
+/// Streams test results in the Test Anything Protocol, version 14.
+///
+/// Each result gets a test point, followed by an indented YAML block with
+/// diagnostics (failure message, duration, captured output) when there are
+/// any. See <https://testanything.org/tap-version-14-specification.html>.
+pub(crate) struct TapFormatter<T> {
+    out: OutputLocation<T>,
+    test_count: usize,
+    /// Number of the last test point that was written.
+    test_number: usize,
+}
+
+impl<T: Write> TapFormatter<T> {
+    pub(crate) fn new(out: OutputLocation<T>) -> Self {
+        Self { out, test_count: 0, test_number: 0 }
+    }
+
+    fn writeln(&mut self, s: &str) -> io::Result<()> {
+        // Write whole lines at once, like the JSON formatter, so that the
+        // output of concurrently running tests can't end up in the middle of
+        // a line.
+        self.out.write_all(format!("{s}\n").as_bytes())
+    }
+
+    fn write_test_point(
+        &mut self,
+        ok: bool,
+        desc: &TestDesc,
+        directive: Option<&str>,
+        diagnostics: &[(&str, String)],
+    ) -> io::Result<()> {
+        self.test_number += 1;
+        let status = if ok { "ok" } else { "not ok" };
+        let directive = directive.map(|d| format!(" # {d}")).unwrap_or_default();
+        self.writeln(&format!(
+            "{status} {} - {}{directive}",
+            self.test_number,
+            escape_description(desc.name.as_slice())
+        ))?;
+
+        if !diagnostics.is_empty() {
+            self.writeln("  ---")?;
+            for (key, value) in diagnostics {
+                self.writeln(&format!("  {key}: {value}"))?;
+            }
+            self.writeln("  ...")?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: Write> OutputFormatter for TapFormatter<T> {
+    fn write_discovery_start(&mut self) -> io::Result<()> {
+        Err(io::const_error!(io::ErrorKind::NotFound, "not yet implemented!"))
+    }
+
+    fn write_test_discovered(&mut self, _desc: &TestDesc, _test_type: &str) -> io::Result<()> {
+        Err(io::const_error!(io::ErrorKind::NotFound, "not yet implemented!"))
+    }
+
+    fn write_discovery_finish(&mut self, _state: &ConsoleTestDiscoveryState) -> io::Result<()> {
+        Err(io::const_error!(io::ErrorKind::NotFound, "not yet implemented!"))
+    }
+
+    fn write_run_start(&mut self, test_count: usize, shuffle_seed: Option<u64>) -> io::Result<()> {
+        self.test_count = test_count;
+        self.writeln("TAP version 14")?;
+        if let Some(shuffle_seed) = shuffle_seed {
+            self.writeln(&format!("# shuffle seed: {shuffle_seed}"))?;
+        }
+        self.writeln(&format!("1..{test_count}"))
+    }
+
+    fn write_test_start(&mut self, _desc: &TestDesc) -> io::Result<()> {
+        // Test points are only written once a test has finished.
+        Ok(())
+    }
+
+    fn write_timeout(&mut self, desc: &TestDesc) -> io::Result<()> {
+        self.writeln(&format!(
+            "# test {} has been running for over {} seconds",
+            desc.name,
+            time::TEST_WARN_TIMEOUT_S
+        ))
+    }
+
+    fn write_result(
+        &mut self,
+        desc: &TestDesc,
+        result: &TestResult,
+        exec_time: Option<&time::TestExecTime>,
+        _resource_usage: Option<&TestResourceUsage>,
+        stdout: &[u8],
+        state: &ConsoleTestState,
+    ) -> io::Result<()> {
+        let stdout = String::from_utf8_lossy(stdout);
+        let mut diagnostics = Vec::new();
+
+        let ok = match *result {
+            TestResult::TrOk | TestResult::TrIgnored => true,
+            TestResult::TrFlaky(ref attempts) => {
+                diagnostics.push(("flaky", "true".to_string()));
+                diagnostics.push(("failed_attempts", attempts.len().to_string()));
+                true
+            }
+            TestResult::TrFailed => {
+                if let Some(message) = panic_message(&stdout) {
+                    diagnostics.push(("message", yaml_string(&message)));
+                }
+                false
+            }
+            TestResult::TrFailedMsg(ref message) => {
+                diagnostics.push(("message", yaml_string(message)));
+                false
+            }
+            TestResult::TrTimedFail => {
+                diagnostics.push(("message", yaml_string("time limit exceeded")));
+                false
+            }
+            TestResult::TrTimedOut(limit) => {
+                let message = format!("timed out after {}s", limit.as_secs());
+                diagnostics.push(("message", yaml_string(&message)));
+                false
+            }
+            TestResult::TrBench(ref bs) => {
+                diagnostics.push(("median_ns", bs.ns_iter_summ.median.to_string()));
+                let deviation = bs.ns_iter_summ.max - bs.ns_iter_summ.min;
+                diagnostics.push(("deviation_ns", deviation.to_string()));
+                if bs.mb_s != 0 {
+                    diagnostics.push(("mib_per_second", bs.mb_s.to_string()));
+                }
+                true
+            }
+        };
+        if !ok {
+            diagnostics.push(("severity", "fail".to_string()));
+        }
+        if let Some(exec_time) = exec_time {
+            diagnostics.push(("duration_ms", (exec_time.0.as_secs_f64() * 1000.0).to_string()));
+        }
+        let display_stdout = state.options.display_output || !ok;
+        if display_stdout && !stdout.is_empty() {
+            diagnostics.push(("stdout", yaml_string(&stdout)));
+        }
+
+        let directive = match *result {
+            TestResult::TrIgnored => Some(match desc.ignore_message {
+                Some(message) => format!("SKIP {}", escape_description(message)),
+                None => "SKIP".to_string(),
+            }),
+            _ => None,
+        };
+        self.write_test_point(ok, desc, directive.as_deref(), &diagnostics)
+    }
+
+    fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
+        // The plan promised a result for every test, which isn't the case when
+        // `--fail-fast` stopped the run early.
+        if self.test_number < self.test_count {
+            self.writeln("Bail out! Stopped after the first failure")?;
+        }
+
+        let flaky =
+            if state.flaky > 0 { format!("{} flaky; ", state.flaky) } else { String::new() };
+        let mut summary = format!(
+            "# {} passed; {flaky}{} failed; {} ignored; {} measured; {} filtered out",
+            state.passed, state.failed, state.ignored, state.measured, state.filtered_out
+        );
+        if let Some(ref exec_time) = state.exec_time {
+            summary.push_str(&format!("; finished in {exec_time}"));
+        }
+        self.writeln(&summary)?;
+
+        Ok(state.failed == 0)
+    }
+
+    fn write_merged_doctests_times(
+        &mut self,
+        total_time: f64,
+        compilation_time: f64,
+    ) -> io::Result<()> {
+        self.writeln(&format!(
+            "# all doctests ran in {total_time:.2}s; merged doctests compilation took {compilation_time:.2}s",
+        ))
+    }
+}
+
+/// Escapes the characters that have a meaning in the description of a test
+/// point: `#` starts a directive and `\` escapes.
+fn escape_description(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('#', "\\#")
+}
+
+/// Formats `s` as a double-quoted YAML scalar. YAML's escape sequences are a
+/// superset of JSON's, so this is the same as a JSON string.
+fn yaml_string(s: &str) -> String {
+    format!("\"{}\"", EscapedString(s))
+}
+
+/// Extracts the message of a panic from the captured output of a test, which
+/// starts on the line after `thread '...' panicked at ...:` and ends before
+/// the note about backtraces or the backtrace itself.
+fn panic_message(stdout: &str) -> Option<String> {
+    let mut lines = stdout.lines().skip_while(|line| {
+        !(line.starts_with("thread '") && line.contains(" panicked at ") && line.ends_with(':'))
+    });
+    lines.next()?;
+    let message = lines
+        .take_while(|line| !line.starts_with("note: ") && *line != "stack backtrace:")
+        .collect::<Vec<_>>()
+        .join("\n");
+    Some(message)
+}
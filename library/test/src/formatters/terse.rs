@@ -4,6 +4,7 @@
 use super::OutputFormatter;
 use crate::bench::fmt_bench_samples;
 use crate::console::{ConsoleTestDiscoveryState, ConsoleTestState, OutputLocation};
+use crate::resources::TestResourceUsage;
 use crate::test_result::TestResult;
 use crate::types::{NamePadding, TestDesc};
 use crate::{term, time};
@@ -62,6 +63,10 @@ pub(crate) fn write_failed(&mut self, name: &str) -> io::Result<()> {
         self.write_plain("\n")
     }
 
+    pub(crate) fn write_flaky(&mut self) -> io::Result<()> {
+        self.write_short_result("~", term::color::YELLOW)
+    }
+
     pub(crate) fn write_ignored(&mut self) -> io::Result<()> {
         self.write_short_result("i", term::color::YELLOW)
     }
@@ -146,6 +151,32 @@ pub(crate) fn write_outputs(&mut self, state: &ConsoleTestState) -> io::Result<(
         Ok(())
     }
 
+    pub(crate) fn write_flaky_tests(&mut self, state: &ConsoleTestState) -> io::Result<()> {
+        self.write_plain("\nflaky tests:\n")?;
+        let mut flaky = Vec::new();
+        let mut flaky_out = String::new();
+        for (f, stdout) in &state.flaky_tests {
+            flaky.push(f.name.to_string());
+            if !stdout.is_empty() {
+                flaky_out.push_str(&format!("---- {} stdout ----\n", f.name));
+                let output = String::from_utf8_lossy(stdout);
+                flaky_out.push_str(&output);
+                flaky_out.push('\n');
+            }
+        }
+        if !flaky_out.is_empty() {
+            self.write_plain("\n")?;
+            self.write_plain(&flaky_out)?;
+        }
+
+        self.write_plain("\nflaky tests:\n")?;
+        flaky.sort();
+        for name in &flaky {
+            self.write_plain(&format!("    {name}\n"))?;
+        }
+        Ok(())
+    }
+
     pub(crate) fn write_failures(&mut self, state: &ConsoleTestState) -> io::Result<()> {
         self.write_plain("\nfailures:\n")?;
         let mut failures = Vec::new();
@@ -225,21 +256,32 @@ fn write_result(
         desc: &TestDesc,
         result: &TestResult,
         _: Option<&time::TestExecTime>,
+        _: Option<&TestResourceUsage>,
         _: &[u8],
-        _: &ConsoleTestState,
+        state: &ConsoleTestState,
     ) -> io::Result<()> {
         match *result {
             TestResult::TrOk => self.write_ok(),
-            TestResult::TrFailed | TestResult::TrFailedMsg(_) | TestResult::TrTimedFail => {
-                self.write_failed(desc.name.as_slice())
-            }
+            TestResult::TrFlaky(_) => self.write_flaky(),
+            TestResult::TrFailed
+            | TestResult::TrFailedMsg(_)
+            | TestResult::TrTimedFail
+            | TestResult::TrTimedOut(_) => self.write_failed(desc.name.as_slice()),
             TestResult::TrIgnored => self.write_ignored(),
             TestResult::TrBench(ref bs) => {
                 if self.is_multithreaded {
                     self.write_test_name(desc)?;
                 }
                 self.write_bench()?;
-                self.write_plain(format!(": {}\n", fmt_bench_samples(bs)))
+                self.write_plain(format!(": {}", fmt_bench_samples(bs)))?;
+                if let Some(comparison) = state
+                    .baseline
+                    .as_ref()
+                    .and_then(|b| b.compare(desc.name.as_slice(), &bs.samples))
+                {
+                    self.write_plain(format!(" ({comparison})"))?;
+                }
+                self.write_plain("\n")
             }
         }
     }
@@ -256,6 +298,9 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
         if state.options.display_output {
             self.write_outputs(state)?;
         }
+        if !state.flaky_tests.is_empty() {
+            self.write_flaky_tests(state)?;
+        }
         let success = state.failed == 0;
         if !success {
             self.write_failures(state)?;
@@ -270,8 +315,10 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
             self.write_pretty("FAILED", term::color::RED)?;
         }
 
+        let flaky =
+            if state.flaky > 0 { format!("{} flaky; ", state.flaky) } else { String::new() };
         let s = format!(
-            ". {} passed; {} failed; {} ignored; {} measured; {} filtered out",
+            ". {} passed; {flaky}{} failed; {} ignored; {} measured; {} filtered out",
             state.passed, state.failed, state.ignored, state.measured, state.filtered_out
         );
 
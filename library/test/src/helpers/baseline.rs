@@ -0,0 +1,214 @@
+//! Named benchmark baselines (`--save-baseline` and `--baseline`).
+//!
+//! A baseline stores the samples of each benchmark of a run, so that a later
+//! run can tell whether a benchmark got significantly slower or faster. All
+//! the test binaries of a crate share the same baseline file: saving a
+//! baseline only replaces the benchmarks that were run.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::{fmt, fs, io};
+
+use crate::stats::Stats;
+
+const HEADER: &str = "# libtest benchmark baseline v1";
+
+/// Changes smaller than this fraction of the baseline mean are never
+/// reported, even if they are statistically significant.
+const NOISE_THRESHOLD: f64 = 0.01;
+
+/// The two-sided quantile of the standard normal distribution for a 95%
+/// confidence level.
+const Z_95: f64 = 1.959963984540054;
+
+pub(crate) struct Baseline {
+    name: String,
+    samples: BTreeMap<String, Vec<f64>>,
+}
+
+impl Baseline {
+    /// Where the baseline called `name` is stored: in the directory named by
+    /// `RUST_TEST_BASELINE_DIR`, or else next to the test binary.
+    fn path(name: &str) -> io::Result<PathBuf> {
+        let dir = match std::env::var_os("RUST_TEST_BASELINE_DIR") {
+            Some(dir) => PathBuf::from(dir),
+            None => {
+                let exe = std::env::current_exe()?;
+                exe.parent().map(PathBuf::from).unwrap_or_default()
+            }
+        };
+        Ok(dir.join(format!("{name}.baseline")))
+    }
+
+    fn read(name: &str) -> io::Result<Option<Baseline>> {
+        let path = Self::path(name)?;
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+
+        let invalid = |line: usize| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid benchmark baseline {} (line {line})", path.display()),
+            )
+        };
+        let mut lines = contents.lines().enumerate();
+        if lines.next().map(|(_, line)| line) != Some(HEADER) {
+            return Err(invalid(1));
+        }
+
+        let mut samples = BTreeMap::new();
+        for (i, line) in lines {
+            // Benchmark names don't contain tabs, but may contain spaces.
+            let (bench, values) = line.rsplit_once('\t').ok_or_else(|| invalid(i + 1))?;
+            let values = values
+                .split(' ')
+                .map(|value| value.parse::<f64>())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| invalid(i + 1))?;
+            samples.insert(bench.to_string(), values);
+        }
+
+        Ok(Some(Baseline { name: name.to_string(), samples }))
+    }
+
+    /// Loads the baseline called `name`, which must have been saved before.
+    pub(crate) fn load(name: &str) -> io::Result<Baseline> {
+        Self::read(name)?.ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("benchmark baseline `{name}` does not exist, save it with --save-baseline"),
+            )
+        })
+    }
+
+    /// Saves the samples of the benchmarks that were run into the baseline
+    /// called `name`, keeping the samples it has for other benchmarks.
+    pub(crate) fn save(name: &str, benches: &[(String, Vec<f64>)]) -> io::Result<()> {
+        let mut baseline = Self::read(name)?
+            .unwrap_or_else(|| Baseline { name: name.to_string(), samples: BTreeMap::new() });
+        for (bench, samples) in benches {
+            baseline.samples.insert(bench.clone(), samples.clone());
+        }
+
+        let mut contents = format!("{HEADER}\n");
+        for (bench, samples) in &baseline.samples {
+            let samples = samples.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+            contents.push_str(&format!("{bench}\t{}\n", samples.join(" ")));
+        }
+
+        let path = Self::path(name)?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, contents)
+    }
+
+    /// Compares new samples of the benchmark `bench` against this baseline.
+    /// Returns `None` if the baseline has no samples for it.
+    pub(crate) fn compare(&self, bench: &str, samples: &[f64]) -> Option<Comparison<'_>> {
+        let baseline = self.samples.get(bench)?;
+        compare_samples(baseline, samples).map(|change| Comparison { baseline: &self.name, change })
+    }
+}
+
+/// Whether a benchmark changed significantly compared to a baseline.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Verdict {
+    Regressed,
+    Improved,
+    NoChange,
+}
+
+impl Verdict {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            Verdict::Regressed => "regressed",
+            Verdict::Improved => "improved",
+            Verdict::NoChange => "no change",
+        }
+    }
+}
+
+/// The relative change of the mean time per iteration of a benchmark.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Change {
+    /// `(new - old) / old`, positive if the benchmark got slower.
+    pub estimate: f64,
+    /// Bounds of the 95% confidence interval of `estimate`.
+    pub lower: f64,
+    pub upper: f64,
+    pub verdict: Verdict,
+}
+
+pub(crate) struct Comparison<'a> {
+    pub baseline: &'a str,
+    pub change: Change,
+}
+
+impl fmt::Display for Comparison<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Change { estimate, lower, upper, verdict } = self.change;
+        write!(
+            f,
+            "{:+.2}% [{:+.2}% {:+.2}%] vs {}: {}",
+            estimate * 100.0,
+            lower * 100.0,
+            upper * 100.0,
+            self.baseline,
+            verdict.as_str()
+        )
+    }
+}
+
+/// Estimates the relative change between the means of two sample sets with
+/// Welch's t-test, which doesn't assume both sets have the same variance.
+///
+/// A change is only significant if its confidence interval excludes zero and
+/// the estimate is larger than `NOISE_THRESHOLD`.
+pub(crate) fn compare_samples(old: &[f64], new: &[f64]) -> Option<Change> {
+    if old.len() < 2 || new.len() < 2 {
+        return None;
+    }
+    let old_mean = old.mean();
+    if old_mean <= 0.0 {
+        return None;
+    }
+
+    let old_var = old.var() / old.len() as f64;
+    let new_var = new.var() / new.len() as f64;
+    let std_err = (old_var + new_var).sqrt();
+    // Welch–Satterthwaite degrees of freedom, infinite if neither set varies.
+    let df = (old_var + new_var).powi(2)
+        / (old_var.powi(2) / (old.len() - 1) as f64 + new_var.powi(2) / (new.len() - 1) as f64);
+
+    let diff = new.mean() - old_mean;
+    let margin = t_critical_95(df) * std_err;
+    let estimate = diff / old_mean;
+    let lower = (diff - margin) / old_mean;
+    let upper = (diff + margin) / old_mean;
+
+    let verdict = if lower > 0.0 && estimate > NOISE_THRESHOLD {
+        Verdict::Regressed
+    } else if upper < 0.0 && estimate < -NOISE_THRESHOLD {
+        Verdict::Improved
+    } else {
+        Verdict::NoChange
+    };
+    Some(Change { estimate, lower, upper, verdict })
+}
+
+/// Approximates the two-sided 95% quantile of Student's t-distribution with
+/// `df` degrees of freedom, using the Cornish-Fisher expansion around the
+/// normal quantile. Accurate to about 1% for `df >= 5`.
+fn t_critical_95(df: f64) -> f64 {
+    if !df.is_finite() {
+        return Z_95;
+    }
+    let z = Z_95;
+    let z3 = z.powi(3);
+    let z5 = z.powi(5);
+    z + (z3 + z) / (4.0 * df) + (5.0 * z5 + 16.0 * z3 + 3.0 * z) / (96.0 * df * df)
+}
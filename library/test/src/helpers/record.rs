@@ -0,0 +1,98 @@
+//! Records of test runs (`--record` and `--rerun-failed`).
+//!
+//! A record has one line per test that was run, with the same shape as the
+//! test events of the JSON output format:
+//!
+//! ```text
+//! { "type": "test", "name": "tests::foo", "event": "failed" }
+//! ```
+//!
+//! Reading a record only looks at these lines, so the output of
+//! `--format json` can be used in place of a record as well.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::{fs, io};
+
+use crate::formatters::EscapedString;
+use crate::test_result::TestResult;
+
+/// The line recorded for a test that finished with `result`.
+pub(crate) fn record_line(name: &str, result: &TestResult) -> String {
+    let event = match *result {
+        TestResult::TrOk | TestResult::TrBench(_) => "ok",
+        TestResult::TrFlaky(_) => "flaky",
+        TestResult::TrIgnored => "ignored",
+        TestResult::TrFailed
+        | TestResult::TrFailedMsg(_)
+        | TestResult::TrTimedFail
+        | TestResult::TrTimedOut(_) => "failed",
+    };
+    format!(r#"{{ "type": "test", "name": "{}", "event": "{event}" }}"#, EscapedString(name))
+}
+
+/// Reads the sorted names of the tests that failed according to the record
+/// at `path`. A test that was recorded several times counts as failed if its
+/// last result was a failure.
+pub(crate) fn failed_tests(path: &Path) -> io::Result<Vec<String>> {
+    let contents = fs::read_to_string(path)?;
+
+    let mut failed = BTreeMap::new();
+    for (i, line) in contents.lines().enumerate() {
+        let Some(rest) = line.strip_prefix(r#"{ "type": "test", "name": ""#) else {
+            // Suite events and the other events of `--format json`.
+            continue;
+        };
+        let (name, event) = parse_name_and_event(rest).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid test record {} (line {})", path.display(), i + 1),
+            )
+        })?;
+        failed.insert(name, event == "failed");
+    }
+
+    Ok(failed.into_iter().filter(|&(_, failed)| failed).map(|(name, _)| name).collect())
+}
+
+/// Parses the rest of a test event, after the opening quote of its name.
+fn parse_name_and_event(s: &str) -> Option<(String, String)> {
+    let (name, rest) = parse_string(s)?;
+    let rest = rest.strip_prefix(r#", "event": ""#)?;
+    let (event, _) = parse_string(rest)?;
+    Some((name, event))
+}
+
+/// Parses the contents of a JSON string up to its closing quote, undoing the
+/// escaping of `EscapedString`. Returns the string and what follows it.
+fn parse_string(s: &str) -> Option<(String, &str)> {
+    let mut parsed = String::new();
+    let mut chars = s.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Some((parsed, &s[i + 1..])),
+            '\\' => {
+                let unescaped = match chars.next()?.1 {
+                    '"' => '"',
+                    '\\' => '\\',
+                    '/' => '/',
+                    'b' => '\x08',
+                    'f' => '\x0c',
+                    'n' => '\n',
+                    'r' => '\r',
+                    't' => '\t',
+                    'u' => {
+                        let hex = (0..4)
+                            .map(|_| chars.next().map(|(_, c)| c))
+                            .collect::<Option<String>>()?;
+                        char::from_u32(u32::from_str_radix(&hex, 16).ok()?)?
+                    }
+                    _ => return None,
+                };
+                parsed.push(unescaped);
+            }
+            c => parsed.push(c),
+        }
+    }
+    None
+}
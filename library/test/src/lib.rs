@@ -35,7 +35,7 @@
 pub use self::ColorConfig::*;
 pub use self::bench::{Bencher, black_box};
 pub use self::console::run_tests_console;
-pub use self::options::{ColorConfig, Options, OutputFormat, RunIgnored, ShouldPanic};
+pub use self::options::{ColorConfig, Options, OutputFormat, RunIgnored, ShouldPanic, TestShard};
 pub use self::types::TestName::*;
 pub use self::types::*;
 
@@ -45,7 +45,10 @@ pub mod test {
     pub use crate::cli::{TestOpts, parse_opts};
     pub use crate::helpers::metrics::{Metric, MetricMap};
     pub use crate::options::{Options, RunIgnored, RunStrategy, ShouldPanic};
-    pub use crate::test_result::{TestResult, TrFailed, TrFailedMsg, TrIgnored, TrOk};
+    pub use crate::resources::TestResourceUsage;
+    pub use crate::test_result::{
+        FailedAttempt, TestResult, TrFailed, TrFailedMsg, TrIgnored, TrOk,
+    };
     pub use crate::time::{TestExecTime, TestTimeOptions};
     pub use crate::types::{
         DynTestFn, DynTestName, StaticBenchFn, StaticTestFn, StaticTestName, TestDesc,
@@ -71,6 +74,7 @@ pub mod test {
 mod formatters;
 mod helpers;
 mod options;
+mod resources;
 pub mod stats;
 mod term;
 mod test_result;
@@ -348,6 +352,77 @@ struct TimeoutEntry {
         timeout: Instant,
     }
 
+    // A running in-process test with a hard time limit. Tests run in a
+    // subprocess enforce their own limit by killing the child process.
+    struct DeadlineEntry {
+        id: TestId,
+        desc: TestDesc,
+        limit: Duration,
+        deadline: Instant,
+    }
+
+    // Tests that are run again after failing, see `--retries`.
+    struct Retries {
+        max_retries: usize,
+        // Copies of the test functions of running tests that can be retried.
+        testfns: HashMap<TestId, TestFn, BuildHasherDefault<DefaultHasher>>,
+        // The failed attempts of retried tests, keyed by the id of their
+        // current attempt. Every attempt gets a fresh id so that a late result
+        // from an abandoned attempt can't be mistaken for the result of the
+        // next one.
+        failed_attempts: HashMap<TestId, Vec<FailedAttempt>, BuildHasherDefault<DefaultHasher>>,
+        next_id: usize,
+    }
+
+    impl Retries {
+        fn is_retry(&self, id: TestId) -> bool {
+            self.failed_attempts.contains_key(&id)
+        }
+
+        fn on_test_start(&mut self, id: TestId, testfn: &TestFn) {
+            if self.max_retries > 0 {
+                if let Some(testfn) = testfn.try_clone() {
+                    self.testfns.insert(id, testfn);
+                }
+            }
+        }
+
+        // Queues a failed test to be run again, in which case `None` is
+        // returned. Otherwise returns the result to report, which is
+        // `TrFlaky` for a retried test that passed.
+        fn on_test_complete(
+            &mut self,
+            mut completed_test: CompletedTest,
+            remaining: &mut VecDeque<(TestId, TestDescAndFn)>,
+        ) -> Option<CompletedTest> {
+            let testfn = self.testfns.remove(&completed_test.id);
+            let mut failed_attempts =
+                self.failed_attempts.remove(&completed_test.id).unwrap_or_default();
+            match completed_test.result {
+                TrFailed | TrFailedMsg(_) | TrTimedFail | TrTimedOut(_) => match testfn {
+                    Some(testfn) if failed_attempts.len() < self.max_retries => {
+                        let id = TestId(self.next_id);
+                        self.next_id += 1;
+                        let desc = completed_test.desc;
+                        failed_attempts.push(FailedAttempt {
+                            result: completed_test.result,
+                            stdout: completed_test.stdout,
+                        });
+                        self.failed_attempts.insert(id, failed_attempts);
+                        remaining.push_front((id, TestDescAndFn { desc, testfn }));
+                        None
+                    }
+                    _ => Some(completed_test),
+                },
+                TrOk if !failed_attempts.is_empty() => {
+                    completed_test.result = TrFlaky(failed_attempts);
+                    Some(completed_test)
+                }
+                TrOk | TrIgnored | TrBench(_) | TrFlaky(_) => Some(completed_test),
+            }
+        }
+    }
+
     let tests_len = tests.tests.len();
 
     let mut filtered = FilteredTests { tests: Vec::new(), benches: Vec::new(), next_id: 0 };
@@ -398,8 +473,26 @@ struct TimeoutEntry {
         RunStrategy::InProcess
     };
 
+    // Only in-process tests are abandoned by the runner when they exceed their
+    // time limit, see `DeadlineEntry`.
+    let in_process_time_limit = |desc: &TestDesc| match run_strategy {
+        RunStrategy::InProcess => time::get_test_time_limit(desc, opts.test_timeout),
+        RunStrategy::SpawnPrimary => None,
+    };
+    // Set once a timed out test has been abandoned. Its thread may still try to
+    // report a result later on, so the receiving end must outlive the run.
+    let mut abandoned_tests = false;
+
+    let mut retries = Retries {
+        max_retries: opts.retries,
+        testfns: HashMap::default(),
+        failed_attempts: HashMap::default(),
+        next_id: filtered.next_id,
+    };
+
     let mut running_tests: TestMap = HashMap::default();
     let mut timeout_queue: VecDeque<TimeoutEntry> = VecDeque::new();
+    let mut deadline_queue: Vec<DeadlineEntry> = Vec::new();
 
     fn get_timed_out_tests(
         running_tests: &TestMap,
@@ -426,75 +519,185 @@ fn calc_timeout(timeout_queue: &VecDeque<TimeoutEntry>) -> Option<Duration> {
         })
     }
 
+    fn get_expired_tests(
+        running_tests: &mut TestMap,
+        deadline_queue: &mut Vec<DeadlineEntry>,
+        report_time: bool,
+    ) -> Vec<CompletedTest> {
+        let now = Instant::now();
+        let mut expired = Vec::new();
+        deadline_queue.retain(|entry| {
+            if !running_tests.contains_key(&entry.id) {
+                return false;
+            }
+            if now < entry.deadline {
+                return true;
+            }
+            // Abandon the test: its thread is detached rather than joined.
+            running_tests.remove(&entry.id);
+            expired.push(timed_out_test(entry.id, entry.desc.clone(), entry.limit, report_time));
+            false
+        });
+        expired
+    }
+
+    fn calc_deadline_timeout(deadline_queue: &[DeadlineEntry]) -> Option<Duration> {
+        deadline_queue
+            .iter()
+            .map(|entry| entry.deadline.saturating_duration_since(Instant::now()))
+            .min()
+    }
+
+    fn timed_out_test(
+        id: TestId,
+        desc: TestDesc,
+        limit: Duration,
+        report_time: bool,
+    ) -> CompletedTest {
+        let exec_time = report_time.then(|| TestExecTime(limit));
+        CompletedTest::new(id, desc, TrTimedOut(limit), exec_time, Vec::new())
+    }
+
     if concurrency == 1 {
         while !remaining.is_empty() {
             let (id, test) = remaining.pop_front().unwrap();
-            let event = TestEvent::TeWait(test.desc.clone());
-            notify_about_test_event(event)?;
+            let desc = test.desc.clone();
+            let time_limit = in_process_time_limit(&desc);
+            if !retries.is_retry(id) {
+                let event = TestEvent::TeWait(desc.clone());
+                notify_about_test_event(event)?;
+            }
+            retries.on_test_start(id, &test.testfn);
             let join_handle = run_test(opts, !opts.run_tests, id, test, run_strategy, tx.clone());
+            let deadline = time_limit.map(|limit| Instant::now() + limit);
             // Wait for the test to complete.
-            let mut completed_test = rx.recv().unwrap();
-            RunningTest { join_handle }.join(&mut completed_test);
+            let completed_test = loop {
+                let res = match deadline {
+                    Some(deadline) => {
+                        rx.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                    }
+                    None => rx.recv().map_err(|_| RecvTimeoutError::Disconnected),
+                };
+                match res {
+                    Ok(mut completed_test) if completed_test.id == id => {
+                        RunningTest { join_handle }.join(&mut completed_test);
+                        break completed_test;
+                    }
+                    Ok(_) => {
+                        // A late result from a test that was abandoned after timing out.
+                    }
+                    Err(RecvTimeoutError::Timeout) => {
+                        abandoned_tests = true;
+                        let report_time = opts.time_options.is_some();
+                        break timed_out_test(id, desc, time_limit.unwrap(), report_time);
+                    }
+                    Err(e @ RecvTimeoutError::Disconnected) => {
+                        panic!("failed to receive test result: {e}")
+                    }
+                }
+            };
+            let Some(completed_test) = retries.on_test_complete(completed_test, &mut remaining)
+            else {
+                continue;
+            };
 
             let fail_fast = match completed_test.result {
-                TrIgnored | TrOk | TrBench(_) => false,
-                TrFailed | TrFailedMsg(_) | TrTimedFail => opts.fail_fast,
+                TrIgnored | TrOk | TrBench(_) | TrFlaky(_) => false,
+                TrFailed | TrFailedMsg(_) | TrTimedFail | TrTimedOut(_) => opts.fail_fast,
             };
 
             let event = TestEvent::TeResult(completed_test);
             notify_about_test_event(event)?;
 
             if fail_fast {
+                if abandoned_tests {
+                    // Prevent abandoned test threads from panicking
+                    std::mem::forget(rx);
+                }
                 return Ok(());
             }
         }
     } else {
+        let mut expired_tests: VecDeque<CompletedTest> = VecDeque::new();
         while pending > 0 || !remaining.is_empty() {
             while pending < concurrency && !remaining.is_empty() {
                 let (id, test) = remaining.pop_front().unwrap();
                 let timeout = time::get_default_test_timeout();
                 let desc = test.desc.clone();
+                let time_limit = in_process_time_limit(&desc);
 
-                let event = TestEvent::TeWait(desc.clone());
-                notify_about_test_event(event)?; //here no pad
+                if !retries.is_retry(id) {
+                    let event = TestEvent::TeWait(desc.clone());
+                    notify_about_test_event(event)?; //here no pad
+                }
+                retries.on_test_start(id, &test.testfn);
                 let join_handle =
                     run_test(opts, !opts.run_tests, id, test, run_strategy, tx.clone());
                 running_tests.insert(id, RunningTest { join_handle });
+                if let Some(limit) = time_limit {
+                    let deadline = Instant::now() + limit;
+                    deadline_queue.push(DeadlineEntry { id, desc: desc.clone(), limit, deadline });
+                }
                 timeout_queue.push_back(TimeoutEntry { id, desc, timeout });
                 pending += 1;
             }
 
-            let mut res;
-            loop {
-                if let Some(timeout) = calc_timeout(&timeout_queue) {
-                    res = rx.recv_timeout(timeout);
+            let mut completed_test = loop {
+                if let Some(completed_test) = expired_tests.pop_front() {
+                    break completed_test;
+                }
+
+                let timeout =
+                    [calc_timeout(&timeout_queue), calc_deadline_timeout(&deadline_queue)]
+                        .into_iter()
+                        .flatten()
+                        .min();
+                let res = if let Some(timeout) = timeout {
+                    let res = rx.recv_timeout(timeout);
                     for test in get_timed_out_tests(&running_tests, &mut timeout_queue) {
                         let event = TestEvent::TeTimeout(test);
                         notify_about_test_event(event)?;
                     }
-
-                    match res {
-                        Err(RecvTimeoutError::Timeout) => {
-                            // Result is not yet ready, continue waiting.
-                        }
-                        _ => {
-                            // We've got a result, stop the loop.
-                            break;
-                        }
+                    let report_time = opts.time_options.is_some();
+                    let expired =
+                        get_expired_tests(&mut running_tests, &mut deadline_queue, report_time);
+                    if !expired.is_empty() {
+                        abandoned_tests = true;
+                        expired_tests.extend(expired);
                     }
+                    res
                 } else {
-                    res = rx.recv().map_err(|_| RecvTimeoutError::Disconnected);
-                    break;
+                    rx.recv().map_err(|_| RecvTimeoutError::Disconnected)
+                };
+
+                match res {
+                    Ok(completed_test) if running_tests.contains_key(&completed_test.id) => {
+                        break completed_test;
+                    }
+                    Ok(_) => {
+                        // A late result from a test that was abandoned after timing out.
+                    }
+                    Err(RecvTimeoutError::Timeout) => {
+                        // Result is not yet ready, continue waiting.
+                    }
+                    Err(e @ RecvTimeoutError::Disconnected) => {
+                        panic!("failed to receive test result: {e}")
+                    }
                 }
-            }
+            };
 
-            let mut completed_test = res.unwrap();
-            let running_test = running_tests.remove(&completed_test.id).unwrap();
-            running_test.join(&mut completed_test);
+            if let Some(running_test) = running_tests.remove(&completed_test.id) {
+                running_test.join(&mut completed_test);
+            }
+            let Some(completed_test) = retries.on_test_complete(completed_test, &mut remaining)
+            else {
+                pending -= 1;
+                continue;
+            };
 
             let fail_fast = match completed_test.result {
-                TrIgnored | TrOk | TrBench(_) => false,
-                TrFailed | TrFailedMsg(_) | TrTimedFail => opts.fail_fast,
+                TrIgnored | TrOk | TrBench(_) | TrFlaky(_) => false,
+                TrFailed | TrFailedMsg(_) | TrTimedFail | TrTimedOut(_) => opts.fail_fast,
             };
 
             let event = TestEvent::TeResult(completed_test);
@@ -515,14 +718,25 @@ fn calc_timeout(timeout_queue: &VecDeque<TimeoutEntry>) -> Option<Duration> {
             let event = TestEvent::TeWait(b.desc.clone());
             notify_about_test_event(event)?;
             let join_handle = run_test(opts, false, id, b, run_strategy, tx.clone());
-            // Wait for the test to complete.
-            let mut completed_test = rx.recv().unwrap();
+            // Wait for the test to complete, skipping late results from tests
+            // that were abandoned after timing out.
+            let mut completed_test = loop {
+                let completed_test = rx.recv().unwrap();
+                if completed_test.id == id {
+                    break completed_test;
+                }
+            };
             RunningTest { join_handle }.join(&mut completed_test);
 
             let event = TestEvent::TeResult(completed_test);
             notify_about_test_event(event)?;
         }
     }
+
+    if abandoned_tests {
+        // Prevent abandoned test threads from panicking
+        std::mem::forget(rx);
+    }
     Ok(())
 }
 
@@ -570,6 +784,13 @@ pub fn filter_tests(opts: &TestOpts, tests: TestList) -> Vec<TestDescAndFn> {
         });
     }
 
+    // Only keep the tests that failed in the recorded run.
+    if let Some(ref failed) = opts.rerun_failed {
+        filtered.retain(|test| {
+            failed.binary_search_by(|name| name.as_str().cmp(test.desc.name.as_slice())).is_ok()
+        });
+    }
+
     // Excludes #[should_panic] tests
     if opts.exclude_should_panic {
         filtered.retain(|test| test.desc.should_panic == ShouldPanic::No);
@@ -587,9 +808,39 @@ pub fn filter_tests(opts: &TestOpts, tests: TestList) -> Vec<TestDescAndFn> {
         RunIgnored::No => {}
     }
 
+    // Only keep the tests assigned to this shard: once the remaining tests are
+    // sorted by name, the test at position `i` goes to shard `i % count`. This
+    // is done after the other filters so that the tests which actually run are
+    // spread evenly over the shards, which means every shard must be given the
+    // same filters.
+    if let Some(shard) = opts.shard {
+        filtered = filter_shard(filtered, order, shard);
+    }
+
     filtered
 }
 
+/// Keep the tests assigned to `shard`: the test at `position` in the list
+/// sorted by name is kept if `position % shard.count == shard.index`.
+fn filter_shard(
+    tests: Vec<TestDescAndFn>,
+    order: TestListOrder,
+    shard: TestShard,
+) -> Vec<TestDescAndFn> {
+    // `by_name[position]` is the index of the test at `position` once sorted.
+    let mut by_name: Vec<usize> = (0..tests.len()).collect();
+    if order == TestListOrder::Unsorted {
+        by_name.sort_by(|&a, &b| tests[a].desc.name.as_slice().cmp(tests[b].desc.name.as_slice()));
+    }
+
+    let mut keep = vec![false; tests.len()];
+    for (position, &index) in by_name.iter().enumerate() {
+        keep[index] = shard.contains(position);
+    }
+
+    tests.into_iter().zip(keep).filter_map(|(test, keep)| keep.then_some(test)).collect()
+}
+
 /// Extract tests whose names exactly match one of the given `filters`, using
 /// binary search on the (assumed sorted) test list.
 fn filter_exact_match(mut tests: Vec<TestDescAndFn>, filters: &[String]) -> Vec<TestDescAndFn> {
@@ -662,6 +913,8 @@ pub fn run_test(
             let name = desc.name.clone();
             let nocapture = opts.nocapture;
             let time_options = opts.time_options;
+            let time_limit = time::get_test_time_limit(&desc, opts.test_timeout);
+            let memory_limit = opts.memory_limit;
             let bench_benchmarks = opts.bench_benchmarks;
 
             let runtest = move || match strategy {
@@ -681,6 +934,8 @@ pub fn run_test(
                     time_options.is_some(),
                     monitor_ch,
                     time_options,
+                    time_limit,
+                    memory_limit,
                     bench_benchmarks,
                 ),
             };
@@ -782,9 +1037,11 @@ fn spawn_test_subprocess(
     report_time: bool,
     monitor_ch: Sender<CompletedTest>,
     time_opts: Option<time::TestTimeOptions>,
+    time_limit: Option<Duration>,
+    memory_limit: Option<u64>,
     bench_benchmarks: bool,
 ) {
-    let (result, test_output, exec_time) = (|| {
+    let (result, test_output, exec_time, resource_usage) = (|| {
         let args = env::args().collect::<Vec<_>>();
         let current_exe = &args[0];
 
@@ -796,16 +1053,22 @@ fn spawn_test_subprocess(
         if nocapture {
             command.stdout(process::Stdio::inherit());
             command.stderr(process::Stdio::inherit());
+        } else {
+            // This is already the default for `Command::output`, but not for
+            // `Command::spawn` which is used when there is a time limit.
+            command.stdout(process::Stdio::piped());
+            command.stderr(process::Stdio::piped());
         }
 
         let start = report_time.then(Instant::now);
-        let output = match command.output() {
-            Ok(out) => out,
-            Err(e) => {
-                let err = format!("Failed to spawn {} as child for test: {:?}", args[0], e);
-                return (TrFailed, err.into_bytes(), None);
-            }
-        };
+        let (output, timed_out, resource_usage) =
+            match output_with_time_limit(&mut command, time_limit) {
+                Ok(out) => out,
+                Err(e) => {
+                    let err = format!("Failed to spawn {} as child for test: {:?}", args[0], e);
+                    return (TrFailed, err.into_bytes(), None, None);
+                }
+            };
         let exec_time = start.map(|start| {
             let duration = start.elapsed();
             TestExecTime(duration)
@@ -816,15 +1079,83 @@ fn spawn_test_subprocess(
         formatters::write_stderr_delimiter(&mut test_output, &desc.name);
         test_output.extend_from_slice(&stderr);
 
-        let result =
-            get_result_from_exit_code(&desc, status, time_opts.as_ref(), exec_time.as_ref());
-        (result, test_output, exec_time)
+        let result = match time_limit {
+            Some(limit) if timed_out => TrTimedOut(limit),
+            _ => get_result_from_exit_code(&desc, status, time_opts.as_ref(), exec_time.as_ref()),
+        };
+        // Like exceeding the critical execution time, exceeding the memory
+        // limit only fails tests that would otherwise pass.
+        let result = match resources::check_memory_limit(resource_usage.as_ref(), memory_limit) {
+            Some(message) if result == TrOk => TrFailedMsg(message),
+            _ => result,
+        };
+        (result, test_output, exec_time, resource_usage)
     })();
 
-    let message = CompletedTest::new(id, desc, result, exec_time, test_output);
+    let mut message = CompletedTest::new(id, desc, result, exec_time, test_output);
+    message.resource_usage = resource_usage;
     monitor_ch.send(message).unwrap();
 }
 
+/// Like `Command::output`, but kills the child process once `limit` (if any)
+/// has elapsed. Returns the collected output along with whether the child had
+/// to be killed and the resources it used, where those can be measured.
+fn output_with_time_limit(
+    command: &mut Command,
+    limit: Option<Duration>,
+) -> io::Result<(process::Output, bool, Option<resources::TestResourceUsage>)> {
+    // How often to check whether the child process has exited.
+    const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+    fn read_pipe<R: io::Read + Send + 'static>(
+        pipe: Option<R>,
+    ) -> Option<thread::JoinHandle<io::Result<Vec<u8>>>> {
+        pipe.map(|mut pipe| {
+            thread::spawn(move || {
+                let mut buf = Vec::new();
+                pipe.read_to_end(&mut buf).map(|_| buf)
+            })
+        })
+    }
+
+    fn join_pipe(reader: Option<thread::JoinHandle<io::Result<Vec<u8>>>>) -> io::Result<Vec<u8>> {
+        match reader {
+            Some(reader) => reader.join().unwrap(),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    // Match the default of `Command::output`; the output streams are
+    // configured by the caller.
+    let mut child = command.stdin(process::Stdio::null()).spawn()?;
+
+    let stdout = read_pipe(child.stdout.take());
+    let stderr = read_pipe(child.stderr.take());
+
+    let ((status, usage), timed_out) = match limit {
+        Some(limit) => {
+            let deadline = Instant::now() + limit;
+            loop {
+                if let Some(status) = resources::try_wait(&mut child)? {
+                    break (status, false);
+                }
+                let now = Instant::now();
+                if now >= deadline {
+                    // The child may have exited in the meantime, which is fine.
+                    let _ = child.kill();
+                    break (resources::wait(&mut child)?, true);
+                }
+                thread::sleep(POLL_INTERVAL.min(deadline - now));
+            }
+        }
+        None => (resources::wait(&mut child)?, false),
+    };
+
+    let stdout = join_pipe(stdout)?;
+    let stderr = join_pipe(stderr)?;
+    Ok((process::Output { status, stdout, stderr }, timed_out, usage))
+}
+
 fn run_test_in_spawned_subprocess(desc: TestDesc, runnable_test: RunnableTest) -> ! {
     let builtin_panic_hook = panic::take_hook();
     let record_result = Arc::new(move |panic_info: Option<&'_ PanicHookInfo<'_>>| {
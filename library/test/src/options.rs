@@ -36,6 +36,8 @@ pub enum OutputFormat {
     Json,
     /// JUnit output
     Junit,
+    /// TAP (Test Anything Protocol) output
+    Tap,
 }
 
 /// Whether ignored test should be run or not
@@ -47,6 +49,27 @@ pub enum RunIgnored {
     Only,
 }
 
+/// Which part of the test list to run when it is split across several test
+/// runners, e.g. CI machines.
+///
+/// Tests are assigned to shards round-robin by their position in the list of
+/// filtered tests sorted by name, so every runner computes the same split no
+/// matter the order the tests were registered or shuffled in.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TestShard {
+    /// Zero-based index of this shard, always less than `count`.
+    pub index: usize,
+    /// Total number of shards.
+    pub count: usize,
+}
+
+impl TestShard {
+    /// Whether the test at `position` in the sorted test list belongs to this shard.
+    pub fn contains(&self, position: usize) -> bool {
+        position % self.count == self.index
+    }
+}
+
 #[derive(Clone, Copy)]
 pub enum RunStrategy {
     /// Runs the test in the current process, and sends the result back over the
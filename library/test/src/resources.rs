@@ -0,0 +1,143 @@
+//! Module `resources` contains everything related to measuring the resources
+//! (peak memory and CPU time) used by tests that run in a subprocess.
+//!
+//! The usage is only measured on Linux, where `wait4` reports it for the
+//! child process that is reaped. Tests that run in-process share the harness
+//! process, so their usage can't be told apart.
+
+use std::io;
+use std::process::{Child, ExitStatus};
+use std::time::Duration;
+
+/// The resources used by a test that ran in a subprocess.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TestResourceUsage {
+    /// The peak resident set size, in bytes.
+    pub max_rss: u64,
+    /// The CPU time spent in user mode.
+    pub user_time: Duration,
+    /// The CPU time spent in the kernel on behalf of the test.
+    pub system_time: Duration,
+}
+
+/// Formats a number of bytes with a binary unit, e.g. `12.5 MiB`.
+pub(crate) fn fmt_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = UNITS[0];
+    for next in &UNITS[1..] {
+        if value < 1024.0 {
+            break;
+        }
+        value /= 1024.0;
+        unit = next;
+    }
+    format!("{value:.1} {unit}")
+}
+
+/// Parses a memory limit given in bytes, optionally with a `K`, `M` or `G`
+/// suffix for KiB, MiB or GiB.
+pub(crate) fn parse_memory_limit(s: &str) -> Option<u64> {
+    let (digits, shift) = match s.as_bytes().last()? {
+        b'K' | b'k' => (&s[..s.len() - 1], 10),
+        b'M' | b'm' => (&s[..s.len() - 1], 20),
+        b'G' | b'g' => (&s[..s.len() - 1], 30),
+        _ => (s, 0),
+    };
+    digits.parse::<u64>().ok()?.checked_mul(1 << shift)
+}
+
+/// Returns the failure message for a test that used more memory than
+/// `memory_limit` (`--ensure-memory`), if it did.
+pub(crate) fn check_memory_limit(
+    usage: Option<&TestResourceUsage>,
+    memory_limit: Option<u64>,
+) -> Option<String> {
+    let (usage, limit) = (usage?, memory_limit?);
+    (usage.max_rss > limit).then(|| {
+        format!(
+            "peak memory usage of {} exceeded the limit of {}",
+            fmt_bytes(usage.max_rss),
+            fmt_bytes(limit)
+        )
+    })
+}
+
+/// Like `Child::try_wait`, but also returns the resources used by the child
+/// once it has exited.
+pub(crate) fn try_wait(
+    child: &mut Child,
+) -> io::Result<Option<(ExitStatus, Option<TestResourceUsage>)>> {
+    imp::wait(child, false)
+}
+
+/// Like `Child::wait`, but also returns the resources used by the child.
+pub(crate) fn wait(child: &mut Child) -> io::Result<(ExitStatus, Option<TestResourceUsage>)> {
+    imp::wait(child, true).map(|status| status.expect("blocking wait returned no status"))
+}
+
+#[cfg(target_os = "linux")]
+mod imp {
+    use std::io;
+    use std::os::unix::process::ExitStatusExt;
+    use std::process::{Child, ExitStatus};
+    use std::time::Duration;
+
+    use super::TestResourceUsage;
+
+    pub(super) fn wait(
+        child: &mut Child,
+        block: bool,
+    ) -> io::Result<Option<(ExitStatus, Option<TestResourceUsage>)>> {
+        let pid = child.id() as libc::pid_t;
+        let options = if block { 0 } else { libc::WNOHANG };
+        let mut status = 0;
+        // SAFETY: `rusage` is plain old data, for which all zeroes is valid.
+        let mut rusage: libc::rusage = unsafe { std::mem::zeroed() };
+        loop {
+            // SAFETY: `status` and `rusage` are valid for writes. The child
+            // is only reaped here, `Child` is never waited on afterwards.
+            match unsafe { libc::wait4(pid, &mut status, options, &mut rusage) } {
+                -1 => {
+                    let err = io::Error::last_os_error();
+                    if err.kind() != io::ErrorKind::Interrupted {
+                        return Err(err);
+                    }
+                }
+                0 => return Ok(None),
+                _ => break,
+            }
+        }
+
+        let timeval = |tv: libc::timeval| {
+            Duration::new(tv.tv_sec as u64, (tv.tv_usec as u32).saturating_mul(1000))
+        };
+        let usage = TestResourceUsage {
+            // `ru_maxrss` is in kilobytes on Linux.
+            max_rss: (rusage.ru_maxrss as u64).saturating_mul(1024),
+            user_time: timeval(rusage.ru_utime),
+            system_time: timeval(rusage.ru_stime),
+        };
+        Ok(Some((ExitStatus::from_raw(status), Some(usage))))
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod imp {
+    use std::io;
+    use std::process::{Child, ExitStatus};
+
+    use super::TestResourceUsage;
+
+    pub(super) fn wait(
+        child: &mut Child,
+        block: bool,
+    ) -> io::Result<Option<(ExitStatus, Option<TestResourceUsage>)>> {
+        let status = if block { Some(child.wait()?) } else { child.try_wait()? };
+        Ok(status.map(|status| (status, None)))
+    }
+}
@@ -2,6 +2,7 @@
 #[cfg(unix)]
 use std::os::unix::process::ExitStatusExt;
 use std::process::ExitStatus;
+use std::time::Duration;
 
 pub use self::TestResult::*;
 use super::bench::BenchSamples;
@@ -35,6 +36,21 @@ pub enum TestResult {
     TrIgnored,
     TrBench(BenchSamples),
     TrTimedFail,
+    /// The test did not finish within its hard time limit and was killed (or,
+    /// when running in-process, abandoned).
+    TrTimedOut(Duration),
+    /// The test failed, but passed when it was run again (see `--retries`).
+    /// Holds the failed attempts, in the order they were run.
+    TrFlaky(Vec<FailedAttempt>),
+}
+
+/// A failed attempt at running a test that is [`TrFlaky`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct FailedAttempt {
+    /// The failing result of the attempt.
+    pub result: TestResult,
+    /// The output captured while running the attempt.
+    pub stdout: Vec<u8>,
 }
 
 /// Creates a `TestResult` depending on the raw result of test execution
@@ -29,9 +29,17 @@ fn new() -> TestOpts {
             format: OutputFormat::Pretty,
             shuffle: false,
             shuffle_seed: None,
+            shard: None,
             test_threads: None,
             skip: vec![],
             time_options: None,
+            test_timeout: None,
+            memory_limit: None,
+            retries: 0,
+            save_baseline: None,
+            baseline: None,
+            record: None,
+            rerun_failed: None,
             options: Options::new(),
             fail_fast: false,
         }
@@ -51,6 +59,7 @@ fn one_ignored_one_unignored_test() -> Vec<TestDescAndFn> {
                 end_line: 0,
                 end_col: 0,
                 should_panic: ShouldPanic::No,
+                timeout_secs: None,
                 compile_fail: false,
                 no_run: false,
                 test_type: TestType::Unknown,
@@ -68,6 +77,7 @@ fn one_ignored_one_unignored_test() -> Vec<TestDescAndFn> {
                 end_line: 0,
                 end_col: 0,
                 should_panic: ShouldPanic::No,
+                timeout_secs: None,
                 compile_fail: false,
                 no_run: false,
                 test_type: TestType::Unknown,
@@ -93,6 +103,7 @@ fn f() -> Result<(), String> {
             end_line: 0,
             end_col: 0,
             should_panic: ShouldPanic::No,
+            timeout_secs: None,
             compile_fail: false,
             no_run: false,
             test_type: TestType::Unknown,
@@ -121,6 +132,7 @@ fn f() -> Result<(), String> {
             end_line: 0,
             end_col: 0,
             should_panic: ShouldPanic::No,
+            timeout_secs: None,
             compile_fail: false,
             no_run: false,
             test_type: TestType::Unknown,
@@ -150,6 +162,7 @@ fn f() -> Result<(), String> {
             end_line: 0,
             end_col: 0,
             should_panic: ShouldPanic::Yes,
+            timeout_secs: None,
             compile_fail: false,
             no_run: false,
             test_type: TestType::Unknown,
@@ -179,6 +192,7 @@ fn f() -> Result<(), String> {
             end_line: 0,
             end_col: 0,
             should_panic: ShouldPanic::YesWithMessage("error message"),
+            timeout_secs: None,
             compile_fail: false,
             no_run: false,
             test_type: TestType::Unknown,
@@ -213,6 +227,7 @@ fn f() -> Result<(), String> {
             end_line: 0,
             end_col: 0,
             should_panic: ShouldPanic::YesWithMessage(expected),
+            timeout_secs: None,
             compile_fail: false,
             no_run: false,
             test_type: TestType::Unknown,
@@ -252,6 +267,7 @@ fn f() -> Result<(), String> {
             end_line: 0,
             end_col: 0,
             should_panic: ShouldPanic::YesWithMessage(expected),
+            timeout_secs: None,
             compile_fail: false,
             no_run: false,
             test_type: TestType::Unknown,
@@ -284,6 +300,7 @@ fn f() -> Result<(), String> {
                 end_line: 0,
                 end_col: 0,
                 should_panic,
+                timeout_secs: None,
                 compile_fail: false,
                 no_run: false,
                 test_type: TestType::Unknown,
@@ -317,6 +334,7 @@ fn f() -> Result<(), String> {
             end_line: 0,
             end_col: 0,
             should_panic: ShouldPanic::No,
+            timeout_secs: None,
             compile_fail: false,
             no_run: false,
             test_type: TestType::Unknown,
@@ -359,6 +377,7 @@ fn f() -> Result<(), String> {
             end_line: 0,
             end_col: 0,
             should_panic: ShouldPanic::No,
+            timeout_secs: None,
             compile_fail: false,
             no_run: false,
             test_type,
@@ -403,6 +422,7 @@ fn typed_test_desc(test_type: TestType) -> TestDesc {
         end_line: 0,
         end_col: 0,
         should_panic: ShouldPanic::No,
+        timeout_secs: None,
         compile_fail: false,
         no_run: false,
         test_type,
@@ -520,6 +540,7 @@ fn exclude_should_panic_option() {
             end_line: 0,
             end_col: 0,
             should_panic: ShouldPanic::Yes,
+            timeout_secs: None,
             compile_fail: false,
             no_run: false,
             test_type: TestType::Unknown,
@@ -549,6 +570,7 @@ fn tests() -> TestList {
                     end_line: 0,
                     end_col: 0,
                     should_panic: ShouldPanic::No,
+                    timeout_secs: None,
                     compile_fail: false,
                     no_run: false,
                     test_type: TestType::Unknown,
@@ -646,6 +668,7 @@ fn testfn() -> Result<(), String> {
                 end_line: 0,
                 end_col: 0,
                 should_panic: ShouldPanic::No,
+                timeout_secs: None,
                 compile_fail: false,
                 no_run: false,
                 test_type: TestType::Unknown,
@@ -722,6 +745,73 @@ fn order_depends_on_more_than_seed() {
     assert!(left.iter().zip(right).any(|(a, b)| a.0 != b.0));
 }
 
+#[test]
+fn shards_split_filtered_tests() {
+    let tests = || TestList::new(sample_tests(), TestListOrder::Unsorted);
+    let count = 3;
+
+    let mut names = Vec::new();
+    for index in 0..count {
+        let opts = TestOpts { shard: Some(TestShard { index, count }), ..TestOpts::new() };
+        let shard = filter_tests(&opts, tests());
+        assert!(shard.len() == 3 || shard.len() == 4);
+        names.extend(shard.into_iter().map(|test| test.desc.name.to_string()));
+    }
+
+    // Every test is run by exactly one shard.
+    let mut expected: Vec<_> =
+        sample_tests().into_iter().map(|t| t.desc.name.to_string()).collect();
+    expected.sort();
+    names.sort();
+    assert_eq!(names, expected);
+}
+
+#[test]
+fn shards_do_not_depend_on_test_order() {
+    let shard = Some(TestShard { index: 1, count: 4 });
+    let opts = TestOpts { shard, ..TestOpts::new() };
+
+    let mut sorted = sample_tests();
+    sorted.sort_by(|a, b| a.desc.name.as_slice().cmp(b.desc.name.as_slice()));
+    let left = filter_tests(&opts, TestList::new(sorted, TestListOrder::Sorted));
+    let right = filter_tests(&opts, TestList::new(sample_tests(), TestListOrder::Unsorted));
+
+    let mut left: Vec<_> = left.into_iter().map(|t| t.desc.name.to_string()).collect();
+    let mut right: Vec<_> = right.into_iter().map(|t| t.desc.name.to_string()).collect();
+    left.sort();
+    right.sort();
+    assert_eq!(
+        left,
+        [
+            "isize::test_to_str",
+            "test::first_free_arg_should_be_a_filter",
+            "test::run_include_ignored_option"
+        ]
+    );
+    assert_eq!(left, right);
+}
+
+#[test]
+fn shards_are_applied_after_filters() {
+    let opts = TestOpts {
+        filters: vec!["test::".into()],
+        shard: Some(TestShard { index: 0, count: 2 }),
+        ..TestOpts::new()
+    };
+    let shard = filter_tests(&opts, TestList::new(sample_tests(), TestListOrder::Unsorted));
+
+    let names: Vec<_> = shard.into_iter().map(|t| t.desc.name.to_string()).collect();
+    assert_eq!(
+        names,
+        [
+            "test::do_not_run_ignored_tests",
+            "test::first_free_arg_should_be_a_filter",
+            "test::parse_ignored_flag",
+            "test::run_include_ignored_option",
+        ]
+    );
+}
+
 #[test]
 fn test_metricmap_compare() {
     let mut m1 = MetricMap::new();
@@ -780,6 +870,7 @@ fn f(_: &mut Bencher) -> Result<(), String> {
         end_line: 0,
         end_col: 0,
         should_panic: ShouldPanic::No,
+        timeout_secs: None,
         compile_fail: false,
         no_run: false,
         test_type: TestType::Unknown,
@@ -808,6 +899,7 @@ fn f(b: &mut Bencher) -> Result<(), String> {
         end_line: 0,
         end_col: 0,
         should_panic: ShouldPanic::No,
+        timeout_secs: None,
         compile_fail: false,
         no_run: false,
         test_type: TestType::Unknown,
@@ -829,6 +921,7 @@ fn should_sort_failures_before_printing_them() {
         end_line: 0,
         end_col: 0,
         should_panic: ShouldPanic::No,
+        timeout_secs: None,
         compile_fail: false,
         no_run: false,
         test_type: TestType::Unknown,
@@ -844,6 +937,7 @@ fn should_sort_failures_before_printing_them() {
         end_line: 0,
         end_col: 0,
         should_panic: ShouldPanic::No,
+        timeout_secs: None,
         compile_fail: false,
         no_run: false,
         test_type: TestType::Unknown,
@@ -855,6 +949,7 @@ fn should_sort_failures_before_printing_them() {
         log_out: None,
         total: 0,
         passed: 0,
+        flaky: 0,
         failed: 0,
         ignored: 0,
         filtered_out: 0,
@@ -866,6 +961,10 @@ fn should_sort_failures_before_printing_them() {
         not_failures: Vec::new(),
         ignores: Vec::new(),
         time_failures: Vec::new(),
+        flaky_tests: Vec::new(),
+        bench_samples: Vec::new(),
+        baseline: None,
+        record_out: None,
     };
 
     out.write_failures(&st).unwrap();
@@ -896,6 +995,7 @@ fn f(_: &mut Bencher) -> Result<(), String> {
             end_line: 0,
             end_col: 0,
             should_panic: ShouldPanic::No,
+            timeout_secs: None,
             compile_fail: false,
             no_run: false,
             test_type: TestType::Unknown,
@@ -914,3 +1014,245 @@ fn f(_: &mut Bencher) -> Result<(), String> {
     let result = rx.recv().unwrap().result;
     assert_eq!(result, TrFailed);
 }
+
+fn hanging_and_passing_tests(timeout_secs: Option<u64>) -> TestList {
+    let desc = |name| TestDesc {
+        name: StaticTestName(name),
+        ignore: false,
+        ignore_message: None,
+        source_file: "",
+        start_line: 0,
+        start_col: 0,
+        end_line: 0,
+        end_col: 0,
+        should_panic: ShouldPanic::No,
+        timeout_secs,
+        compile_fail: false,
+        no_run: false,
+        test_type: TestType::Unknown,
+    };
+    let tests = vec![
+        TestDescAndFn {
+            desc: desc("hangs"),
+            testfn: DynTestFn(Box::new(|| {
+                thread::sleep(Duration::from_secs(60));
+                Ok(())
+            })),
+        },
+        TestDescAndFn { desc: desc("passes"), testfn: DynTestFn(Box::new(|| Ok(()))) },
+    ];
+    TestList::new(tests, TestListOrder::Sorted)
+}
+
+fn run_tests_and_collect_results(opts: &TestOpts, tests: TestList) -> Vec<(String, TestResult)> {
+    let mut results = Vec::new();
+    let notify = |event: TestEvent| {
+        if let TestEvent::TeResult(completed_test) = event {
+            results.push((completed_test.desc.name.to_string(), completed_test.result));
+        }
+        Ok(())
+    };
+    run_tests(opts, tests, notify).unwrap();
+    results.sort_by(|a, b| a.0.cmp(&b.0));
+    results
+}
+
+#[test]
+#[cfg(not(target_os = "emscripten"))]
+fn test_timeout_attribute_fails_hung_test() {
+    for test_threads in [1, 2] {
+        let opts =
+            TestOpts { run_tests: true, test_threads: Some(test_threads), ..TestOpts::new() };
+        let results = run_tests_and_collect_results(&opts, hanging_and_passing_tests(Some(1)));
+        assert_eq!(
+            results,
+            [
+                ("hangs".to_string(), TrTimedOut(Duration::from_secs(1))),
+                ("passes".to_string(), TrOk),
+            ]
+        );
+    }
+}
+
+#[test]
+#[cfg(not(target_os = "emscripten"))]
+fn test_timeout_option_fails_hung_test() {
+    let opts = TestOpts {
+        run_tests: true,
+        test_threads: Some(2),
+        test_timeout: Some(Duration::from_secs(1)),
+        ..TestOpts::new()
+    };
+    let results = run_tests_and_collect_results(&opts, hanging_and_passing_tests(None));
+    assert_eq!(
+        results,
+        [("hangs".to_string(), TrTimedOut(Duration::from_secs(1))), ("passes".to_string(), TrOk)]
+    );
+}
+
+#[test]
+fn test_timeout_attribute_overrides_option() {
+    let mut desc = typed_test_desc(TestType::UnitTest);
+    let option = Some(Duration::from_secs(10));
+    assert_eq!(time::get_test_time_limit(&desc, None), None);
+    assert_eq!(time::get_test_time_limit(&desc, option), option);
+
+    desc.timeout_secs = Some(3);
+    assert_eq!(time::get_test_time_limit(&desc, None), Some(Duration::from_secs(3)));
+    assert_eq!(time::get_test_time_limit(&desc, option), Some(Duration::from_secs(3)));
+}
+
+fn retried_tests(fails_once: fn() -> Result<(), String>) -> TestList {
+    let desc = |name| TestDesc { name: StaticTestName(name), ..typed_test_desc(TestType::Unknown) };
+    let tests = vec![
+        TestDescAndFn { desc: desc("always_fails"), testfn: StaticTestFn(|| Err("no".into())) },
+        TestDescAndFn {
+            desc: desc("dyn_fails"),
+            testfn: DynTestFn(Box::new(|| panic!("dynamic tests can't be retried"))),
+        },
+        TestDescAndFn { desc: desc("fails_once"), testfn: StaticTestFn(fails_once) },
+    ];
+    TestList::new(tests, TestListOrder::Sorted)
+}
+
+#[test]
+fn retries_report_flaky_tests() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    for test_threads in [1, 2] {
+        static ATTEMPTS: AtomicUsize = AtomicUsize::new(0);
+        ATTEMPTS.store(0, Ordering::SeqCst);
+        fn fails_once() -> Result<(), String> {
+            match ATTEMPTS.fetch_add(1, Ordering::SeqCst) {
+                0 => Err("first attempt".into()),
+                _ => Ok(()),
+            }
+        }
+
+        let opts = TestOpts {
+            run_tests: true,
+            test_threads: Some(test_threads),
+            retries: 2,
+            ..TestOpts::new()
+        };
+        let results = run_tests_and_collect_results(&opts, retried_tests(fails_once));
+
+        assert_eq!(ATTEMPTS.load(Ordering::SeqCst), 2);
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0], ("always_fails".to_string(), TrFailed));
+        assert_eq!(results[1], ("dyn_fails".to_string(), TrFailed));
+        assert_eq!(results[2].0, "fails_once");
+        let TrFlaky(ref attempts) = results[2].1 else {
+            panic!("expected a flaky result, got {:?}", results[2].1)
+        };
+        assert_eq!(attempts.len(), 1);
+        assert_eq!(attempts[0].result, TrFailed);
+    }
+}
+
+#[test]
+fn failing_tests_are_not_retried_by_default() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static ATTEMPTS: AtomicUsize = AtomicUsize::new(0);
+    fn fails_once() -> Result<(), String> {
+        match ATTEMPTS.fetch_add(1, Ordering::SeqCst) {
+            0 => Err("first attempt".into()),
+            _ => Ok(()),
+        }
+    }
+
+    let opts = TestOpts { run_tests: true, test_threads: Some(1), ..TestOpts::new() };
+    let results = run_tests_and_collect_results(&opts, retried_tests(fails_once));
+
+    assert_eq!(ATTEMPTS.load(Ordering::SeqCst), 1);
+    assert_eq!(results[2], ("fails_once".to_string(), TrFailed));
+}
+
+#[test]
+fn baseline_comparison_verdicts() {
+    use crate::helpers::baseline::{Verdict, compare_samples};
+
+    let old: Vec<f64> = (0..50).map(|i| 1000.0 + (i % 10) as f64).collect();
+    let slower: Vec<f64> = old.iter().map(|s| s * 1.1).collect();
+    let faster: Vec<f64> = old.iter().map(|s| s * 0.9).collect();
+    let noisy: Vec<f64> = old.iter().enumerate().map(|(i, s)| s * (1.0 + (i % 2) as f64)).collect();
+
+    let change = compare_samples(&old, &slower).unwrap();
+    assert_eq!(change.verdict, Verdict::Regressed);
+    assert!((change.estimate - 0.1).abs() < 1e-9);
+    assert!(change.lower < change.estimate && change.estimate < change.upper);
+    assert!(change.lower > 0.0);
+
+    let change = compare_samples(&old, &faster).unwrap();
+    assert_eq!(change.verdict, Verdict::Improved);
+    assert!(change.upper < 0.0);
+
+    assert_eq!(compare_samples(&old, &old).unwrap().verdict, Verdict::NoChange);
+    // A large but noisy difference is not significant.
+    assert_eq!(compare_samples(&old[..3], &noisy[..3]).unwrap().verdict, Verdict::NoChange);
+    // A significant but tiny difference is within the noise threshold.
+    let tiny: Vec<f64> = old.iter().map(|s| s + 1.0).collect();
+    assert_eq!(compare_samples(&old, &tiny).unwrap().verdict, Verdict::NoChange);
+
+    assert_eq!(compare_samples(&old[..1], &slower), None);
+}
+
+#[test]
+fn rerun_failed_reads_record_and_json_output() {
+    use crate::helpers::record::{failed_tests, record_line};
+
+    let path = env::temp_dir().join(format!("libtest-record-{}", process::id()));
+    let record = [
+        record_line("test::parse_ignored_flag", &TrOk),
+        record_line("test::do_not_run_ignored_tests", &TrFailed),
+        record_line("test::ignored_tests_result_in_ignored", &TrIgnored),
+        record_line("a::\"quoted\"\tname", &TrFailedMsg("boom".to_string())),
+        record_line("isize::test_to_str", &TrFailed),
+        // Lines written by `--format json` work as well.
+        r#"{ "type": "suite", "event": "started", "test_count": 2 }"#.to_string(),
+        r#"{ "type": "test", "event": "started", "name": "isize::test_to_str" }"#.to_string(),
+        r#"{ "type": "test", "name": "isize::test_to_str", "event": "ok", "exec_time": 0.1 }"#
+            .to_string(),
+        r#"{ "type": "test", "name": "sha1::test", "event": "failed", "reason": "timed out" }"#
+            .to_string(),
+    ];
+    std::fs::write(&path, record.join("\n")).unwrap();
+    let failed = failed_tests(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(failed, ["a::\"quoted\"\tname", "sha1::test", "test::do_not_run_ignored_tests"]);
+
+    let opts = TestOpts { rerun_failed: Some(failed), ..TestOpts::new() };
+    let filtered = filter_tests(&opts, TestList::new(sample_tests(), TestListOrder::Unsorted));
+    let mut names: Vec<_> = filtered.into_iter().map(|t| t.desc.name.to_string()).collect();
+    names.sort();
+    assert_eq!(names, ["sha1::test", "test::do_not_run_ignored_tests"]);
+}
+
+#[test]
+fn memory_limit_parsing_and_checking() {
+    use crate::resources::{TestResourceUsage, check_memory_limit, parse_memory_limit};
+
+    assert_eq!(parse_memory_limit("1000"), Some(1000));
+    assert_eq!(parse_memory_limit("4K"), Some(4 << 10));
+    assert_eq!(parse_memory_limit("512m"), Some(512 << 20));
+    assert_eq!(parse_memory_limit("2G"), Some(2 << 30));
+    assert_eq!(parse_memory_limit(""), None);
+    assert_eq!(parse_memory_limit("M"), None);
+    assert_eq!(parse_memory_limit("1.5G"), None);
+    assert_eq!(parse_memory_limit("99999999999999999999G"), None);
+
+    let usage = TestResourceUsage {
+        max_rss: 3 << 20,
+        user_time: Duration::from_millis(10),
+        system_time: Duration::ZERO,
+    };
+    assert_eq!(check_memory_limit(Some(&usage), Some(4 << 20)), None);
+    assert_eq!(check_memory_limit(Some(&usage), None), None);
+    assert_eq!(check_memory_limit(None, Some(1)), None);
+    assert_eq!(
+        check_memory_limit(Some(&usage), Some(2 << 20)).as_deref(),
+        Some("peak memory usage of 3.0 MiB exceeded the limit of 2.0 MiB")
+    );
+}
@@ -2,6 +2,7 @@
 //! execution.
 //! The purposes of this module:
 //! - Check whether test is timed out.
+//! - Determine the hard time limit of a test, if any.
 //! - Provide helpers for `report-time` and `measure-time` options.
 //! - Provide newtypes for executions times.
 
@@ -61,6 +62,15 @@ pub(crate) fn get_default_test_timeout() -> Instant {
     Instant::now() + Duration::from_secs(TEST_WARN_TIMEOUT_S)
 }
 
+/// Returns the hard time limit for the test, if any. A limit set on the test
+/// itself with `#[test_timeout]` takes precedence over the `--test-timeout` option.
+pub(crate) fn get_test_time_limit(
+    desc: &TestDesc,
+    test_timeout: Option<Duration>,
+) -> Option<Duration> {
+    desc.timeout_secs.map(Duration::from_secs).or(test_timeout)
+}
+
 /// The measured execution time of a unit test.
 #[derive(Debug, Clone, PartialEq)]
 pub struct TestExecTime(pub Duration);
@@ -102,6 +102,16 @@ pub fn padding(&self) -> NamePadding {
         }
     }
 
+    /// Returns a copy of the test function, unless it can only be run once.
+    pub(crate) fn try_clone(&self) -> Option<TestFn> {
+        match *self {
+            StaticTestFn(f) => Some(StaticTestFn(f)),
+            StaticBenchFn(f) => Some(StaticBenchFn(f)),
+            StaticBenchAsTestFn(f) => Some(StaticBenchAsTestFn(f)),
+            DynTestFn(..) | DynBenchFn(..) | DynBenchAsTestFn(..) => None,
+        }
+    }
+
     pub(crate) fn into_runnable(self) -> Runnable {
         match self {
             StaticTestFn(f) => Runnable::Test(RunnableTest::Static(f)),
@@ -204,6 +214,9 @@ pub struct TestDesc {
     pub end_line: usize,
     pub end_col: usize,
     pub should_panic: options::ShouldPanic,
+    /// Hard time limit in seconds set with `#[test_timeout]`, which takes
+    /// precedence over the `--test-timeout` option.
+    pub timeout_secs: Option<u64>,
     pub compile_fail: bool,
     pub no_run: bool,
     pub test_type: TestType,
@@ -278,6 +291,7 @@ pub const fn new_doctest(
                 } else {
                     options::ShouldPanic::No
                 },
+                timeout_secs: None,
                 test_type: TestType::DocTest,
             },
             testfn,
@@ -25,7 +25,7 @@
 use crate::html::markdown::IdMap;
 use crate::html::render::StylePath;
 use crate::html::static_files;
-use crate::passes::{self, Condition};
+use crate::passes::{self, Condition, CoverageOptions};
 use crate::scrape_examples::{AllCallLocations, ScrapeExamplesOptions};
 use crate::{html, opts, theme};
 
@@ -35,11 +35,15 @@ pub(crate) enum OutputFormat {
     #[default]
     Html,
     Doctest,
+    /// Compares the JSON output against a baseline, see `--baseline`.
+    ApiDiff,
+    Markdown,
+    Man,
 }
 
 impl OutputFormat {
     pub(crate) fn is_json(&self) -> bool {
-        matches!(self, OutputFormat::Json)
+        matches!(self, OutputFormat::Json | OutputFormat::ApiDiff)
     }
 }
 
@@ -51,6 +55,9 @@ fn try_from(value: &str) -> Result<Self, Self::Error> {
             "json" => Ok(OutputFormat::Json),
             "html" => Ok(OutputFormat::Html),
             "doctest" => Ok(OutputFormat::Doctest),
+            "api-diff" => Ok(OutputFormat::ApiDiff),
+            "markdown" => Ok(OutputFormat::Markdown),
+            "man" => Ok(OutputFormat::Man),
             _ => Err(format!("unknown output format `{value}`")),
         }
     }
@@ -155,6 +162,8 @@ pub(crate) struct Options {
     /// Whether to run the `calculate-doc-coverage` pass, which counts the number of public items
     /// with and without documentation.
     pub(crate) show_coverage: bool,
+    /// The thresholds and level of detail of `--show-coverage`.
+    pub(crate) coverage_options: CoverageOptions,
 
     // Options that alter generated documentation pages
     /// Crate version to note on the sidebar of generated docs.
@@ -218,6 +227,7 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             .field("test_run_directory", &self.test_run_directory)
             .field("persist_doctests", &self.persist_doctests)
             .field("show_coverage", &self.show_coverage)
+            .field("coverage_options", &self.coverage_options)
             .field("crate_version", &self.crate_version)
             .field("test_runtool", &self.test_runtool)
             .field("test_runtool_args", &self.test_runtool_args)
@@ -306,6 +316,9 @@ pub(crate) struct RenderOptions {
     /// This field is only used for the JSON output. If it's set to true, no file will be created
     /// and content will be displayed in stdout directly.
     pub(crate) output_to_stdout: bool,
+    /// The JSON output of a previous version of the crate, which `--output-format=api-diff`
+    /// compares the crate against.
+    pub(crate) api_diff_baseline: Option<PathBuf>,
     /// Whether we should read or write rendered cross-crate info in the doc root.
     pub(crate) should_merge: ShouldMerge,
     /// Path to crate-info for external crates.
@@ -330,6 +343,8 @@ pub(crate) enum EmitType {
     HtmlNonStaticFiles,
     // not explicitly nameable by the user for now
     JsonFiles,
+    MarkdownFiles,
+    ManPages,
     DepInfo(Option<OutFileName>),
 }
 
@@ -339,6 +354,8 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             Self::HtmlStaticFiles => "html-static-files",
             Self::HtmlNonStaticFiles => "html-non-static-files",
             Self::JsonFiles => "json-files",
+            Self::MarkdownFiles => "markdown-files",
+            Self::ManPages => "man-pages",
             Self::DepInfo(_) => "dep-info",
         })
     }
@@ -478,6 +495,7 @@ fn println_condition(condition: Condition) {
         let should_test = matches.opt_present("test");
 
         let show_coverage = matches.opt_present("show-coverage");
+        let coverage_options = CoverageOptions::new(matches, dcx, show_coverage);
         let output_format_s = matches.opt_str("output-format");
         let output_format = match output_format_s {
             Some(ref s) => match OutputFormat::try_from(s.as_str()) {
@@ -513,6 +531,32 @@ fn println_condition(condition: Condition) {
                     "the -Z unstable-options flag must be passed to enable --output-format for documentation generation (see https://github.com/rust-lang/rust/issues/134529)",
                 );
             }
+            (Some(OutputFormat::ApiDiff), false, false) => {
+                dcx.fatal(
+                    "the -Z unstable-options flag must be passed to enable --output-format=api-diff for documentation generation",
+                );
+            }
+            (Some(OutputFormat::Markdown), false, false) => {
+                dcx.fatal(
+                    "the -Z unstable-options flag must be passed to enable --output-format=markdown for documentation generation",
+                );
+            }
+            (Some(OutputFormat::Man), false, false) => {
+                dcx.fatal(
+                    "the -Z unstable-options flag must be passed to enable --output-format=man for documentation generation",
+                );
+            }
+        }
+
+        let api_diff_baseline = matches.opt_str("baseline").map(PathBuf::from);
+        match (output_format, &api_diff_baseline) {
+            (OutputFormat::ApiDiff, None) => {
+                dcx.fatal("`--output-format=api-diff` requires a `--baseline` to compare against")
+            }
+            (OutputFormat::ApiDiff, Some(_)) | (_, None) => {}
+            (_, Some(_)) => {
+                dcx.fatal("the `--baseline` flag is only supported with `--output-format=api-diff`")
+            }
         }
 
         let mut emit = FxIndexMap::default();
@@ -531,7 +575,11 @@ fn println_condition(condition: Condition) {
 
                 match typ {
                     EmitType::DepInfo(_) => match output_format {
-                        OutputFormat::Json | OutputFormat::Html => {}
+                        OutputFormat::Json
+                        | OutputFormat::Html
+                        | OutputFormat::ApiDiff
+                        | OutputFormat::Markdown
+                        | OutputFormat::Man => {}
                         OutputFormat::Doctest => unreachable!(),
                     },
                     EmitType::HtmlStaticFiles | EmitType::HtmlNonStaticFiles => match output_format
@@ -540,9 +588,22 @@ fn println_condition(condition: Condition) {
                         OutputFormat::Json => dcx.fatal(format!(
                             "the `--emit={typ}` flag is not supported with `--output-format=json`",
                         )),
+                        OutputFormat::ApiDiff => dcx.fatal(format!(
+                            "the `--emit={typ}` flag is not supported with \
+                             `--output-format=api-diff`",
+                        )),
+                        OutputFormat::Markdown => dcx.fatal(format!(
+                            "the `--emit={typ}` flag is not supported with \
+                             `--output-format=markdown`",
+                        )),
+                        OutputFormat::Man => dcx.fatal(format!(
+                            "the `--emit={typ}` flag is not supported with `--output-format=man`",
+                        )),
                         OutputFormat::Doctest => unreachable!(),
                     },
-                    EmitType::JsonFiles => unreachable!(),
+                    EmitType::JsonFiles | EmitType::MarkdownFiles | EmitType::ManPages => {
+                        unreachable!()
+                    }
                 }
 
                 // De-duplicate emit types and the last wins.
@@ -558,7 +619,9 @@ fn println_condition(condition: Condition) {
         // will have already been rejected above.
         if emit.is_empty() {
             match output_format {
-                OutputFormat::Json => emit.push(EmitType::JsonFiles),
+                OutputFormat::Json | OutputFormat::ApiDiff => emit.push(EmitType::JsonFiles),
+                OutputFormat::Markdown => emit.push(EmitType::MarkdownFiles),
+                OutputFormat::Man => emit.push(EmitType::ManPages),
                 OutputFormat::Html => {
                     emit.push(EmitType::HtmlStaticFiles);
                     emit.push(EmitType::HtmlNonStaticFiles);
@@ -911,6 +974,7 @@ fn println_condition(condition: Condition) {
             should_test,
             test_args,
             show_coverage,
+            coverage_options,
             crate_version,
             test_run_directory,
             persist_doctests,
@@ -962,6 +1026,7 @@ fn println_condition(condition: Condition) {
             no_emit_shared: false,
             html_no_source,
             output_to_stdout,
+            api_diff_baseline,
             should_merge,
             include_parts_dir,
             parts_out_dir,
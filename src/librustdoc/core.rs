@@ -4,11 +4,11 @@
 use rustc_data_structures::fx::{FxHashMap, FxHashSet, FxIndexMap};
 use rustc_data_structures::unord::UnordSet;
 use rustc_driver::USING_INTERNAL_FEATURES;
-use rustc_errors::TerminalUrl;
 use rustc_errors::annotate_snippet_emitter_writer::AnnotateSnippetEmitter;
 use rustc_errors::codes::*;
 use rustc_errors::emitter::{DynEmitter, HumanReadableErrorType, OutputTheme, stderr_destination};
 use rustc_errors::json::JsonEmitter;
+use rustc_errors::{ColorConfig, TerminalUrl};
 use rustc_feature::UnstableFeatures;
 use rustc_hir::def::Res;
 use rustc_hir::def_id::{DefId, DefIdMap, DefIdSet, LocalDefId};
@@ -31,9 +31,9 @@
 use crate::config::{Options as RustdocOptions, OutputFormat, RenderOptions};
 use crate::formats::cache::Cache;
 use crate::html::macro_expansion::{ExpandedCode, source_macro_expansion};
-use crate::passes;
 use crate::passes::Condition::*;
 use crate::passes::collect_intra_doc_links::LinkCollector;
+use crate::passes::{self, CoverageOptions};
 
 pub(crate) struct DocContext<'tcx> {
     pub(crate) tcx: TyCtxt<'tcx>,
@@ -68,6 +68,8 @@ pub(crate) struct DocContext<'tcx> {
     pub(crate) output_format: OutputFormat,
     /// Used by `strip_private`.
     pub(crate) show_coverage: bool,
+    /// Used by `calculate_doc_coverage`.
+    pub(crate) coverage_options: CoverageOptions,
 }
 
 impl<'tcx> DocContext<'tcx> {
@@ -184,6 +186,14 @@ pub(crate) fn new_dcx(
                 .terminal_url(TerminalUrl::No),
             )
         }
+        // A SARIF log is a single document, which the session of the compiler writes for the
+        // whole run. Errors from before there is a session are rendered for humans instead.
+        ErrorOutputType::Sarif => Box::new(
+            AnnotateSnippetEmitter::new(stderr_destination(ColorConfig::Never))
+                .sm(source_map.map(|sm| sm as _))
+                .diagnostic_width(diagnostic_width)
+                .ui_testing(unstable_opts.ui_testing),
+        ),
     };
 
     rustc_errors::DiagCtxt::new(emitter).with_flags(unstable_opts.dcx_flags(true))
@@ -328,6 +338,7 @@ pub(crate) fn create_config(
 pub(crate) fn run_global_ctxt(
     tcx: TyCtxt<'_>,
     show_coverage: bool,
+    coverage_options: CoverageOptions,
     render_options: RenderOptions,
     output_format: OutputFormat,
 ) -> (clean::Crate, RenderOptions, Cache, FxHashMap<rustc_span::BytePos, Vec<ExpandedCode>>) {
@@ -375,6 +386,7 @@ pub(crate) fn run_global_ctxt(
         inlined: FxHashSet::default(),
         output_format,
         show_coverage,
+        coverage_options,
     };
 
     for cnum in tcx.crates(()) {
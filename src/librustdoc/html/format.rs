@@ -487,11 +487,11 @@ fn is_unnamable(tcx: TyCtxt<'_>, did: DefId) -> bool {
     return false;
 }
 
-fn to_module_fqp(shortty: ItemType, fqp: &[Symbol]) -> &[Symbol] {
+pub(crate) fn to_module_fqp(shortty: ItemType, fqp: &[Symbol]) -> &[Symbol] {
     if shortty == ItemType::Module { fqp } else { &fqp[..fqp.len() - 1] }
 }
 
-fn remote_url_prefix(url: &str, is_absolute: bool, depth: usize) -> UrlPartsBuilder {
+pub(crate) fn remote_url_prefix(url: &str, is_absolute: bool, depth: usize) -> UrlPartsBuilder {
     let url = url.trim_end_matches('/');
     if is_absolute {
         UrlPartsBuilder::singleton(url)
@@ -1164,7 +1164,7 @@ fn default() -> Self {
 }
 
 impl LangString {
-    fn parse_without_check(string: &str, allow_error_code_check: ErrorCodes) -> Self {
+    pub(crate) fn parse_without_check(string: &str, allow_error_code_check: ErrorCodes) -> Self {
         Self::parse(string, allow_error_code_check, None)
     }
 
@@ -2541,7 +2541,7 @@ pub(crate) enum ItemSection {
 }
 
 impl ItemSection {
-    const ALL: &'static [Self] = {
+    pub(crate) const ALL: &'static [Self] = {
         use ItemSection::*;
         // NOTE: The order here affects the order in the UI.
         // Keep this synchronized with addSidebarItems in main.js
@@ -2604,7 +2604,7 @@ fn id(self) -> &'static str {
         }
     }
 
-    fn name(self) -> &'static str {
+    pub(crate) fn name(self) -> &'static str {
         match self {
             Self::Reexports => "Re-exports",
             Self::Modules => "Modules",
@@ -2635,7 +2635,7 @@ fn name(self) -> &'static str {
     }
 }
 
-fn item_ty_to_section(ty: ItemType) -> ItemSection {
+pub(crate) fn item_ty_to_section(ty: ItemType) -> ItemSection {
     match ty {
         ItemType::ExternCrate | ItemType::Import => ItemSection::Reexports,
         ItemType::Module => ItemSection::Modules,
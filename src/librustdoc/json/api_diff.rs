@@ -0,0 +1,1483 @@
+//! Comparing the public API of a crate against a baseline (`--output-format=api-diff`).
+//!
+//! The baseline is the JSON output of rustdoc for a previous version of the crate. IDs are not
+//! stable between two runs of rustdoc, so the items of both versions are matched by the path under
+//! which they are reachable from the crate root, and types are compared by how they are printed.
+//! Every difference is reported with the kind of [semantic version][semver] bump it requires.
+//!
+//! The classification follows the [SemVer compatibility] chapter of the Cargo book. It only looks
+//! at the public API, so changes in behavior and changes that only break code relying on type
+//! inference or glob imports are not reported.
+//!
+//! [semver]: https://semver.org/
+//! [SemVer compatibility]: https://doc.rust-lang.org/cargo/reference/semver.html
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::fs::{self, File, create_dir_all};
+use std::io::{BufWriter, Write, stdout};
+use std::path::Path;
+
+use rustc_session::Session;
+use rustdoc_json_types::{self as types, ItemEnum, ItemKind};
+use serde::{Deserialize, Serialize};
+
+use crate::docfs::PathError;
+use crate::error::Error;
+use crate::try_err;
+
+/// The version of the API diff report, written to its `format_version` field.
+///
+/// It covers the layout of [`ApiDiff`] and [`Change`], and the names of the [`Semver`] and
+/// [`ChangeKind`] values, but not the wording of the descriptions or how items and types are
+/// printed. It is independent of [`types::FORMAT_VERSION`], the version of the JSON output that
+/// the baseline has to match.
+const FORMAT_VERSION: u32 = 1;
+
+/// The kind of version bump a change requires.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum Semver {
+    /// The change is visible, but can't break any code using the crate.
+    Patch,
+    /// The change adds to the API in a backwards compatible way.
+    Minor,
+    /// The change can break code using the crate.
+    Major,
+}
+
+impl fmt::Display for Semver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Semver::Patch => "patch",
+            Semver::Minor => "minor",
+            Semver::Major => "major",
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum ChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+#[derive(Debug, Serialize)]
+struct Change {
+    /// The path of the item, e.g. `krate::module::Struct::field`. Trait implementations are
+    /// written as `<krate::Struct as core::marker::Send>`.
+    path: String,
+    item_kind: &'static str,
+    change: ChangeKind,
+    semver: Semver,
+    description: String,
+    /// How the item looks in the baseline, if it exists there.
+    old: Option<String>,
+    /// How the item looks in the new version, if it exists there.
+    new: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct ApiDiff {
+    format_version: u32,
+    baseline_version: Option<String>,
+    version: Option<String>,
+    /// The highest version bump required by any of the changes.
+    required_bump: Semver,
+    changes: Vec<Change>,
+}
+
+/// Compares `krate` against the baseline at `baseline_path` and writes the report to `out_dir`,
+/// or to `stdout` if it is `None`.
+pub(super) fn write_api_diff(
+    sess: &Session,
+    baseline_path: &Path,
+    krate: &types::Crate,
+    out_dir: Option<&Path>,
+) -> Result<(), Error> {
+    /// Only the format version, to check it before parsing the whole baseline.
+    #[derive(Deserialize)]
+    struct FormatVersion {
+        format_version: u32,
+    }
+
+    let baseline = try_err!(fs::read_to_string(baseline_path), baseline_path);
+    let FormatVersion { format_version } =
+        try_err!(serde_json::from_str(&baseline).map_err(|e| e.to_string()), baseline_path);
+    if format_version != types::FORMAT_VERSION {
+        return Err(Error::new(
+            format!(
+                "the baseline uses format version {format_version}, but this rustdoc uses format \
+                 version {}; regenerate the baseline with `--output-format=json`",
+                types::FORMAT_VERSION
+            ),
+            baseline_path,
+        ));
+    }
+    let baseline: types::Crate =
+        try_err!(serde_json::from_str(&baseline).map_err(|e| e.to_string()), baseline_path);
+
+    let diff = diff(&baseline, krate);
+    if let (Some(old), Some(new)) = (&diff.baseline_version, &diff.version)
+        && let Some(bump) = version_bump(old, new)
+        && bump < diff.required_bump
+    {
+        sess.dcx().warn(format!(
+            "the API changes require a {} version bump, but the version changed from `{old}` to \
+             `{new}`",
+            diff.required_bump
+        ));
+    }
+
+    if let Some(out_dir) = out_dir {
+        try_err!(create_dir_all(out_dir), out_dir);
+        let crate_name = krate.index[&krate.root].name.as_deref().unwrap();
+        let path = out_dir.join(format!("{crate_name}.api-diff.json"));
+        serialize_and_write(&diff, try_err!(File::create_buffered(&path), path), &path)
+    } else {
+        serialize_and_write(&diff, BufWriter::new(stdout().lock()), Path::new("<stdout>"))
+    }
+}
+
+fn serialize_and_write<T: Write>(
+    diff: &ApiDiff,
+    mut writer: BufWriter<T>,
+    path: &Path,
+) -> Result<(), Error> {
+    try_err!(serde_json::ser::to_writer(&mut writer, diff).map_err(|e| e.to_string()), path);
+    try_err!(writer.flush(), path);
+    Ok(())
+}
+
+/// Returns the kind of version bump from `old` to `new`, if both are semantic versions and `new` is
+/// the later one. As in Cargo, the first non-zero component is the one for breaking changes.
+fn version_bump(old: &str, new: &str) -> Option<Semver> {
+    fn parse(version: &str) -> Option<[u64; 3]> {
+        // Pre-release and build metadata don't matter here.
+        let core = version.split(['-', '+']).next()?;
+        let mut components = core.split('.').map(|c| c.parse().ok());
+        let version = [components.next()??, components.next()??, components.next()??];
+        components.next().is_none().then_some(version)
+    }
+
+    let (old, new) = (parse(old)?, parse(new)?);
+    if new <= old {
+        return None;
+    }
+    let breaking = old.iter().position(|&c| c != 0).unwrap_or(2);
+    let changed = old.iter().zip(&new).position(|(o, n)| o != n)?;
+    Some(if changed <= breaking {
+        Semver::Major
+    } else if changed == breaking + 1 {
+        Semver::Minor
+    } else {
+        Semver::Patch
+    })
+}
+
+/// The namespace of an item. Items of different namespaces can be reachable under the same path,
+/// like `pub mod foo` and `pub fn foo`, or a field and a method with the same name.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Namespace {
+    Type,
+    Value,
+    Macro,
+    Field,
+}
+
+/// Identifies an item of the public API by its path and namespace.
+type Key = (String, Namespace);
+
+/// An item of the public API.
+struct ApiItem<'a> {
+    /// The item that contains this one, e.g. the module of a function or the struct of a field.
+    /// Items whose owner was added or removed are not reported on their own.
+    owner: Option<Key>,
+    item: &'a types::Item,
+}
+
+/// The public API of a crate, by path and namespace.
+struct Api<'a> {
+    krate: &'a types::Crate,
+    items: BTreeMap<Key, ApiItem<'a>>,
+}
+
+impl<'a> Api<'a> {
+    fn collect(krate: &'a types::Crate) -> Self {
+        let mut api = Api { krate, items: BTreeMap::new() };
+        let root = &krate.index[&krate.root];
+        let name = root.name.clone().unwrap();
+        api.add(name.clone(), None, root);
+        api.add_module(&name, root, &mut vec![root.id]);
+        api
+    }
+
+    /// Adds the public items of `module` under `path`. `stack` contains the modules on the way
+    /// there, to not loop forever on modules re-exporting their parents.
+    fn add_module(&mut self, path: &str, module: &'a types::Item, stack: &mut Vec<types::Id>) {
+        let krate = self.krate;
+        let ItemEnum::Module(m) = &module.inner else { return };
+        let owner = (path.to_owned(), Namespace::Type);
+        let owner = Some(&owner);
+        for item in m.items.iter().filter_map(|id| krate.index.get(id)) {
+            if item.visibility != types::Visibility::Public {
+                continue;
+            }
+            match &item.inner {
+                ItemEnum::Use(use_) => {
+                    let target = use_.id.and_then(|id| krate.index.get(&id));
+                    if use_.is_glob {
+                        match target.map(|target| &target.inner) {
+                            Some(ItemEnum::Module(_)) => {
+                                let target = target.unwrap();
+                                if !stack.contains(&target.id) {
+                                    stack.push(target.id);
+                                    self.add_module(path, target, stack);
+                                    stack.pop();
+                                }
+                            }
+                            Some(ItemEnum::Enum(e)) => {
+                                for variant in
+                                    e.variants.iter().filter_map(|id| krate.index.get(id))
+                                {
+                                    let name = variant.name.as_deref().unwrap();
+                                    self.add(format!("{path}::{name}"), owner, variant);
+                                }
+                            }
+                            // Glob imports of other crates aren't part of the JSON output.
+                            _ => {}
+                        }
+                    } else {
+                        let item_path = format!("{path}::{}", use_.name);
+                        match target {
+                            Some(target) => {
+                                self.add(item_path.clone(), owner, target);
+                                if matches!(target.inner, ItemEnum::Module(_))
+                                    && !stack.contains(&target.id)
+                                {
+                                    stack.push(target.id);
+                                    self.add_module(&item_path, target, stack);
+                                    stack.pop();
+                                }
+                            }
+                            // A re-export of an item of another crate, compared by its source.
+                            None => self.add(item_path, owner, item),
+                        }
+                    }
+                }
+                // Impls are reached through the types they are for.
+                ItemEnum::Impl(_) => {}
+                _ => {
+                    let Some(name) = &item.name else { continue };
+                    let item_path = format!("{path}::{name}");
+                    self.add(item_path.clone(), owner, item);
+                    if matches!(item.inner, ItemEnum::Module(_)) && !stack.contains(&item.id) {
+                        stack.push(item.id);
+                        self.add_module(&item_path, item, stack);
+                        stack.pop();
+                    }
+                }
+            }
+        }
+    }
+
+    /// Adds `item` and its members, like fields and methods, under `path`.
+    fn add(&mut self, path: String, owner: Option<&Key>, item: &'a types::Item) {
+        let krate = self.krate;
+        let key = (path, self.namespace(item));
+        if self.items.contains_key(&key) {
+            return;
+        }
+        self.items.insert(key.clone(), ApiItem { owner: owner.cloned(), item });
+
+        let owner = &key;
+        match &item.inner {
+            ItemEnum::Struct(s) => {
+                match &s.kind {
+                    types::StructKind::Unit => {}
+                    types::StructKind::Tuple(fields) => {
+                        self.add_fields(owner, fields.iter().flatten())
+                    }
+                    types::StructKind::Plain { fields, .. } => self.add_fields(owner, fields),
+                }
+                self.add_impls(owner, &s.impls);
+            }
+            ItemEnum::Union(u) => {
+                self.add_fields(owner, &u.fields);
+                self.add_impls(owner, &u.impls);
+            }
+            ItemEnum::Enum(e) => {
+                for variant in e.variants.iter().filter_map(|id| krate.index.get(id)) {
+                    let name = variant.name.as_deref().unwrap();
+                    self.add(format!("{}::{name}", owner.0), Some(owner), variant);
+                }
+                self.add_impls(owner, &e.impls);
+            }
+            ItemEnum::Variant(v) => match &v.kind {
+                types::VariantKind::Plain => {}
+                types::VariantKind::Tuple(fields) => {
+                    self.add_fields(owner, fields.iter().flatten())
+                }
+                types::VariantKind::Struct { fields, .. } => self.add_fields(owner, fields),
+            },
+            ItemEnum::Trait(t) => {
+                for trait_item in t.items.iter().filter_map(|id| krate.index.get(id)) {
+                    let name = trait_item.name.as_deref().unwrap();
+                    self.add(format!("{}::{name}", owner.0), Some(owner), trait_item);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn add_fields(&mut self, owner: &Key, fields: impl IntoIterator<Item = &'a types::Id>) {
+        let krate = self.krate;
+        for field in fields.into_iter().filter_map(|id| krate.index.get(id)) {
+            if field.visibility == types::Visibility::Public {
+                let name = field.name.as_deref().unwrap();
+                self.add(format!("{}::{name}", owner.0), Some(owner), field);
+            }
+        }
+    }
+
+    /// Adds the items of the inherent impls and the trait impls of the type `owner`.
+    fn add_impls(&mut self, owner: &Key, impls: &[types::Id]) {
+        let krate = self.krate;
+        let path = owner.0.as_str();
+        for item in impls.iter().filter_map(|id| krate.index.get(id)) {
+            let ItemEnum::Impl(impl_) = &item.inner else { continue };
+            // Blanket impls and negative impls don't belong to the type.
+            if impl_.blanket_impl.is_some() || impl_.is_negative {
+                continue;
+            }
+            match &impl_.trait_ {
+                None => {
+                    for impl_item in impl_.items.iter().filter_map(|id| krate.index.get(id)) {
+                        if impl_item.visibility == types::Visibility::Public {
+                            let name = impl_item.name.as_deref().unwrap();
+                            self.add(format!("{path}::{name}"), Some(owner), impl_item);
+                        }
+                    }
+                }
+                Some(trait_) => {
+                    // Impls for different instantiations of the type must get different paths.
+                    let args = match &impl_.for_ {
+                        types::Type::ResolvedPath(for_) => self.args(for_.args.as_deref()),
+                        _ => String::new(),
+                    };
+                    self.add(format!("<{path}{args} as {}>", self.path(trait_)), Some(owner), item);
+                }
+            }
+        }
+    }
+
+    /// Returns the namespace of `item`. Re-exports of items of other crates are in the namespace
+    /// of the item they refer to, if it is known.
+    fn namespace(&self, item: &types::Item) -> Namespace {
+        match &item.inner {
+            ItemEnum::Function(_)
+            | ItemEnum::Constant { .. }
+            | ItemEnum::Static(_)
+            | ItemEnum::AssocConst { .. } => Namespace::Value,
+            ItemEnum::Macro(_) | ItemEnum::ProcMacro(_) => Namespace::Macro,
+            ItemEnum::StructField(_) => Namespace::Field,
+            ItemEnum::Use(use_) => {
+                let target = use_.id.and_then(|id| self.krate.paths.get(&id));
+                match target.map(|summary| summary.kind) {
+                    Some(
+                        ItemKind::Function
+                        | ItemKind::Constant
+                        | ItemKind::Static
+                        | ItemKind::AssocConst,
+                    ) => Namespace::Value,
+                    Some(ItemKind::Macro | ItemKind::ProcAttribute | ItemKind::ProcDerive) => {
+                        Namespace::Macro
+                    }
+                    _ => Namespace::Type,
+                }
+            }
+            _ => Namespace::Type,
+        }
+    }
+
+    /// Returns the kind of `item`, as it is written in the report.
+    fn kind(&self, item: &types::Item) -> &'static str {
+        match &item.inner {
+            ItemEnum::Module(_) => "module",
+            ItemEnum::ExternCrate { .. } => "extern crate",
+            ItemEnum::Use(_) => "re-export",
+            ItemEnum::Union(_) => "union",
+            ItemEnum::Struct(_) => "struct",
+            ItemEnum::StructField(_) => "field",
+            ItemEnum::Enum(_) => "enum",
+            ItemEnum::Variant(_) => "variant",
+            ItemEnum::Function(_) => "function",
+            ItemEnum::Trait(_) => "trait",
+            ItemEnum::TraitAlias(_) => "trait alias",
+            ItemEnum::Impl(impl_) if impl_.is_synthetic => "auto trait impl",
+            ItemEnum::Impl(_) => "trait impl",
+            ItemEnum::TypeAlias(_) => "type alias",
+            ItemEnum::Constant { .. } => "constant",
+            ItemEnum::Static(_) => "static",
+            ItemEnum::ExternType => "extern type",
+            ItemEnum::Macro(_) => "macro",
+            ItemEnum::ProcMacro(_) => "proc macro",
+            ItemEnum::Primitive(_) => "primitive",
+            ItemEnum::AssocConst { .. } => "associated constant",
+            ItemEnum::AssocType { .. } => "associated type",
+        }
+    }
+
+    /// Prints the declaration of `item`, without its body.
+    fn declaration(&self, item: &types::Item) -> String {
+        let name = item.name.as_deref().unwrap_or_default();
+        match &item.inner {
+            ItemEnum::Module(_) => format!("mod {name}"),
+            ItemEnum::ExternCrate { name, rename: None } => format!("extern crate {name}"),
+            ItemEnum::ExternCrate { name, rename: Some(rename) } => {
+                format!("extern crate {name} as {rename}")
+            }
+            ItemEnum::Use(use_) if use_.is_glob => format!("pub use {}::*", use_.source),
+            ItemEnum::Use(use_) => format!("pub use {}", use_.source),
+            ItemEnum::Union(u) => format!("union {name}{}", self.generics(&u.generics)),
+            ItemEnum::Struct(s) => {
+                let fields = match &s.kind {
+                    types::StructKind::Unit => ";",
+                    types::StructKind::Tuple(_) => "(..);",
+                    types::StructKind::Plain { .. } => " { .. }",
+                };
+                format!("struct {name}{}{fields}", self.generics(&s.generics))
+            }
+            ItemEnum::StructField(ty) => format!("{name}: {}", self.ty(ty)),
+            ItemEnum::Enum(e) => format!("enum {name}{}", self.generics(&e.generics)),
+            ItemEnum::Variant(v) => {
+                let fields = match &v.kind {
+                    types::VariantKind::Plain => "",
+                    types::VariantKind::Tuple(_) => "(..)",
+                    types::VariantKind::Struct { .. } => " { .. }",
+                };
+                match &v.discriminant {
+                    Some(discriminant) => format!("{name}{fields} = {}", discriminant.value),
+                    None => format!("{name}{fields}"),
+                }
+            }
+            ItemEnum::Function(f) => {
+                format!(
+                    "{}fn {name}{}{}",
+                    self.header(&f.header),
+                    self.params(&f.generics),
+                    self.signature(&f.sig)
+                ) + &self.where_clause(&f.generics)
+            }
+            ItemEnum::Trait(t) => {
+                let supertraits = if t.bounds.is_empty() {
+                    String::new()
+                } else {
+                    format!(": {}", self.bounds(&t.bounds))
+                };
+                format!(
+                    "{}{}trait {name}{}{supertraits}",
+                    if t.is_unsafe { "unsafe " } else { "" },
+                    if t.is_auto { "auto " } else { "" },
+                    self.generics(&t.generics)
+                )
+            }
+            ItemEnum::TraitAlias(t) => {
+                format!("trait {name}{} = {}", self.generics(&t.generics), self.bounds(&t.params))
+            }
+            ItemEnum::Impl(impl_) => {
+                format!(
+                    "{}impl{} {}{} for {}",
+                    if impl_.is_unsafe { "unsafe " } else { "" },
+                    self.params(&impl_.generics),
+                    if impl_.is_negative { "!" } else { "" },
+                    impl_.trait_.as_ref().map(|trait_| self.path(trait_)).unwrap_or_default(),
+                    self.ty(&impl_.for_)
+                ) + &self.where_clause(&impl_.generics)
+            }
+            ItemEnum::TypeAlias(t) => {
+                format!("type {name}{} = {}", self.params(&t.generics), self.ty(&t.type_))
+                    + &self.where_clause(&t.generics)
+            }
+            ItemEnum::Constant { type_, const_ } => format!(
+                "const {name}: {} = {}",
+                self.ty(type_),
+                const_.value.as_ref().unwrap_or(&const_.expr)
+            ),
+            ItemEnum::Static(s) => format!(
+                "{}static {}{name}: {}",
+                if s.is_unsafe { "unsafe " } else { "" },
+                if s.is_mutable { "mut " } else { "" },
+                self.ty(&s.type_)
+            ),
+            ItemEnum::ExternType => format!("type {name}"),
+            ItemEnum::Macro(source) => source.clone(),
+            ItemEnum::ProcMacro(p) => {
+                let helpers = if p.helpers.is_empty() {
+                    String::new()
+                } else {
+                    format!(", attributes({})", p.helpers.join(", "))
+                };
+                match p.kind {
+                    types::MacroKind::Bang => format!("{name}!"),
+                    types::MacroKind::Attr => format!("#[{name}]"),
+                    types::MacroKind::Derive => format!("#[derive({name}{helpers})]"),
+                }
+            }
+            ItemEnum::Primitive(p) => p.name.clone(),
+            ItemEnum::AssocConst { type_, value } => match value {
+                Some(value) => format!("const {name}: {} = {value}", self.ty(type_)),
+                None => format!("const {name}: {}", self.ty(type_)),
+            },
+            ItemEnum::AssocType { generics, bounds, type_ } => {
+                let mut declaration = format!("type {name}{}", self.params(generics));
+                if !bounds.is_empty() {
+                    declaration += &format!(": {}", self.bounds(bounds));
+                }
+                if let Some(type_) = type_ {
+                    declaration += &format!(" = {}", self.ty(type_));
+                }
+                declaration + &self.where_clause(generics)
+            }
+        }
+    }
+
+    /// Prints the generic parameters and the where clause of `generics`.
+    fn generics(&self, generics: &types::Generics) -> String {
+        self.params(generics) + &self.where_clause(generics)
+    }
+
+    /// Prints the generic parameters of `generics`, without their bounds.
+    fn params(&self, generics: &types::Generics) -> String {
+        let params = self.param_list(generics);
+        if params.is_empty() { String::new() } else { format!("<{}>", params.join(", ")) }
+    }
+
+    /// Returns the generic parameters of `generics` that are written in the source, without their
+    /// bounds, and whether they have a default.
+    fn param_list(&self, generics: &types::Generics) -> Vec<String> {
+        generics
+            .params
+            .iter()
+            .filter(|param| {
+                !matches!(param.kind, types::GenericParamDefKind::Type { is_synthetic: true, .. })
+            })
+            .map(|param| self.param(param))
+            .collect()
+    }
+
+    fn param(&self, param: &types::GenericParamDef) -> String {
+        let name = &param.name;
+        match &param.kind {
+            types::GenericParamDefKind::Lifetime { .. } => name.clone(),
+            types::GenericParamDefKind::Type { default: Some(default), .. } => {
+                format!("{name} = {}", self.ty(default))
+            }
+            types::GenericParamDefKind::Type { default: None, .. } => name.clone(),
+            types::GenericParamDefKind::Const { type_, default } => match default {
+                Some(default) => format!("const {name}: {} = {default}", self.ty(type_)),
+                None => format!("const {name}: {}", self.ty(type_)),
+            },
+        }
+    }
+
+    /// Prints all the bounds of `generics` as a where clause, including the ones that are written
+    /// next to the parameters.
+    fn where_clause(&self, generics: &types::Generics) -> String {
+        let predicates = self.predicates(generics);
+        if predicates.is_empty() {
+            String::new()
+        } else {
+            format!(" where {}", predicates.into_iter().collect::<Vec<_>>().join(", "))
+        }
+    }
+
+    /// Returns the bounds of `generics`, one bound per entry, e.g. `T: Clone`.
+    fn predicates(&self, generics: &types::Generics) -> BTreeSet<String> {
+        let mut predicates = BTreeSet::new();
+        for param in &generics.params {
+            let name = &param.name;
+            match &param.kind {
+                types::GenericParamDefKind::Lifetime { outlives } => {
+                    predicates.extend(outlives.iter().map(|lifetime| format!("{name}: {lifetime}")))
+                }
+                // The bounds of synthetic parameters are part of the `impl Trait` types.
+                types::GenericParamDefKind::Type { bounds, is_synthetic: false, .. } => predicates
+                    .extend(bounds.iter().map(|bound| format!("{name}: {}", self.bound(bound)))),
+                types::GenericParamDefKind::Type { is_synthetic: true, .. }
+                | types::GenericParamDefKind::Const { .. } => {}
+            }
+        }
+        for predicate in &generics.where_predicates {
+            match predicate {
+                types::WherePredicate::BoundPredicate { type_, bounds, generic_params } => {
+                    let ty = format!("{}{}", self.hrtb(generic_params), self.ty(type_));
+                    predicates
+                        .extend(bounds.iter().map(|bound| format!("{ty}: {}", self.bound(bound))));
+                }
+                types::WherePredicate::LifetimePredicate { lifetime, outlives } => predicates
+                    .extend(outlives.iter().map(|outlives| format!("{lifetime}: {outlives}"))),
+                types::WherePredicate::EqPredicate { lhs, rhs } => {
+                    predicates.insert(format!("{} == {}", self.ty(lhs), self.term(rhs)));
+                }
+            }
+        }
+        predicates
+    }
+
+    fn header(&self, header: &types::FunctionHeader) -> String {
+        let mut s = String::new();
+        if header.is_const {
+            s.push_str("const ");
+        }
+        if header.is_async {
+            s.push_str("async ");
+        }
+        if header.is_unsafe {
+            s.push_str("unsafe ");
+        }
+        if header.abi != types::Abi::Rust {
+            s.push_str(&format!("extern \"{}\" ", self.abi(&header.abi)));
+        }
+        s
+    }
+
+    fn abi(&self, abi: &types::Abi) -> String {
+        let (name, unwind) = match abi {
+            types::Abi::Rust => ("Rust", false),
+            types::Abi::C { unwind } => ("C", *unwind),
+            types::Abi::Cdecl { unwind } => ("cdecl", *unwind),
+            types::Abi::Stdcall { unwind } => ("stdcall", *unwind),
+            types::Abi::Fastcall { unwind } => ("fastcall", *unwind),
+            types::Abi::Aapcs { unwind } => ("aapcs", *unwind),
+            types::Abi::Win64 { unwind } => ("win64", *unwind),
+            types::Abi::SysV64 { unwind } => ("sysv64", *unwind),
+            types::Abi::System { unwind } => ("system", *unwind),
+            types::Abi::Other(abi) => (abi.as_str(), false),
+        };
+        if unwind { format!("{name}-unwind") } else { name.to_owned() }
+    }
+
+    /// Prints the parameters and the return type of a function.
+    fn signature(&self, sig: &types::FunctionSignature) -> String {
+        let mut inputs: Vec<String> = sig
+            .inputs
+            .iter()
+            .map(|(name, ty)| match ty {
+                // Don't print `self: Self` and `self: &Self`.
+                types::Type::Generic(generic) if name == "self" && generic == "Self" => {
+                    name.clone()
+                }
+                types::Type::BorrowedRef { lifetime, is_mutable, type_ }
+                    if name == "self" && **type_ == types::Type::Generic("Self".to_owned()) =>
+                {
+                    let lifetime = lifetime.as_ref().map(|l| format!("{l} ")).unwrap_or_default();
+                    format!("&{lifetime}{}self", if *is_mutable { "mut " } else { "" })
+                }
+                _ => format!("{name}: {}", self.ty(ty)),
+            })
+            .collect();
+        if sig.is_c_variadic {
+            inputs.push("...".to_owned());
+        }
+        match &sig.output {
+            Some(output) => format!("({}) -> {}", inputs.join(", "), self.ty(output)),
+            None => format!("({})", inputs.join(", ")),
+        }
+    }
+
+    fn ty(&self, ty: &types::Type) -> String {
+        match ty {
+            types::Type::ResolvedPath(path) => self.path(path),
+            types::Type::DynTrait(dyn_trait) => {
+                let mut bounds: Vec<String> = dyn_trait
+                    .traits
+                    .iter()
+                    .map(|poly| {
+                        format!("{}{}", self.hrtb(&poly.generic_params), self.path(&poly.trait_))
+                    })
+                    .collect();
+                bounds.extend(dyn_trait.lifetime.clone());
+                format!("dyn {}", bounds.join(" + "))
+            }
+            types::Type::Generic(name) | types::Type::Primitive(name) => name.clone(),
+            types::Type::FunctionPointer(fn_ptr) => format!(
+                "{}{}fn{}",
+                self.hrtb(&fn_ptr.generic_params),
+                self.header(&fn_ptr.header),
+                self.signature(&fn_ptr.sig)
+            ),
+            types::Type::Tuple(types) if types.len() == 1 => format!("({},)", self.ty(&types[0])),
+            types::Type::Tuple(types) => format!("({})", self.types(types)),
+            types::Type::Slice(ty) => format!("[{}]", self.ty(ty)),
+            types::Type::Array { type_, len } => format!("[{}; {len}]", self.ty(type_)),
+            types::Type::Pat { type_, __pat_unstable_do_not_use: pat } => {
+                format!("{} is {pat}", self.ty(type_))
+            }
+            types::Type::ImplTrait(bounds) => format!("impl {}", self.bounds(bounds)),
+            types::Type::Infer => "_".to_owned(),
+            types::Type::RawPointer { is_mutable, type_ } => {
+                format!("*{} {}", if *is_mutable { "mut" } else { "const" }, self.ty(type_))
+            }
+            types::Type::BorrowedRef { lifetime, is_mutable, type_ } => format!(
+                "&{}{}{}",
+                lifetime.as_ref().map(|l| format!("{l} ")).unwrap_or_default(),
+                if *is_mutable { "mut " } else { "" },
+                self.ty(type_)
+            ),
+            types::Type::QualifiedPath { name, args, self_type, trait_ } => {
+                let args = self.args(args.as_deref());
+                match trait_ {
+                    Some(trait_) => {
+                        format!("<{} as {}>::{name}{args}", self.ty(self_type), self.path(trait_))
+                    }
+                    None => format!("{}::{name}{args}", self.ty(self_type)),
+                }
+            }
+        }
+    }
+
+    fn types(&self, types: &[types::Type]) -> String {
+        types.iter().map(|ty| self.ty(ty)).collect::<Vec<_>>().join(", ")
+    }
+
+    /// Prints `path` with the full path of the item it refers to, as the path in the source
+    /// depends on the imports.
+    fn path(&self, path: &types::Path) -> String {
+        let name = match self.krate.paths.get(&path.id) {
+            Some(summary) => summary.path.join("::"),
+            None => path.path.clone(),
+        };
+        name + &self.args(path.args.as_deref())
+    }
+
+    fn args(&self, args: Option<&types::GenericArgs>) -> String {
+        match args {
+            None => String::new(),
+            Some(types::GenericArgs::AngleBracketed { args, constraints }) => {
+                let mut list: Vec<String> = args
+                    .iter()
+                    .map(|arg| match arg {
+                        types::GenericArg::Lifetime(lifetime) => lifetime.clone(),
+                        types::GenericArg::Type(ty) => self.ty(ty),
+                        types::GenericArg::Const(constant) => constant.expr.clone(),
+                        types::GenericArg::Infer => "_".to_owned(),
+                    })
+                    .collect();
+                list.extend(constraints.iter().map(|constraint| {
+                    let name =
+                        format!("{}{}", constraint.name, self.args(constraint.args.as_deref()));
+                    match &constraint.binding {
+                        types::AssocItemConstraintKind::Equality(term) => {
+                            format!("{name} = {}", self.term(term))
+                        }
+                        types::AssocItemConstraintKind::Constraint(bounds) => {
+                            format!("{name}: {}", self.bounds(bounds))
+                        }
+                    }
+                }));
+                if list.is_empty() { String::new() } else { format!("<{}>", list.join(", ")) }
+            }
+            Some(types::GenericArgs::Parenthesized { inputs, output }) => match output {
+                Some(output) => format!("({}) -> {}", self.types(inputs), self.ty(output)),
+                None => format!("({})", self.types(inputs)),
+            },
+            Some(types::GenericArgs::ReturnTypeNotation) => "(..)".to_owned(),
+        }
+    }
+
+    fn term(&self, term: &types::Term) -> String {
+        match term {
+            types::Term::Type(ty) => self.ty(ty),
+            types::Term::Constant(constant) => constant.expr.clone(),
+        }
+    }
+
+    fn bound(&self, bound: &types::GenericBound) -> String {
+        match bound {
+            types::GenericBound::TraitBound { trait_, generic_params, modifier } => {
+                let modifier = match modifier {
+                    types::TraitBoundModifier::None => "",
+                    types::TraitBoundModifier::Maybe => "?",
+                    types::TraitBoundModifier::MaybeConst => "[const] ",
+                };
+                format!("{}{modifier}{}", self.hrtb(generic_params), self.path(trait_))
+            }
+            types::GenericBound::Outlives(lifetime) => lifetime.clone(),
+            types::GenericBound::Use(args) => {
+                let args: Vec<&str> = args
+                    .iter()
+                    .map(|arg| match arg {
+                        types::PreciseCapturingArg::Lifetime(name)
+                        | types::PreciseCapturingArg::Param(name) => name.as_str(),
+                    })
+                    .collect();
+                format!("use<{}>", args.join(", "))
+            }
+        }
+    }
+
+    fn bounds(&self, bounds: &[types::GenericBound]) -> String {
+        bounds.iter().map(|bound| self.bound(bound)).collect::<Vec<_>>().join(" + ")
+    }
+
+    /// Prints the binder of a higher-ranked bound, e.g. `for<'a> `.
+    fn hrtb(&self, params: &[types::GenericParamDef]) -> String {
+        if params.is_empty() {
+            String::new()
+        } else {
+            let params: Vec<String> = params.iter().map(|param| self.param(param)).collect();
+            format!("for<{}> ", params.join(", "))
+        }
+    }
+}
+
+fn is_non_exhaustive(item: &types::Item) -> bool {
+    item.attrs.contains(&types::Attribute::NonExhaustive)
+}
+
+/// Returns whether the struct `item` can be constructed and destructured outside of its crate.
+fn is_exhaustive_struct(item: &types::Item) -> bool {
+    let ItemEnum::Struct(s) = &item.inner else { return false };
+    let has_private_fields = match &s.kind {
+        types::StructKind::Unit => false,
+        types::StructKind::Tuple(fields) => fields.iter().any(Option::is_none),
+        types::StructKind::Plain { has_stripped_fields, .. } => *has_stripped_fields,
+    };
+    !has_private_fields && !is_non_exhaustive(item)
+}
+
+/// Collects the changes between two versions of the API.
+struct Differ<'a> {
+    old: Api<'a>,
+    new: Api<'a>,
+    changes: Vec<Change>,
+}
+
+/// The changes to a single item, with the version bump they require.
+type Findings = Vec<(Semver, String)>;
+
+fn diff(baseline: &types::Crate, krate: &types::Crate) -> ApiDiff {
+    let mut differ =
+        Differ { old: Api::collect(baseline), new: Api::collect(krate), changes: Vec::new() };
+    differ.diff();
+    let required_bump =
+        differ.changes.iter().map(|change| change.semver).max().unwrap_or(Semver::Patch);
+    ApiDiff {
+        format_version: FORMAT_VERSION,
+        baseline_version: baseline.crate_version.clone(),
+        version: krate.crate_version.clone(),
+        required_bump,
+        changes: differ.changes,
+    }
+}
+
+impl<'a> Differ<'a> {
+    fn diff(&mut self) {
+        let old_api = &self.old;
+        let new_api = &self.new;
+        let mut changes = Vec::new();
+        let owner_in_both = |item: &ApiItem<'_>| {
+            item.owner.as_ref().is_none_or(|owner| {
+                old_api.items.contains_key(owner) && new_api.items.contains_key(owner)
+            })
+        };
+
+        for (key, old) in &old_api.items {
+            match new_api.items.get(key) {
+                Some(new) => {
+                    let kind = new_api.kind(new.item);
+                    for (semver, description) in self.compare(old.item, new.item) {
+                        changes.push(Change {
+                            path: key.0.clone(),
+                            item_kind: kind,
+                            change: ChangeKind::Changed,
+                            semver,
+                            description,
+                            old: Some(old_api.declaration(old.item)),
+                            new: Some(new_api.declaration(new.item)),
+                        });
+                    }
+                }
+                None if owner_in_both(old) => {
+                    let description = match &old.item.inner {
+                        ItemEnum::Impl(impl_) => format!(
+                            "no longer implements `{}`",
+                            impl_.trait_.as_ref().map(|trait_| old_api.path(trait_)).unwrap()
+                        ),
+                        _ => "removed from the public API".to_owned(),
+                    };
+                    changes.push(Change {
+                        path: key.0.clone(),
+                        item_kind: old_api.kind(old.item),
+                        change: ChangeKind::Removed,
+                        semver: Semver::Major,
+                        description,
+                        old: Some(old_api.declaration(old.item)),
+                        new: None,
+                    });
+                }
+                None => {}
+            }
+        }
+
+        for (key, new) in &new_api.items {
+            if old_api.items.contains_key(key) || !owner_in_both(new) {
+                continue;
+            }
+            let (semver, description) = self.addition(new);
+            changes.push(Change {
+                path: key.0.clone(),
+                item_kind: new_api.kind(new.item),
+                change: ChangeKind::Added,
+                semver,
+                description,
+                old: None,
+                new: Some(new_api.declaration(new.item)),
+            });
+        }
+
+        changes.sort_by(|a, b| a.path.cmp(&b.path));
+        self.changes = changes;
+    }
+
+    /// Returns the version bump required by adding `new`, whose owner exists in both versions.
+    fn addition(&self, new: &ApiItem<'_>) -> (Semver, String) {
+        let owner = new.owner.as_ref().map(|owner| self.old.items[owner].item);
+        match (&new.item.inner, owner.map(|owner| &owner.inner)) {
+            (ItemEnum::StructField(_), Some(ItemEnum::Struct(_)))
+                if is_exhaustive_struct(owner.unwrap()) =>
+            {
+                (
+                    Semver::Major,
+                    "new public field of a struct whose fields were all public, which breaks \
+                     struct expressions and patterns"
+                        .to_owned(),
+                )
+            }
+            (ItemEnum::StructField(_), Some(ItemEnum::Variant(_)))
+                if !is_non_exhaustive(owner.unwrap()) =>
+            {
+                (
+                    Semver::Major,
+                    "new field of an exhaustive variant, which breaks struct expressions and \
+                     patterns"
+                        .to_owned(),
+                )
+            }
+            (ItemEnum::Variant(_), Some(ItemEnum::Enum(_)))
+                if !is_non_exhaustive(owner.unwrap()) =>
+            {
+                (
+                    Semver::Major,
+                    "new variant of an exhaustive enum, which breaks exhaustive matches".to_owned(),
+                )
+            }
+            (ItemEnum::Function(f), Some(ItemEnum::Trait(_))) if !f.has_body => {
+                (Semver::Major, "new required method, which breaks existing impls".to_owned())
+            }
+            (ItemEnum::AssocConst { value: None, .. }, Some(ItemEnum::Trait(_))) => (
+                Semver::Major,
+                "new associated constant without a default, which breaks existing impls".to_owned(),
+            ),
+            (ItemEnum::AssocType { type_: None, .. }, Some(ItemEnum::Trait(_))) => (
+                Semver::Major,
+                "new associated type without a default, which breaks existing impls".to_owned(),
+            ),
+            (ItemEnum::Impl(impl_), _) => (
+                Semver::Minor,
+                format!(
+                    "now implements `{}`",
+                    impl_.trait_.as_ref().map(|trait_| self.new.path(trait_)).unwrap()
+                ),
+            ),
+            _ => (Semver::Minor, "added to the public API".to_owned()),
+        }
+    }
+
+    /// Returns the changes between two versions of the same item.
+    fn compare(&self, old: &types::Item, new: &types::Item) -> Findings {
+        let mut findings = Findings::new();
+        if old.deprecation.is_none() && new.deprecation.is_some() {
+            findings.push((Semver::Minor, "is deprecated now".to_owned()));
+        }
+
+        match (&old.inner, &new.inner) {
+            (ItemEnum::Function(old_fn), ItemEnum::Function(new_fn)) => {
+                self.compare_functions(old_fn, new_fn, &mut findings)
+            }
+            (ItemEnum::Struct(old_struct), ItemEnum::Struct(new_struct)) => {
+                let kind = |kind: &types::StructKind| match kind {
+                    types::StructKind::Unit => "a unit struct",
+                    types::StructKind::Tuple(_) => "a tuple struct",
+                    types::StructKind::Plain { .. } => "a struct with named fields",
+                };
+                let (old_kind, new_kind) = (kind(&old_struct.kind), kind(&new_struct.kind));
+                if old_kind != new_kind {
+                    findings
+                        .push((Semver::Major, format!("changed from {old_kind} to {new_kind}")));
+                }
+                match (is_exhaustive_struct(old), is_exhaustive_struct(new)) {
+                    (true, false) => findings.push((
+                        Semver::Major,
+                        "can no longer be constructed or destructured outside of its crate, as it \
+                         has private fields or is `#[non_exhaustive]` now"
+                            .to_owned(),
+                    )),
+                    (false, true) => findings.push((
+                        Semver::Minor,
+                        "can be constructed and destructured outside of its crate now".to_owned(),
+                    )),
+                    _ => {}
+                }
+                self.compare_generics(
+                    &old_struct.generics,
+                    &new_struct.generics,
+                    true,
+                    &mut findings,
+                );
+            }
+            (ItemEnum::Union(old_union), ItemEnum::Union(new_union)) => {
+                match (old_union.has_stripped_fields, new_union.has_stripped_fields) {
+                    (false, true) => findings.push((
+                        Semver::Major,
+                        "can no longer be constructed outside of its crate, as it has private \
+                         fields now"
+                            .to_owned(),
+                    )),
+                    (true, false) => findings.push((
+                        Semver::Minor,
+                        "can be constructed outside of its crate now".to_owned(),
+                    )),
+                    _ => {}
+                }
+                self.compare_generics(
+                    &old_union.generics,
+                    &new_union.generics,
+                    true,
+                    &mut findings,
+                );
+            }
+            (ItemEnum::Enum(old_enum), ItemEnum::Enum(new_enum)) => {
+                match (is_non_exhaustive(old), is_non_exhaustive(new)) {
+                    (false, true) => findings.push((
+                        Semver::Major,
+                        "is `#[non_exhaustive]` now, which breaks exhaustive matches".to_owned(),
+                    )),
+                    (true, false) => findings
+                        .push((Semver::Minor, "is no longer `#[non_exhaustive]`".to_owned())),
+                    _ => {}
+                }
+                self.compare_generics(&old_enum.generics, &new_enum.generics, true, &mut findings);
+            }
+            (ItemEnum::Variant(old_variant), ItemEnum::Variant(new_variant)) => {
+                let kind = |kind: &types::VariantKind| match kind {
+                    types::VariantKind::Plain => "a unit variant",
+                    types::VariantKind::Tuple(_) => "a tuple variant",
+                    types::VariantKind::Struct { .. } => "a struct variant",
+                };
+                let (old_kind, new_kind) = (kind(&old_variant.kind), kind(&new_variant.kind));
+                if old_kind != new_kind {
+                    findings
+                        .push((Semver::Major, format!("changed from {old_kind} to {new_kind}")));
+                }
+                match (is_non_exhaustive(old), is_non_exhaustive(new)) {
+                    (false, true) => findings.push((
+                        Semver::Major,
+                        "is `#[non_exhaustive]` now, which breaks constructing it outside of its \
+                         crate"
+                            .to_owned(),
+                    )),
+                    (true, false) => findings
+                        .push((Semver::Minor, "is no longer `#[non_exhaustive]`".to_owned())),
+                    _ => {}
+                }
+                let discriminant = |variant: &types::Variant| {
+                    variant.discriminant.as_ref().map(|discriminant| discriminant.value.clone())
+                };
+                if let (Some(old_value), Some(new_value)) =
+                    (discriminant(old_variant), discriminant(new_variant))
+                    && old_value != new_value
+                {
+                    findings.push((
+                        Semver::Major,
+                        format!("the discriminant changed from {old_value} to {new_value}"),
+                    ));
+                }
+            }
+            (ItemEnum::StructField(old_ty), ItemEnum::StructField(new_ty)) => {
+                self.compare_types("the type", old_ty, new_ty, &mut findings)
+            }
+            (ItemEnum::Trait(old_trait), ItemEnum::Trait(new_trait)) => {
+                if old_trait.is_unsafe != new_trait.is_unsafe {
+                    let now = if new_trait.is_unsafe {
+                        "is `unsafe` now"
+                    } else {
+                        "is no longer `unsafe`"
+                    };
+                    findings.push((Semver::Major, format!("{now}, which breaks existing impls")));
+                }
+                if old_trait.is_auto != new_trait.is_auto {
+                    let now = if new_trait.is_auto {
+                        "is an auto trait now"
+                    } else {
+                        "is no longer an auto trait"
+                    };
+                    findings.push((Semver::Major, now.to_owned()));
+                }
+                match (old_trait.is_dyn_compatible, new_trait.is_dyn_compatible) {
+                    (true, false) => findings.push((
+                        Semver::Major,
+                        "is no longer dyn compatible, which breaks `dyn` types of it".to_owned(),
+                    )),
+                    (false, true) => {
+                        findings.push((Semver::Minor, "is dyn compatible now".to_owned()))
+                    }
+                    _ => {}
+                }
+                // Impls have to satisfy new supertraits, and users can't rely on removed ones.
+                let old_bounds: BTreeSet<String> =
+                    old_trait.bounds.iter().map(|bound| self.old.bound(bound)).collect();
+                let new_bounds: BTreeSet<String> =
+                    new_trait.bounds.iter().map(|bound| self.new.bound(bound)).collect();
+                for bound in new_bounds.difference(&old_bounds) {
+                    findings.push((Semver::Major, format!("new supertrait bound `{bound}`")));
+                }
+                for bound in old_bounds.difference(&new_bounds) {
+                    findings.push((Semver::Major, format!("removed supertrait bound `{bound}`")));
+                }
+                self.compare_generics(
+                    &old_trait.generics,
+                    &new_trait.generics,
+                    true,
+                    &mut findings,
+                );
+            }
+            (ItemEnum::TraitAlias(old_alias), ItemEnum::TraitAlias(new_alias)) => {
+                let (old_bounds, new_bounds) =
+                    (self.old.bounds(&old_alias.params), self.new.bounds(&new_alias.params));
+                if old_bounds != new_bounds {
+                    findings.push((
+                        Semver::Major,
+                        format!("the bounds changed from `{old_bounds}` to `{new_bounds}`"),
+                    ));
+                }
+                self.compare_generics(
+                    &old_alias.generics,
+                    &new_alias.generics,
+                    true,
+                    &mut findings,
+                );
+            }
+            (ItemEnum::Impl(old_impl), ItemEnum::Impl(new_impl)) => {
+                // The parameters of impls are not part of the API, only their bounds are.
+                self.compare_bounds(&old_impl.generics, &new_impl.generics, &mut findings);
+            }
+            (ItemEnum::TypeAlias(old_alias), ItemEnum::TypeAlias(new_alias)) => {
+                self.compare_types(
+                    "the aliased type",
+                    &old_alias.type_,
+                    &new_alias.type_,
+                    &mut findings,
+                );
+                self.compare_generics(
+                    &old_alias.generics,
+                    &new_alias.generics,
+                    true,
+                    &mut findings,
+                );
+            }
+            (
+                ItemEnum::Constant { type_: old_ty, const_: old_const },
+                ItemEnum::Constant { type_: new_ty, const_: new_const },
+            ) => {
+                self.compare_types("the type", old_ty, new_ty, &mut findings);
+                let value = |constant: &types::Constant| {
+                    constant.value.clone().unwrap_or_else(|| constant.expr.clone())
+                };
+                let (old_value, new_value) = (value(old_const), value(new_const));
+                if old_value != new_value {
+                    findings.push((
+                        Semver::Patch,
+                        format!("the value changed from `{old_value}` to `{new_value}`"),
+                    ));
+                }
+            }
+            (ItemEnum::Static(old_static), ItemEnum::Static(new_static)) => {
+                self.compare_types("the type", &old_static.type_, &new_static.type_, &mut findings);
+                if old_static.is_mutable != new_static.is_mutable {
+                    let now = if new_static.is_mutable {
+                        "is mutable now"
+                    } else {
+                        "is no longer mutable"
+                    };
+                    findings.push((Semver::Major, now.to_owned()));
+                }
+                match (old_static.is_unsafe, new_static.is_unsafe) {
+                    (false, true) => findings.push((Semver::Major, "is `unsafe` now".to_owned())),
+                    (true, false) => {
+                        findings.push((Semver::Minor, "is no longer `unsafe`".to_owned()))
+                    }
+                    _ => {}
+                }
+            }
+            (
+                ItemEnum::AssocConst { type_: old_ty, value: old_value },
+                ItemEnum::AssocConst { type_: new_ty, value: new_value },
+            ) => {
+                self.compare_types("the type", old_ty, new_ty, &mut findings);
+                match (old_value, new_value) {
+                    (Some(_), None) => findings.push((
+                        Semver::Major,
+                        "no longer has a default, which breaks existing impls".to_owned(),
+                    )),
+                    (None, Some(_)) => {
+                        findings.push((Semver::Minor, "has a default now".to_owned()))
+                    }
+                    (Some(old_value), Some(new_value)) if old_value != new_value => {
+                        findings.push((
+                            Semver::Patch,
+                            format!("the value changed from `{old_value}` to `{new_value}`"),
+                        ))
+                    }
+                    _ => {}
+                }
+            }
+            (
+                ItemEnum::AssocType { generics: old_generics, bounds: old_bounds, type_: old_ty },
+                ItemEnum::AssocType { generics: new_generics, bounds: new_bounds, type_: new_ty },
+            ) => {
+                // Impls have to satisfy new bounds, and users can't rely on removed ones.
+                let old_bounds: BTreeSet<String> =
+                    old_bounds.iter().map(|bound| self.old.bound(bound)).collect();
+                let new_bounds: BTreeSet<String> =
+                    new_bounds.iter().map(|bound| self.new.bound(bound)).collect();
+                for bound in new_bounds.difference(&old_bounds) {
+                    findings.push((Semver::Major, format!("new bound `{bound}`")));
+                }
+                for bound in old_bounds.difference(&new_bounds) {
+                    findings.push((Semver::Major, format!("removed bound `{bound}`")));
+                }
+                match (old_ty, new_ty) {
+                    (Some(_), None) => findings.push((
+                        Semver::Major,
+                        "no longer has a default, which breaks existing impls".to_owned(),
+                    )),
+                    (None, Some(_)) => {
+                        findings.push((Semver::Minor, "has a default now".to_owned()))
+                    }
+                    (Some(old_ty), Some(new_ty)) => {
+                        self.compare_types("the type", old_ty, new_ty, &mut findings)
+                    }
+                    (None, None) => {}
+                }
+                self.compare_generics(old_generics, new_generics, false, &mut findings);
+            }
+            (ItemEnum::Use(old_use), ItemEnum::Use(new_use)) => {
+                if old_use.source != new_use.source {
+                    findings.push((
+                        Semver::Major,
+                        format!("re-exports `{}` instead of `{}`", new_use.source, old_use.source),
+                    ));
+                }
+            }
+            (ItemEnum::Macro(old_macro), ItemEnum::Macro(new_macro)) => {
+                // Only the matchers of the rules are part of the output, but we can't tell
+                // whether rules were added or removed.
+                if old_macro != new_macro {
+                    findings.push((
+                        Semver::Minor,
+                        "the rules changed; check that they still accept all previous inputs"
+                            .to_owned(),
+                    ));
+                }
+            }
+            (ItemEnum::ProcMacro(old_macro), ItemEnum::ProcMacro(new_macro)) => {
+                if old_macro.kind != new_macro.kind {
+                    findings
+                        .push((Semver::Major, "is a different kind of proc macro now".to_owned()));
+                }
+                for helper in &new_macro.helpers {
+                    if !old_macro.helpers.contains(helper) {
+                        findings.push((Semver::Minor, format!("new helper attribute `{helper}`")));
+                    }
+                }
+                for helper in &old_macro.helpers {
+                    if !new_macro.helpers.contains(helper) {
+                        findings
+                            .push((Semver::Major, format!("removed helper attribute `{helper}`")));
+                    }
+                }
+            }
+            (
+                ItemEnum::ExternCrate { name: old_name, .. },
+                ItemEnum::ExternCrate { name: new_name, .. },
+            ) => {
+                if old_name != new_name {
+                    findings.push((
+                        Semver::Major,
+                        format!("refers to the crate `{new_name}` instead of `{old_name}`"),
+                    ));
+                }
+            }
+            (old_inner, new_inner)
+                if std::mem::discriminant(old_inner) != std::mem::discriminant(new_inner) =>
+            {
+                findings.push((
+                    Semver::Major,
+                    format!("changed from a {} to a {}", self.old.kind(old), self.new.kind(new)),
+                ));
+            }
+            // Modules, primitives and extern types have nothing to compare besides their members.
+            _ => {}
+        }
+        findings
+    }
+
+    fn compare_functions(
+        &self,
+        old: &types::Function,
+        new: &types::Function,
+        findings: &mut Findings,
+    ) {
+        let (old_header, new_header) = (&old.header, &new.header);
+        match (old_header.is_const, new_header.is_const) {
+            (true, false) => findings.push((
+                Semver::Major,
+                "is no longer `const`, which breaks calls in constant contexts".to_owned(),
+            )),
+            (false, true) => findings.push((Semver::Minor, "is `const` now".to_owned())),
+            _ => {}
+        }
+        match (old_header.is_unsafe, new_header.is_unsafe) {
+            (false, true) => findings.push((
+                Semver::Major,
+                "is `unsafe` now, which breaks calls outside of `unsafe` blocks".to_owned(),
+            )),
+            (true, false) => findings.push((Semver::Minor, "is no longer `unsafe`".to_owned())),
+            _ => {}
+        }
+        if old_header.is_async != new_header.is_async {
+            let now = if new_header.is_async { "is `async` now" } else { "is no longer `async`" };
+            findings.push((Semver::Major, now.to_owned()));
+        }
+        if old_header.abi != new_header.abi {
+            findings.push((
+                Semver::Major,
+                format!(
+                    "the ABI changed from `extern \"{}\"` to `extern \"{}\"`",
+                    self.old.abi(&old_header.abi),
+                    self.new.abi(&new_header.abi)
+                ),
+            ));
+        }
+
+        // The names of the parameters are not part of the API.
+        let old_inputs: Vec<String> =
+            old.sig.inputs.iter().map(|(_, ty)| self.old.ty(ty)).collect();
+        let new_inputs: Vec<String> =
+            new.sig.inputs.iter().map(|(_, ty)| self.new.ty(ty)).collect();
+        if old_inputs != new_inputs || old.sig.is_c_variadic != new.sig.is_c_variadic {
+            findings.push((
+                Semver::Major,
+                format!(
+                    "the parameters changed from `{}` to `{}`",
+                    self.old
+                        .signature(&types::FunctionSignature { output: None, ..old.sig.clone() }),
+                    self.new
+                        .signature(&types::FunctionSignature { output: None, ..new.sig.clone() })
+                ),
+            ));
+        }
+        let output = |api: &Api<'_>, sig: &types::FunctionSignature| {
+            sig.output.as_ref().map_or_else(|| "()".to_owned(), |output| api.ty(output))
+        };
+        let (old_output, new_output) = (output(&self.old, &old.sig), output(&self.new, &new.sig));
+        if old_output != new_output {
+            findings.push((
+                Semver::Major,
+                format!("the return type changed from `{old_output}` to `{new_output}`"),
+            ));
+        }
+
+        // Only trait methods can lack a body.
+        match (old.has_body, new.has_body) {
+            (true, false) => findings.push((
+                Semver::Major,
+                "no longer has a default implementation, which breaks existing impls".to_owned(),
+            )),
+            (false, true) => {
+                findings.push((Semver::Minor, "has a default implementation now".to_owned()))
+            }
+            _ => {}
+        }
+        self.compare_generics(&old.generics, &new.generics, false, findings);
+    }
+
+    fn compare_types(
+        &self,
+        what: &str,
+        old: &types::Type,
+        new: &types::Type,
+        findings: &mut Findings,
+    ) {
+        let (old, new) = (self.old.ty(old), self.new.ty(new));
+        if old != new {
+            findings.push((Semver::Major, format!("{what} changed from `{old}` to `{new}`")));
+        }
+    }
+
+    /// Compares the generic parameters and their bounds. If `defaults_allowed` is set, new
+    /// parameters with defaults after the existing ones are fine, as they are for types; for
+    /// functions, any new parameter breaks explicitly passed arguments.
+    fn compare_generics(
+        &self,
+        old: &types::Generics,
+        new: &types::Generics,
+        defaults_allowed: bool,
+        findings: &mut Findings,
+    ) {
+        let (old_params, new_params) = (self.old.param_list(old), self.new.param_list(new));
+        if old_params != new_params {
+            let compatible = defaults_allowed
+                && new_params.starts_with(&old_params)
+                && new_params[old_params.len()..].iter().all(|param| param.contains(" = "));
+            findings.push((
+                if compatible { Semver::Minor } else { Semver::Major },
+                format!(
+                    "the generic parameters changed from `<{}>` to `<{}>`",
+                    old_params.join(", "),
+                    new_params.join(", ")
+                ),
+            ));
+        }
+        self.compare_bounds(old, new, findings);
+    }
+
+    /// Compares the bounds of the generic parameters. New bounds break the users that don't
+    /// satisfy them, except for `?Sized`, which relaxes the implicit `Sized` bound.
+    fn compare_bounds(
+        &self,
+        old: &types::Generics,
+        new: &types::Generics,
+        findings: &mut Findings,
+    ) {
+        let (old_bounds, new_bounds) = (self.old.predicates(old), self.new.predicates(new));
+        for bound in new_bounds.difference(&old_bounds) {
+            if bound.contains(": ?") {
+                findings.push((Semver::Minor, format!("relaxed bound `{bound}`")));
+            } else {
+                findings.push((Semver::Major, format!("new trait bound `{bound}`")));
+            }
+        }
+        for bound in old_bounds.difference(&new_bounds) {
+            if bound.contains(": ?") {
+                findings.push((Semver::Major, format!("removed relaxed bound `{bound}`")));
+            } else {
+                findings.push((Semver::Minor, format!("removed trait bound `{bound}`")));
+            }
+        }
+    }
+}
@@ -4,6 +4,7 @@
 //! output. See [the RFC](https://github.com/rust-lang/rfcs/pull/2963) and the [`types`] module
 //! docs for usage and details.
 
+mod api_diff;
 mod conversions;
 mod ids;
 mod import_finder;
@@ -44,6 +45,9 @@ pub(crate) struct JsonRenderer<'tcx> {
     ///
     /// If this is `None`, the blob will be printed to `stdout` instead.
     out_dir: Option<PathBuf>,
+    /// With `--output-format=api-diff`, the JSON output of a previous version of the crate. The
+    /// crate is compared against it instead of being written out.
+    api_diff_baseline: Option<PathBuf>,
     cache: Rc<Cache>,
     imported_items: DefIdSet,
     id_interner: RefCell<ids::IdInterner>,
@@ -122,6 +126,7 @@ pub(crate) fn init(
                 tcx,
                 index: FxHashMap::default(),
                 out_dir: if options.output_to_stdout { None } else { Some(options.output) },
+                api_diff_baseline: options.api_diff_baseline,
                 cache: Rc::new(cache),
                 imported_items,
                 id_interner: Default::default(),
@@ -299,6 +304,11 @@ fn after_krate(self) -> Result<(), Error> {
             target,
             format_version: types::FORMAT_VERSION,
         };
+        if let Some(ref baseline) = self.api_diff_baseline {
+            return sess.time("rustdoc_json_api_diff", || {
+                api_diff::write_api_diff(sess, baseline, &output_crate, self.out_dir.as_deref())
+            });
+        }
         if let Some(ref out_dir) = self.out_dir {
             try_err!(create_dir_all(out_dir), out_dir);
 
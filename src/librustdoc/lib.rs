@@ -121,6 +121,7 @@ macro_rules! map {
 mod markdown;
 mod passes;
 mod scrape_examples;
+mod text;
 mod theme;
 mod visit;
 mod visit_ast;
@@ -492,6 +493,41 @@ fn opts() -> Vec<RustcOptGroup> {
             "calculate percentage of public items with documentation",
             "",
         ),
+        opt(
+            Unstable,
+            Opt,
+            "",
+            "coverage-min",
+            "with --show-coverage, fail if less than PCT percent of the items are documented",
+            "PCT",
+        ),
+        opt(
+            Unstable,
+            Opt,
+            "",
+            "coverage-min-examples",
+            "with --show-coverage, fail if less than PCT percent of the items that should have \
+                examples have one",
+            "PCT",
+        ),
+        opt(
+            Unstable,
+            FlagMulti,
+            "",
+            "coverage-details",
+            "with --show-coverage, break the counts down by module and kind of item, and list \
+                the undocumented items",
+            "",
+        ),
+        opt(
+            Unstable,
+            Opt,
+            "",
+            "baseline",
+            "JSON output of a previous version of the crate to compare against with \
+                --output-format=api-diff",
+            "PATH",
+        ),
         opt(
             Stable,
             Opt,
@@ -892,6 +928,7 @@ fn main_args(early_dcx: &mut EarlyDiagCtxt, at_args: &[String]) {
     // need to move these items separately because we lose them by the time the closure is called,
     // but we can't create the dcx ahead of time because it's not Send
     let show_coverage = options.show_coverage;
+    let coverage_options = options.coverage_options;
     let run_check = options.run_check;
 
     // First, parse the crate and extract all relevant information.
@@ -931,9 +968,15 @@ fn main_args(early_dcx: &mut EarlyDiagCtxt, at_args: &[String]) {
                 sess.dcx().fatal("Compilation failed, aborting rustdoc");
             }
 
-            let (krate, render_opts, mut cache, expanded_macros) = sess
-                .time("run_global_ctxt", || {
-                    core::run_global_ctxt(tcx, show_coverage, render_options, output_format)
+            let (krate, render_opts, mut cache, expanded_macros) =
+                sess.time("run_global_ctxt", || {
+                    core::run_global_ctxt(
+                        tcx,
+                        show_coverage,
+                        coverage_options,
+                        render_options,
+                        output_format,
+                    )
                 });
             info!("finished with rustc");
 
@@ -983,8 +1026,16 @@ fn main_args(early_dcx: &mut EarlyDiagCtxt, at_args: &[String]) {
                         },
                     )
                 }),
-                config::OutputFormat::Json => sess.time("render_json", || {
-                    run_renderer(krate, render_opts, cache, tcx, json::JsonRenderer::init)
+                config::OutputFormat::Json | config::OutputFormat::ApiDiff => {
+                    sess.time("render_json", || {
+                        run_renderer(krate, render_opts, cache, tcx, json::JsonRenderer::init)
+                    })
+                }
+                config::OutputFormat::Markdown => sess.time("render_markdown", || {
+                    run_renderer(krate, render_opts, cache, tcx, text::MarkdownRenderer::init)
+                }),
+                config::OutputFormat::Man => sess.time("render_man", || {
+                    run_renderer(krate, render_opts, cache, tcx, text::ManRenderer::init)
                 }),
                 // Already handled above with doctest runners.
                 config::OutputFormat::Doctest => unreachable!(),
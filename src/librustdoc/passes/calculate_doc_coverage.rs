@@ -3,16 +3,19 @@
 use std::collections::BTreeMap;
 use std::ops;
 
+use rustc_errors::DiagCtxtHandle;
 use rustc_hir as hir;
+use rustc_hir::def_id::{DefId, LOCAL_CRATE};
 use rustc_lint::builtin::MISSING_DOCS;
 use rustc_middle::lint::{LevelAndSource, LintLevelSource};
-use rustc_session::lint;
+use rustc_session::{getopts, lint};
 use rustc_span::{FileName, RemapPathScopeComponents};
 use serde::Serialize;
 use tracing::debug;
 
 use crate::clean;
 use crate::core::DocContext;
+use crate::formats::item_type::ItemType;
 use crate::html::markdown::{ErrorCodes, find_testable_code};
 use crate::passes::Pass;
 use crate::passes::check_doc_test_visibility::{Tests, should_have_doc_example};
@@ -24,11 +27,66 @@
     description: "counts the number of items with and without documentation",
 };
 
+/// The options of `--show-coverage`.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct CoverageOptions {
+    /// The percentage of documented items below which the coverage fails, from `--coverage-min`.
+    min: Option<f64>,
+    /// The percentage of items with examples below which the coverage fails, from
+    /// `--coverage-min-examples`.
+    min_examples: Option<f64>,
+    /// Whether to break the counts down by module and kind of item, and list the undocumented
+    /// items, from `--coverage-details`.
+    details: bool,
+}
+
+impl CoverageOptions {
+    pub(crate) fn new(
+        matches: &getopts::Matches,
+        dcx: DiagCtxtHandle<'_>,
+        show_coverage: bool,
+    ) -> Self {
+        let parse_percentage = |name: &str| {
+            let value = matches.opt_str(name)?;
+            match value.strip_suffix('%').unwrap_or(&value).parse::<f64>() {
+                Ok(percentage) if (0.0..=100.0).contains(&percentage) => Some(percentage),
+                _ => dcx.fatal(format!(
+                    "`--{name}` must be a percentage between 0 and 100, found `{value}`"
+                )),
+            }
+        };
+        let options = CoverageOptions {
+            min: parse_percentage("coverage-min"),
+            min_examples: parse_percentage("coverage-min-examples"),
+            details: matches.opt_present("coverage-details"),
+        };
+        if !show_coverage {
+            for (name, is_set) in [
+                ("coverage-min", options.min.is_some()),
+                ("coverage-min-examples", options.min_examples.is_some()),
+                ("coverage-details", options.details),
+            ] {
+                if is_set {
+                    dcx.fatal(format!("the `--{name}` flag requires `--show-coverage`"));
+                }
+            }
+        }
+        options
+    }
+}
+
 fn calculate_doc_coverage(krate: clean::Crate, ctx: &mut DocContext<'_>) -> clean::Crate {
-    let mut calc = CoverageCalculator { items: Default::default(), ctx };
+    let mut calc = CoverageCalculator {
+        items: Default::default(),
+        modules: Default::default(),
+        kinds: Default::default(),
+        undocumented: Vec::new(),
+        ctx,
+    };
     calc.visit_crate(&krate);
 
     calc.print_results();
+    calc.check_thresholds();
 
     krate
 }
@@ -103,8 +161,22 @@ fn add_assign(&mut self, rhs: Self) {
     }
 }
 
+/// An item that should be documented but isn't, listed with `--coverage-details`.
+#[derive(Serialize, Debug)]
+struct UndocumentedItem {
+    path: String,
+    kind: &'static str,
+    file: String,
+    line: usize,
+}
+
 struct CoverageCalculator<'a, 'b> {
     items: BTreeMap<FileName, ItemCount>,
+    /// The counts of each module, by path. Only filled with `--coverage-details`, like the
+    /// fields below.
+    modules: BTreeMap<String, ItemCount>,
+    kinds: BTreeMap<ItemType, ItemCount>,
+    undocumented: Vec<UndocumentedItem>,
     ctx: &'a mut DocContext<'b>,
 }
 
@@ -118,15 +190,71 @@ fn limit_filename_len(filename: String) -> String {
     }
 }
 
+fn print_table_line() {
+    println!("+-{0:->35}-+-{0:->10}-+-{0:->10}-+-{0:->10}-+-{0:->10}-+", "");
+}
+
+fn print_table_header(first_column: &str) {
+    print_table_line();
+    println!(
+        "| {:<35} | {:>10} | {:>10} | {:>10} | {:>10} |",
+        first_column, "Documented", "Percentage", "Examples", "Percentage",
+    );
+    print_table_line();
+}
+
+fn print_table_record(name: &str, count: ItemCount, percentage: f64, examples_percentage: f64) {
+    println!(
+        "| {name:<35} | {with_docs:>10} | {percentage:>9.1}% | {with_examples:>10} | \
+        {examples_percentage:>9.1}% |",
+        with_docs = count.with_docs,
+        with_examples = count.with_examples,
+    );
+}
+
+/// Prints the counts of each module or kind of item, with `--coverage-details`.
+fn print_breakdown(first_column: &str, rows: impl Iterator<Item = (String, ItemCount)>) {
+    println!();
+    print_table_header(first_column);
+    for (name, count) in rows {
+        if let Some(percentage) = count.percentage() {
+            print_table_record(
+                &limit_filename_len(name),
+                count,
+                percentage,
+                count.examples_percentage().unwrap_or(0.),
+            );
+        }
+    }
+    print_table_line();
+}
+
+/// The JSON output with `--coverage-details`.
+#[derive(Serialize)]
+struct CoverageDetails<'a> {
+    files: BTreeMap<String, &'a ItemCount>,
+    modules: &'a BTreeMap<String, ItemCount>,
+    kinds: BTreeMap<&'static str, &'a ItemCount>,
+    undocumented: &'a [UndocumentedItem],
+}
+
 impl CoverageCalculator<'_, '_> {
     fn to_json(&self) -> String {
-        serde_json::to_string(
-            &self
-                .items
-                .iter()
-                .map(|(k, v)| (k.display(RemapPathScopeComponents::COVERAGE).to_string(), v))
-                .collect::<BTreeMap<String, &ItemCount>>(),
-        )
+        let files = self
+            .items
+            .iter()
+            .map(|(k, v)| (k.display(RemapPathScopeComponents::COVERAGE).to_string(), v))
+            .collect::<BTreeMap<String, &ItemCount>>();
+        if self.ctx.coverage_options.details {
+            serde_json::to_string(&CoverageDetails {
+                files,
+                modules: &self.modules,
+                kinds: self.kinds.iter().map(|(kind, count)| (kind.as_str(), count)).collect(),
+                undocumented: &self.undocumented,
+            })
+        } else {
+            serde_json::to_string(&files)
+        }
         .expect("failed to convert JSON data to string")
     }
 
@@ -139,30 +267,7 @@ fn print_results(&self) {
         }
         let mut total = ItemCount::default();
 
-        fn print_table_line() {
-            println!("+-{0:->35}-+-{0:->10}-+-{0:->10}-+-{0:->10}-+-{0:->10}-+", "");
-        }
-
-        fn print_table_record(
-            name: &str,
-            count: ItemCount,
-            percentage: f64,
-            examples_percentage: f64,
-        ) {
-            println!(
-                "| {name:<35} | {with_docs:>10} | {percentage:>9.1}% | {with_examples:>10} | \
-                {examples_percentage:>9.1}% |",
-                with_docs = count.with_docs,
-                with_examples = count.with_examples,
-            );
-        }
-
-        print_table_line();
-        println!(
-            "| {:<35} | {:>10} | {:>10} | {:>10} | {:>10} |",
-            "File", "Documented", "Percentage", "Examples", "Percentage",
-        );
-        print_table_line();
+        print_table_header("File");
 
         for (file, &count) in &self.items {
             if let Some(percentage) = count.percentage() {
@@ -187,6 +292,91 @@ fn print_table_record(
             total.examples_percentage().unwrap_or(0.0),
         );
         print_table_line();
+
+        if !self.ctx.coverage_options.details {
+            return;
+        }
+        print_breakdown("Module", self.modules.iter().map(|(path, &count)| (path.clone(), count)));
+        print_breakdown(
+            "Kind",
+            self.kinds.iter().map(|(kind, &count)| (kind.as_str().to_owned(), count)),
+        );
+        if !self.undocumented.is_empty() {
+            println!();
+            println!("Undocumented items:");
+            for item in &self.undocumented {
+                println!("  {} ({}) at {}:{}", item.path, item.kind, item.file, item.line);
+            }
+        }
+    }
+
+    /// Emits an error if the coverage is below the minimums given with `--coverage-min` and
+    /// `--coverage-min-examples`.
+    fn check_thresholds(&self) {
+        let CoverageOptions { min, min_examples, .. } = self.ctx.coverage_options;
+        let mut total = ItemCount::default();
+        for &count in self.items.values() {
+            total += count;
+        }
+
+        let dcx = self.ctx.tcx.dcx();
+        if let Some(min) = min
+            && let Some(percentage) = total.percentage()
+            && percentage < min
+        {
+            dcx.err(format!(
+                "{percentage:.1}% of the items are documented, below the minimum of {min}% set \
+                 with `--coverage-min`"
+            ));
+        }
+        if let Some(min) = min_examples
+            && let Some(percentage) = total.examples_percentage()
+            && percentage < min
+        {
+            dcx.err(format!(
+                "{percentage:.1}% of the items that should have examples have one, below the \
+                 minimum of {min}% set with `--coverage-min-examples`"
+            ));
+        }
+    }
+
+    /// The path of a local item, starting with the crate name.
+    fn path(&self, def_id: DefId) -> String {
+        let tcx = self.ctx.tcx;
+        let krate = tcx.crate_name(LOCAL_CRATE);
+        if def_id.is_crate_root() {
+            krate.to_string()
+        } else {
+            format!("{krate}::{}", tcx.def_path_str(def_id))
+        }
+    }
+
+    /// Counts `item` in its module and kind, and lists it if it's missing its docs.
+    fn count_details(
+        &mut self,
+        item: &clean::Item,
+        count: impl Fn(&mut ItemCount),
+        undocumented_at: Option<(String, usize)>,
+    ) {
+        let def_id = item.item_id.expect_def_id();
+        let module = if item.is_mod() {
+            def_id
+        } else {
+            self.ctx.tcx.parent_module_from_def_id(def_id.expect_local()).to_def_id()
+        };
+        let module = self.path(module);
+        count(self.modules.entry(module).or_default());
+        count(self.kinds.entry(item.type_()).or_default());
+
+        if let Some((file, line)) = undocumented_at {
+            let path = self.path(def_id);
+            self.undocumented.push(UndocumentedItem {
+                path,
+                kind: item.type_().as_str(),
+                file,
+                line,
+            });
+        }
     }
 }
 
@@ -263,12 +453,23 @@ fn visit_item(&mut self, i: &clean::Item) {
                 if let Some(span) = i.span(self.ctx.tcx) {
                     let filename = span.filename(self.ctx.sess());
                     debug!("counting {:?} {:?} in {filename:?}", i.type_(), i.name);
-                    self.items.entry(filename).or_default().count_item(
-                        has_docs,
-                        has_doc_example,
-                        should_have_doc_example(self.ctx, i),
-                        should_have_docs,
-                    );
+                    let should_have_doc_examples = should_have_doc_example(self.ctx, i);
+                    let count = |counts: &mut ItemCount| {
+                        counts.count_item(
+                            has_docs,
+                            has_doc_example,
+                            should_have_doc_examples,
+                            should_have_docs,
+                        )
+                    };
+                    if self.ctx.coverage_options.details {
+                        let undocumented_at = (should_have_docs && !has_docs).then(|| {
+                            let file = filename.display(RemapPathScopeComponents::COVERAGE);
+                            (file.to_string(), span.lo(self.ctx.sess()).line)
+                        });
+                        self.count_details(i, count, undocumented_at);
+                    }
+                    count(self.items.entry(filename).or_default());
                 }
             }
         }
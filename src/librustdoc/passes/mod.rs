@@ -36,7 +36,7 @@
 pub(crate) use self::collect_trait_impls::COLLECT_TRAIT_IMPLS;
 
 mod calculate_doc_coverage;
-pub(crate) use self::calculate_doc_coverage::CALCULATE_DOC_COVERAGE;
+pub(crate) use self::calculate_doc_coverage::{CALCULATE_DOC_COVERAGE, CoverageOptions};
 
 mod lint;
 pub(crate) use self::lint::RUN_LINTS;
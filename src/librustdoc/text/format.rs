@@ -0,0 +1,791 @@
+//! Plain-text formatting of `clean` types.
+//!
+//! This mirrors [`crate::html::format`], but prints the items the way they'd be written in Rust
+//! source: there are no links to insert and nothing to escape, so nothing here needs an HTML
+//! [`Context`](crate::html::render::Context).
+
+use std::fmt::{self, Display, Write};
+
+use itertools::Either;
+use rustc_abi::ExternAbi;
+use rustc_data_structures::fx::FxHashSet;
+use rustc_hir as hir;
+use rustc_hir::def::CtorKind;
+use rustc_middle::ty::{self, TyCtxt};
+use rustc_span::hygiene::MacroKind;
+use rustc_span::symbol::kw;
+
+use crate::clean::utils::find_nearest_parent_module;
+use crate::clean::{self, ItemKind};
+use crate::display::{Joined as _, MaybeDisplay as _, Wrapped};
+use crate::html::format::{PrintWithSpace, print_constness_with_space, print_lifetime};
+
+fn print_generic_bounds(bounds: &[clean::GenericBound], tcx: TyCtxt<'_>) -> impl Display {
+    fmt::from_fn(move |f| {
+        let mut bounds_dup = FxHashSet::default();
+
+        bounds
+            .iter()
+            .filter(move |b| bounds_dup.insert(*b))
+            .map(|bound| print_generic_bound(bound, tcx))
+            .joined(" + ", f)
+    })
+}
+
+fn print_generic_param_def(
+    generic_param: &clean::GenericParamDef,
+    tcx: TyCtxt<'_>,
+) -> impl Display {
+    fmt::from_fn(move |f| match &generic_param.kind {
+        clean::GenericParamDefKind::Lifetime { outlives } => {
+            write!(f, "{}", generic_param.name)?;
+            if !outlives.is_empty() {
+                f.write_str(": ")?;
+                outlives.iter().map(|lt| print_lifetime(lt)).joined(" + ", f)?;
+            }
+            Ok(())
+        }
+        clean::GenericParamDefKind::Type { bounds, default, .. } => {
+            f.write_str(generic_param.name.as_str())?;
+            if !bounds.is_empty() {
+                write!(f, ": {}", print_generic_bounds(bounds, tcx))?;
+            }
+            if let Some(ty) = default {
+                write!(f, " = {}", print_type(ty, tcx))?;
+            }
+            Ok(())
+        }
+        clean::GenericParamDefKind::Const { ty, default, .. } => {
+            write!(f, "const {}: {}", generic_param.name, print_type(ty, tcx))?;
+            if let Some(default) = default {
+                write!(f, " = {default}")?;
+            }
+            Ok(())
+        }
+    })
+}
+
+pub(super) fn print_generics(generics: &clean::Generics, tcx: TyCtxt<'_>) -> impl Display {
+    let mut real_params = generics.params.iter().filter(|p| !p.is_synthetic_param()).peekable();
+    if real_params.peek().is_none() {
+        None
+    } else {
+        Some(Wrapped::with_angle_brackets().wrap_fn(move |f| {
+            real_params.clone().map(|g| print_generic_param_def(g, tcx)).joined(", ", f)
+        }))
+    }
+    .maybe_display()
+}
+
+fn print_higher_ranked_params_with_space(
+    params: &[clean::GenericParamDef],
+    tcx: TyCtxt<'_>,
+    keyword: &'static str,
+) -> impl Display {
+    fmt::from_fn(move |f| {
+        if !params.is_empty() {
+            f.write_str(keyword)?;
+            Wrapped::with_angle_brackets()
+                .wrap_fn(|f| params.iter().map(|p| print_generic_param_def(p, tcx)).joined(", ", f))
+                .fmt(f)?;
+            f.write_char(' ')?;
+        }
+        Ok(())
+    })
+}
+
+fn print_where_predicate(predicate: &clean::WherePredicate, tcx: TyCtxt<'_>) -> impl Display {
+    fmt::from_fn(move |f| match predicate {
+        clean::WherePredicate::BoundPredicate { ty, bounds, bound_params } => {
+            print_higher_ranked_params_with_space(bound_params, tcx, "for").fmt(f)?;
+            write!(f, "{}:", print_type(ty, tcx))?;
+            if !bounds.is_empty() {
+                write!(f, " {}", print_generic_bounds(bounds, tcx))?;
+            }
+            Ok(())
+        }
+        clean::WherePredicate::RegionPredicate { lifetime, bounds } => {
+            write!(f, "{}:", print_lifetime(lifetime))?;
+            if !bounds.is_empty() {
+                write!(f, " {}", print_generic_bounds(bounds, tcx))?;
+            }
+            Ok(())
+        }
+        clean::WherePredicate::EqPredicate { lhs, rhs } => {
+            write!(f, "{} == {}", print_qpath_data(lhs, tcx), print_term(rhs, tcx))
+        }
+    })
+}
+
+/// How a where-clause is laid out by [`print_where_clause`].
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(super) enum WhereClause {
+    /// ` where A: B, C: D`, for headings that have to fit on a single line.
+    Inline,
+    /// `\nwhere\n    A: B,\n    C: D,\n`, the way rustfmt lays out item declarations.
+    Block,
+}
+
+pub(super) fn print_where_clause(
+    generics: &clean::Generics,
+    tcx: TyCtxt<'_>,
+    style: WhereClause,
+) -> impl Display {
+    fmt::from_fn(move |f| {
+        if generics.where_predicates.is_empty() {
+            return Ok(());
+        }
+        match style {
+            WhereClause::Inline => {
+                f.write_str(" where ")?;
+                generics
+                    .where_predicates
+                    .iter()
+                    .map(|predicate| print_where_predicate(predicate, tcx))
+                    .joined(", ", f)
+            }
+            WhereClause::Block => {
+                f.write_str("\nwhere\n")?;
+                for predicate in &generics.where_predicates {
+                    writeln!(f, "    {},", print_where_predicate(predicate, tcx))?;
+                }
+                Ok(())
+            }
+        }
+    })
+}
+
+fn print_poly_trait(poly_trait: &clean::PolyTrait, tcx: TyCtxt<'_>) -> impl Display {
+    fmt::from_fn(move |f| {
+        print_higher_ranked_params_with_space(&poly_trait.generic_params, tcx, "for").fmt(f)?;
+        print_path(&poly_trait.trait_, tcx).fmt(f)
+    })
+}
+
+fn print_generic_bound(generic_bound: &clean::GenericBound, tcx: TyCtxt<'_>) -> impl Display {
+    fmt::from_fn(move |f| match generic_bound {
+        clean::GenericBound::Outlives(lt) => f.write_str(print_lifetime(lt)),
+        clean::GenericBound::TraitBound(ty, modifiers) => {
+            // `const` and `[const]` trait bounds are experimental; don't render them.
+            let hir::TraitBoundModifiers { polarity, constness: _ } = modifiers;
+            f.write_str(match polarity {
+                hir::BoundPolarity::Positive => "",
+                hir::BoundPolarity::Maybe(_) => "?",
+                hir::BoundPolarity::Negative(_) => "!",
+            })?;
+            print_poly_trait(ty, tcx).fmt(f)
+        }
+        clean::GenericBound::Use(args) => {
+            f.write_str("use")?;
+            Wrapped::with_angle_brackets()
+                .wrap_fn(|f| args.iter().map(|arg| arg.name()).joined(", ", f))
+                .fmt(f)
+        }
+    })
+}
+
+fn print_generic_args(generic_args: &clean::GenericArgs, tcx: TyCtxt<'_>) -> impl Display {
+    fmt::from_fn(move |f| {
+        match generic_args {
+            clean::GenericArgs::AngleBracketed { args, constraints } => {
+                if !args.is_empty() || !constraints.is_empty() {
+                    Wrapped::with_angle_brackets()
+                        .wrap_fn(|f| {
+                            args.iter()
+                                .map(Either::Left)
+                                .chain(constraints.iter().map(Either::Right))
+                                .map(|either| {
+                                    either.map_either(
+                                        |arg| print_generic_arg(arg, tcx),
+                                        |constraint| print_assoc_item_constraint(constraint, tcx),
+                                    )
+                                })
+                                .joined(", ", f)
+                        })
+                        .fmt(f)?;
+                }
+            }
+            clean::GenericArgs::Parenthesized { inputs, output } => {
+                Wrapped::with_parens()
+                    .wrap_fn(|f| inputs.iter().map(|ty| print_type(ty, tcx)).joined(", ", f))
+                    .fmt(f)?;
+                if let Some(ty) = output {
+                    write!(f, " -> {}", print_type(ty, tcx))?;
+                }
+            }
+            clean::GenericArgs::ReturnTypeNotation => f.write_str("(..)")?,
+        }
+        Ok(())
+    })
+}
+
+fn print_generic_arg(generic_arg: &clean::GenericArg, tcx: TyCtxt<'_>) -> impl Display {
+    fmt::from_fn(move |f| match generic_arg {
+        clean::GenericArg::Lifetime(lt) => f.write_str(print_lifetime(lt)),
+        clean::GenericArg::Type(ty) => print_type(ty, tcx).fmt(f),
+        clean::GenericArg::Const(ct) => f.write_str(&ct.expr(tcx)),
+        clean::GenericArg::Infer => f.write_char('_'),
+    })
+}
+
+fn print_term(term: &clean::Term, tcx: TyCtxt<'_>) -> impl Display {
+    fmt::from_fn(move |f| match term {
+        clean::Term::Type(ty) => print_type(ty, tcx).fmt(f),
+        clean::Term::Constant(ct) => f.write_str(&ct.expr(tcx)),
+    })
+}
+
+fn print_assoc_item_constraint(
+    constraint: &clean::AssocItemConstraint,
+    tcx: TyCtxt<'_>,
+) -> impl Display {
+    fmt::from_fn(move |f| {
+        write!(f, "{}{}", constraint.assoc.name, print_generic_args(&constraint.assoc.args, tcx))?;
+        match &constraint.kind {
+            clean::AssocItemConstraintKind::Equality { term } => {
+                write!(f, " = {}", print_term(term, tcx))
+            }
+            clean::AssocItemConstraintKind::Bound { bounds } if !bounds.is_empty() => {
+                write!(f, ": {}", print_generic_bounds(bounds, tcx))
+            }
+            clean::AssocItemConstraintKind::Bound { .. } => Ok(()),
+        }
+    })
+}
+
+/// Prints the last segment of `path` with its generic arguments, like the HTML output does.
+pub(super) fn print_path(path: &clean::Path, tcx: TyCtxt<'_>) -> impl Display {
+    fmt::from_fn(move |f| {
+        // Paths like `T::Output` and `Self::Output` are printed with all segments.
+        if path.is_assoc_ty() {
+            for seg in &path.segments[..path.segments.len() - 1] {
+                write!(f, "{}::", if seg.name == kw::PathRoot { "" } else { seg.name.as_str() })?;
+            }
+        }
+        let last = path.segments.last().unwrap();
+        write!(f, "{}{}", last.name, print_generic_args(&last.args, tcx))
+    })
+}
+
+fn print_qpath_data(qpath_data: &clean::QPathData, tcx: TyCtxt<'_>) -> impl Display {
+    fmt::from_fn(move |f| {
+        let clean::QPathData { assoc, self_type, should_fully_qualify, trait_ } = qpath_data;
+        if let Some(trait_) = trait_
+            && *should_fully_qualify
+        {
+            write!(f, "<{} as {}>", print_type(self_type, tcx), print_path(trait_, tcx))?;
+        } else {
+            print_type(self_type, tcx).fmt(f)?;
+        }
+        write!(f, "::{}{}", assoc.name, print_generic_args(&assoc.args, tcx))
+    })
+}
+
+fn print_abi_with_space(abi: ExternAbi) -> impl Display {
+    fmt::from_fn(move |f| match abi {
+        ExternAbi::Rust => Ok(()),
+        abi => write!(f, "extern \"{}\" ", abi.name()),
+    })
+}
+
+pub(super) fn print_type(type_: &clean::Type, tcx: TyCtxt<'_>) -> impl Display {
+    fmt::from_fn(move |f| match type_ {
+        clean::Generic(name) => f.write_str(name.as_str()),
+        clean::SelfTy => f.write_str("Self"),
+        clean::Type::Path { path } => print_path(path, tcx).fmt(f),
+        clean::DynTrait(bounds, lt) => {
+            f.write_str("dyn ")?;
+            bounds.iter().map(|bound| print_poly_trait(bound, tcx)).joined(" + ", f)?;
+            if let Some(lt) = lt {
+                write!(f, " + {}", print_lifetime(lt))?;
+            }
+            Ok(())
+        }
+        clean::Infer => f.write_char('_'),
+        clean::Primitive(clean::PrimitiveType::Never) => f.write_char('!'),
+        clean::Primitive(prim) => f.write_str(prim.as_sym().as_str()),
+        clean::BareFunction(decl) => {
+            print_higher_ranked_params_with_space(&decl.generic_params, tcx, "for").fmt(f)?;
+            write!(
+                f,
+                "{}{}fn{}",
+                decl.safety.print_with_space(),
+                print_abi_with_space(decl.abi),
+                print_fn_decl(&decl.decl, tcx),
+            )
+        }
+        clean::UnsafeBinder(binder) => {
+            print_higher_ranked_params_with_space(&binder.generic_params, tcx, "unsafe").fmt(f)?;
+            print_type(&binder.ty, tcx).fmt(f)
+        }
+        clean::Tuple(types) => match &types[..] {
+            [one] => write!(f, "({},)", print_type(one, tcx)),
+            many => Wrapped::with_parens()
+                .wrap_fn(|f| many.iter().map(|ty| print_type(ty, tcx)).joined(", ", f))
+                .fmt(f),
+        },
+        clean::Slice(ty) => write!(f, "[{}]", print_type(ty, tcx)),
+        clean::Array(ty, n) => write!(f, "[{}; {n}]", print_type(ty, tcx)),
+        clean::Type::Pat(ty, pat) => write!(f, "{} is {pat}", print_type(ty, tcx)),
+        clean::Type::FieldOf(ty, field) => {
+            write!(f, "field_of!({}, {field})", print_type(ty, tcx))
+        }
+        clean::RawPointer(m, ty) => write!(f, "*{} {}", m.ptr_str(), print_type(ty, tcx)),
+        clean::BorrowedRef { lifetime, mutability, type_ } => {
+            f.write_char('&')?;
+            if let Some(lt) = lifetime {
+                write!(f, "{} ", print_lifetime(lt))?;
+            }
+            let needs_parens = match **type_ {
+                clean::DynTrait(ref bounds, ref trait_lt) => bounds.len() > 1 || trait_lt.is_some(),
+                clean::ImplTrait(ref bounds) => bounds.len() > 1,
+                _ => false,
+            };
+            write!(
+                f,
+                "{}{}",
+                mutability.print_with_space(),
+                Wrapped::with_parens().when(needs_parens).wrap(print_type(type_, tcx)),
+            )
+        }
+        clean::ImplTrait(bounds) => write!(f, "impl {}", print_generic_bounds(bounds, tcx)),
+        clean::QPath(qpath) => print_qpath_data(qpath, tcx).fmt(f),
+    })
+}
+
+fn print_parameter(parameter: &clean::Parameter, tcx: TyCtxt<'_>) -> impl Display {
+    fmt::from_fn(move |f| {
+        if let Some(self_ty) = parameter.to_receiver() {
+            match self_ty {
+                clean::SelfTy => f.write_str("self"),
+                clean::BorrowedRef { lifetime, mutability, type_: box clean::SelfTy } => {
+                    f.write_char('&')?;
+                    if let Some(lt) = lifetime {
+                        write!(f, "{} ", print_lifetime(lt))?;
+                    }
+                    write!(f, "{}self", mutability.print_with_space())
+                }
+                _ => write!(f, "self: {}", print_type(self_ty, tcx)),
+            }
+        } else {
+            if parameter.is_const {
+                f.write_str("const ")?;
+            }
+            if let Some(name) = parameter.name {
+                write!(f, "{name}: ")?;
+            }
+            print_type(&parameter.type_, tcx).fmt(f)
+        }
+    })
+}
+
+fn print_fn_decl(decl: &clean::FnDecl, tcx: TyCtxt<'_>) -> impl Display {
+    fmt::from_fn(move |f| {
+        Wrapped::with_parens()
+            .wrap_fn(|f| {
+                decl.inputs.iter().map(|param| print_parameter(param, tcx)).joined(", ", f)?;
+                if decl.c_variadic { f.write_str(", ...") } else { Ok(()) }
+            })
+            .fmt(f)?;
+        if !decl.output.is_unit() {
+            write!(f, " -> {}", print_type(&decl.output, tcx))?;
+        }
+        Ok(())
+    })
+}
+
+pub(super) fn print_impl(impl_: &clean::Impl, tcx: TyCtxt<'_>) -> impl Display {
+    fmt::from_fn(move |f| {
+        write!(
+            f,
+            "{}impl{} ",
+            impl_.safety.print_with_space(),
+            print_generics(&impl_.generics, tcx)
+        )?;
+        if let Some(trait_) = &impl_.trait_ {
+            if impl_.is_negative_trait_impl() {
+                f.write_char('!')?;
+            }
+            write!(f, "{} for ", print_path(trait_, tcx))?;
+        }
+        let for_ = impl_.kind.as_blanket_ty().unwrap_or(&impl_.for_);
+        write!(
+            f,
+            "{}{}",
+            print_type(for_, tcx),
+            print_where_clause(&impl_.generics, tcx, WhereClause::Inline),
+        )
+    })
+}
+
+fn print_import(import: &clean::Import) -> impl Display {
+    fmt::from_fn(move |f| {
+        let source = fmt::from_fn(|f| {
+            import
+                .source
+                .path
+                .segments
+                .iter()
+                .map(|seg| if seg.name == kw::PathRoot { "" } else { seg.name.as_str() })
+                .joined("::", f)
+        });
+        match import.kind {
+            clean::ImportKind::Simple(name) if name == import.source.path.last() => {
+                write!(f, "use {source};")
+            }
+            clean::ImportKind::Simple(name) => write!(f, "use {source} as {name};"),
+            clean::ImportKind::Glob if import.source.path.segments.is_empty() => {
+                f.write_str("use *;")
+            }
+            clean::ImportKind::Glob => write!(f, "use {source}::*;"),
+        }
+    })
+}
+
+pub(super) fn print_visibility_with_space(item: &clean::Item, tcx: TyCtxt<'_>) -> impl Display {
+    fmt::from_fn(move |f| {
+        let Some(vis) = item.visibility(tcx) else {
+            return Ok(());
+        };
+        match vis {
+            ty::Visibility::Public => f.write_str("pub "),
+            ty::Visibility::Restricted(vis_did) => {
+                let parent_module = find_nearest_parent_module(tcx, item.item_id.expect_def_id());
+                if vis_did.is_crate_root() {
+                    f.write_str("pub(crate) ")
+                } else if parent_module == Some(vis_did) {
+                    // `pub(in foo)` where `foo` is the parent module
+                    // is the same as no visibility modifier; do nothing
+                    Ok(())
+                } else if parent_module.and_then(|parent| find_nearest_parent_module(tcx, parent))
+                    == Some(vis_did)
+                {
+                    f.write_str("pub(super) ")
+                } else {
+                    write!(f, "pub(in {}) ", tcx.def_path_str(vis_did))
+                }
+            }
+        }
+    })
+}
+
+/// Prints the header of a function-like item: everything up to and including its where-clause.
+fn print_fn_item(
+    item: &clean::Item,
+    func: &clean::Function,
+    tcx: TyCtxt<'_>,
+    where_clause: WhereClause,
+) -> impl Display {
+    fmt::from_fn(move |f| {
+        let header = item.fn_header(tcx).expect("function items always have a header");
+        write!(
+            f,
+            "{vis}{constness}{asyncness}{safety}{abi}fn {name}{generics}{decl}{where_clause}",
+            vis = print_visibility_with_space(item, tcx),
+            constness = print_constness_with_space(
+                &header.constness,
+                item.stable_since(tcx),
+                item.const_stability(tcx),
+            ),
+            asyncness = header.asyncness.print_with_space(),
+            safety = header.safety.print_with_space(),
+            abi = print_abi_with_space(header.abi),
+            name = item.name.unwrap(),
+            generics = print_generics(&func.generics, tcx),
+            decl = print_fn_decl(&func.decl, tcx),
+            where_clause = print_where_clause(&func.generics, tcx, where_clause),
+        )
+    })
+}
+
+fn print_variant(item: &clean::Item, variant: &clean::Variant, tcx: TyCtxt<'_>) -> impl Display {
+    fmt::from_fn(move |f| {
+        f.write_str(item.name.unwrap().as_str())?;
+        match &variant.kind {
+            clean::VariantKind::CLike => {
+                if let Some(expr) = variant.discriminant.as_ref().and_then(|d| d.expr(tcx)) {
+                    write!(f, " = {expr}")?;
+                }
+                Ok(())
+            }
+            clean::VariantKind::Tuple(fields) => Wrapped::with_parens()
+                .wrap_fn(|f| {
+                    fields.iter().map(|field| print_tuple_field(field, tcx)).joined(", ", f)
+                })
+                .fmt(f),
+            clean::VariantKind::Struct(s) => {
+                f.write_str(" { ")?;
+                s.fields
+                    .iter()
+                    .filter(|field| !field.is_stripped())
+                    .map(|field| print_member(field, tcx))
+                    .joined(", ", f)?;
+                if s.has_stripped_entries() {
+                    f.write_str(", /* private fields */")?;
+                }
+                f.write_str(" }")
+            }
+        }
+    })
+}
+
+fn print_tuple_field(field: &clean::Item, tcx: TyCtxt<'_>) -> impl Display {
+    fmt::from_fn(move |f| match &field.kind {
+        ItemKind::StrippedItem(_) => f.write_char('_'),
+        ItemKind::StructFieldItem(ty) => {
+            write!(f, "{}{}", print_visibility_with_space(field, tcx), print_type(ty, tcx))
+        }
+        _ => unreachable!(),
+    })
+}
+
+fn print_fields(
+    f: &mut fmt::Formatter<'_>,
+    fields: &[clean::Item],
+    has_stripped_entries: bool,
+    tcx: TyCtxt<'_>,
+) -> fmt::Result {
+    if fields.iter().all(|field| field.is_stripped()) && !has_stripped_entries {
+        return f.write_str(" {}");
+    }
+    f.write_str(" {\n")?;
+    for field in fields.iter().filter(|field| !field.is_stripped()) {
+        writeln!(f, "    {},", print_member(field, tcx))?;
+    }
+    if has_stripped_entries {
+        f.write_str("    /* private fields */\n")?;
+    }
+    f.write_char('}')
+}
+
+/// Prints a member of a page (a field, variant or associated item) on a single line.
+pub(super) fn print_member(item: &clean::Item, tcx: TyCtxt<'_>) -> impl Display {
+    fmt::from_fn(move |f| {
+        let name = item.name.unwrap();
+        match &item.kind {
+            ItemKind::StructFieldItem(ty) => write!(
+                f,
+                "{}{name}: {}",
+                print_visibility_with_space(item, tcx),
+                print_type(ty, tcx),
+            ),
+            ItemKind::VariantItem(variant) => print_variant(item, variant, tcx).fmt(f),
+            ItemKind::RequiredMethodItem(func, _) | ItemKind::MethodItem(func, _) => {
+                print_fn_item(item, func, tcx, WhereClause::Inline).fmt(f)
+            }
+            ItemKind::RequiredAssocConstItem(generics, ty) => write!(
+                f,
+                "const {name}{}: {}{}",
+                print_generics(generics, tcx),
+                print_type(ty, tcx),
+                print_where_clause(generics, tcx, WhereClause::Inline),
+            ),
+            ItemKind::ProvidedAssocConstItem(ct) | ItemKind::ImplAssocConstItem(ct) => write!(
+                f,
+                "{}const {name}{}: {} = {}{}",
+                print_visibility_with_space(item, tcx),
+                print_generics(&ct.generics, tcx),
+                print_type(&ct.type_, tcx),
+                ct.kind.expr(tcx),
+                print_where_clause(&ct.generics, tcx, WhereClause::Inline),
+            ),
+            ItemKind::RequiredAssocTypeItem(generics, bounds) => {
+                write!(f, "type {name}{}", print_generics(generics, tcx))?;
+                if !bounds.is_empty() {
+                    write!(f, ": {}", print_generic_bounds(bounds, tcx))?;
+                }
+                print_where_clause(generics, tcx, WhereClause::Inline).fmt(f)
+            }
+            ItemKind::AssocTypeItem(alias, bounds) => {
+                write!(f, "type {name}{}", print_generics(&alias.generics, tcx))?;
+                if !bounds.is_empty() {
+                    write!(f, ": {}", print_generic_bounds(bounds, tcx))?;
+                }
+                write!(
+                    f,
+                    "{} = {}",
+                    print_where_clause(&alias.generics, tcx, WhereClause::Inline),
+                    print_type(&alias.type_, tcx),
+                )
+            }
+            _ => f.write_str(name.as_str()),
+        }
+    })
+}
+
+/// Prints the declaration shown at the top of an item's page, or `None` if the item doesn't
+/// have one (modules, primitives, keywords...).
+pub(super) fn print_item_decl(item: &clean::Item, tcx: TyCtxt<'_>) -> Option<String> {
+    let mut w = String::new();
+    let vis = print_visibility_with_space(item, tcx);
+    // Glob imports are the only items without a name that have a declaration.
+    let name = item.name.map(|name| name.to_string()).unwrap_or_default();
+    match &item.kind {
+        ItemKind::FunctionItem(func) | ItemKind::ForeignFunctionItem(func, _) => {
+            write!(w, "{}", print_fn_item(item, func, tcx, WhereClause::Block)).unwrap();
+        }
+        ItemKind::StructItem(s) => {
+            write!(w, "{vis}struct {name}{}", print_generics(&s.generics, tcx)).unwrap();
+            let where_clause = print_where_clause(&s.generics, tcx, WhereClause::Block);
+            match s.ctor_kind {
+                None => write!(
+                    w,
+                    "{where_clause}{}",
+                    fmt::from_fn(|f| print_fields(f, &s.fields, s.has_stripped_entries(), tcx)),
+                )
+                .unwrap(),
+                Some(CtorKind::Fn) => write!(
+                    w,
+                    "({}){where_clause};",
+                    fmt::from_fn(|f| {
+                        s.fields.iter().map(|field| print_tuple_field(field, tcx)).joined(", ", f)
+                    }),
+                )
+                .unwrap(),
+                Some(CtorKind::Const) => write!(w, "{where_clause};").unwrap(),
+            }
+        }
+        ItemKind::UnionItem(u) => {
+            write!(
+                w,
+                "{vis}union {name}{}{}{}",
+                print_generics(&u.generics, tcx),
+                print_where_clause(&u.generics, tcx, WhereClause::Block),
+                fmt::from_fn(|f| print_fields(f, &u.fields, u.has_stripped_entries(), tcx)),
+            )
+            .unwrap();
+        }
+        ItemKind::EnumItem(e) => {
+            write!(
+                w,
+                "{vis}enum {name}{}{}",
+                print_generics(&e.generics, tcx),
+                print_where_clause(&e.generics, tcx, WhereClause::Block),
+            )
+            .unwrap();
+            if e.variants.is_empty() {
+                w.push_str(" {}");
+            } else {
+                w.push_str(" {\n");
+                for variant in e.non_stripped_variants() {
+                    writeln!(w, "    {},", print_member(variant, tcx)).unwrap();
+                }
+                if e.has_stripped_entries() {
+                    w.push_str("    // some variants omitted\n");
+                }
+                w.push('}');
+            }
+        }
+        ItemKind::TraitItem(t) => {
+            write!(
+                w,
+                "{vis}{}{}trait {name}{}",
+                t.safety(tcx).print_with_space(),
+                if t.is_auto(tcx) { "auto " } else { "" },
+                print_generics(&t.generics, tcx),
+            )
+            .unwrap();
+            if !t.bounds.is_empty() {
+                write!(w, ": {}", print_generic_bounds(&t.bounds, tcx)).unwrap();
+            }
+            write!(w, "{}", print_where_clause(&t.generics, tcx, WhereClause::Block)).unwrap();
+            let items = t.items.iter().filter(|item| !item.is_stripped()).collect::<Vec<_>>();
+            if items.is_empty() {
+                w.push_str(" {}");
+            } else {
+                w.push_str(" {\n");
+                for item in items {
+                    let body = if item.is_method() { " { ... }" } else { ";" };
+                    writeln!(w, "    {}{body}", print_member(item, tcx)).unwrap();
+                }
+                w.push('}');
+            }
+        }
+        ItemKind::TraitAliasItem(alias) => {
+            write!(
+                w,
+                "trait {name}{} = {}{};",
+                print_generics(&alias.generics, tcx),
+                print_generic_bounds(&alias.bounds, tcx),
+                print_where_clause(&alias.generics, tcx, WhereClause::Block),
+            )
+            .unwrap();
+        }
+        ItemKind::TypeAliasItem(alias) => {
+            write!(
+                w,
+                "{vis}type {name}{}{} = {};",
+                print_generics(&alias.generics, tcx),
+                print_where_clause(&alias.generics, tcx, WhereClause::Block),
+                print_type(&alias.type_, tcx),
+            )
+            .unwrap();
+        }
+        ItemKind::ConstantItem(ct) => {
+            write!(
+                w,
+                "{vis}const {name}{}: {} = {};",
+                print_generics(&ct.generics, tcx),
+                print_type(&ct.type_, tcx),
+                ct.kind.expr(tcx),
+            )
+            .unwrap();
+        }
+        ItemKind::StaticItem(s) | ItemKind::ForeignStaticItem(s, hir::Safety::Safe) => {
+            write!(
+                w,
+                "{vis}static {}{name}: {};",
+                s.mutability.print_with_space(),
+                print_type(&s.type_, tcx),
+            )
+            .unwrap();
+        }
+        ItemKind::ForeignStaticItem(s, hir::Safety::Unsafe) => {
+            write!(
+                w,
+                "{vis}unsafe static {}{name}: {};",
+                s.mutability.print_with_space(),
+                print_type(&s.type_, tcx),
+            )
+            .unwrap();
+        }
+        ItemKind::ForeignTypeItem => write!(w, "{vis}type {name};").unwrap(),
+        ItemKind::MacroItem(m) => w.push_str(&m.source),
+        ItemKind::ProcMacroItem(m) => match m.kind {
+            MacroKind::Bang => write!(w, "{name}!() {{ /* proc-macro */ }}").unwrap(),
+            MacroKind::Attr => write!(w, "#[{name}]").unwrap(),
+            MacroKind::Derive => {
+                write!(w, "#[derive({name})]").unwrap();
+                if !m.helpers.is_empty() {
+                    w.push_str("\n{\n    // Attributes available to this derive:\n");
+                    for attr in &m.helpers {
+                        writeln!(w, "    #[{attr}]").unwrap();
+                    }
+                    w.push('}');
+                }
+            }
+        },
+        ItemKind::StructFieldItem(_)
+        | ItemKind::VariantItem(_)
+        | ItemKind::RequiredMethodItem(..)
+        | ItemKind::MethodItem(..)
+        | ItemKind::RequiredAssocConstItem(..)
+        | ItemKind::ProvidedAssocConstItem(_)
+        | ItemKind::ImplAssocConstItem(_)
+        | ItemKind::RequiredAssocTypeItem(..)
+        | ItemKind::AssocTypeItem(..) => write!(w, "{}", print_member(item, tcx)).unwrap(),
+        ItemKind::ImportItem(import) => write!(w, "{vis}{}", print_import(import)).unwrap(),
+        ItemKind::ExternCrateItem { src } => match src {
+            Some(src) => write!(w, "{vis}extern crate {src} as {name};").unwrap(),
+            None => write!(w, "{vis}extern crate {name};").unwrap(),
+        },
+        ItemKind::ImplItem(impl_) => write!(w, "{}", print_impl(impl_, tcx)).unwrap(),
+        ItemKind::ModuleItem(_)
+        | ItemKind::PrimitiveItem(_)
+        | ItemKind::KeywordItem
+        | ItemKind::AttributeItem
+        | ItemKind::PlaceholderImplItem
+        | ItemKind::StrippedItem(_) => return None,
+    }
+    Some(w)
+}
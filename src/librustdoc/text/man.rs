@@ -0,0 +1,442 @@
+//! Rustdoc's man page backend.
+//!
+//! Each module and item gets a roff page named after its path, in the section of the manual
+//! it belongs to: the crate root of a binary goes in section 1, everything else in section 3.
+//! The segments of the path are joined with `.`, as `:` can't be used in file names on Windows,
+//! so `foo::Bar` is written to `man3/foo.Bar.3` and can be read with `man 3 foo.Bar`.
+
+use std::fmt::{self, Write as _};
+use std::fs;
+use std::path::PathBuf;
+
+use rustc_ast::join_path_syms;
+use rustc_hir::attrs::CrateType;
+use rustc_middle::ty::TyCtxt;
+use rustc_resolve::rustdoc::pulldown_cmark::{
+    BrokenLink, CodeBlockKind, Event, Parser, Tag, TagEnd,
+};
+use rustc_span::Symbol;
+
+use super::page::{Entry, Page};
+use crate::clean::{self, RenderedLink};
+use crate::config::{EmitType, RenderOptions};
+use crate::docfs::PathError;
+use crate::error::Error;
+use crate::formats::FormatRenderer;
+use crate::formats::cache::Cache;
+use crate::html::markdown::{
+    ErrorCodes, LangString, Line, main_body_opts, map_line, plain_text_summary,
+};
+use crate::try_err;
+
+pub(crate) struct ManRenderer<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    cache: Cache,
+    /// The path of the module being rendered, starting with the crate name.
+    current: Vec<Symbol>,
+    /// The output directory, which holds one directory per section of the manual.
+    dst: PathBuf,
+    /// Whether the current module is stripped. Its items are still visited, but they're
+    /// documented where they're re-exported, so no pages are written for them here.
+    in_stripped_module: bool,
+}
+
+impl<'tcx> ManRenderer<'tcx> {
+    pub(crate) fn init(
+        krate: clean::Crate,
+        options: RenderOptions,
+        cache: Cache,
+        tcx: TyCtxt<'tcx>,
+    ) -> Result<(Self, clean::Crate), Error> {
+        Ok((
+            ManRenderer {
+                tcx,
+                cache,
+                current: Vec::new(),
+                dst: options.output,
+                in_stripped_module: false,
+            },
+            krate,
+        ))
+    }
+
+    /// The path `item` is documented under.
+    fn path(&self, item: &clean::Item) -> String {
+        if item.is_fake_item() {
+            item.name.unwrap().to_string()
+        } else if item.is_mod() {
+            join_path_syms(&self.current)
+        } else {
+            join_path_syms(self.current.iter().chain(&item.name))
+        }
+    }
+
+    fn write_page(&self, item: &clean::Item) -> Result<(), Error> {
+        let is_command = item.is_crate() && self.tcx.crate_types().contains(&CrateType::Executable);
+        let section = if is_command { 1 } else { 3 };
+        let path = self.path(item);
+        let name = page_name(&path);
+        let page = self.render_page(item, &path, &name, section);
+
+        let dir = self.dst.join(format!("man{section}"));
+        try_err!(fs::create_dir_all(&dir), &dir);
+        let file = dir.join(format!("{name}.{section}"));
+        try_err!(fs::write(&file, page), &file);
+        Ok(())
+    }
+
+    fn render_page(&self, item: &clean::Item, path: &str, name: &str, section: u8) -> String {
+        let page = Page::new(item, &self.cache, self.tcx);
+        let mut roff = Roff::default();
+
+        let krate = self.current.first().map_or(path, |krate| krate.as_str());
+        let source = match &self.cache.crate_version {
+            Some(version) => format!("{krate} {version}"),
+            None => krate.to_owned(),
+        };
+        let manual =
+            if section == 1 { "General Commands Manual" } else { "Library Functions Manual" };
+        roff.request(format_args!(
+            ".TH {} {section} \"\" {} {}",
+            quoted(name),
+            quoted(&source),
+            quoted(manual)
+        ));
+
+        roff.request(".SH NAME");
+        let summary = plain_text_summary(&item.doc_value(), &item.link_names(&self.cache));
+        roff.text(path);
+        roff.text(" - ");
+        roff.text(if summary.is_empty() { page.kind } else { summary.as_str() });
+
+        if let Some(declaration) = &page.declaration {
+            roff.request(".SH SYNOPSIS");
+            roff.request(".nf");
+            roff.text(declaration);
+            roff.request(".fi");
+        }
+
+        if !item.doc_value().is_empty() {
+            roff.request(".SH DESCRIPTION");
+            self.write_docs(&mut roff, item);
+        }
+
+        for section in &page.sections {
+            roff.request(format_args!(".SH {}", quoted(&section.title.to_uppercase())));
+            for entry in &section.entries {
+                if item.is_mod() {
+                    self.write_module_entry(&mut roff, name, entry);
+                } else {
+                    self.write_entry(&mut roff, entry);
+                }
+            }
+        }
+        roff.finish()
+    }
+
+    /// Writes an item of a module as a reference to its own page, followed by its summary.
+    /// `module` is the name of the module's page.
+    fn write_module_entry(&self, roff: &mut Roff, module: &str, entry: &Entry<'_>) {
+        if !entry.has_page {
+            roff.request(".PP");
+            roff.font("B", &entry.code);
+            return;
+        }
+        roff.request(".TP");
+        roff.font("B", &format!("{module}.{}", entry.code));
+        roff.text("(3)");
+        let item = entry.item;
+        let summary = plain_text_summary(&item.doc_value(), &item.link_names(&self.cache));
+        roff.newline();
+        roff.text(&summary);
+    }
+
+    /// Writes a field, variant, implementation... followed by its indented docs.
+    fn write_entry(&self, roff: &mut Roff, entry: &Entry<'_>) {
+        roff.request(".PP");
+        roff.font("B", &entry.code);
+        if entry.item.doc_value().is_empty() && entry.children.is_empty() {
+            return;
+        }
+        roff.request(".RS 4");
+        self.write_docs(roff, entry.item);
+        for child in &entry.children {
+            self.write_entry(roff, child);
+        }
+        roff.request(".RE");
+    }
+
+    fn write_docs(&self, roff: &mut Roff, item: &clean::Item) {
+        markdown_to_roff(roff, &item.doc_value(), &item.link_names(&self.cache));
+    }
+}
+
+/// Converts Markdown to roff, using the requests of the `man` macro package.
+///
+/// Intra-doc links are shown as their text only, while the URL of external links is written
+/// after it.
+fn markdown_to_roff(roff: &mut Roff, md: &str, links: &[RenderedLink]) {
+    if md.is_empty() {
+        return;
+    }
+
+    let mut replacer = |broken_link: BrokenLink<'_>| {
+        links
+            .iter()
+            .find(|link| *link.original_text == *broken_link.reference)
+            .map(|link| (link.href.as_str().into(), link.tooltip.as_str().into()))
+    };
+    let p = Parser::new_with_broken_link_callback(md, main_body_opts(), Some(&mut replacer));
+
+    // The number of the next item of each list being written, `None` for bullet lists.
+    let mut lists: Vec<Option<u64>> = Vec::new();
+    // Whether an item was just started, in which case its first paragraph goes on the same line.
+    let mut item_started = false;
+    let mut code_block: Option<(bool, String)> = None;
+    let mut link_stack: Vec<(String, Option<&RenderedLink>)> = Vec::new();
+    let mut table_cell = 0;
+
+    for event in p {
+        match event {
+            Event::Start(Tag::Paragraph) => {
+                if lists.is_empty() {
+                    roff.request(".PP");
+                } else if !std::mem::take(&mut item_started) {
+                    roff.request(format_args!(".IP \"\" {}", item_indent(&lists)));
+                }
+            }
+            Event::Start(Tag::Heading { .. }) => roff.request(".SS"),
+            Event::End(TagEnd::Heading(_)) => roff.newline(),
+            Event::Start(Tag::BlockQuote(_)) => roff.request(".RS 4"),
+            Event::End(TagEnd::BlockQuote) => roff.request(".RE"),
+            Event::Start(Tag::CodeBlock(kind)) => {
+                let is_rust = match kind {
+                    CodeBlockKind::Fenced(lang) => {
+                        LangString::parse_without_check(&lang, ErrorCodes::No).rust
+                    }
+                    CodeBlockKind::Indented => true,
+                };
+                code_block = Some((is_rust, String::new()));
+            }
+            Event::End(TagEnd::CodeBlock) => {
+                let Some((is_rust, code)) = code_block.take() else { continue };
+                roff.request(".RS 4");
+                roff.request(".nf");
+                for line in code.lines() {
+                    match map_line(line) {
+                        Line::Hidden(_) if is_rust => {}
+                        _ => {
+                            roff.text(line);
+                            roff.newline();
+                        }
+                    }
+                }
+                roff.request(".fi");
+                roff.request(".RE");
+            }
+            Event::Start(Tag::List(start)) => {
+                if !lists.is_empty() {
+                    roff.request(format_args!(".RS {}", item_indent(&lists)));
+                }
+                lists.push(start);
+            }
+            Event::End(TagEnd::List(_)) => {
+                lists.pop();
+                if !lists.is_empty() {
+                    roff.request(".RE");
+                }
+            }
+            Event::Start(Tag::Item) => {
+                let indent = item_indent(&lists);
+                match lists.last_mut() {
+                    Some(Some(number)) => {
+                        roff.request(format_args!(".IP \"{number}.\" {indent}"));
+                        *number += 1;
+                    }
+                    _ => roff.request(format_args!(".IP \\(bu {indent}")),
+                }
+                item_started = true;
+            }
+            Event::Start(Tag::Emphasis) => roff.raw("\\fI"),
+            Event::Start(Tag::Strong) => roff.raw("\\fB"),
+            Event::End(TagEnd::Emphasis | TagEnd::Strong) => roff.raw("\\fP"),
+            Event::Start(Tag::Link { dest_url, .. } | Tag::Image { dest_url, .. }) => {
+                let link = links.iter().find(|link| link.href == *dest_url);
+                link_stack.push((dest_url.into_string(), link));
+            }
+            Event::End(TagEnd::Link | TagEnd::Image) => {
+                let Some((dest_url, _)) = link_stack.pop() else { continue };
+                if dest_url.starts_with("http://") || dest_url.starts_with("https://") {
+                    roff.text(&format!(" <{dest_url}>"));
+                }
+            }
+            Event::Start(Tag::Table(_)) => roff.request(".PP"),
+            Event::Start(Tag::TableHead | Tag::TableRow) => table_cell = 0,
+            Event::End(TagEnd::TableHead | TagEnd::TableRow) => roff.request(".br"),
+            Event::Start(Tag::TableCell) => {
+                if table_cell > 0 {
+                    roff.text(" | ");
+                }
+                table_cell += 1;
+            }
+            Event::Start(Tag::FootnoteDefinition(name)) => {
+                roff.request(".PP");
+                roff.text(&format!("[{name}] "));
+                // The footnote's paragraph goes on the same line as its name.
+                item_started = true;
+                lists.push(None);
+            }
+            Event::End(TagEnd::FootnoteDefinition) => {
+                lists.pop();
+            }
+            Event::FootnoteReference(name) => roff.text(&format!("[{name}]")),
+            Event::TaskListMarker(checked) => roff.text(if checked { "[x] " } else { "[ ] " }),
+            Event::Text(text) => match &mut code_block {
+                Some((_, code)) => code.push_str(&text),
+                None => roff.text(&link_text(&text, &link_stack)),
+            },
+            Event::Code(code) => roff.font("B", &link_text(&code, &link_stack)),
+            Event::InlineHtml(html) => roff.text(&html),
+            Event::SoftBreak => roff.newline(),
+            Event::HardBreak => roff.request(".br"),
+            _ => {}
+        }
+    }
+}
+
+/// The indentation of the items of the innermost list.
+fn item_indent(lists: &[Option<u64>]) -> usize {
+    match lists.last() {
+        Some(Some(_)) => 4,
+        _ => 2,
+    }
+}
+
+/// Removes the disambiguator of an intra-doc link from its text, like the HTML output does.
+fn link_text<'a>(text: &'a str, link_stack: &'a [(String, Option<&RenderedLink>)]) -> &'a str {
+    match link_stack.last() {
+        Some((_, Some(link))) if text == link.original_text.trim_matches('`') => {
+            link.new_text.trim_matches('`')
+        }
+        _ => text,
+    }
+}
+
+/// The name of the page of the item at `path`, which is also its file name without the section.
+/// Identifiers can't contain `.`, so this doesn't make two paths share a page.
+fn page_name(path: &str) -> String {
+    path.replace("::", ".")
+}
+
+/// Quotes an argument of a request.
+fn quoted(arg: &str) -> String {
+    let mut quoted = String::with_capacity(arg.len() + 2);
+    quoted.push('"');
+    for c in arg.chars() {
+        match c {
+            '"' => quoted.push_str("\\(dq"),
+            '\\' => quoted.push_str("\\e"),
+            '-' => quoted.push_str("\\-"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// A roff document being written.
+#[derive(Default)]
+struct Roff {
+    out: String,
+}
+
+impl Roff {
+    fn at_line_start(&self) -> bool {
+        self.out.is_empty() || self.out.ends_with('\n')
+    }
+
+    fn newline(&mut self) {
+        if !self.at_line_start() {
+            self.out.push('\n');
+        }
+    }
+
+    /// Writes a request on its own line.
+    fn request(&mut self, request: impl fmt::Display) {
+        self.newline();
+        writeln!(self.out, "{request}").unwrap();
+    }
+
+    /// Writes text, escaping the characters roff would interpret.
+    fn text(&mut self, text: &str) {
+        for c in text.chars() {
+            // A line starting with either of these would be read as a request.
+            if self.at_line_start() && matches!(c, '.' | '\'') {
+                self.out.push_str("\\&");
+            }
+            match c {
+                '\\' => self.out.push_str("\\e"),
+                '-' => self.out.push_str("\\-"),
+                c => self.out.push(c),
+            }
+        }
+    }
+
+    /// Writes text in the given font, then switches back to the previous one.
+    fn font(&mut self, font: &str, text: &str) {
+        self.raw(&format!("\\f{font}"));
+        self.text(text);
+        self.raw("\\fP");
+    }
+
+    /// Writes an escape sequence as is.
+    fn raw(&mut self, escape: &str) {
+        self.out.push_str(escape);
+    }
+
+    fn finish(mut self) -> String {
+        self.newline();
+        self.out
+    }
+}
+
+impl<'tcx> FormatRenderer<'tcx> for ManRenderer<'tcx> {
+    const DESCR: &'static str = "man";
+    const RUN_ON_MODULE: bool = true;
+    const NON_STATIC_FILE_EMIT_TYPE: EmitType = EmitType::ManPages;
+
+    type ModuleData = bool;
+
+    fn save_module_data(&mut self) -> Self::ModuleData {
+        self.in_stripped_module
+    }
+
+    fn restore_module_data(&mut self, in_stripped_module: Self::ModuleData) {
+        self.in_stripped_module = in_stripped_module;
+    }
+
+    fn item(&mut self, item: &clean::Item) -> Result<(), Error> {
+        if self.in_stripped_module || item.is_stripped() {
+            return Ok(());
+        }
+        self.write_page(item)
+    }
+
+    fn mod_item_in(&mut self, item: &clean::Item) -> Result<(), Error> {
+        self.in_stripped_module |= item.is_stripped();
+        self.current.push(item.name.unwrap());
+        if self.in_stripped_module {
+            return Ok(());
+        }
+        self.write_page(item)
+    }
+
+    fn mod_item_out(&mut self) -> Result<(), Error> {
+        self.current.pop();
+        Ok(())
+    }
+
+    fn after_krate(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
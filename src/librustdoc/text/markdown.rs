@@ -0,0 +1,369 @@
+//! Rustdoc's Markdown backend.
+//!
+//! The pages are laid out like the HTML output: `index.md` for modules and `struct.Foo.md`,
+//! `fn.bar.md`... for the other items, in one directory per module. Intra-doc links are
+//! rewritten to relative links between those pages, so the output can be browsed or indexed
+//! without rustdoc.
+
+use std::fmt::Write as _;
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use rustc_ast::join_path_syms;
+use rustc_data_structures::fx::FxHashSet;
+use rustc_hir::def_id::DefId;
+use rustc_middle::ty::TyCtxt;
+use rustc_resolve::rustdoc::pulldown_cmark::{CodeBlockKind, Event, LinkType, Parser, Tag, TagEnd};
+use rustc_span::Symbol;
+
+use super::page::{Entry, Page};
+use crate::clean::{self, ExternalLocation, ItemLink};
+use crate::config::{EmitType, RenderOptions};
+use crate::docfs::PathError;
+use crate::error::Error;
+use crate::formats::FormatRenderer;
+use crate::formats::cache::Cache;
+use crate::formats::item_type::ItemType;
+use crate::html::format::{fragment, href_relative_parts, remote_url_prefix, to_module_fqp};
+use crate::html::markdown::{
+    ErrorCodes, LangString, Line, MarkdownLinkRange, main_body_opts, map_line, markdown_links,
+    plain_text_summary,
+};
+use crate::passes::collect_intra_doc_links::UrlFragment;
+use crate::try_err;
+
+pub(crate) struct MarkdownRenderer<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    cache: Cache,
+    /// The path of the module being rendered, starting with the crate name.
+    current: Vec<Symbol>,
+    /// The directory the pages of the current module are written to.
+    dst: PathBuf,
+    /// Whether the current module is stripped. Its items are still visited, but they're
+    /// documented where they're re-exported, so no pages are written for them here.
+    in_stripped_module: bool,
+}
+
+impl<'tcx> MarkdownRenderer<'tcx> {
+    pub(crate) fn init(
+        krate: clean::Crate,
+        options: RenderOptions,
+        cache: Cache,
+        tcx: TyCtxt<'tcx>,
+    ) -> Result<(Self, clean::Crate), Error> {
+        Ok((
+            MarkdownRenderer {
+                tcx,
+                cache,
+                current: Vec::new(),
+                dst: options.output,
+                in_stripped_module: false,
+            },
+            krate,
+        ))
+    }
+
+    fn write(&self, path: &Path, contents: String) -> Result<(), Error> {
+        try_err!(fs::create_dir_all(&self.dst), &self.dst);
+        try_err!(fs::write(path, contents), path);
+        Ok(())
+    }
+
+    fn render_page(&self, item: &clean::Item) -> String {
+        let page = Page::new(item, &self.cache, self.tcx);
+        let mut w = String::new();
+
+        let path = if item.is_fake_item() {
+            item.name.unwrap().to_string()
+        } else if item.is_mod() {
+            join_path_syms(&self.current)
+        } else {
+            join_path_syms(self.current.iter().chain(&item.name))
+        };
+        write!(w, "# {} `{path}`\n\n", page.kind).unwrap();
+        if let Some(declaration) = &page.declaration {
+            write!(w, "```rust\n{declaration}\n```\n\n").unwrap();
+        }
+        self.write_docs(&mut w, item, 1);
+
+        for section in &page.sections {
+            write!(w, "## {}\n\n", section.title).unwrap();
+            if item.is_mod() {
+                for entry in &section.entries {
+                    self.write_module_entry(&mut w, entry);
+                }
+                w.push('\n');
+            } else {
+                for entry in &section.entries {
+                    self.write_entry(&mut w, entry, 3);
+                }
+            }
+        }
+        w
+    }
+
+    /// Writes an item of a module as a list item, linking to its own page.
+    fn write_module_entry(&self, w: &mut String, entry: &Entry<'_>) {
+        let item = entry.item;
+        if !entry.has_page {
+            writeln!(w, "- `{}`", entry.code).unwrap();
+            return;
+        }
+        if item.is_mod() {
+            write!(w, "- [`{0}`]({0}/index.md)", entry.code).unwrap();
+        } else {
+            write!(w, "- [`{0}`]({1}.{0}.md)", entry.code, item.type_()).unwrap();
+        }
+        let summary = plain_text_summary(&item.doc_value(), &item.link_names(&self.cache));
+        if !summary.is_empty() {
+            write!(w, ": {summary}").unwrap();
+        }
+        w.push('\n');
+    }
+
+    /// Writes a field, variant, implementation... as a heading followed by its docs.
+    fn write_entry(&self, w: &mut String, entry: &Entry<'_>, level: usize) {
+        let hashes = "#".repeat(level);
+        match &entry.anchor {
+            Some(anchor) => write!(w, "{hashes} <a id=\"{anchor}\"></a>`{}`\n\n", entry.code),
+            None => write!(w, "{hashes} `{}`\n\n", entry.code),
+        }
+        .unwrap();
+        self.write_docs(w, entry.item, level);
+        for child in &entry.children {
+            self.write_entry(w, child, level + 1);
+        }
+    }
+
+    fn write_docs(&self, w: &mut String, item: &clean::Item, heading_offset: usize) {
+        let docs = self.docs(item, heading_offset);
+        let docs = docs.trim_end();
+        if !docs.is_empty() {
+            w.push_str(docs);
+            w.push_str("\n\n");
+        }
+    }
+
+    /// Returns the docs of `item`, rewritten for the page they're shown on: intra-doc links
+    /// point to the other pages, hidden lines are removed from Rust code blocks, and headings
+    /// are moved `heading_offset` levels down so they nest under the page's own headings.
+    fn docs(&self, item: &clean::Item, heading_offset: usize) -> String {
+        let md = item.doc_value();
+        if md.is_empty() {
+            return md;
+        }
+
+        let mut edits = Vec::new();
+        let mut reference_definitions = String::new();
+
+        let item_links = self.cache.intra_doc_links.get(&item.item_or_reexport_id());
+        let mut defined = FxHashSet::default();
+        for link in markdown_links(&md, Some) {
+            let Some(href) = item_links
+                .and_then(|links| links.iter().find(|l| *l.link == *link.link))
+                .and_then(|l| self.item_link_href(l))
+            else {
+                continue;
+            };
+            match (link.kind, link.range) {
+                (
+                    LinkType::ReferenceUnknown
+                    | LinkType::CollapsedUnknown
+                    | LinkType::ShortcutUnknown,
+                    _,
+                ) => {
+                    // There's no destination to replace, so define the reference instead.
+                    if defined.insert(link.link.clone()) {
+                        writeln!(reference_definitions, "[{}]: {href}", link.link).unwrap();
+                    }
+                }
+                (_, MarkdownLinkRange::Destination(range)) => edits.push((range, href)),
+                // The destination couldn't be located, e.g. because it contains escapes.
+                (_, MarkdownLinkRange::WholeLink(_)) => {}
+            }
+        }
+
+        let mut code_block: Option<(Range<usize>, bool, String)> = None;
+        for (event, range) in Parser::new_ext(&md, main_body_opts()).into_offset_iter() {
+            match event {
+                Event::Start(Tag::Heading { level, .. }) => {
+                    // Only ATX headings (`# Title`) can be moved down.
+                    let start = range.end - md[range].trim_start().len();
+                    if md[start..].starts_with('#') {
+                        let level = level as usize;
+                        edits.push((
+                            start..start,
+                            "#".repeat((level + heading_offset).min(6) - level),
+                        ));
+                    }
+                }
+                Event::Start(Tag::CodeBlock(kind)) => {
+                    let (is_rust, is_indented) = match kind {
+                        CodeBlockKind::Fenced(lang) => {
+                            (LangString::parse_without_check(&lang, ErrorCodes::No).rust, false)
+                        }
+                        CodeBlockKind::Indented => (true, true),
+                    };
+                    if is_rust {
+                        code_block = Some((range, is_indented, String::new()));
+                    }
+                }
+                Event::Text(text) => {
+                    if let Some((_, _, code)) = &mut code_block {
+                        code.push_str(&text);
+                    }
+                }
+                Event::End(TagEnd::CodeBlock) => {
+                    if let Some((range, is_indented, code)) = code_block.take() {
+                        edits.push(rust_code_block(&md, range, is_indented, &code));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        edits.sort_by_key(|(range, _)| (range.start, range.end));
+        let mut docs = String::with_capacity(md.len());
+        let mut pos = 0;
+        for (range, replacement) in edits {
+            // Several links can share the same reference definition.
+            if range.start < pos {
+                continue;
+            }
+            docs.push_str(&md[pos..range.start]);
+            docs.push_str(&replacement);
+            pos = range.end;
+        }
+        docs.push_str(&md[pos..]);
+        if !reference_definitions.is_empty() {
+            docs.push_str("\n\n");
+            docs.push_str(&reference_definitions);
+        }
+        docs
+    }
+
+    fn item_link_href(&self, link: &ItemLink) -> Option<String> {
+        let mut href = self.href(link.page_id)?;
+        match &link.fragment {
+            Some(UrlFragment::Item(def_id)) => {
+                write!(href, "{}", fragment(*def_id, self.tcx)).unwrap()
+            }
+            Some(UrlFragment::UserWritten(raw)) => {
+                href.push('#');
+                href.push_str(raw);
+            }
+            None => {}
+        }
+        Some(href)
+    }
+
+    /// Returns the link to the page of `did`, relative to the current module.
+    ///
+    /// Items of crates documented with another backend are linked to their HTML page.
+    fn href(&self, did: DefId) -> Option<String> {
+        let (mut url, fqp, shortty, extension) =
+            if let Some(&(ref fqp, shortty)) = self.cache.paths.get(&did) {
+                let url = href_relative_parts(to_module_fqp(shortty, fqp), &self.current);
+                (url, fqp, shortty, "md")
+            } else {
+                let &(ref fqp, shortty) = self.cache.external_paths.get(&did)?;
+                let module_fqp = to_module_fqp(shortty, fqp);
+                match self.cache.extern_locations.get(&did.krate)? {
+                    ExternalLocation::Remote { url, is_absolute } => {
+                        let mut url = remote_url_prefix(url, *is_absolute, self.current.len());
+                        url.extend(module_fqp.iter().copied());
+                        (url, fqp, shortty, "html")
+                    }
+                    ExternalLocation::Local => {
+                        (href_relative_parts(module_fqp, &self.current), fqp, shortty, "md")
+                    }
+                    ExternalLocation::Unknown => return None,
+                }
+            };
+        match shortty {
+            ItemType::Module => url.push_fmt(format_args!("index.{extension}")),
+            _ => url.push_fmt(format_args!("{shortty}.{}.{extension}", fqp.last().unwrap())),
+        }
+        Some(url.finish())
+    }
+}
+
+/// Rewrites a Rust code block as a `rust` fenced code block without its hidden lines.
+fn rust_code_block(
+    md: &str,
+    range: Range<usize>,
+    is_indented: bool,
+    code: &str,
+) -> (Range<usize>, String) {
+    // Keep the indentation of code blocks nested in list items.
+    let line_start = md[..range.start].rfind('\n').map_or(0, |i| i + 1);
+    let (start, mut indent) = if md[line_start..range.start].trim().is_empty() {
+        (line_start, &md[line_start..range.start])
+    } else {
+        (range.start, "")
+    };
+    if is_indented {
+        indent = &indent[..indent.len().saturating_sub(4)];
+    }
+
+    let mut block = format!("{indent}```rust\n");
+    for line in code.lines() {
+        if let Line::Shown(line) = map_line(line) {
+            writeln!(block, "{indent}{line}").unwrap();
+        }
+    }
+    write!(block, "{indent}```").unwrap();
+    if md[..range.end].ends_with('\n') {
+        block.push('\n');
+    }
+    (start..range.end, block)
+}
+
+impl<'tcx> FormatRenderer<'tcx> for MarkdownRenderer<'tcx> {
+    const DESCR: &'static str = "markdown";
+    const RUN_ON_MODULE: bool = true;
+    const NON_STATIC_FILE_EMIT_TYPE: EmitType = EmitType::MarkdownFiles;
+
+    type ModuleData = bool;
+
+    fn save_module_data(&mut self) -> Self::ModuleData {
+        self.in_stripped_module
+    }
+
+    fn restore_module_data(&mut self, in_stripped_module: Self::ModuleData) {
+        self.in_stripped_module = in_stripped_module;
+    }
+
+    fn item(&mut self, item: &clean::Item) -> Result<(), Error> {
+        if self.in_stripped_module || item.is_stripped() {
+            return Ok(());
+        }
+        let file_name = format!("{}.{}.md", item.type_(), item.name.unwrap());
+        let page = self.render_page(item);
+        self.write(&self.dst.join(file_name), page)
+    }
+
+    fn mod_item_in(&mut self, item: &clean::Item) -> Result<(), Error> {
+        self.in_stripped_module |= item.is_stripped();
+        let name = item.name.unwrap();
+        self.dst.push(name.as_str());
+        self.current.push(name);
+
+        if !self.in_stripped_module {
+            let page = self.render_page(item);
+            self.write(&self.dst.join("index.md"), page)?;
+        }
+        Ok(())
+    }
+
+    fn mod_item_out(&mut self) -> Result<(), Error> {
+        self.dst.pop();
+        self.current.pop();
+        Ok(())
+    }
+
+    fn after_krate(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
@@ -0,0 +1,12 @@
+//! Rustdoc's text backends: Markdown, and man pages.
+//!
+//! Both lay out the pages like the HTML output does, but write the declarations and the
+//! documentation as plain text, so they can be read without a browser.
+
+mod format;
+mod man;
+mod markdown;
+mod page;
+
+pub(crate) use self::man::ManRenderer;
+pub(crate) use self::markdown::MarkdownRenderer;
@@ -0,0 +1,271 @@
+//! The contents of a page, independent of the format it's written in.
+
+use std::cmp::Ordering;
+
+use rustc_data_structures::fx::FxHashSet;
+use rustc_middle::ty::TyCtxt;
+use rustc_span::hygiene::MacroKind;
+
+use super::format::{print_impl, print_item_decl, print_member};
+use crate::clean::{self, ItemKind};
+use crate::formats::Impl;
+use crate::formats::cache::Cache;
+use crate::formats::item_type::ItemType;
+use crate::html::format::fragment;
+use crate::html::render::{ItemSection, item_ty_to_section};
+
+/// A page documenting a single module or item.
+pub(super) struct Page<'a> {
+    pub(super) item: &'a clean::Item,
+    /// What kind of item this is (`"Struct"`, `"Module"`...), as in the HTML page titles.
+    pub(super) kind: &'static str,
+    /// The item's declaration, if it has one.
+    pub(super) declaration: Option<String>,
+    pub(super) sections: Vec<Section<'a>>,
+}
+
+pub(super) struct Section<'a> {
+    pub(super) title: &'static str,
+    pub(super) entries: Vec<Entry<'a>>,
+}
+
+pub(super) struct Entry<'a> {
+    pub(super) item: &'a clean::Item,
+    /// The code that names the entry, on a single line.
+    pub(super) code: String,
+    /// Whether the entry has its own page, which it should link to instead of being documented
+    /// inline. This is the case for the items of a module.
+    pub(super) has_page: bool,
+    /// The anchor other pages use to link to this entry, the same as in the HTML output.
+    pub(super) anchor: Option<String>,
+    /// The associated items of an implementation.
+    pub(super) children: Vec<Entry<'a>>,
+}
+
+impl<'a> Page<'a> {
+    pub(super) fn new(item: &'a clean::Item, cache: &'a Cache, tcx: TyCtxt<'_>) -> Self {
+        let mut anchors = FxHashSet::default();
+        let mut sections = Vec::new();
+        match &item.kind {
+            ItemKind::ModuleItem(module) => module_sections(module, tcx, &mut sections),
+            ItemKind::StructItem(s) => {
+                add_members(&mut sections, "Fields", &s.fields, tcx, &mut anchors);
+            }
+            ItemKind::UnionItem(u) => {
+                add_members(&mut sections, "Fields", &u.fields, tcx, &mut anchors);
+            }
+            ItemKind::EnumItem(e) => {
+                add_members(&mut sections, "Variants", &e.variants, tcx, &mut anchors);
+            }
+            ItemKind::TraitItem(t) => {
+                let members: [(&'static str, fn(&clean::Item) -> bool); 4] = [
+                    ("Associated Types", clean::Item::is_associated_type),
+                    ("Associated Constants", clean::Item::is_associated_const),
+                    ("Required Methods", clean::Item::is_ty_method),
+                    ("Provided Methods", clean::Item::is_method),
+                ];
+                for (title, is_member) in members {
+                    let items = t.items.iter().filter(|item| is_member(item));
+                    add_members(&mut sections, title, items, tcx, &mut anchors);
+                }
+            }
+            _ => {}
+        }
+
+        if let Some(def_id) = item.item_id.as_def_id() {
+            if has_impls(item) {
+                impl_sections(cache.impls.get(&def_id), tcx, &mut anchors, &mut sections);
+            }
+            if let ItemKind::TraitItem(_) = item.kind
+                && let Some(implementors) = cache.implementors.get(&def_id)
+            {
+                let entries =
+                    implementors.iter().map(|i| impl_entry(i, false, tcx, &mut anchors)).collect();
+                push_section(&mut sections, "Implementors", entries);
+            }
+        }
+
+        Page { item, kind: item_kind(item), declaration: print_item_decl(item, tcx), sections }
+    }
+}
+
+fn push_section<'a>(sections: &mut Vec<Section<'a>>, title: &'static str, entries: Vec<Entry<'a>>) {
+    if !entries.is_empty() {
+        sections.push(Section { title, entries });
+    }
+}
+
+fn item_kind(item: &clean::Item) -> &'static str {
+    match &item.kind {
+        ItemKind::ModuleItem(_) if item.is_crate() => "Crate",
+        ItemKind::ModuleItem(_) => "Module",
+        ItemKind::FunctionItem(..) | ItemKind::ForeignFunctionItem(..) => "Function",
+        ItemKind::TraitItem(..) => "Trait",
+        ItemKind::StructItem(..) => "Struct",
+        ItemKind::UnionItem(..) => "Union",
+        ItemKind::EnumItem(..) => "Enum",
+        ItemKind::TypeAliasItem(..) => "Type Alias",
+        ItemKind::MacroItem(..) => "Macro",
+        ItemKind::ProcMacroItem(mac) => match mac.kind {
+            MacroKind::Bang => "Macro",
+            MacroKind::Attr => "Attribute Macro",
+            MacroKind::Derive => "Derive Macro",
+        },
+        ItemKind::PrimitiveItem(..) => "Primitive Type",
+        ItemKind::StaticItem(..) | ItemKind::ForeignStaticItem(..) => "Static",
+        ItemKind::ConstantItem(..) => "Constant",
+        ItemKind::ForeignTypeItem => "Foreign Type",
+        ItemKind::KeywordItem => "Keyword",
+        ItemKind::AttributeItem => "Attribute",
+        ItemKind::TraitAliasItem(..) => "Trait Alias",
+        // We don't generate pages for any other type.
+        _ => unreachable!(),
+    }
+}
+
+fn module_sections<'a>(
+    module: &'a clean::Module,
+    tcx: TyCtxt<'_>,
+    sections: &mut Vec<Section<'a>>,
+) {
+    let mut items = module
+        .items
+        .iter()
+        .filter(|item| match &item.kind {
+            ItemKind::StrippedItem(_) | ItemKind::ImplItem(_) => false,
+            ItemKind::ImportItem(import) => import.should_be_displayed,
+            _ => item.name.is_some(),
+        })
+        .map(|item| (item_ty_to_section(item.type_()), item))
+        .collect::<Vec<_>>();
+    let position = |section| ItemSection::ALL.iter().position(|&s| s == section);
+    items.sort_by(|&(a_section, a), &(b_section, b)| {
+        position(a_section).cmp(&position(b_section)).then_with(|| {
+            // Re-exports are kept in the order they're written in.
+            if a_section == ItemSection::Reexports {
+                Ordering::Equal
+            } else {
+                a.name.unwrap().as_str().cmp(b.name.unwrap().as_str())
+            }
+        })
+    });
+
+    for chunk in items.chunk_by(|(a, _), (b, _)| a == b) {
+        let entries = chunk
+            .iter()
+            .map(|&(section, item)| {
+                let has_page = section != ItemSection::Reexports;
+                Entry {
+                    item,
+                    code: if has_page {
+                        item.name.unwrap().to_string()
+                    } else {
+                        print_item_decl(item, tcx).unwrap_or_default()
+                    },
+                    has_page,
+                    anchor: None,
+                    children: Vec::new(),
+                }
+            })
+            .collect();
+        push_section(sections, chunk[0].0.name(), entries);
+    }
+}
+
+fn member<'a>(
+    item: &'a clean::Item,
+    tcx: TyCtxt<'_>,
+    anchors: &mut FxHashSet<String>,
+) -> Entry<'a> {
+    // Like the HTML output, only the first item with a given anchor gets it.
+    let anchor = item
+        .def_id()
+        .map(|def_id| fragment(def_id, tcx).to_string())
+        .and_then(|fragment| fragment.strip_prefix('#').map(str::to_owned))
+        .filter(|anchor| anchors.insert(anchor.clone()));
+    Entry {
+        item,
+        code: print_member(item, tcx).to_string(),
+        has_page: false,
+        anchor,
+        children: Vec::new(),
+    }
+}
+
+fn add_members<'a>(
+    sections: &mut Vec<Section<'a>>,
+    title: &'static str,
+    items: impl IntoIterator<Item = &'a clean::Item>,
+    tcx: TyCtxt<'_>,
+    anchors: &mut FxHashSet<String>,
+) {
+    let entries = items
+        .into_iter()
+        .filter(|item| !item.is_stripped())
+        .map(|item| member(item, tcx, anchors))
+        .collect();
+    push_section(sections, title, entries);
+}
+
+fn impl_entry<'a>(
+    i: &'a Impl,
+    with_items: bool,
+    tcx: TyCtxt<'_>,
+    anchors: &mut FxHashSet<String>,
+) -> Entry<'a> {
+    let impl_ = i.inner_impl();
+    let children = if with_items {
+        impl_
+            .items
+            .iter()
+            .filter(|item| !item.is_stripped())
+            .map(|item| member(item, tcx, anchors))
+            .collect()
+    } else {
+        Vec::new()
+    };
+    Entry {
+        item: &i.impl_item,
+        code: print_impl(impl_, tcx).to_string(),
+        has_page: false,
+        anchor: None,
+        children,
+    }
+}
+
+fn impl_sections<'a>(
+    impls: Option<&'a Vec<Impl>>,
+    tcx: TyCtxt<'_>,
+    anchors: &mut FxHashSet<String>,
+    sections: &mut Vec<Section<'a>>,
+) {
+    let Some(impls) = impls else { return };
+    let (inherent, traits): (Vec<_>, Vec<_>) =
+        impls.iter().partition(|i| i.inner_impl().trait_.is_none());
+    let (synthetic, concrete): (Vec<_>, Vec<_>) =
+        traits.into_iter().partition(|i| i.inner_impl().kind.is_auto());
+    let (blanket, concrete): (Vec<_>, Vec<_>) =
+        concrete.into_iter().partition(|i| i.inner_impl().kind.is_blanket());
+
+    for (title, impls, with_items) in [
+        ("Implementations", inherent, true),
+        ("Trait Implementations", concrete, true),
+        ("Auto Trait Implementations", synthetic, false),
+        ("Blanket Implementations", blanket, false),
+    ] {
+        let entries = impls.into_iter().map(|i| impl_entry(i, with_items, tcx, anchors)).collect();
+        push_section(sections, title, entries);
+    }
+}
+
+/// Whether the page of `item` lists the implementations of the type it defines.
+fn has_impls(item: &clean::Item) -> bool {
+    matches!(
+        item.type_(),
+        ItemType::Struct
+            | ItemType::Enum
+            | ItemType::Union
+            | ItemType::Primitive
+            | ItemType::ForeignType
+    )
+}
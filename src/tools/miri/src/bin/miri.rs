@@ -38,6 +38,7 @@
 use std::env;
 use std::num::{NonZero, NonZeroI32};
 use std::ops::Range;
+use std::path::PathBuf;
 use std::process::ExitCode;
 use std::rc::Rc;
 use std::str::FromStr;
@@ -558,6 +559,8 @@ fn main() -> ExitCode {
             miri_config.isolated_op = miri::IsolatedOp::Allow;
         } else if arg == "-Zmiri-disable-leak-backtraces" {
             miri_config.collect_leak_backtraces = false;
+        } else if let Some(param) = arg.strip_prefix("-Zmiri-leak-graph=") {
+            miri_config.leak_graph_out = Some(PathBuf::from(param));
         } else if arg == "-Zmiri-disable-weak-memory-emulation" {
             miri_config.weak_memory_emulation = false;
         } else if arg == "-Zmiri-track-weak-memory-loads" {
@@ -678,6 +681,18 @@ fn main() -> ExitCode {
             miri_config.gc_interval = interval;
         } else if let Some(param) = arg.strip_prefix("-Zmiri-measureme=") {
             miri_config.measureme_out = Some(param.to_string());
+        } else if let Some(param) = arg.strip_prefix("-Zmiri-coverage=") {
+            miri_config.coverage_out = Some(PathBuf::from(param));
+        } else if let Some(param) = arg.strip_prefix("-Zmiri-record=") {
+            if miri_config.host_log.is_some() {
+                fatal_error!("only one of `-Zmiri-record` and `-Zmiri-replay` can be set");
+            }
+            miri_config.host_log = Some(miri::HostLogMode::Record(PathBuf::from(param)));
+        } else if let Some(param) = arg.strip_prefix("-Zmiri-replay=") {
+            if miri_config.host_log.is_some() {
+                fatal_error!("only one of `-Zmiri-record` and `-Zmiri-replay` can be set");
+            }
+            miri_config.host_log = Some(miri::HostLogMode::Replay(PathBuf::from(param)));
         } else if let Some(param) = arg.strip_prefix("-Zmiri-backtrace=") {
             miri_config.backtrace_style = match param {
                 "0" => BacktraceStyle::Off,
@@ -747,6 +762,33 @@ fn main() -> ExitCode {
             so if the native library has global state, it will leak across execution bundaries"
         );
     }
+    // Without the leak check, there is no leak graph.
+    if miri_config.leak_graph_out.is_some() && miri_config.ignore_leaks {
+        fatal_error!("`-Zmiri-leak-graph` cannot be used with `-Zmiri-ignore-leaks`");
+    }
+    // Recording and replaying are about the interactions of a single execution with the host.
+    if miri_config.host_log.is_some() {
+        if miri_config.isolated_op != miri::IsolatedOp::Allow {
+            fatal_error!("`-Zmiri-record` and `-Zmiri-replay` require `-Zmiri-disable-isolation`");
+        }
+        if many_seeds.is_some() || miri_config.genmc_config.is_some() {
+            fatal_error!(
+                "`-Zmiri-record` and `-Zmiri-replay` are incompatible with multiple executions"
+            );
+        }
+    }
+    // The seed is part of the recording.
+    if matches!(miri_config.host_log, Some(miri::HostLogMode::Replay(_)))
+        && miri_config.seed.is_some()
+    {
+        fatal_error!("`-Zmiri-seed` cannot be used with `-Zmiri-replay`");
+    }
+    // Every execution would overwrite the coverage report of the previous ones.
+    if miri_config.coverage_out.is_some()
+        && (many_seeds.is_some() || miri_config.genmc_config.is_some())
+    {
+        fatal_error!("`-Zmiri-coverage` is not compatible with running multiple executions");
+    }
     // You can set either one seed or many.
     if many_seeds.is_some() && miri_config.seed.is_some() {
         fatal_error!("Only one of `-Zmiri-seed` and `-Zmiri-many-seeds can be set");
@@ -1,6 +1,9 @@
 use std::cell::Cell;
+use std::rc::Rc;
 use std::time::{Duration, Instant as StdInstant};
 
+use crate::HostLog;
+
 /// When using a virtual clock, this defines how many nanoseconds we pretend are passing for each
 /// basic block.
 /// This number is pretty random, but it has been shown to approximately cause
@@ -65,6 +68,8 @@ enum MonotonicClockKind {
         /// The "epoch" for this machine's monotone clock:
         /// the moment we consider to be time = 0.
         epoch: StdInstant,
+        /// The log to record the current time to, or to replay it from.
+        host_log: Option<Rc<HostLog>>,
     },
     Virtual {
         /// The "current virtual time".
@@ -74,9 +79,9 @@ enum MonotonicClockKind {
 
 impl MonotonicClock {
     /// Create a new clock based on the availability of communication with the host.
-    pub fn new(communicate: bool) -> Self {
+    pub fn new(communicate: bool, host_log: Option<Rc<HostLog>>) -> Self {
         let kind = if communicate {
-            MonotonicClockKind::Host { epoch: StdInstant::now() }
+            MonotonicClockKind::Host { epoch: StdInstant::now(), host_log }
         } else {
             MonotonicClockKind::Virtual { nanoseconds: 0.into() }
         };
@@ -114,7 +119,7 @@ pub fn sleep(&self, duration: Duration) {
     /// Return the `epoch` instant (time = 0), to convert between monotone instants and absolute durations.
     pub fn epoch(&self) -> Instant {
         match &self.kind {
-            MonotonicClockKind::Host { epoch } => Instant { kind: InstantKind::Host(*epoch) },
+            MonotonicClockKind::Host { epoch, .. } => Instant { kind: InstantKind::Host(*epoch) },
             MonotonicClockKind::Virtual { .. } =>
                 Instant { kind: InstantKind::Virtual { nanoseconds: 0 } },
         }
@@ -122,8 +127,13 @@ pub fn epoch(&self) -> Instant {
 
     pub fn now(&self) -> Instant {
         match &self.kind {
-            MonotonicClockKind::Host { .. } =>
-                Instant { kind: InstantKind::Host(StdInstant::now()) },
+            MonotonicClockKind::Host { epoch, host_log } => {
+                let now = match host_log {
+                    Some(host_log) => host_log.monotonic_now(*epoch),
+                    None => StdInstant::now(),
+                };
+                Instant { kind: InstantKind::Host(now) }
+            }
             MonotonicClockKind::Virtual { nanoseconds } =>
                 Instant { kind: InstantKind::Virtual { nanoseconds: nanoseconds.get() } },
         }
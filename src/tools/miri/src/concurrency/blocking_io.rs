@@ -19,7 +19,7 @@
 
 /// Trait for file descriptions that contain a mio [`Source`].
 pub trait SourceFileDescription: FileDescription {
-    /// Invoke `f` on the source inside `self`.
+    /// Invoke `f` on the source inside `self`, if there is one on the host.
     fn with_source(&self, f: &mut dyn FnMut(&mut dyn Source) -> io::Result<()>) -> io::Result<()>;
 
     /// Get a mutable reference to the readiness of the source.
@@ -40,7 +40,7 @@ pub enum BlockingIoInterest {
 }
 
 /// Struct reflecting the readiness of a source file description.
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub struct BlockingIoSourceReadiness {
     /// Boolean whether the source is currently readable.
     pub readable: bool,
@@ -166,27 +166,24 @@ pub fn poll<'tcx>(
         ecx: &mut MiriInterpCx<'tcx>,
         timeout: Option<Duration>,
     ) -> InterpResult<'tcx, Result<(), io::Error>> {
-        let poll = ecx
-            .machine
-            .blocking_io
-            .poll
-            .as_mut()
-            .expect("Blocking I/O should not be called with isolation enabled");
-
-        // Poll for new I/O events from OS and store them in the events buffer.
-        if let Err(err) = poll.poll(&mut ecx.machine.blocking_io.events, timeout) {
-            return interp_ok(Err(err));
+        let host_log = ecx.machine.host_log.as_deref();
+        let events = if let Some(events) = host_log.and_then(HostLog::replay_io_events) {
+            // Deliver the events from the recording instead of the ones that happen now.
+            ecx.machine.blocking_io.replay_events(host_log.unwrap(), events)
+        } else {
+            let events = match ecx.machine.blocking_io.poll_host(timeout) {
+                Ok(events) => events,
+                Err(err) => return interp_ok(Err(err)),
+            };
+            if let Some(host_log) = host_log {
+                host_log.record_io_events(&events);
+            }
+            events
         };
 
-        let event_fds = ecx
-            .machine
-            .blocking_io
-            .events
-            .iter()
-            .map(|event| {
-                let token = event.token();
-                // We know all tokens are valid `FdId`.
-                let fd_id = FdId::new_unchecked(token.0);
+        let event_fds = events
+            .into_iter()
+            .map(|(fd_id, readiness)| {
                 let source = ecx
                     .machine
                     .blocking_io
@@ -199,7 +196,7 @@ pub fn poll<'tcx>(
 
                 assert_eq!(fd.id(), fd_id);
                 // Update the readiness of the source.
-                *fd.get_readiness_mut() |= BlockingIoSourceReadiness::from(event);
+                *fd.get_readiness_mut() |= readiness;
                 // Put FD into `event_fds` list.
                 fd
             })
@@ -238,6 +235,46 @@ pub fn poll<'tcx>(
         interp_ok(Ok(()))
     }
 
+    /// Poll for new I/O events from the OS, as described in [`BlockingIoManager::poll`],
+    /// and return them.
+    fn poll_host(
+        &mut self,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<(FdId, BlockingIoSourceReadiness)>, io::Error> {
+        let poll =
+            self.poll.as_mut().expect("Blocking I/O should not be called with isolation enabled");
+
+        // Poll for new I/O events from OS and store them in the events buffer.
+        poll.poll(&mut self.events, timeout)?;
+
+        let events = self
+            .events
+            .iter()
+            // We know all tokens are valid `FdId`.
+            .map(|event| (FdId::new_unchecked(event.token().0), event.into()))
+            .collect();
+        Ok(events)
+    }
+
+    /// Return the recorded `events` of a poll, which get delivered in place of the events of the
+    /// host. The host is not polled at all: the threads which get unblocked by these events read
+    /// and write the recorded data, so the events only have to arrive in the recorded order.
+    fn replay_events(
+        &self,
+        host_log: &HostLog,
+        mut events: Vec<(FdId, BlockingIoSourceReadiness)>,
+    ) -> Vec<(FdId, BlockingIoSourceReadiness)> {
+        // Events of sources that don't exist (anymore) mean that the execution diverged.
+        if let Some((fd_id, _)) = events.iter().find(|(fd_id, _)| !self.contains_source(fd_id)) {
+            host_log.diverge(format!(
+                "did not have the I/O source {} that became ready during the recording",
+                fd_id.to_usize()
+            ));
+            events.retain(|(fd_id, _)| self.contains_source(fd_id));
+        }
+        events
+    }
+
     /// Return whether a source file description is currently registered in the
     /// blocking I/O poll.
     /// This can also be used to check whether a file description is a host
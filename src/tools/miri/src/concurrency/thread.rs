@@ -18,7 +18,7 @@
 use rustc_target::spec::Os;
 
 use crate::concurrency::GlobalDataRaceHandler;
-use crate::shims::{Epoll, EpollEvalContextExt, FileDescriptionRef, tls};
+use crate::shims::{Epoll, EpollEvalContextExt, FileDescriptionRef, TimerFdEvalContextExt, tls};
 use crate::*;
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -110,6 +110,10 @@ pub enum BlockReason {
     Epoll { epfd: FileDescriptionRef<Epoll> },
     /// Blocked on eventfd.
     Eventfd,
+    /// Blocked on timerfd.
+    Timerfd,
+    /// Blocked on signalfd.
+    Signalfd,
     /// Blocked on virtual socket.
     VirtualSocket,
     /// Blocked on an IO operation.
@@ -383,11 +387,13 @@ enum Timeout {
 
 impl Timeout {
     /// How long do we have to wait from now until the specified time?
-    fn get_wait_time(&self, clock: &MonotonicClock) -> Duration {
+    fn get_wait_time(&self, clock: &MonotonicClock, host_log: Option<&HostLog>) -> Duration {
         match self {
             Timeout::Monotonic(instant) => instant.duration_since(clock.now()),
-            Timeout::RealTime(time) =>
-                time.duration_since(SystemTime::now()).unwrap_or(Duration::ZERO),
+            Timeout::RealTime(time) => {
+                let now = host_log.map_or_else(SystemTime::now, HostLog::system_time_now);
+                time.duration_since(now).unwrap_or(Duration::ZERO)
+            }
         }
     }
 
@@ -749,12 +755,21 @@ fn schedule(&mut self) -> InterpResult<'tcx, SchedulingAction> {
             this.poll_and_unblock(Some(Duration::ZERO))?;
         }
 
+        // Let the timerfds that expired in the meantime fire, which may unblock threads as well.
+        this.fire_expired_timerfds()?;
+
         // We also check timeouts before running any other thread, to ensure that timeouts
         // "in the past" fire before any other thread can take an action. This ensures that for
         // `pthread_cond_timedwait`, "an error is returned if [...] the absolute time specified by
         // abstime has already been passed at the time of the call".
         // <https://pubs.opengroup.org/onlinepubs/9699919799/functions/pthread_cond_timedwait.html>
         let potential_sleep_time = this.unblock_expired_timeouts()?;
+        // Threads waiting for a timerfd don't have a timeout, but we still have to wake up
+        // when the timer expires.
+        let potential_sleep_time = match (potential_sleep_time, this.next_timerfd_expiration()) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
 
         let thread_manager = &mut this.machine.threads;
         let rng = this.machine.rng.get_mut();
@@ -860,6 +875,7 @@ fn poll_and_unblock(&mut self, timeout: Option<Duration>) -> InterpResult<'tcx>
     fn unblock_expired_timeouts(&mut self) -> InterpResult<'tcx, Option<Duration>> {
         let this = self.eval_context_mut();
         let clock = &this.machine.monotonic_clock;
+        let host_log = this.machine.host_log.as_deref();
 
         let mut min_wait_time = Option::<Duration>::None;
         let mut callbacks = Vec::new();
@@ -867,7 +883,7 @@ fn unblock_expired_timeouts(&mut self) -> InterpResult<'tcx, Option<Duration>> {
         for (id, thread) in this.machine.threads.threads.iter_enumerated_mut() {
             match &thread.state {
                 ThreadState::Blocked { timeout: Some(timeout), .. } => {
-                    let wait_time = timeout.get_wait_time(clock);
+                    let wait_time = timeout.get_wait_time(clock, host_log);
                     if wait_time.is_zero() {
                         // The timeout expired for this thread.
                         let old_state = mem::replace(&mut thread.state, ThreadState::Enabled);
@@ -1113,7 +1129,7 @@ fn block_thread(
                     );
                     Timeout::RealTime(match anchor {
                         TimeoutAnchor::Absolute => SystemTime::UNIX_EPOCH,
-                        TimeoutAnchor::Relative => SystemTime::now(),
+                        TimeoutAnchor::Relative => this.machine.system_time_now(),
                     })
                 }
                 TimeoutClock::Monotonic =>
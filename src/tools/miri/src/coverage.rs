@@ -0,0 +1,196 @@
+//! Code coverage of the interpreted program (`-Zmiri-coverage`).
+//!
+//! We count how often every basic block of the user-relevant functions was executed, and map the
+//! blocks back to the source lines of their statements. We also count how often every target of a
+//! `SwitchInt` terminator was jumped to, which gives the branch coverage. At the end of the
+//! execution, the counts are written out in the lcov tracefile format, which is understood by most
+//! coverage tools.
+//! Since Miri does not inline or otherwise optimize MIR, every block belongs to a single
+//! function, and the counts of all instances of a generic function are added up.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::{fs, io};
+
+use rustc_data_structures::fx::{FxHashMap, FxIndexMap};
+use rustc_hir::def::DefKind;
+use rustc_middle::mir::{BasicBlock, START_BLOCK, TerminatorKind};
+use rustc_middle::ty::print::with_no_trimmed_paths;
+use rustc_middle::ty::{self, TyCtxt};
+use rustc_span::def_id::DefId;
+use rustc_span::{FileName, Span};
+
+use crate::*;
+
+/// The coverage information collected for `-Zmiri-coverage`.
+#[derive(Debug)]
+pub struct Coverage {
+    /// The file to write the report to.
+    out: PathBuf,
+    /// How often every basic block of the user-relevant functions was executed.
+    blocks: FxHashMap<(DefId, BasicBlock), u64>,
+    /// How often control went from the first to the second block of a function.
+    edges: FxHashMap<(DefId, BasicBlock, BasicBlock), u64>,
+}
+
+/// The coverage of a single source file.
+#[derive(Default)]
+struct FileCoverage {
+    /// The first line, name, and number of calls of every function in the file.
+    functions: Vec<(usize, String, u64)>,
+    /// How often every line with code on it was executed.
+    lines: BTreeMap<usize, u64>,
+    /// The line, branch point, branch, and number of times taken of every branch in the file.
+    /// The number is `None` if the branch point itself never ran.
+    branches: Vec<(usize, usize, usize, Option<u64>)>,
+}
+
+impl Coverage {
+    pub fn new(out: PathBuf) -> Self {
+        Coverage { out, blocks: FxHashMap::default(), edges: FxHashMap::default() }
+    }
+
+    /// Record that `instance` is about to execute the terminator of `block`, and that `prev` was
+    /// the block of the same frame that ran before it.
+    /// This is called exactly once for every basic block that is executed.
+    pub fn record_block(
+        &mut self,
+        instance: ty::Instance<'_>,
+        prev: Option<BasicBlock>,
+        block: BasicBlock,
+    ) {
+        let ty::InstanceKind::Item(def_id) = instance.def else { return };
+        *self.blocks.entry((def_id, block)).or_default() += 1;
+        if let Some(prev) = prev {
+            *self.edges.entry((def_id, prev, block)).or_default() += 1;
+        }
+    }
+
+    /// Write the report to the file given to `-Zmiri-coverage`.
+    ///
+    /// The report covers all functions of the local crate, including the ones that never ran,
+    /// and the functions of other user-relevant crates that ran at least once.
+    pub fn write(&self, tcx: TyCtxt<'_>) -> io::Result<()> {
+        let mut functions: Vec<DefId> = tcx
+            .mir_keys(())
+            .iter()
+            .map(|def_id| def_id.to_def_id())
+            .filter(|&def_id| {
+                matches!(tcx.def_kind(def_id), DefKind::Fn | DefKind::AssocFn | DefKind::Closure)
+            })
+            .collect();
+        let mut executed: Vec<DefId> = self
+            .blocks
+            .keys()
+            .map(|&(def_id, _)| def_id)
+            .filter(|def_id| !def_id.is_local())
+            .collect();
+        executed.sort();
+        executed.dedup();
+        functions.extend(executed);
+
+        let mut files: FxIndexMap<PathBuf, FileCoverage> = FxIndexMap::default();
+        for def_id in functions {
+            let body = tcx.instance_mir(ty::InstanceKind::Item(def_id));
+            // Functions whose source isn't available, like the ones of the standard library
+            // with remapped paths, are not part of the report.
+            let Some((path, first_line)) = source_line(tcx, body.span) else { continue };
+            let file = files.entry(path).or_default();
+            let calls = self.blocks.get(&(def_id, START_BLOCK)).copied().unwrap_or(0);
+            let name = with_no_trimmed_paths!(tcx.def_path_str(def_id));
+            file.functions.push((first_line, name, calls));
+
+            // Every `SwitchInt` terminator is a branch point, and each of its targets a branch.
+            let mut branch_point = 0;
+            for (block, data) in body.basic_blocks.iter_enumerated() {
+                if data.is_cleanup {
+                    continue;
+                }
+                let terminator = data.terminator();
+                let TerminatorKind::SwitchInt { targets, .. } = &terminator.kind else { continue };
+                let Some(span) =
+                    terminator.source_info.span.find_ancestor_inside_same_ctxt(body.span)
+                else {
+                    continue;
+                };
+                let Some((_, line)) = source_line(tcx, span) else { continue };
+                let ran = self.blocks.contains_key(&(def_id, block));
+                for (branch, &target) in targets.all_targets().iter().enumerate() {
+                    let taken =
+                        ran.then(|| self.edges.get(&(def_id, block, target)).copied().unwrap_or(0));
+                    file.branches.push((line, branch_point, branch, taken));
+                }
+                branch_point += 1;
+            }
+
+            for (block, data) in body.basic_blocks.iter_enumerated() {
+                // Cleanup blocks only run during unwinding, and don't correspond to any code the
+                // user wrote.
+                if data.is_cleanup {
+                    continue;
+                }
+                let count = self.blocks.get(&(def_id, block)).copied().unwrap_or(0);
+                let spans =
+                    data.statements.iter().map(|statement| statement.source_info.span).chain(
+                        data.terminator.as_ref().map(|terminator| terminator.source_info.span),
+                    );
+                for span in spans {
+                    // Code expanded from macros is attributed to the macro invocation.
+                    let Some(span) = span.find_ancestor_inside_same_ctxt(body.span) else {
+                        continue;
+                    };
+                    let Some((_, line)) = source_line(tcx, span) else { continue };
+                    // A line counts as executed as often as the code on it that ran the most.
+                    let line_count = file.lines.entry(line).or_default();
+                    *line_count = (*line_count).max(count);
+                }
+            }
+        }
+
+        let mut report = String::new();
+        for (path, file) in files {
+            writeln!(report, "SF:{}", path.display()).unwrap();
+            for (line, name, _) in &file.functions {
+                writeln!(report, "FN:{line},{name}").unwrap();
+            }
+            for (_, name, calls) in &file.functions {
+                writeln!(report, "FNDA:{calls},{name}").unwrap();
+            }
+            let functions_hit = file.functions.iter().filter(|(_, _, calls)| *calls > 0).count();
+            writeln!(report, "FNF:{}", file.functions.len()).unwrap();
+            writeln!(report, "FNH:{functions_hit}").unwrap();
+            for (line, branch_point, branch, taken) in &file.branches {
+                let taken = taken.map_or_else(|| "-".to_owned(), |taken| taken.to_string());
+                writeln!(report, "BRDA:{line},{branch_point},{branch},{taken}").unwrap();
+            }
+            let branches_hit =
+                file.branches.iter().filter(|&&(.., taken)| taken.is_some_and(|t| t > 0)).count();
+            writeln!(report, "BRF:{}", file.branches.len()).unwrap();
+            writeln!(report, "BRH:{branches_hit}").unwrap();
+            for (line, count) in &file.lines {
+                writeln!(report, "DA:{line},{count}").unwrap();
+            }
+            let lines_hit = file.lines.values().filter(|&&count| count > 0).count();
+            writeln!(report, "LF:{}", file.lines.len()).unwrap();
+            writeln!(report, "LH:{lines_hit}").unwrap();
+            writeln!(report, "end_of_record").unwrap();
+        }
+        fs::write(&self.out, report)
+    }
+
+    pub fn out(&self) -> &Path {
+        &self.out
+    }
+}
+
+/// Returns the path of the source file `span` starts in, and the (1-based) line it starts at.
+/// Returns `None` if the file is not available on disk.
+fn source_line(tcx: TyCtxt<'_>, span: Span) -> Option<(PathBuf, usize)> {
+    if span.is_dummy() {
+        return None;
+    }
+    let loc = tcx.sess.source_map().lookup_char_pos(span.lo());
+    let FileName::Real(real) = &loc.file.name else { return None };
+    Some((real.local_path()?.to_owned(), loc.line))
+}
@@ -21,6 +21,7 @@
 use crate::concurrency::GenmcCtx;
 use crate::concurrency::thread::TlsAllocAction;
 use crate::diagnostics::report_leaks;
+use crate::leak_graph::write_leak_graph;
 use crate::shims::{global_ctor, tls};
 use crate::*;
 
@@ -79,6 +80,10 @@ pub struct MiriConfig {
     /// If `Some`, enable the `measureme` profiler, writing results to a file
     /// with the specified prefix.
     pub measureme_out: Option<String>,
+    /// If `Some`, write an lcov coverage report of the user-relevant code to this file.
+    pub coverage_out: Option<PathBuf>,
+    /// If `Some`, record the interactions with the host to a directory, or replay them from it.
+    pub host_log: Option<HostLogMode>,
     /// Which style to use for printing backtraces.
     pub backtrace_style: BacktraceStyle,
     /// Which provenance to use for int2ptr casts.
@@ -102,6 +107,8 @@ pub struct MiriConfig {
     pub page_size: Option<u64>,
     /// Whether to collect a backtrace when each allocation is created, just in case it leaks.
     pub collect_leak_backtraces: bool,
+    /// If `Some`, write a graph of the leaked allocations to this file.
+    pub leak_graph_out: Option<PathBuf>,
     /// Probability for address reuse.
     pub address_reuse_rate: f64,
     /// Probability for address reuse across threads.
@@ -142,6 +149,8 @@ fn default() -> MiriConfig {
             track_outdated_loads: false,
             cmpxchg_weak_failure_rate: 0.8, // 80%
             measureme_out: None,
+            coverage_out: None,
+            host_log: None,
             backtrace_style: BacktraceStyle::Short,
             provenance_mode: ProvenanceMode::Default,
             mute_stdout_stderr: false,
@@ -153,6 +162,7 @@ fn default() -> MiriConfig {
             num_cpus: 1,
             page_size: None,
             collect_leak_backtraces: true,
+            leak_graph_out: None,
             address_reuse_rate: 0.5,
             address_reuse_cross_thread_rate: 0.1,
             fixed_scheduling: false,
@@ -489,6 +499,21 @@ pub fn eval_entry<'tcx>(
     // indicate an error.
     let Err(res) = res.report_err();
 
+    // Write the coverage report, even if the program had UB: it shows what ran up to that point.
+    if let Some(coverage) = &ecx.machine.coverage
+        && let Err(err) = coverage.write(tcx)
+    {
+        tcx.dcx().fatal(format!(
+            "failed to write the coverage report to `{}`: {err}",
+            coverage.out().display()
+        ));
+    }
+    // Write the host log when recording, also if the program had UB: reproducing that is the
+    // point of recording it.
+    if let Some(host_log) = &ecx.machine.host_log {
+        host_log.finish(tcx);
+    }
+
     // Error reporting: if we survive all checks, we return the exit code the program gave us.
     'miri_error: {
         // Show diagnostic, if any.
@@ -510,7 +535,21 @@ pub fn eval_entry<'tcx>(
             info!("Additional static roots: {:?}", ecx.machine.static_roots);
             let leaks = ecx.take_leaked_allocations(|ecx| &ecx.machine.static_roots);
             if !leaks.is_empty() {
+                if let Some(path) = &config.leak_graph_out
+                    && let Err(err) = write_leak_graph(&ecx, &leaks, path)
+                {
+                    tcx.dcx().fatal(format!(
+                        "failed to write the leak graph to `{}`: {err}",
+                        path.display()
+                    ));
+                }
                 report_leaks(&ecx, leaks);
+                if let Some(path) = &config.leak_graph_out {
+                    tcx.dcx().note(format!(
+                        "a graph of the leaked allocations was written to `{}`",
+                        path.display()
+                    ));
+                }
                 tcx.dcx().note("set `MIRIFLAGS=-Zmiri-ignore-leaks` to disable this check");
                 // Ignore the provided return code - let the reported error
                 // determine the return code.
@@ -371,8 +371,11 @@ fn gen_random(&mut self, ptr: Pointer, len: u64) -> InterpResult<'tcx> {
 
         if this.machine.communicate() {
             // Fill the buffer using the host's rng.
-            getrandom::fill(&mut data)
-                .map_err(|err| err_unsup_format!("host getrandom failed: {}", err))?;
+            match &this.machine.host_log {
+                Some(host_log) => host_log.random(&mut data, getrandom::fill),
+                None => getrandom::fill(&mut data).map_err(|err| err.to_string()),
+            }
+            .map_err(|err| err_unsup_format!("host getrandom failed: {}", err))?;
         } else {
             let rng = this.machine.rng.get_mut();
             rng.fill_bytes(&mut data);
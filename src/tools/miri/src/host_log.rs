@@ -0,0 +1,942 @@
+//! Recording and replaying the interactions with the host (`-Zmiri-record`, `-Zmiri-replay`).
+//!
+//! With isolation disabled, the execution of the interpreted program depends on the host: the
+//! clocks, the environment, the host's randomness, the data of files and sockets, and when host
+//! I/O becomes ready all influence what the program does and how its threads are interleaved.
+//! Everything else Miri decides based on its seed, so recording these results together with the
+//! seed lets us reproduce the execution, and in particular the scheduling decisions that led to a
+//! bug.
+//!
+//! The log is a sequence of entries, one per line, in the order in which the interpreter asked
+//! the host. When replaying, every question is answered with the next entry instead. If the
+//! execution takes a different path than during the recording, the entries no longer match
+//! the questions; from then on, we ask the host again and report the divergence at the end.
+//!
+//! Operations on host files and sockets are answered from the log without touching the host:
+//! opening and reading files, changing the file system, connecting and accepting sockets, and
+//! so on. Files and sockets opened during the replay are stand-ins that only exist to be the
+//! targets of these answers, and host I/O becomes ready in the recorded order instead of when the
+//! host reports it. Listing directories and the file operations of Windows targets still go to
+//! the host, so the directories and files they need have to exist during the replay.
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::ffi::{OsStr, OsString};
+use std::fmt::{self, Write as _};
+use std::iter::Peekable;
+#[cfg(unix)]
+use std::os::unix::ffi::{OsStrExt, OsStringExt};
+#[cfg(windows)]
+use std::os::windows::ffi::{OsStrExt, OsStringExt};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Duration, Instant as StdInstant, SystemTime};
+use std::{fs, io};
+
+use rustc_data_structures::fx::FxHashMap;
+use rustc_middle::ty::TyCtxt;
+
+use crate::shims::io_error::host_error_kinds;
+use crate::shims::{FdId, FileMetadata, SocketAddress};
+use crate::*;
+
+/// Whether to record the interactions with the host or replay them.
+#[derive(Debug, Clone)]
+pub enum HostLogMode {
+    /// Record the interactions into a log in the given directory.
+    Record(PathBuf),
+    /// Replay the interactions from the log in the given directory.
+    Replay(PathBuf),
+}
+
+/// A single interaction with the host.
+#[derive(Debug)]
+enum Entry {
+    /// The seed of Miri's random number generator.
+    Seed(u64),
+    /// The environment variables passed to the program.
+    Env(Vec<(OsString, OsString)>),
+    /// The time of the monotonic clock, relative to its epoch.
+    Monotonic(Duration),
+    /// The system time, in nanoseconds since the Unix epoch.
+    SystemTime(i128),
+    /// Random bytes from the host, or why the host could not provide them.
+    Random(Result<Vec<u8>, String>),
+    /// The I/O events of a poll of the host sources.
+    Io(Vec<(FdId, BlockingIoSourceReadiness)>),
+    /// The bytes read from a host file or socket.
+    Read(Result<Vec<u8>, LoggedError>),
+    /// The bytes of a datagram received from a host socket, and where it came from.
+    RecvFrom(Result<(Vec<u8>, SocketAddress), LoggedError>),
+    /// The number of bytes written to a host file or socket.
+    Write(Result<usize, LoggedError>),
+    /// The new offset of a host file after a seek.
+    Seek(Result<u64, LoggedError>),
+    /// The metadata of a host file.
+    Metadata(Result<FileMetadata, LoggedError>),
+    /// The result of another host operation, like opening a file or connecting a socket, with
+    /// the name of the operation and the value it returned as written by [`LogValue::write`].
+    Op { name: String, result: Result<String, LoggedError> },
+}
+
+/// An error of a host operation, as far as it matters to the interpreted program.
+#[derive(Debug, Clone, Copy)]
+enum LoggedError {
+    /// An error with an OS error code, which is only meaningful on the same host OS.
+    Os(i32),
+    /// An error without an OS error code.
+    Kind(io::ErrorKind),
+}
+
+impl From<&io::Error> for LoggedError {
+    fn from(err: &io::Error) -> Self {
+        match err.raw_os_error() {
+            Some(code) => LoggedError::Os(code),
+            None => LoggedError::Kind(err.kind()),
+        }
+    }
+}
+
+impl From<LoggedError> for io::Error {
+    fn from(err: LoggedError) -> Self {
+        match err {
+            LoggedError::Os(code) => io::Error::from_raw_os_error(code),
+            LoggedError::Kind(kind) => kind.into(),
+        }
+    }
+}
+
+impl fmt::Display for LoggedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoggedError::Os(code) => write!(f, "{code}"),
+            LoggedError::Kind(kind) => write!(f, "{kind:?}"),
+        }
+    }
+}
+
+impl FromStr for LoggedError {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, ()> {
+        if let Ok(code) = s.parse() {
+            return Ok(LoggedError::Os(code));
+        }
+        // Only the kinds we can turn into an error of the target can matter to the program.
+        host_error_kinds()
+            .chain([io::ErrorKind::Other])
+            .find(|kind| format!("{kind:?}") == s)
+            .map(LoggedError::Kind)
+            .ok_or(())
+    }
+}
+
+/// Turns the result of a host operation into its representation in the log.
+fn logged<T: Clone>(result: &io::Result<T>) -> Result<T, LoggedError> {
+    result.as_ref().map(T::clone).map_err(LoggedError::from)
+}
+
+impl Entry {
+    fn name(&self) -> &'static str {
+        match self {
+            Entry::Seed(_) => "seed",
+            Entry::Env(_) => "env",
+            Entry::Monotonic(_) => "monotonic",
+            Entry::SystemTime(_) => "system",
+            Entry::Random(_) => "random",
+            Entry::Io(_) => "io",
+            Entry::Read(_) => "read",
+            Entry::RecvFrom(_) => "recvfrom",
+            Entry::Write(_) => "write",
+            Entry::Seek(_) => "seek",
+            Entry::Metadata(_) => "metadata",
+            Entry::Op { .. } => "op",
+        }
+    }
+
+    /// Describes the interaction of the entry, for reporting divergences.
+    fn description(&self) -> &str {
+        match self {
+            Entry::Op { name, .. } => name,
+            _ => self.name(),
+        }
+    }
+
+    fn write(&self, out: &mut String) {
+        out.push_str(self.name());
+        match self {
+            Entry::Seed(seed) => write!(out, " {seed}").unwrap(),
+            Entry::Env(vars) =>
+                for (name, value) in vars {
+                    write!(out, " {}={}", hex(&os_str_to_bytes(name)), hex(&os_str_to_bytes(value)))
+                        .unwrap();
+                },
+            Entry::Monotonic(duration) => write!(out, " {}", duration.as_nanos()).unwrap(),
+            Entry::SystemTime(nanos) => write!(out, " {nanos}").unwrap(),
+            Entry::Random(Ok(bytes)) => write!(out, " {}", hex(bytes)).unwrap(),
+            // The message of the error is hex-encoded, as it can contain spaces.
+            Entry::Random(Err(err)) => write!(out, " !{}", hex(err.as_bytes())).unwrap(),
+            Entry::Io(events) =>
+                for (id, readiness) in events {
+                    write!(out, " {}:", id.to_usize()).unwrap();
+                    for (flag, set) in readiness_flags(readiness) {
+                        if set {
+                            out.push(flag);
+                        }
+                    }
+                },
+            Entry::Read(result) =>
+                write_result(out, result, |out, bytes| write!(out, " {}", hex(bytes)).unwrap()),
+            Entry::RecvFrom(result) =>
+                write_result(out, result, |out, (bytes, address)| {
+                    write!(out, " {} ", hex(bytes)).unwrap();
+                    write_socket_address(out, address);
+                }),
+            Entry::Write(result) =>
+                write_result(out, result, |out, len| write!(out, " {len}").unwrap()),
+            Entry::Seek(result) =>
+                write_result(out, result, |out, offset| write!(out, " {offset}").unwrap()),
+            Entry::Metadata(result) => write_result(out, result, write_metadata),
+            Entry::Op { name, result } => {
+                write!(out, " {name}").unwrap();
+                write_result(out, result, |out, value| out.push_str(value));
+            }
+        }
+        out.push('\n');
+    }
+
+    fn parse(line: &str) -> Option<Entry> {
+        let mut words = line.split(' ').peekable();
+        let name = words.next()?;
+        let entry = match name {
+            "seed" => Entry::Seed(words.next()?.parse().ok()?),
+            "env" =>
+                Entry::Env(
+                    words
+                        .by_ref()
+                        .map(|var| {
+                            let (name, value) = var.split_once('=')?;
+                            Some((
+                                bytes_to_os_string(unhex(name)?)?,
+                                bytes_to_os_string(unhex(value)?)?,
+                            ))
+                        })
+                        .collect::<Option<_>>()?,
+                ),
+            "monotonic" => Entry::Monotonic(Duration::from_nanos_u128(words.next()?.parse().ok()?)),
+            "system" => Entry::SystemTime(words.next()?.parse().ok()?),
+            "random" => {
+                let word = words.next()?;
+                Entry::Random(match word.strip_prefix('!') {
+                    Some(err) => Err(String::from_utf8(unhex(err)?).ok()?),
+                    None => Ok(unhex(word)?),
+                })
+            }
+            "io" =>
+                Entry::Io(
+                    words
+                        .by_ref()
+                        .map(|event| {
+                            let (id, flags) = event.split_once(':')?;
+                            let mut readiness = BlockingIoSourceReadiness::empty();
+                            for flag in flags.chars() {
+                                match flag {
+                                    'r' => readiness.readable = true,
+                                    'w' => readiness.writable = true,
+                                    'R' => readiness.read_closed = true,
+                                    'W' => readiness.write_closed = true,
+                                    'e' => readiness.error = true,
+                                    _ => return None,
+                                }
+                            }
+                            Some((FdId::new_unchecked(id.parse().ok()?), readiness))
+                        })
+                        .collect::<Option<_>>()?,
+                ),
+            "read" => Entry::Read(parse_result(&mut words, |words| unhex(words.next()?))?),
+            "recvfrom" =>
+                Entry::RecvFrom(parse_result(&mut words, |words| {
+                    Some((unhex(words.next()?)?, parse_socket_address(words.next()?)?))
+                })?),
+            "write" => Entry::Write(parse_result(&mut words, |words| words.next()?.parse().ok())?),
+            "seek" => Entry::Seek(parse_result(&mut words, |words| words.next()?.parse().ok())?),
+            "metadata" => Entry::Metadata(parse_result(&mut words, parse_metadata)?),
+            "op" => {
+                let name = words.next()?.to_owned();
+                // Operations without a value have nothing after their name when they succeed.
+                let result = if words.peek().is_none() {
+                    Ok(String::new())
+                } else {
+                    parse_result(&mut words, |words| {
+                        Some(words.by_ref().map(|word| format!(" {word}")).collect())
+                    })?
+                };
+                Entry::Op { name, result }
+            }
+            _ => return None,
+        };
+        // There must not be anything left.
+        words.next().is_none().then_some(entry)
+    }
+}
+
+/// The flags of `readiness`, with the letters that represent them in the log.
+fn readiness_flags(readiness: &BlockingIoSourceReadiness) -> [(char, bool); 5] {
+    [
+        ('r', readiness.readable),
+        ('w', readiness.writable),
+        ('R', readiness.read_closed),
+        ('W', readiness.write_closed),
+        ('e', readiness.error),
+    ]
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn unhex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len()).step_by(2).map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok()).collect()
+}
+
+/// Writes the result of a host operation: `!` followed by the error, or what `ok` writes.
+fn write_result<T>(
+    out: &mut String,
+    result: &Result<T, LoggedError>,
+    ok: impl FnOnce(&mut String, &T),
+) {
+    match result {
+        Ok(value) => ok(out, value),
+        Err(err) => write!(out, " !{err}").unwrap(),
+    }
+}
+
+/// Parses the result of a host operation written by [`write_result`], using `ok` to parse the
+/// words of a successful result.
+fn parse_result<'a, I: Iterator<Item = &'a str>, T>(
+    words: &mut Peekable<I>,
+    ok: impl FnOnce(&mut Peekable<I>) -> Option<T>,
+) -> Option<Result<T, LoggedError>> {
+    match words.peek()?.strip_prefix('!') {
+        Some(err) => {
+            let err = err.parse().ok()?;
+            words.next();
+            Some(Err(err))
+        }
+        None => ok(words).map(Ok),
+    }
+}
+
+fn write_metadata(out: &mut String, metadata: &FileMetadata) {
+    let FileMetadata {
+        mode,
+        size,
+        created,
+        accessed,
+        modified,
+        dev,
+        ino,
+        nlink,
+        uid,
+        gid,
+        blksize,
+        blocks,
+    } = metadata;
+    write!(out, " {mode} {size}").unwrap();
+    for time in [created, accessed, modified] {
+        match time {
+            Some((sec, nsec)) => write!(out, " {sec}.{nsec}").unwrap(),
+            None => out.push_str(" -"),
+        }
+    }
+    let uid = uid.map(u64::from);
+    let gid = gid.map(u64::from);
+    for field in [dev, ino, nlink, &uid, &gid, blksize, blocks] {
+        match field {
+            Some(value) => write!(out, " {value}").unwrap(),
+            None => out.push_str(" -"),
+        }
+    }
+}
+
+fn parse_metadata<'a>(words: &mut impl Iterator<Item = &'a str>) -> Option<FileMetadata> {
+    /// Parses a field that is `-` if the host did not provide it.
+    fn optional<T: FromStr>(word: Option<&str>) -> Option<Option<T>> {
+        match word? {
+            "-" => Some(None),
+            word => word.parse().ok().map(Some),
+        }
+    }
+    fn time(word: Option<&str>) -> Option<Option<(u64, u32)>> {
+        match word? {
+            "-" => Some(None),
+            word => {
+                let (sec, nsec) = word.split_once('.')?;
+                Some(Some((sec.parse().ok()?, nsec.parse().ok()?)))
+            }
+        }
+    }
+    Some(FileMetadata {
+        mode: words.next()?.parse().ok()?,
+        size: words.next()?.parse().ok()?,
+        created: time(words.next())?,
+        accessed: time(words.next())?,
+        modified: time(words.next())?,
+        dev: optional(words.next())?,
+        ino: optional(words.next())?,
+        nlink: optional(words.next())?,
+        uid: optional(words.next())?,
+        gid: optional(words.next())?,
+        blksize: optional(words.next())?,
+        blocks: optional(words.next())?,
+    })
+}
+
+/// Writes `address` as `inet:` followed by the IP address and port, or `unix:` followed by the
+/// hex-encoded path, which is empty for unnamed sockets.
+fn write_socket_address(out: &mut String, address: &SocketAddress) {
+    match address {
+        SocketAddress::Inet(address) => write!(out, "inet:{address}").unwrap(),
+        SocketAddress::Unix(path) => {
+            let path = path.as_deref().map(|path| os_str_to_bytes(path.as_os_str()));
+            write!(out, "unix:{}", hex(path.as_deref().unwrap_or_default())).unwrap()
+        }
+    }
+}
+
+fn parse_socket_address(word: &str) -> Option<SocketAddress> {
+    if let Some(address) = word.strip_prefix("inet:") {
+        return Some(SocketAddress::Inet(address.parse().ok()?));
+    }
+    let path = word.strip_prefix("unix:")?;
+    if path.is_empty() {
+        return Some(SocketAddress::Unix(None));
+    }
+    Some(SocketAddress::Unix(Some(bytes_to_os_string(unhex(path)?)?.into())))
+}
+
+/// A value returned by a host operation, which can be written to the log.
+pub trait LogValue: Sized {
+    /// Writes the value as words that are each preceded by a space.
+    fn write(&self, out: &mut String);
+    /// Parses the words written by [`LogValue::write`].
+    fn parse<'a>(words: &mut impl Iterator<Item = &'a str>) -> Option<Self>;
+}
+
+impl LogValue for () {
+    fn write(&self, _out: &mut String) {}
+
+    fn parse<'a>(_words: &mut impl Iterator<Item = &'a str>) -> Option<Self> {
+        Some(())
+    }
+}
+
+impl LogValue for u32 {
+    fn write(&self, out: &mut String) {
+        write!(out, " {self}").unwrap();
+    }
+
+    fn parse<'a>(words: &mut impl Iterator<Item = &'a str>) -> Option<Self> {
+        words.next()?.parse().ok()
+    }
+}
+
+impl LogValue for PathBuf {
+    fn write(&self, out: &mut String) {
+        write!(out, " {}", hex(&os_str_to_bytes(self.as_os_str()))).unwrap();
+    }
+
+    fn parse<'a>(words: &mut impl Iterator<Item = &'a str>) -> Option<Self> {
+        Some(bytes_to_os_string(unhex(words.next()?)?)?.into())
+    }
+}
+
+impl LogValue for SocketAddress {
+    fn write(&self, out: &mut String) {
+        out.push(' ');
+        write_socket_address(out, self);
+    }
+
+    fn parse<'a>(words: &mut impl Iterator<Item = &'a str>) -> Option<Self> {
+        parse_socket_address(words.next()?)
+    }
+}
+
+/// A pending error of a socket, which is `-` if there is none.
+impl LogValue for Option<io::Error> {
+    fn write(&self, out: &mut String) {
+        match self {
+            Some(err) => write!(out, " {}", LoggedError::from(err)).unwrap(),
+            None => out.push_str(" -"),
+        }
+    }
+
+    fn parse<'a>(words: &mut impl Iterator<Item = &'a str>) -> Option<Self> {
+        match words.next()? {
+            "-" => Some(None),
+            err => Some(Some(err.parse::<LoggedError>().ok()?.into())),
+        }
+    }
+}
+
+/// Returns the raw representation of `s` on the host: its bytes on Unix hosts, and its UTF-16 code
+/// units in little endian on Windows hosts.
+#[cfg(unix)]
+fn os_str_to_bytes(s: &OsStr) -> Vec<u8> {
+    s.as_bytes().to_vec()
+}
+#[cfg(windows)]
+fn os_str_to_bytes(s: &OsStr) -> Vec<u8> {
+    s.encode_wide().flat_map(u16::to_le_bytes).collect()
+}
+
+/// The inverse of [`os_str_to_bytes`].
+#[cfg(unix)]
+fn bytes_to_os_string(bytes: Vec<u8>) -> Option<OsString> {
+    Some(OsString::from_vec(bytes))
+}
+#[cfg(windows)]
+fn bytes_to_os_string(bytes: Vec<u8>) -> Option<OsString> {
+    if !bytes.len().is_multiple_of(2) {
+        return None;
+    }
+    let wide: Vec<u16> =
+        bytes.chunks_exact(2).map(|unit| u16::from_le_bytes([unit[0], unit[1]])).collect();
+    Some(OsString::from_wide(&wide))
+}
+
+#[derive(Debug)]
+enum State {
+    Recording(Vec<Entry>),
+    Replaying {
+        entries: VecDeque<Entry>,
+        /// The number of entries that were replayed so far.
+        replayed: usize,
+        /// Why the execution diverged from the recording, if it did.
+        diverged: Option<String>,
+    },
+}
+
+/// The log of the interactions with the host.
+#[derive(Debug)]
+pub struct HostLog {
+    /// The file the log is recorded to or replayed from.
+    path: PathBuf,
+    state: RefCell<State>,
+}
+
+impl HostLog {
+    /// Start recording or replaying the log of the crate `crate_name`.
+    pub fn new(mode: &HostLogMode, crate_name: &str) -> io::Result<HostLog> {
+        let file_name = format!("{crate_name}.log");
+        let (path, state) = match mode {
+            HostLogMode::Record(dir) => (dir.join(file_name), State::Recording(Vec::new())),
+            HostLogMode::Replay(dir) => {
+                let path = dir.join(file_name);
+                let log = fs::read_to_string(&path).map_err(|err| {
+                    io::Error::new(err.kind(), format!("`{}`: {err}", path.display()))
+                })?;
+                let entries = log
+                    .lines()
+                    .enumerate()
+                    .map(|(i, line)| {
+                        Entry::parse(line).ok_or_else(|| {
+                            io::Error::new(
+                                io::ErrorKind::InvalidData,
+                                format!("`{}`: invalid entry on line {}", path.display(), i + 1),
+                            )
+                        })
+                    })
+                    .collect::<io::Result<_>>()?;
+                (path, State::Replaying { entries, replayed: 0, diverged: None })
+            }
+        };
+        Ok(HostLog { path, state: RefCell::new(state) })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the next entry when replaying, turned back into the result of an interaction by
+    /// `extract`. Returns `None` if we are not replaying, or if the execution diverged from the
+    /// recording, e.g. because `extract` found an entry for a different interaction.
+    fn replay<T>(&self, what: &str, extract: impl FnOnce(Entry) -> Result<T, Entry>) -> Option<T> {
+        let mut state = self.state.borrow_mut();
+        let State::Replaying { entries, replayed, diverged: diverged @ None } = &mut *state else {
+            return None;
+        };
+        let Some(entry) = entries.pop_front() else {
+            *diverged = Some(format!("asked for the {what} after the recording ended"));
+            return None;
+        };
+        match extract(entry) {
+            Ok(result) => {
+                *replayed += 1;
+                Some(result)
+            }
+            Err(entry) => {
+                *diverged = Some(format!(
+                    "asked for the {what} instead of the {} of entry {}",
+                    entry.description(),
+                    *replayed + 1,
+                ));
+                None
+            }
+        }
+    }
+
+    /// Add the entry returned by `entry` to the log when recording.
+    fn record(&self, entry: impl FnOnce() -> Entry) {
+        if let State::Recording(entries) = &mut *self.state.borrow_mut() {
+            entries.push(entry());
+        }
+    }
+
+    /// Returns the result of the interaction `live` and records it, or returns the next entry
+    /// when replaying.
+    fn interact<T>(
+        &self,
+        what: &str,
+        live: impl FnOnce() -> T,
+        record: impl FnOnce(&T) -> Entry,
+        extract: impl FnOnce(Entry) -> Result<T, Entry>,
+    ) -> T {
+        if let Some(result) = self.replay(what, extract) {
+            return result;
+        }
+        let result = live();
+        self.record(|| record(&result));
+        result
+    }
+
+    /// Returns the seed to use, which is the recorded seed when replaying.
+    pub fn seed(&self, seed: u64) -> u64 {
+        self.interact(
+            "seed",
+            || seed,
+            |&seed| Entry::Seed(seed),
+            |entry| if let Entry::Seed(seed) = entry { Ok(seed) } else { Err(entry) },
+        )
+    }
+
+    /// Returns the environment variables to pass to the program, which are the recorded ones
+    /// when replaying.
+    pub fn env_vars(&self, vars: FxHashMap<OsString, OsString>) -> FxHashMap<OsString, OsString> {
+        self.interact(
+            "environment",
+            || vars,
+            |vars| {
+                let mut vars: Vec<(OsString, OsString)> =
+                    vars.iter().map(|(name, value)| (name.clone(), value.clone())).collect();
+                vars.sort();
+                Entry::Env(vars)
+            },
+            |entry| {
+                if let Entry::Env(vars) = entry {
+                    Ok(vars.into_iter().collect())
+                } else {
+                    Err(entry)
+                }
+            },
+        )
+    }
+
+    /// Returns the current instant of the host's monotonic clock, whose epoch is `epoch`.
+    pub fn monotonic_now(&self, epoch: StdInstant) -> StdInstant {
+        self.interact(
+            "monotonic time",
+            StdInstant::now,
+            |now| Entry::Monotonic(now.saturating_duration_since(epoch)),
+            |entry| {
+                if let Entry::Monotonic(duration) = entry {
+                    Ok(epoch + duration)
+                } else {
+                    Err(entry)
+                }
+            },
+        )
+    }
+
+    /// Returns the current system time of the host.
+    pub fn system_time_now(&self) -> SystemTime {
+        self.interact(
+            "system time",
+            SystemTime::now,
+            |now| {
+                Entry::SystemTime(match now.duration_since(SystemTime::UNIX_EPOCH) {
+                    Ok(duration) => duration.as_nanos().try_into().unwrap(),
+                    Err(err) => -i128::try_from(err.duration().as_nanos()).unwrap(),
+                })
+            },
+            |entry| {
+                let Entry::SystemTime(nanos) = entry else { return Err(entry) };
+                let duration = Duration::from_nanos_u128(nanos.unsigned_abs());
+                Ok(if nanos < 0 {
+                    SystemTime::UNIX_EPOCH - duration
+                } else {
+                    SystemTime::UNIX_EPOCH + duration
+                })
+            },
+        )
+    }
+
+    /// Fills `data` with random bytes using `fill`, or with the recorded bytes when replaying.
+    /// Errors are turned into their message, which is what gets recorded.
+    pub fn random<E: fmt::Display>(
+        &self,
+        data: &mut [u8],
+        fill: impl FnOnce(&mut [u8]) -> Result<(), E>,
+    ) -> Result<(), String> {
+        let len = data.len();
+        let bytes = self.interact(
+            "random bytes",
+            || {
+                let mut bytes = vec![0; len];
+                fill(&mut bytes).map(|()| bytes).map_err(|err| err.to_string())
+            },
+            |result| Entry::Random(result.clone()),
+            |entry| match entry {
+                Entry::Random(Ok(bytes)) if bytes.len() == len => Ok(Ok(bytes)),
+                Entry::Random(Err(err)) => Ok(Err(err)),
+                _ => Err(entry),
+            },
+        )?;
+        data.copy_from_slice(&bytes);
+        Ok(())
+    }
+
+    /// Reads from a host file or socket into `buf` using `read`, or returns the recorded result
+    /// without calling `read` when replaying.
+    pub fn read(
+        &self,
+        buf: &mut [u8],
+        read: impl FnOnce(&mut [u8]) -> io::Result<usize>,
+    ) -> io::Result<usize> {
+        let len = buf.len();
+        let bytes = self.interact(
+            "read",
+            || {
+                let mut bytes = vec![0; len];
+                read(&mut bytes).map(|read_size| {
+                    bytes.truncate(read_size);
+                    bytes
+                })
+            },
+            |result| Entry::Read(logged(result)),
+            |entry| match entry {
+                Entry::Read(result) if result.as_ref().is_ok_and(|bytes| bytes.len() > len) =>
+                    Err(Entry::Read(result)),
+                Entry::Read(result) => Ok(result.map_err(io::Error::from)),
+                _ => Err(entry),
+            },
+        )?;
+        buf[..bytes.len()].copy_from_slice(&bytes);
+        Ok(bytes.len())
+    }
+
+    /// Receives a datagram from a host socket into `buf` using `recv_from`, or returns the recorded
+    /// result without calling `recv_from` when replaying.
+    pub fn recv_from(
+        &self,
+        buf: &mut [u8],
+        recv_from: impl FnOnce(&mut [u8]) -> io::Result<(usize, SocketAddress)>,
+    ) -> io::Result<(usize, SocketAddress)> {
+        let len = buf.len();
+        let (bytes, address) = self.interact(
+            "received datagram",
+            || {
+                let mut bytes = vec![0; len];
+                recv_from(&mut bytes).map(|(read_size, address)| {
+                    bytes.truncate(read_size);
+                    (bytes, address)
+                })
+            },
+            |result| Entry::RecvFrom(logged(result)),
+            |entry| match entry {
+                Entry::RecvFrom(result)
+                    if result.as_ref().is_ok_and(|(bytes, _)| bytes.len() > len) =>
+                    Err(Entry::RecvFrom(result)),
+                Entry::RecvFrom(result) => Ok(result.map_err(io::Error::from)),
+                _ => Err(entry),
+            },
+        )?;
+        buf[..bytes.len()].copy_from_slice(&bytes);
+        Ok((bytes.len(), address))
+    }
+
+    /// Writes `bytes` to a host file or socket using `write`, or returns the recorded result
+    /// without calling `write` when replaying.
+    pub fn write(
+        &self,
+        bytes: &[u8],
+        write: impl FnOnce(&[u8]) -> io::Result<usize>,
+    ) -> io::Result<usize> {
+        self.interact(
+            "write",
+            || write(bytes),
+            |result| Entry::Write(logged(result)),
+            |entry| match entry {
+                Entry::Write(result) if result.is_ok_and(|len| len > bytes.len()) =>
+                    Err(Entry::Write(result)),
+                Entry::Write(result) => Ok(result.map_err(io::Error::from)),
+                _ => Err(entry),
+            },
+        )
+    }
+
+    /// Seeks in a host file using `seek`, or returns the recorded result without calling `seek`
+    /// when replaying.
+    pub fn seek<'tcx>(
+        &self,
+        seek: impl FnOnce() -> InterpResult<'tcx, io::Result<u64>>,
+    ) -> InterpResult<'tcx, io::Result<u64>> {
+        let replayed = self.replay("seek", |entry| {
+            if let Entry::Seek(result) = entry { Ok(result) } else { Err(entry) }
+        });
+        if let Some(result) = replayed {
+            return interp_ok(result.map_err(io::Error::from));
+        }
+        let result = seek()?;
+        self.record(|| Entry::Seek(logged(&result)));
+        interp_ok(result)
+    }
+
+    /// Returns the metadata of a host file computed by `metadata`, or the recorded metadata
+    /// without calling `metadata` when replaying.
+    pub fn metadata<'tcx>(
+        &self,
+        metadata: impl FnOnce() -> InterpResult<'tcx, io::Result<FileMetadata>>,
+    ) -> InterpResult<'tcx, io::Result<FileMetadata>> {
+        let replayed = self.replay("metadata", |entry| {
+            if let Entry::Metadata(result) = entry { Ok(result) } else { Err(entry) }
+        });
+        if let Some(result) = replayed {
+            return interp_ok(result.map_err(io::Error::from));
+        }
+        let result = metadata()?;
+        self.record(|| Entry::Metadata(logged(&result)));
+        interp_ok(result)
+    }
+
+    /// Performs the host operation `what` using `live` and records its result, or returns the
+    /// recorded result without calling `live` when replaying.
+    pub fn op<T: LogValue>(
+        &self,
+        what: &'static str,
+        live: impl FnOnce() -> io::Result<T>,
+    ) -> io::Result<T> {
+        self.interact(
+            what,
+            live,
+            |result| {
+                let result = result.as_ref().map_err(LoggedError::from).map(|value| {
+                    let mut out = String::new();
+                    value.write(&mut out);
+                    out
+                });
+                Entry::Op { name: what.to_owned(), result }
+            },
+            |entry| {
+                let Entry::Op { name, result } = &entry else { return Err(entry) };
+                if name != what {
+                    return Err(entry);
+                }
+                match result {
+                    Ok(value) => {
+                        // `value` starts with a space, unless it is empty.
+                        let mut words = value.split(' ').skip(1);
+                        match T::parse(&mut words) {
+                            Some(value) if words.next().is_none() => Ok(Ok(value)),
+                            _ => Err(entry),
+                        }
+                    }
+                    Err(err) => Ok(Err((*err).into())),
+                }
+            },
+        )
+    }
+
+    /// Creates a host object, like a file or a socket, using `live`, which also returns a value
+    /// that is recorded, like the address of an accepted connection. When replaying, nothing is
+    /// created on the host: the recorded value is returned with the object made by `stand_in`,
+    /// all of whose operations are answered from the log as well.
+    pub fn create<H, T: LogValue>(
+        &self,
+        what: &'static str,
+        live: impl FnOnce() -> io::Result<(H, T)>,
+        stand_in: impl FnOnce() -> H,
+    ) -> io::Result<(H, T)> {
+        let mut host = None;
+        let value = self.op(what, || {
+            live().map(|(object, value)| {
+                host = Some(object);
+                value
+            })
+        })?;
+        Ok((host.unwrap_or_else(stand_in), value))
+    }
+
+    /// Returns the recorded I/O events of the next poll when replaying, or `None` if the events
+    /// have to be polled from the host. In the latter case, [`HostLog::record_io_events`] must be
+    /// called with the events.
+    pub fn replay_io_events(&self) -> Option<Vec<(FdId, BlockingIoSourceReadiness)>> {
+        self.replay("I/O events", |entry| {
+            if let Entry::Io(events) = entry { Ok(events) } else { Err(entry) }
+        })
+    }
+
+    /// Record the I/O events of a poll of the host sources.
+    pub fn record_io_events(&self, events: &[(FdId, BlockingIoSourceReadiness)]) {
+        self.record(|| Entry::Io(events.to_vec()));
+    }
+
+    /// Stop replaying because the execution diverged from the recording.
+    pub fn diverge(&self, reason: String) {
+        if let State::Replaying { diverged: diverged @ None, .. } = &mut *self.state.borrow_mut() {
+            *diverged = Some(reason);
+        }
+    }
+
+    /// Write the log when recording, and report whether the replay diverged when replaying.
+    pub fn finish(&self, tcx: TyCtxt<'_>) {
+        match &*self.state.borrow() {
+            State::Recording(entries) => {
+                let mut log = String::new();
+                for entry in entries {
+                    entry.write(&mut log);
+                }
+                let result: io::Result<()> = try {
+                    if let Some(dir) = self.path.parent() {
+                        fs::create_dir_all(dir)?;
+                    }
+                    fs::write(&self.path, log)?;
+                };
+                if let Err(err) = result {
+                    tcx.dcx().fatal(format!(
+                        "failed to write the host log to `{}`: {err}",
+                        self.path.display()
+                    ));
+                }
+            }
+            State::Replaying { diverged: Some(reason), replayed, .. } =>
+                tcx.dcx().warn(format!(
+                    "the execution diverged from the recording in `{}` after {replayed} entries: \
+                    the program {reason}",
+                    self.path.display()
+                )),
+            State::Replaying { entries, replayed, diverged: None } =>
+                if !entries.is_empty() {
+                    tcx.dcx().warn(format!(
+                        "the execution ended after replaying {replayed} entries of the recording \
+                        in `{}`, but it has {} more",
+                        self.path.display(),
+                        entries.len()
+                    ));
+                },
+        }
+    }
+}
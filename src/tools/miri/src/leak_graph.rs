@@ -0,0 +1,217 @@
+//! Exporting the leaked allocations as a graph (`-Zmiri-leak-graph`).
+//!
+//! The graph has a node for every leaked allocation and an edge for every pointer from one leaked
+//! allocation to another, as recorded in the provenance of the allocation's bytes. Pointers whose
+//! provenance was lost, e.g. by casting them to integers, are not part of the graph.
+//!
+//! To help figuring out why the allocations leaked, we highlight the *roots* of the graph: every
+//! leaked allocation is reachable from a root, and no other leaked allocation points to a root.
+//! Roots are usually what the program forgot to free, or the head of a reference cycle (e.g. of
+//! `Rc`s) that keeps the other allocations alive. In a cycle, every allocation is reachable from
+//! every other one, so we pick the one that was allocated first as the root.
+
+use std::fmt::Write as _;
+use std::path::Path;
+use std::{fs, io};
+
+use rustc_data_structures::fx::FxHashMap;
+
+use crate::*;
+
+/// Write the graph of `leaks` to `path`, in the DOT format of Graphviz.
+pub fn write_leak_graph<'tcx>(
+    ecx: &MiriInterpCx<'tcx>,
+    leaks: &[(AllocId, MemoryKind, Allocation<Provenance, AllocExtra<'tcx>, MiriAllocBytes>)],
+    path: &Path,
+) -> io::Result<()> {
+    let mut leaks: Vec<_> = leaks.iter().collect();
+    leaks.sort_by_key(|(id, _, _)| *id);
+    let index: FxHashMap<AllocId, usize> =
+        leaks.iter().enumerate().map(|(i, (id, _, _))| (*id, i)).collect();
+
+    // The edges between the leaked allocations. Pointers to other allocations don't matter,
+    // as those are not leaked.
+    let edges: Vec<Vec<usize>> = leaks
+        .iter()
+        .map(|(_, _, alloc)| {
+            let mut targets: Vec<usize> = alloc
+                .provenance()
+                .provenances()
+                .filter_map(|prov| index.get(&prov.get_alloc_id()?).copied())
+                .collect();
+            targets.sort();
+            targets.dedup();
+            targets
+        })
+        .collect();
+
+    let components = strongly_connected_components(&edges);
+    // A component is a root component if no other component points to it.
+    let mut has_incoming = vec![false; leaks.len()];
+    for (from, targets) in edges.iter().enumerate() {
+        for &to in targets {
+            if components[from] != components[to] {
+                has_incoming[components[to]] = true;
+            }
+        }
+    }
+    // The size of every component, to tell whether it is a cycle.
+    let mut component_len = vec![0usize; leaks.len()];
+    for &component in &components {
+        component_len[component] += 1;
+    }
+    // The root of every root component is its first allocation. Since the allocations are
+    // sorted by their ID, that is the one allocated first.
+    let mut is_root = vec![false; leaks.len()];
+    let mut component_has_root = vec![false; leaks.len()];
+    for (i, &component) in components.iter().enumerate() {
+        if !has_incoming[component] && !component_has_root[component] {
+            component_has_root[component] = true;
+            is_root[i] = true;
+        }
+    }
+
+    let sm = ecx.tcx.sess.source_map();
+    let mut dot = String::from("digraph leaks {\n    node [shape=box];\n");
+    for (i, (id, kind, alloc)) in leaks.iter().enumerate() {
+        let mut label = format!(
+            "{id:?}\n{kind}, size: {}, align: {}",
+            alloc.size().bytes(),
+            alloc.align.bytes()
+        );
+        // Point at the code that allocated it, preferring user-relevant code over the
+        // allocator.
+        if let Some(backtrace) = &alloc.extra.backtrace
+            && let Some(frame) = backtrace
+                .iter()
+                .find(|frame| ecx.machine.is_local(frame.instance))
+                .or(backtrace.first())
+        {
+            write!(
+                label,
+                "\nallocated in {} at {}",
+                frame.instance,
+                sm.span_to_diagnostic_string(frame.span)
+            )
+            .unwrap();
+        }
+        let in_cycle = component_len[components[i]] > 1 || edges[i].contains(&i);
+        if is_root[i] {
+            label.push_str(if in_cycle { "\nroot (head of a cycle)" } else { "\nroot" });
+        } else if in_cycle && !has_incoming[components[i]] {
+            label.push_str("\npart of the cycle of a root");
+        }
+        let style = if is_root[i] { ", color=red, penwidth=2" } else { "" };
+        writeln!(dot, "    {id:?} [label=\"{}\"{style}];", escape(&label)).unwrap();
+    }
+    for (from, targets) in edges.iter().enumerate() {
+        for &to in targets {
+            writeln!(dot, "    {:?} -> {:?};", leaks[from].0, leaks[to].0).unwrap();
+        }
+    }
+    dot.push_str("}\n");
+    fs::write(path, dot)
+}
+
+/// Computes the strongly connected components of the graph with the given adjacency lists,
+/// using Kosaraju's algorithm. Returns the component of every node, which is identified by one
+/// of the nodes in it.
+fn strongly_connected_components(edges: &[Vec<usize>]) -> Vec<usize> {
+    let len = edges.len();
+
+    // The nodes in the order in which a depth-first search finished them.
+    let mut finished = Vec::with_capacity(len);
+    let mut visited = vec![false; len];
+    for start in 0..len {
+        if visited[start] {
+            continue;
+        }
+        visited[start] = true;
+        // The nodes on the current path, with the number of edges that were already followed.
+        let mut stack = vec![(start, 0)];
+        while let Some((node, next_edge)) = stack.last_mut() {
+            if let Some(&target) = edges[*node].get(*next_edge) {
+                *next_edge += 1;
+                if !visited[target] {
+                    visited[target] = true;
+                    stack.push((target, 0));
+                }
+            } else {
+                finished.push(*node);
+                stack.pop();
+            }
+        }
+    }
+
+    // In reverse finishing order, every node that is not assigned yet starts a new component,
+    // which contains all unassigned nodes that can reach it.
+    let mut reverse_edges = vec![Vec::new(); len];
+    for (from, targets) in edges.iter().enumerate() {
+        for &to in targets {
+            reverse_edges[to].push(from);
+        }
+    }
+    let mut components = vec![usize::MAX; len];
+    for &start in finished.iter().rev() {
+        if components[start] != usize::MAX {
+            continue;
+        }
+        components[start] = start;
+        let mut stack = vec![start];
+        while let Some(node) = stack.pop() {
+            for &source in &reverse_edges[node] {
+                if components[source] == usize::MAX {
+                    components[source] = start;
+                    stack.push(source);
+                }
+            }
+        }
+    }
+    components
+}
+
+/// Escapes `label` for use as a quoted string in DOT.
+fn escape(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Computes the components of the graph and checks that every component is identified by one
+    /// of its nodes.
+    fn components(edges: &[Vec<usize>]) -> Vec<usize> {
+        let components = strongly_connected_components(edges);
+        for &component in &components {
+            assert_eq!(components[component], component);
+        }
+        components
+    }
+
+    #[test]
+    fn self_loop() {
+        assert_eq!(components(&[vec![0]]), [0]);
+    }
+
+    #[test]
+    fn cycle_with_tail() {
+        // 0 and 1 point to each other, and 1 points to 2.
+        let components = components(&[vec![1], vec![0, 2], vec![]]);
+        assert_eq!(components[0], components[1]);
+        assert_ne!(components[0], components[2]);
+    }
+
+    #[test]
+    fn disjoint() {
+        // A cycle of 0 and 1, the lone node 2, and the edge from 3 to 4.
+        let components = components(&[vec![1], vec![0], vec![], vec![4], vec![]]);
+        assert_eq!(components[0], components[1]);
+        assert_eq!(components[2], 2);
+        assert_eq!(components[3], 3);
+        assert_eq!(components[4], 4);
+        assert_ne!(components[0], 2);
+        assert_ne!(components[0], 3);
+        assert_ne!(components[0], 4);
+    }
+}
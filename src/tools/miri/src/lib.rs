@@ -86,11 +86,14 @@
 mod borrow_tracker;
 mod clock;
 mod concurrency;
+mod coverage;
 mod data_structures;
 mod diagnostics;
 mod eval;
 mod helpers;
+mod host_log;
 mod intrinsics;
+mod leak_graph;
 mod machine;
 mod math;
 mod operator;
@@ -149,6 +152,7 @@ pub mod native_lib {
     ThreadManager, TimeoutAnchor, TimeoutClock, UnblockKind,
 };
 pub use crate::concurrency::{GenmcConfig, GenmcCtx, run_genmc_mode};
+pub use crate::coverage::Coverage;
 pub use crate::data_structures::dedup_range_map::DedupRangeMap;
 pub use crate::data_structures::mono_hash_map::MonoHashMap;
 pub use crate::diagnostics::{
@@ -156,6 +160,7 @@ pub mod native_lib {
 };
 pub use crate::eval::{MiriConfig, MiriEntryFnType, create_ecx, eval_entry};
 pub use crate::helpers::{EvalContextExt as _, ToU64 as _, ToUsize as _};
+pub use crate::host_log::{HostLog, HostLogMode, LogValue};
 pub use crate::intrinsics::EvalContextExt as _;
 pub use crate::machine::{
     AlignmentCheck, AllocExtra, BacktraceStyle, DynMachineCallback, FloatRoundingErrorMode,
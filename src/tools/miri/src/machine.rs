@@ -6,6 +6,7 @@
 use std::collections::BTreeMap;
 use std::path::Path;
 use std::rc::Rc;
+use std::time::SystemTime;
 use std::{fmt, process};
 
 use rand::rngs::StdRng;
@@ -27,7 +28,7 @@
 };
 use rustc_middle::ty::{self, Instance, Ty, TyCtxt};
 use rustc_session::config::InliningThreshold;
-use rustc_span::def_id::{CrateNum, DefId};
+use rustc_span::def_id::{CrateNum, DefId, LOCAL_CRATE};
 use rustc_span::{Span, SpanData, Symbol};
 use rustc_symbol_mangling::mangle_internal_symbol;
 use rustc_target::callconv::FnAbi;
@@ -148,26 +149,42 @@ pub struct FrameExtra<'tcx> {
 
     /// Data race detector per-frame data.
     pub data_race: Option<data_race::FrameState>,
+
+    /// The basic block of this frame that ran last, for the branch counts of `-Zmiri-coverage`.
+    pub coverage_prev_block: Option<mir::BasicBlock>,
 }
 
 impl<'tcx> std::fmt::Debug for FrameExtra<'tcx> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         // Omitting `timing`, it does not support `Debug`.
-        let FrameExtra { borrow_tracker, catch_unwind, timing: _, user_relevance, data_race } =
-            self;
+        let FrameExtra {
+            borrow_tracker,
+            catch_unwind,
+            timing: _,
+            user_relevance,
+            data_race,
+            coverage_prev_block,
+        } = self;
         f.debug_struct("FrameData")
             .field("borrow_tracker", borrow_tracker)
             .field("catch_unwind", catch_unwind)
             .field("user_relevance", user_relevance)
             .field("data_race", data_race)
+            .field("coverage_prev_block", coverage_prev_block)
             .finish()
     }
 }
 
 impl VisitProvenance for FrameExtra<'_> {
     fn visit_provenance(&self, visit: &mut VisitWith<'_>) {
-        let FrameExtra { catch_unwind, borrow_tracker, timing: _, user_relevance: _, data_race: _ } =
-            self;
+        let FrameExtra {
+            catch_unwind,
+            borrow_tracker,
+            timing: _,
+            user_relevance: _,
+            data_race: _,
+            coverage_prev_block: _,
+        } = self;
 
         catch_unwind.visit_provenance(visit);
         borrow_tracker.visit_provenance(visit);
@@ -534,9 +551,15 @@ pub struct MiriMachine<'tcx> {
     /// The list of all EpollEventInterest.
     pub(crate) epoll_interests: shims::EpollInterestTable,
 
+    /// The table of all timerfds.
+    pub(crate) timerfds: shims::TimerFdTable,
+
     /// This machine's monotone clock.
     pub(crate) monotonic_clock: MonotonicClock,
 
+    /// The log of the interactions with the host, if we record or replay them.
+    pub(crate) host_log: Option<Rc<HostLog>>,
+
     /// The set of threads.
     pub(crate) threads: ThreadManager<'tcx>,
 
@@ -600,6 +623,8 @@ pub struct MiriMachine<'tcx> {
     pub(crate) report_progress: Option<u32>,
     // The total number of blocks that have been executed.
     pub(crate) basic_block_count: u64,
+    /// If `Some`, we count how often every basic block is executed, for `-Zmiri-coverage`.
+    pub(crate) coverage: Option<Coverage>,
 
     /// Handle of the optional shared object file for native functions.
     #[cfg(all(feature = "native-lib", unix))]
@@ -690,7 +715,16 @@ pub(crate) fn new(
             let path = Path::new(out).join(filename);
             measureme::Profiler::new(path).expect("Couldn't create `measureme` profiler")
         });
-        let rng = StdRng::seed_from_u64(config.seed.unwrap_or(0));
+        let host_log = config.host_log.as_ref().map(|mode| {
+            let crate_name = tcx.crate_name(LOCAL_CRATE);
+            let host_log = HostLog::new(mode, crate_name.as_str()).unwrap_or_else(|err| {
+                tcx.dcx().fatal(format!("failed to read the host log {err}"))
+            });
+            Rc::new(host_log)
+        });
+        let seed = config.seed.unwrap_or(0);
+        let seed = host_log.as_ref().map_or(seed, |host_log| host_log.seed(seed));
+        let rng = StdRng::seed_from_u64(seed);
         let borrow_tracker = config.borrow_tracker.map(|bt| bt.instantiate_global_state(config));
         let data_race = if config.genmc_config.is_some() {
             // `genmc_ctx` persists across executions, so we don't create a new one here.
@@ -758,6 +792,7 @@ pub(crate) fn new(
             validation: config.validation,
             fds: shims::FdTable::init(config.mute_stdout_stderr),
             epoll_interests: shims::EpollInterestTable::new(),
+            timerfds: shims::TimerFdTable::new(),
             dirs: Default::default(),
             layouts,
             threads,
@@ -780,7 +815,12 @@ pub(crate) fn new(
             preemption_rate: config.preemption_rate,
             report_progress: config.report_progress,
             basic_block_count: 0,
-            monotonic_clock: MonotonicClock::new(config.isolated_op == IsolatedOp::Allow),
+            coverage: config.coverage_out.clone().map(Coverage::new),
+            monotonic_clock: MonotonicClock::new(
+                config.isolated_op == IsolatedOp::Allow,
+                host_log.clone(),
+            ),
+            host_log,
             #[cfg(all(feature = "native-lib", unix))]
             native_lib: config.native_lib.iter().map(|lib_file_path| {
                 let host_triple = rustc_session::config::host_tuple();
@@ -905,6 +945,14 @@ pub(crate) fn communicate(&self) -> bool {
         self.isolated_op == IsolatedOp::Allow
     }
 
+    /// Returns the current system time of the host, or the recorded one when replaying.
+    pub(crate) fn system_time_now(&self) -> SystemTime {
+        match &self.host_log {
+            Some(host_log) => host_log.system_time_now(),
+            None => SystemTime::now(),
+        }
+    }
+
     /// Check whether the stack frame that this `FrameInfo` refers to is part of a local crate.
     pub(crate) fn is_local(&self, instance: ty::Instance<'tcx>) -> bool {
         let def_id = instance.def_id();
@@ -1021,10 +1069,12 @@ fn visit_provenance(&self, visit: &mut VisitWith<'_>) {
             fds,
             blocking_io:_,
             epoll_interests:_,
+            timerfds:_,
             tcx: _,
             isolated_op: _,
             validation: _,
             monotonic_clock: _,
+            host_log: _,
             layouts: _,
             static_roots: _,
             profiler: _,
@@ -1041,6 +1091,7 @@ fn visit_provenance(&self, visit: &mut VisitWith<'_>) {
             preemption_rate: _,
             report_progress: _,
             basic_block_count: _,
+            coverage: _,
             native_lib: _,
             #[cfg(all(feature = "native-lib", unix))]
             native_lib_ecx_interchange: _,
@@ -1731,6 +1782,7 @@ fn init_frame(
                 .data_race
                 .as_vclocks_ref()
                 .map(|_| data_race::FrameState::default()),
+            coverage_prev_block: None,
         };
 
         interp_ok(frame.with_extra(extra))
@@ -1751,6 +1803,17 @@ fn stack_mut<'a>(
     fn before_terminator(ecx: &mut InterpCx<'tcx, Self>) -> InterpResult<'tcx> {
         ecx.machine.basic_block_count += 1u64; // a u64 that is only incremented by 1 will "never" overflow
         ecx.machine.since_gc += 1;
+        // Only blocks of user-relevant functions are part of the coverage report.
+        if ecx.machine.coverage.is_some() {
+            let frame = ecx.frame();
+            let (instance, location) = (frame.instance(), frame.current_loc());
+            if ecx.machine.is_local(instance)
+                && let Either::Left(location) = location
+            {
+                let prev = ecx.frame_mut().extra.coverage_prev_block.replace(location.block);
+                ecx.machine.coverage.as_mut().unwrap().record_block(instance, prev, location.block);
+            }
+        }
         // Possibly report our progress. This will point at the terminator we are about to execute.
         if let Some(report_progress) = ecx.machine.report_progress {
             if ecx.machine.basic_block_count.is_multiple_of(u64::from(report_progress)) {
@@ -48,6 +48,11 @@ pub(crate) fn init(
             env_vars.insert(OsString::from(name), OsString::from(value));
         }
 
+        // The environment is part of the interactions with the host we record and replay.
+        if let Some(host_log) = &ecx.machine.host_log {
+            env_vars = host_log.env_vars(env_vars);
+        }
+
         let env_vars = if ecx.target_os_is_unix() {
             EnvVars::Unix(UnixEnvVars::new(ecx, env_vars)?)
         } else if ecx.tcx.sess.target.os == Os::Windows {
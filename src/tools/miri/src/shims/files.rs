@@ -269,7 +269,7 @@ fn read<'tcx>(
         }
 
         let mut stdin = &*self;
-        let result = ecx.read_from_host(|buf| stdin.read(buf), len, ptr)?;
+        let result = ecx.read_from_host_logged(|buf| stdin.read(buf), len, ptr)?;
         finish.call(ecx, result)
     }
 
@@ -365,6 +365,20 @@ pub struct FileHandle {
     pub(crate) writable: bool,
 }
 
+impl FileHandle {
+    /// Returns the file that stands in for a host file opened while replaying the host log.
+    /// Its operations are answered from the log, and only go to the null device if the
+    /// execution diverged from the recording.
+    pub(crate) fn stand_in() -> File {
+        let null = if cfg!(windows) { "NUL" } else { "/dev/null" };
+        fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(null)
+            .expect("failed to open the null device")
+    }
+}
+
 impl FileDescription for FileHandle {
     fn name(&self) -> &'static str {
         "file"
@@ -381,7 +395,7 @@ fn read<'tcx>(
         assert!(communicate_allowed, "isolation should have prevented even opening a file");
 
         let mut file = &self.file;
-        let result = ecx.read_from_host(|buf| file.read(buf), len, ptr)?;
+        let result = ecx.read_from_host_logged(|buf| file.read(buf), len, ptr)?;
         finish.call(ecx, result)
     }
 
@@ -404,7 +418,7 @@ fn write<'tcx>(
             // thing.
             return finish.call(ecx, Err(ErrorKind::PermissionDenied.into()));
         }
-        let result = ecx.write_to_host(&self.file, len, ptr)?;
+        let result = ecx.write_to_host_logged(&self.file, len, ptr)?;
         finish.call(ecx, result)
     }
 
@@ -421,14 +435,14 @@ fn destroy<'tcx>(
         self,
         _self_id: FdId,
         communicate_allowed: bool,
-        _ecx: &mut MiriInterpCx<'tcx>,
+        ecx: &mut MiriInterpCx<'tcx>,
     ) -> InterpResult<'tcx, io::Result<()>> {
         assert!(communicate_allowed, "isolation should have prevented even opening a file");
         // We sync the file if it was opened in a mode different than read-only.
         if self.writable {
             // `File::sync_all` does the checks that are done when closing a file. We do this to
             // to handle possible errors correctly.
-            let result = self.file.sync_all();
+            let result = ecx.host_op("close", || self.file.sync_all());
             // Now we actually close the file and return the result.
             drop(self.file);
             interp_ok(result)
@@ -634,4 +648,67 @@ fn write_to_host(
         let result = file.write(bytes);
         interp_ok(result.map_err(IoError::HostError))
     }
+
+    /// Like [`EvalContextExt::read_from_host`], for reads whose data comes from the host, like the
+    /// ones of host files and sockets. Their results are part of the host log: they are recorded
+    /// with `-Zmiri-record`, and replayed without calling `read_cb` with `-Zmiri-replay`.
+    fn read_from_host_logged(
+        &mut self,
+        mut read_cb: impl FnMut(&mut [u8]) -> io::Result<usize>,
+        len: usize,
+        ptr: Pointer,
+    ) -> InterpResult<'tcx, Result<usize, IoError>> {
+        let this = self.eval_context_mut();
+        match this.machine.host_log.clone() {
+            Some(host_log) => this.read_from_host(|buf| host_log.read(buf, &mut read_cb), len, ptr),
+            None => this.read_from_host(read_cb, len, ptr),
+        }
+    }
+
+    /// Like [`EvalContextExt::write_to_host`], for writes to host files and sockets, whose results
+    /// are part of the host log like the ones of [`EvalContextExt::read_from_host_logged`].
+    fn write_to_host_logged(
+        &mut self,
+        mut file: impl io::Write,
+        len: usize,
+        ptr: Pointer,
+    ) -> InterpResult<'tcx, Result<usize, IoError>> {
+        let this = self.eval_context_mut();
+
+        let Some(host_log) = this.machine.host_log.as_deref() else {
+            return this.write_to_host(file, len, ptr);
+        };
+        let bytes = this.read_bytes_ptr_strip_provenance(ptr, Size::from_bytes(len))?;
+        let result = host_log.write(bytes, |bytes| file.write(bytes));
+        interp_ok(result.map_err(IoError::HostError))
+    }
+
+    /// Performs the host operation `what`, like removing a file, using `live`. Its result is part
+    /// of the host log: when replaying, the recorded result is returned without calling `live`.
+    fn host_op<T: LogValue>(
+        &self,
+        what: &'static str,
+        live: impl FnOnce() -> io::Result<T>,
+    ) -> io::Result<T> {
+        let this = self.eval_context_ref();
+        match this.machine.host_log.as_deref() {
+            Some(host_log) => host_log.op(what, live),
+            None => live(),
+        }
+    }
+
+    /// Like [`EvalContextExt::host_op`], for operations that create a host object, like opening a
+    /// file. When replaying, the object is made by `stand_in` instead.
+    fn create_host_object<H, T: LogValue>(
+        &self,
+        what: &'static str,
+        live: impl FnOnce() -> io::Result<(H, T)>,
+        stand_in: impl FnOnce() -> H,
+    ) -> io::Result<(H, T)> {
+        let this = self.eval_context_ref();
+        match this.machine.host_log.as_deref() {
+            Some(host_log) => host_log.create(what, live, stand_in),
+            None => live(),
+        }
+    }
 }
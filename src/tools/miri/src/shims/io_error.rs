@@ -259,6 +259,11 @@ fn from(value: Scalar) -> Self {
     ]
 };
 
+/// The kinds of host errors that can be turned into an error of the target.
+pub(crate) fn host_error_kinds() -> impl Iterator<Item = ErrorKind> {
+    UNIX_IO_ERROR_TABLE.iter().chain(WINDOWS_IO_ERROR_TABLE).map(|&(_, kind)| kind)
+}
+
 impl<'tcx> EvalContextExt<'tcx> for crate::MiriInterpCx<'tcx> {}
 pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
     /// Get last error variable as a place, lazily allocating thread-local storage for it if
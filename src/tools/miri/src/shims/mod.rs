@@ -26,7 +26,10 @@
 pub use self::files::{FdId, FdTable, FileDescription, FileDescriptionRef, WeakFileDescriptionRef};
 #[cfg(all(feature = "native-lib", unix))]
 pub use self::native_lib::trace::{init_sv, register_retcode_sv};
-pub use self::unix::{DirTable, Epoll, EpollEvalContextExt, EpollInterestTable};
+pub use self::unix::{
+    DirTable, Epoll, EpollEvalContextExt, EpollInterestTable, FileMetadata, SocketAddress,
+    TimerFdEvalContextExt, TimerFdTable,
+};
 
 /// What needs to be done after emulating an item (a shim or an intrinsic) is done.
 pub enum EmulateItemResult {
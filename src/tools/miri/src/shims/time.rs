@@ -73,7 +73,7 @@ fn clock_gettime(
         let duration = match this.parse_clockid(clk_id) {
             Some(TimeoutClock::RealTime) => {
                 this.check_no_isolation("`clock_gettime` with `REALTIME` clocks")?;
-                system_time_to_duration(&SystemTime::now())?
+                system_time_to_duration(&this.machine.system_time_now())?
             }
             Some(TimeoutClock::Monotonic) =>
                 this.machine
@@ -112,7 +112,7 @@ fn gettimeofday(
             return this.set_last_error_and_return_i32(LibcError("EINVAL"));
         }
 
-        let duration = system_time_to_duration(&SystemTime::now())?;
+        let duration = system_time_to_duration(&this.machine.system_time_now())?;
         let tv_sec = duration.as_secs();
         let tv_usec = duration.subsec_micros();
 
@@ -221,7 +221,7 @@ fn GetSystemTimeAsFileTime(
 
         let filetime = this.deref_pointer_as(LPFILETIME_op, this.windows_ty_layout("FILETIME"))?;
 
-        let duration = this.system_time_since_windows_epoch(&SystemTime::now())?;
+        let duration = this.system_time_since_windows_epoch(&this.machine.system_time_now())?;
         let duration_ticks = this.windows_ticks_for(duration)?;
 
         let dwLowDateTime = u32::try_from(duration_ticks & 0x00000000FFFFFFFF).unwrap();
@@ -8,6 +8,7 @@
 use rustc_middle::ty::Ty;
 use rustc_target::spec::Os;
 
+use crate::shims::files::EvalContextExt as _;
 use crate::*;
 
 pub struct UnixEnvVars<'tcx> {
@@ -206,7 +207,7 @@ fn getcwd(&mut self, buf_op: &OpTy<'tcx>, size_op: &OpTy<'tcx>) -> InterpResult<
         }
 
         // If we cannot get the current directory, we return null
-        match env::current_dir() {
+        match this.host_op("getcwd", env::current_dir) {
             Ok(cwd) => {
                 if this.write_path_to_c_str(&cwd, buf, size)?.0 {
                     return interp_ok(buf);
@@ -230,7 +231,7 @@ fn chdir(&mut self, path_op: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar> {
             return this.set_last_error_and_return_i32(ErrorKind::PermissionDenied);
         }
 
-        let result = env::set_current_dir(path).map(|()| 0);
+        let result = this.host_op("chdir", || env::set_current_dir(path)).map(|()| 0);
         interp_ok(Scalar::from_i32(this.try_unwrap_io_result(result)?))
     }
 
@@ -659,7 +659,25 @@ fn emulate_foreign_item_inner(
                     abi,
                     args,
                 )?;
-                this.send(socket, buffer, length, flags, dest)?;
+                this.send(socket, buffer, length, flags, /* address */ None, dest)?;
+            }
+            "sendto" => {
+                let [socket, buffer, length, flags, address, address_len] = this.check_shim_sig(
+                    shim_sig!(
+                        extern "C" fn(
+                            i32,
+                            *const _,
+                            libc::size_t,
+                            i32,
+                            *const _,
+                            libc::socklen_t,
+                        ) -> libc::ssize_t
+                    ),
+                    link_name,
+                    abi,
+                    args,
+                )?;
+                this.send(socket, buffer, length, flags, Some((address, address_len)), dest)?;
             }
             "recv" => {
                 let [socket, buffer, length, flags] = this.check_shim_sig(
@@ -668,7 +686,25 @@ fn emulate_foreign_item_inner(
                     abi,
                     args,
                 )?;
-                this.recv(socket, buffer, length, flags, dest)?;
+                this.recv(socket, buffer, length, flags, /* address */ None, dest)?;
+            }
+            "recvfrom" => {
+                let [socket, buffer, length, flags, address, address_len] = this.check_shim_sig(
+                    shim_sig!(
+                        extern "C" fn(
+                            i32,
+                            *mut _,
+                            libc::size_t,
+                            i32,
+                            *mut _,
+                            *mut _,
+                        ) -> libc::ssize_t
+                    ),
+                    link_name,
+                    abi,
+                    args,
+                )?;
+                this.recv(socket, buffer, length, flags, Some((address, address_len)), dest)?;
             }
             "setsockopt" => {
                 let [socket, level, option_name, option_value, option_len] = this.check_shim_sig(
@@ -2,7 +2,7 @@
 
 use std::borrow::Cow;
 use std::ffi::OsString;
-use std::fs::{self, DirBuilder, File, FileType, OpenOptions, TryLockError};
+use std::fs::{self, DirBuilder, File, FileType, OpenOptions};
 use std::io::{self, ErrorKind, Read, Seek, SeekFrom, Write};
 use std::path::{self, Path, PathBuf};
 use std::time::SystemTime;
@@ -13,7 +13,7 @@
 use rustc_target::spec::Os;
 
 use self::shims::time::system_time_to_duration;
-use crate::shims::files::FileHandle;
+use crate::shims::files::{EvalContextExt as _, FileHandle};
 use crate::shims::os_str::bytes_to_os_str;
 use crate::shims::sig::check_min_vararg_count;
 use crate::shims::unix::fd::{FlockOp, UnixFileDescription};
@@ -64,30 +64,20 @@ fn pread<'tcx>(
         finish: DynMachineCallback<'tcx, Result<usize, IoError>>,
     ) -> InterpResult<'tcx> {
         assert!(communicate_allowed, "isolation should have prevented even opening a file");
-        let mut bytes = vec![0; len];
         // Emulates pread using seek + read + seek to restore cursor position.
         // Correctness of this emulation relies on sequential nature of Miri execution.
         // The closure is used to emulate `try` block, since we "bubble" `io::Error` using `?`.
         let file = &mut &self.file;
-        let mut f = || {
+        let f = |bytes: &mut [u8]| {
             let cursor_pos = file.stream_position()?;
             file.seek(SeekFrom::Start(offset))?;
-            let res = file.read(&mut bytes);
+            let res = file.read(bytes);
             // Attempt to restore cursor position even if the read has failed
             file.seek(SeekFrom::Start(cursor_pos))
                 .expect("failed to restore file position, this shouldn't be possible");
             res
         };
-        let result = match f() {
-            Ok(read_size) => {
-                // If reading to `bytes` did not fail, we write those bytes to the buffer.
-                // Crucially, if fewer than `bytes.len()` bytes were read, only write
-                // that much into the output buffer!
-                ecx.write_bytes_ptr(ptr, bytes[..read_size].iter().copied())?;
-                Ok(read_size)
-            }
-            Err(e) => Err(IoError::HostError(e)),
-        };
+        let result = ecx.read_from_host_logged(f, len, ptr)?;
         finish.call(ecx, result)
     }
 
@@ -106,7 +96,7 @@ fn pwrite<'tcx>(
         // The closure is used to emulate `try` block, since we "bubble" `io::Error` using `?`.
         let file = &mut &self.file;
         let bytes = ecx.read_bytes_ptr_strip_provenance(ptr, Size::from_bytes(len))?;
-        let mut f = || {
+        let mut f = |bytes: &[u8]| {
             let cursor_pos = file.stream_position()?;
             file.seek(SeekFrom::Start(offset))?;
             let res = file.write(bytes);
@@ -115,7 +105,10 @@ fn pwrite<'tcx>(
                 .expect("failed to restore file position, this shouldn't be possible");
             res
         };
-        let result = f();
+        let result = match ecx.machine.host_log.as_deref() {
+            Some(host_log) => host_log.write(bytes, f),
+            None => f(bytes),
+        };
         finish.call(ecx, result.map_err(IoError::HostError))
     }
 
@@ -123,28 +116,27 @@ fn flock<'tcx>(
         &self,
         communicate_allowed: bool,
         op: FlockOp,
+        ecx: &mut MiriInterpCx<'tcx>,
     ) -> InterpResult<'tcx, io::Result<()>> {
         assert!(communicate_allowed, "isolation should have prevented even opening a file");
 
         use FlockOp::*;
-        // We must not block the interpreter loop, so we always `try_lock`.
+        // We must not block the interpreter loop, so we always `try_lock`. A lock that is held
+        // elsewhere is reported as a `WouldBlock` error.
         let (res, nonblocking) = match op {
-            SharedLock { nonblocking } => (self.file.try_lock_shared(), nonblocking),
-            ExclusiveLock { nonblocking } => (self.file.try_lock(), nonblocking),
+            SharedLock { nonblocking } =>
+                (ecx.host_op("flock", || Ok(self.file.try_lock_shared()?)), nonblocking),
+            ExclusiveLock { nonblocking } =>
+                (ecx.host_op("flock", || Ok(self.file.try_lock()?)), nonblocking),
             Unlock => {
-                return interp_ok(self.file.unlock());
+                return interp_ok(ecx.host_op("flock", || self.file.unlock()));
             }
         };
 
         match res {
-            Ok(()) => interp_ok(Ok(())),
-            Err(TryLockError::Error(err)) => interp_ok(Err(err)),
-            Err(TryLockError::WouldBlock) =>
-                if nonblocking {
-                    interp_ok(Err(ErrorKind::WouldBlock.into()))
-                } else {
-                    throw_unsup_format!("blocking `flock` is not currently supported");
-                },
+            Err(err) if err.kind() == ErrorKind::WouldBlock && !nonblocking =>
+                throw_unsup_format!("blocking `flock` is not currently supported"),
+            res => interp_ok(res),
         }
     }
 }
@@ -202,15 +194,14 @@ fn maybe_sync_file(
     file: &File,
     writable: bool,
     operation: fn(&File) -> std::io::Result<()>,
-) -> std::io::Result<i32> {
+) -> std::io::Result<()> {
     if !writable && cfg!(windows) {
         // sync_all() and sync_data() will return an error on Windows hosts if the file is not opened
         // for writing. (FlushFileBuffers requires that the file handle have the
         // GENERIC_WRITE right)
-        Ok(0i32)
+        Ok(())
     } else {
-        let result = operation(file);
-        result.map(|_| 0i32)
+        operation(file)
     }
 }
 
@@ -493,9 +484,13 @@ fn open(
             return this.set_last_error_and_return_i32(ErrorKind::PermissionDenied);
         }
 
-        let fd = options
-            .open(path)
-            .map(|file| this.machine.fds.insert_new(FileHandle { file, writable }));
+        let fd = this
+            .create_host_object(
+                "open",
+                || options.open(path).map(|file| (file, ())),
+                FileHandle::stand_in,
+            )
+            .map(|(file, ())| this.machine.fds.insert_new(FileHandle { file, writable }));
 
         interp_ok(Scalar::from_i32(this.try_unwrap_io_result(fd)?))
     }
@@ -531,7 +526,11 @@ fn lseek(
         let Some(fd) = this.machine.fds.get(fd_num) else {
             return this.set_last_error_and_return(LibcError("EBADF"), dest);
         };
-        let result = fd.seek(communicate, seek_from)?.map(|offset| i64::try_from(offset).unwrap());
+        let result = match this.machine.host_log.as_deref() {
+            Some(host_log) => host_log.seek(|| fd.seek(communicate, seek_from))?,
+            None => fd.seek(communicate, seek_from)?,
+        };
+        let result = result.map(|offset| i64::try_from(offset).unwrap());
         drop(fd);
 
         let result = this.try_unwrap_io_result(result)?;
@@ -550,7 +549,7 @@ fn unlink(&mut self, path_op: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar> {
             return this.set_last_error_and_return_i32(ErrorKind::PermissionDenied);
         }
 
-        let result = fs::remove_file(path).map(|_| 0);
+        let result = this.host_op("unlink", || fs::remove_file(path)).map(|_| 0);
         interp_ok(Scalar::from_i32(this.try_unwrap_io_result(result)?))
     }
 
@@ -580,7 +579,7 @@ fn create_link(src: &Path, dst: &Path) -> std::io::Result<()> {
             return this.set_last_error_and_return_i32(ErrorKind::PermissionDenied);
         }
 
-        let result = create_link(&target, &linkpath).map(|_| 0);
+        let result = this.host_op("symlink", || create_link(&target, &linkpath)).map(|_| 0);
         interp_ok(Scalar::from_i32(this.try_unwrap_io_result(result)?))
     }
 
@@ -870,7 +869,7 @@ fn chmod(&mut self, path_op: &OpTy<'tcx>, mode_op: &OpTy<'tcx>) -> InterpResult<
         }
 
         let permissions = this.host_permissions_from_mode(mode.try_into().unwrap())?;
-        if let Err(err) = fs::set_permissions(path, permissions) {
+        if let Err(err) = this.host_op("chmod", || fs::set_permissions(path, permissions)) {
             return this.set_last_error_and_return_i32(IoError::HostError(err));
         }
 
@@ -898,7 +897,7 @@ fn fchmod(&mut self, fd_op: &OpTy<'tcx>, mode_op: &OpTy<'tcx>) -> InterpResult<'
         }
 
         let permissions = this.host_permissions_from_mode(mode.try_into().unwrap())?;
-        if let Err(err) = file.file.set_permissions(permissions) {
+        if let Err(err) = this.host_op("fchmod", || file.file.set_permissions(permissions)) {
             return this.set_last_error_and_return_i32(IoError::HostError(err));
         }
 
@@ -928,7 +927,7 @@ fn rename(
             return this.set_last_error_and_return_i32(ErrorKind::PermissionDenied);
         }
 
-        let result = fs::rename(oldpath, newpath).map(|_| 0);
+        let result = this.host_op("rename", || fs::rename(oldpath, newpath)).map(|_| 0);
 
         interp_ok(Scalar::from_i32(this.try_unwrap_io_result(result)?))
     }
@@ -962,7 +961,7 @@ fn mkdir(&mut self, path_op: &OpTy<'tcx>, mode_op: &OpTy<'tcx>) -> InterpResult<
             builder.mode(mode);
         }
 
-        let result = builder.create(path).map(|_| 0i32);
+        let result = this.host_op("mkdir", || builder.create(path)).map(|_| 0i32);
 
         interp_ok(Scalar::from_i32(this.try_unwrap_io_result(result)?))
     }
@@ -978,7 +977,7 @@ fn rmdir(&mut self, path_op: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar> {
             return this.set_last_error_and_return_i32(ErrorKind::PermissionDenied);
         }
 
-        let result = fs::remove_dir(path).map(|_| 0i32);
+        let result = this.host_op("rmdir", || fs::remove_dir(path)).map(|_| 0i32);
 
         interp_ok(Scalar::from_i32(this.try_unwrap_io_result(result)?))
     }
@@ -1270,7 +1269,7 @@ fn ftruncate64(&mut self, fd_num: i32, length: i128) -> InterpResult<'tcx, Scala
 
         if file.writable {
             if let Ok(length) = length.try_into() {
-                let result = file.file.set_len(length);
+                let result = this.host_op("ftruncate", || file.file.set_len(length));
                 let result = this.try_unwrap_io_result(result.map(|_| 0i32))?;
                 interp_ok(Scalar::from_i32(result))
             } else {
@@ -1318,26 +1317,22 @@ fn posix_fallocate(
             return interp_ok(this.eval_libc("EBADF"));
         }
 
-        let current_size = match file.file.metadata() {
-            Ok(metadata) => metadata.len(),
-            Err(err) => return this.io_error_to_errnum(err),
-        };
         // Checked i64 addition, to ensure the result does not exceed the max file size.
         let new_size = match offset.checked_add(len) {
             // `new_size` is definitely non-negative, so we can cast to `u64`.
             Some(new_size) => u64::try_from(new_size).unwrap(),
             None => return interp_ok(this.eval_libc("EFBIG")), // new size too big
         };
-        // If the size of the file is less than offset+size, then the file is increased to this size;
-        // otherwise the file size is left unchanged.
-        if current_size < new_size {
-            interp_ok(match file.file.set_len(new_size) {
-                Ok(()) => Scalar::from_i32(0),
-                Err(e) => this.io_error_to_errnum(e)?,
-            })
-        } else {
-            interp_ok(Scalar::from_i32(0))
-        }
+        let result = this.host_op("fallocate", || {
+            let current_size = file.file.metadata()?.len();
+            // If the size of the file is less than offset+size, then the file is increased to this
+            // size; otherwise the file size is left unchanged.
+            if current_size < new_size { file.file.set_len(new_size) } else { Ok(()) }
+        });
+        interp_ok(match result {
+            Ok(()) => Scalar::from_i32(0),
+            Err(e) => this.io_error_to_errnum(e)?,
+        })
     }
 
     fn fsync(&mut self, fd_op: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar> {
@@ -1369,7 +1364,9 @@ fn ffullsync_fd(&mut self, fd_num: i32) -> InterpResult<'tcx, Scalar> {
         let file = fd.downcast::<FileHandle>().ok_or_else(|| {
             err_unsup_format!("`fsync` is only supported on file-backed file descriptors")
         })?;
-        let io_result = maybe_sync_file(&file.file, file.writable, File::sync_all);
+        let io_result = this
+            .host_op("fsync", || maybe_sync_file(&file.file, file.writable, File::sync_all))
+            .map(|()| 0i32);
         interp_ok(Scalar::from_i32(this.try_unwrap_io_result(io_result)?))
     }
 
@@ -1392,7 +1389,9 @@ fn fdatasync(&mut self, fd_op: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar> {
         let file = fd.downcast::<FileHandle>().ok_or_else(|| {
             err_unsup_format!("`fdatasync` is only supported on file-backed file descriptors")
         })?;
-        let io_result = maybe_sync_file(&file.file, file.writable, File::sync_data);
+        let io_result = this
+            .host_op("fdatasync", || maybe_sync_file(&file.file, file.writable, File::sync_data))
+            .map(|()| 0i32);
         interp_ok(Scalar::from_i32(this.try_unwrap_io_result(io_result)?))
     }
 
@@ -1434,7 +1433,11 @@ fn sync_file_range(
         let file = fd.downcast::<FileHandle>().ok_or_else(|| {
             err_unsup_format!("`sync_data_range` is only supported on file-backed file descriptors")
         })?;
-        let io_result = maybe_sync_file(&file.file, file.writable, File::sync_data);
+        let io_result = this
+            .host_op("sync_file_range", || {
+                maybe_sync_file(&file.file, file.writable, File::sync_data)
+            })
+            .map(|()| 0i32);
         interp_ok(Scalar::from_i32(this.try_unwrap_io_result(io_result)?))
     }
 
@@ -1457,7 +1460,7 @@ fn readlink(
             return interp_ok(-1);
         }
 
-        let result = std::fs::read_link(pathname);
+        let result = this.host_op("readlink", || std::fs::read_link(pathname));
         match result {
             Ok(resolved) => {
                 // 'readlink' truncates the resolved path if the provided buffer is not large
@@ -1519,7 +1522,7 @@ fn realpath(
             return interp_ok(Scalar::from_target_usize(0, this));
         }
 
-        let result = std::fs::canonicalize(pathname);
+        let result = this.host_op("realpath", || std::fs::canonicalize(pathname));
         match result {
             Ok(resolved) => {
                 let path_max = this
@@ -1654,10 +1657,14 @@ fn mkstemp(&mut self, template_op: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar> {
 
             let possibly_unique = std::env::temp_dir().join::<PathBuf>(p.into());
 
-            let file = fopts.open(possibly_unique);
+            let file = this.create_host_object(
+                "mkstemp",
+                || fopts.open(possibly_unique).map(|f| (f, ())),
+                FileHandle::stand_in,
+            );
 
             match file {
-                Ok(f) => {
+                Ok((f, ())) => {
                     let fd = this.machine.fds.insert_new(FileHandle { file: f, writable: true });
                     return interp_ok(Scalar::from_i32(fd));
                 }
@@ -1730,20 +1737,21 @@ fn file_type_to_mode_name(file_type: std::fs::FileType) -> &'static str {
 /// this field, for example because the metadata is synthetic or because the host platform does not
 /// expose it. `statx` must only advertise the corresponding `STATX_*` bit when the field is `Some`;
 /// legacy `stat` writes zero for `None` to preserve the old fallback behavior.
-struct FileMetadata {
+#[derive(Debug, Clone)]
+pub struct FileMetadata {
     /// This holds both the file type (dir, regular, symlink, ...) and permissions.
-    mode: u32,
-    size: u64,
-    created: Option<(u64, u32)>,
-    accessed: Option<(u64, u32)>,
-    modified: Option<(u64, u32)>,
-    dev: Option<u64>,
-    ino: Option<u64>,
-    nlink: Option<u64>,
-    uid: Option<u32>,
-    gid: Option<u32>,
-    blksize: Option<u64>,
-    blocks: Option<u64>,
+    pub mode: u32,
+    pub size: u64,
+    pub created: Option<(u64, u32)>,
+    pub accessed: Option<(u64, u32)>,
+    pub modified: Option<(u64, u32)>,
+    pub dev: Option<u64>,
+    pub ino: Option<u64>,
+    pub nlink: Option<u64>,
+    pub uid: Option<u32>,
+    pub gid: Option<u32>,
+    pub blksize: Option<u64>,
+    pub blocks: Option<u64>,
 }
 
 impl FileMetadata {
@@ -1752,10 +1760,14 @@ fn from_path<'tcx>(
         path: &Path,
         follow_symlink: bool,
     ) -> InterpResult<'tcx, Result<FileMetadata, IoError>> {
-        let metadata =
-            if follow_symlink { std::fs::metadata(path) } else { std::fs::symlink_metadata(path) };
-
-        FileMetadata::from_meta(ecx, metadata)
+        FileMetadata::from_host(ecx, |ecx| {
+            let metadata = if follow_symlink {
+                std::fs::metadata(path)
+            } else {
+                std::fs::symlink_metadata(path)
+            };
+            FileMetadata::from_meta(ecx, metadata)
+        })
     }
 
     fn from_fd_num<'tcx>(
@@ -1765,12 +1777,33 @@ fn from_fd_num<'tcx>(
         let Some(fd) = ecx.machine.fds.get(fd_num) else {
             return interp_ok(Err(LibcError("EBADF")));
         };
+        // Only host files have the metadata of the host, everything else is synthetic.
+        if let Some(file) = fd.downcast::<FileHandle>() {
+            return Self::from_host(ecx, |ecx| Self::from_meta(ecx, file.file.metadata()));
+        }
         match fd.metadata()? {
-            Either::Left(host) => Self::from_meta(ecx, host),
+            Either::Left(host) =>
+                Self::from_meta(ecx, host).map(|result| result.map_err(IoError::from)),
             Either::Right(name) => Self::synthetic(ecx, name),
         }
     }
 
+    /// Returns the metadata computed by `metadata` from the host's, which is part of the host log:
+    /// it is recorded with `-Zmiri-record`, and replayed without calling `metadata` with
+    /// `-Zmiri-replay`.
+    fn from_host<'tcx>(
+        ecx: &mut MiriInterpCx<'tcx>,
+        metadata: impl FnOnce(
+            &mut MiriInterpCx<'tcx>,
+        ) -> InterpResult<'tcx, io::Result<FileMetadata>>,
+    ) -> InterpResult<'tcx, Result<FileMetadata, IoError>> {
+        let result = match ecx.machine.host_log.clone() {
+            Some(host_log) => host_log.metadata(|| metadata(ecx))?,
+            None => metadata(ecx)?,
+        };
+        interp_ok(result.map_err(IoError::from))
+    }
+
     fn synthetic<'tcx>(
         ecx: &mut MiriInterpCx<'tcx>,
         mode_name: &str,
@@ -1798,11 +1831,11 @@ fn synthetic<'tcx>(
     fn from_meta<'tcx>(
         ecx: &mut MiriInterpCx<'tcx>,
         metadata: Result<std::fs::Metadata, std::io::Error>,
-    ) -> InterpResult<'tcx, Result<FileMetadata, IoError>> {
+    ) -> InterpResult<'tcx, io::Result<FileMetadata>> {
         let metadata = match metadata {
             Ok(metadata) => metadata,
             Err(e) => {
-                return interp_ok(Err(e.into()));
+                return interp_ok(Err(e));
             }
         };
 
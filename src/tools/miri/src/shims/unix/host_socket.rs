@@ -0,0 +1,365 @@
+//! The host sockets backing the emulated sockets.
+//!
+//! An emulated socket is either backed by an internet socket or, on Unix hosts, by a
+//! Unix-domain socket. The types in this module wrap the corresponding `mio` sockets
+//! such that the socket shims don't need to care about which kind of host socket they use.
+//! When replaying the host log, sockets are not created on the host; they are represented by
+//! the `Replayed` variants instead, whose operations are all answered from the log.
+
+use std::io::{self, Read, Write};
+use std::net::Shutdown;
+#[cfg(unix)]
+use std::path::Path;
+
+use mio::event::Source;
+use mio::net::{TcpListener, TcpStream, UdpSocket};
+#[cfg(unix)]
+use mio::net::{UnixDatagram, UnixListener, UnixStream};
+
+use crate::shims::unix::socket_address::SocketAddress;
+
+/// The error of operations on a socket that was created while replaying the host log, which are
+/// only performed if the execution diverged from the recording.
+fn replayed() -> io::Error {
+    io::Error::other("the socket was created while replaying the host log")
+}
+
+/// Convert the pathname of a host Unix-domain socket address into a [`SocketAddress`].
+#[cfg(unix)]
+fn unix_address(path: Option<&Path>) -> SocketAddress {
+    SocketAddress::Unix(path.map(Path::to_path_buf))
+}
+
+/// The host socket of a listening stream socket.
+#[derive(Debug)]
+pub enum Listener {
+    Tcp(TcpListener),
+    #[cfg(unix)]
+    Unix(UnixListener),
+    /// A stand-in for a host socket while replaying the host log.
+    Replayed,
+}
+
+impl Listener {
+    pub fn bind(address: &SocketAddress) -> io::Result<Listener> {
+        match address {
+            SocketAddress::Inet(address) => TcpListener::bind(*address).map(Listener::Tcp),
+            #[cfg(unix)]
+            SocketAddress::Unix(Some(path)) => UnixListener::bind(path).map(Listener::Unix),
+            SocketAddress::Unix(_) =>
+                unreachable!("Unix-domain sockets are only created on Unix hosts with a path"),
+        }
+    }
+
+    pub fn accept(&self) -> io::Result<(Stream, SocketAddress)> {
+        match self {
+            Listener::Tcp(listener) =>
+                listener
+                    .accept()
+                    .map(|(stream, address)| (Stream::Tcp(stream), SocketAddress::Inet(address))),
+            #[cfg(unix)]
+            Listener::Unix(listener) =>
+                listener.accept().map(|(stream, address)| {
+                    (Stream::Unix(stream), unix_address(address.as_pathname()))
+                }),
+            Listener::Replayed => Err(replayed()),
+        }
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddress> {
+        match self {
+            Listener::Tcp(listener) => listener.local_addr().map(SocketAddress::Inet),
+            #[cfg(unix)]
+            Listener::Unix(listener) =>
+                listener.local_addr().map(|address| unix_address(address.as_pathname())),
+            Listener::Replayed => Err(replayed()),
+        }
+    }
+
+    pub fn take_error(&self) -> io::Result<Option<io::Error>> {
+        match self {
+            Listener::Tcp(listener) => listener.take_error(),
+            #[cfg(unix)]
+            Listener::Unix(listener) => listener.take_error(),
+            Listener::Replayed => Err(replayed()),
+        }
+    }
+
+    /// Get the TTL of the internet socket.
+    pub fn ttl(&self) -> io::Result<u32> {
+        match self {
+            Listener::Tcp(listener) => listener.ttl(),
+            #[cfg(unix)]
+            Listener::Unix(_) => unreachable!("Unix-domain sockets don't have a TTL"),
+            Listener::Replayed => Err(replayed()),
+        }
+    }
+
+    /// Get the `mio` source of the host socket, which stand-ins don't have.
+    pub fn source(&mut self) -> Option<&mut dyn Source> {
+        match self {
+            Listener::Tcp(listener) => Some(listener),
+            #[cfg(unix)]
+            Listener::Unix(listener) => Some(listener),
+            Listener::Replayed => None,
+        }
+    }
+}
+
+/// The host socket of a connecting or connected stream socket.
+#[derive(Debug)]
+pub enum Stream {
+    Tcp(TcpStream),
+    #[cfg(unix)]
+    Unix(UnixStream),
+    /// A stand-in for a host socket while replaying the host log.
+    Replayed,
+}
+
+impl Stream {
+    /// Start connecting to `address`. Like [`TcpStream::connect`], this returns a
+    /// potentially unconnected stream.
+    pub fn connect(address: &SocketAddress) -> io::Result<Stream> {
+        match address {
+            SocketAddress::Inet(address) => TcpStream::connect(*address).map(Stream::Tcp),
+            #[cfg(unix)]
+            SocketAddress::Unix(Some(path)) => UnixStream::connect(path).map(Stream::Unix),
+            SocketAddress::Unix(_) =>
+                unreachable!("Unix-domain sockets are only created on Unix hosts with a path"),
+        }
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddress> {
+        match self {
+            Stream::Tcp(stream) => stream.local_addr().map(SocketAddress::Inet),
+            #[cfg(unix)]
+            Stream::Unix(stream) =>
+                stream.local_addr().map(|address| unix_address(address.as_pathname())),
+            Stream::Replayed => Err(replayed()),
+        }
+    }
+
+    pub fn peer_addr(&self) -> io::Result<SocketAddress> {
+        match self {
+            Stream::Tcp(stream) => stream.peer_addr().map(SocketAddress::Inet),
+            #[cfg(unix)]
+            Stream::Unix(stream) =>
+                stream.peer_addr().map(|address| unix_address(address.as_pathname())),
+            Stream::Replayed => Err(replayed()),
+        }
+    }
+
+    pub fn take_error(&self) -> io::Result<Option<io::Error>> {
+        match self {
+            Stream::Tcp(stream) => stream.take_error(),
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.take_error(),
+            Stream::Replayed => Err(replayed()),
+        }
+    }
+
+    pub fn shutdown(&self, how: Shutdown) -> io::Result<()> {
+        match self {
+            Stream::Tcp(stream) => stream.shutdown(how),
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.shutdown(how),
+            Stream::Replayed => Err(replayed()),
+        }
+    }
+
+    /// Peek at the bytes of the TCP stream.
+    pub fn peek(&self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Stream::Tcp(stream) => stream.peek(buf),
+            #[cfg(unix)]
+            Stream::Unix(_) => unreachable!("peeking Unix-domain streams is unsupported"),
+            Stream::Replayed => Err(replayed()),
+        }
+    }
+
+    /// Get the TTL of the internet socket.
+    pub fn ttl(&self) -> io::Result<u32> {
+        match self {
+            Stream::Tcp(stream) => stream.ttl(),
+            #[cfg(unix)]
+            Stream::Unix(_) => unreachable!("Unix-domain sockets don't have a TTL"),
+            Stream::Replayed => Err(replayed()),
+        }
+    }
+
+    /// Get the `mio` source of the host socket, which stand-ins don't have.
+    pub fn source(&mut self) -> Option<&mut dyn Source> {
+        match self {
+            Stream::Tcp(stream) => Some(stream),
+            #[cfg(unix)]
+            Stream::Unix(stream) => Some(stream),
+            Stream::Replayed => None,
+        }
+    }
+}
+
+impl Read for Stream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Stream::Tcp(stream) => stream.read(buf),
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.read(buf),
+            Stream::Replayed => Err(replayed()),
+        }
+    }
+}
+
+impl Write for Stream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Stream::Tcp(stream) => stream.write(buf),
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.write(buf),
+            Stream::Replayed => Err(replayed()),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Stream::Tcp(stream) => stream.flush(),
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.flush(),
+            Stream::Replayed => Err(replayed()),
+        }
+    }
+}
+
+/// The host socket of a datagram socket.
+#[derive(Debug)]
+pub enum Datagram {
+    Udp(UdpSocket),
+    #[cfg(unix)]
+    Unix(UnixDatagram),
+    /// A stand-in for a host socket while replaying the host log.
+    Replayed,
+}
+
+impl Datagram {
+    pub fn bind(address: &SocketAddress) -> io::Result<Datagram> {
+        match address {
+            SocketAddress::Inet(address) => UdpSocket::bind(*address).map(Datagram::Udp),
+            #[cfg(unix)]
+            SocketAddress::Unix(Some(path)) => UnixDatagram::bind(path).map(Datagram::Unix),
+            SocketAddress::Unix(_) =>
+                unreachable!("Unix-domain sockets are only created on Unix hosts with a path"),
+        }
+    }
+
+    /// Create a Unix-domain datagram socket which isn't bound to any path.
+    #[cfg(unix)]
+    pub fn unbound_unix() -> io::Result<Datagram> {
+        UnixDatagram::unbound().map(Datagram::Unix)
+    }
+
+    /// Set the default destination of the socket and only receive datagrams from `address`.
+    ///
+    /// The address must be of the same family as the socket.
+    pub fn connect(&self, address: &SocketAddress) -> io::Result<()> {
+        match (self, address) {
+            (Datagram::Udp(socket), SocketAddress::Inet(address)) => socket.connect(*address),
+            #[cfg(unix)]
+            (Datagram::Unix(socket), SocketAddress::Unix(Some(path))) => socket.connect(path),
+            (Datagram::Replayed, _) => Err(replayed()),
+            _ => unreachable!("connecting a datagram socket to an address of another family"),
+        }
+    }
+
+    /// Send a datagram to the address the socket is connected to.
+    pub fn send(&self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Datagram::Udp(socket) => socket.send(buf),
+            #[cfg(unix)]
+            Datagram::Unix(socket) => socket.send(buf),
+            Datagram::Replayed => Err(replayed()),
+        }
+    }
+
+    /// Send a datagram to `address`, which must be of the same family as the socket.
+    pub fn send_to(&self, buf: &[u8], address: &SocketAddress) -> io::Result<usize> {
+        match (self, address) {
+            (Datagram::Udp(socket), SocketAddress::Inet(address)) => socket.send_to(buf, *address),
+            #[cfg(unix)]
+            (Datagram::Unix(socket), SocketAddress::Unix(Some(path))) => socket.send_to(buf, path),
+            (Datagram::Replayed, _) => Err(replayed()),
+            _ => unreachable!("sending a datagram to an address of another family"),
+        }
+    }
+
+    pub fn recv_from(&self, buf: &mut [u8]) -> io::Result<(usize, SocketAddress)> {
+        match self {
+            Datagram::Udp(socket) =>
+                socket.recv_from(buf).map(|(len, address)| (len, SocketAddress::Inet(address))),
+            #[cfg(unix)]
+            Datagram::Unix(socket) =>
+                socket
+                    .recv_from(buf)
+                    .map(|(len, address)| (len, unix_address(address.as_pathname()))),
+            Datagram::Replayed => Err(replayed()),
+        }
+    }
+
+    /// Peek at the next datagram of the UDP socket.
+    pub fn peek_from(&self, buf: &mut [u8]) -> io::Result<(usize, SocketAddress)> {
+        match self {
+            Datagram::Udp(socket) =>
+                socket.peek_from(buf).map(|(len, address)| (len, SocketAddress::Inet(address))),
+            #[cfg(unix)]
+            Datagram::Unix(_) => unreachable!("peeking Unix-domain datagrams is unsupported"),
+            Datagram::Replayed => Err(replayed()),
+        }
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddress> {
+        match self {
+            Datagram::Udp(socket) => socket.local_addr().map(SocketAddress::Inet),
+            #[cfg(unix)]
+            Datagram::Unix(socket) =>
+                socket.local_addr().map(|address| unix_address(address.as_pathname())),
+            Datagram::Replayed => Err(replayed()),
+        }
+    }
+
+    pub fn peer_addr(&self) -> io::Result<SocketAddress> {
+        match self {
+            Datagram::Udp(socket) => socket.peer_addr().map(SocketAddress::Inet),
+            #[cfg(unix)]
+            Datagram::Unix(socket) =>
+                socket.peer_addr().map(|address| unix_address(address.as_pathname())),
+            Datagram::Replayed => Err(replayed()),
+        }
+    }
+
+    pub fn take_error(&self) -> io::Result<Option<io::Error>> {
+        match self {
+            Datagram::Udp(socket) => socket.take_error(),
+            #[cfg(unix)]
+            Datagram::Unix(socket) => socket.take_error(),
+            Datagram::Replayed => Err(replayed()),
+        }
+    }
+
+    /// Get the TTL of the internet socket.
+    pub fn ttl(&self) -> io::Result<u32> {
+        match self {
+            Datagram::Udp(socket) => socket.ttl(),
+            #[cfg(unix)]
+            Datagram::Unix(_) => unreachable!("Unix-domain sockets don't have a TTL"),
+            Datagram::Replayed => Err(replayed()),
+        }
+    }
+
+    /// Get the `mio` source of the host socket, which stand-ins don't have.
+    pub fn source(&mut self) -> Option<&mut dyn Source> {
+        match self {
+            Datagram::Udp(socket) => Some(socket),
+            #[cfg(unix)]
+            Datagram::Unix(socket) => Some(socket),
+            Datagram::Replayed => None,
+        }
+    }
+}
@@ -5,6 +5,8 @@
 
 use self::shims::unix::linux::mem::EvalContextExt as _;
 use self::shims::unix::linux_like::eventfd::EvalContextExt as _;
+use self::shims::unix::linux_like::poll::EvalContextExt as _;
+use self::shims::unix::linux_like::signalfd::EvalContextExt as _;
 use self::shims::unix::linux_like::syscall::syscall;
 use crate::machine::{SIGRTMAX, SIGRTMIN};
 use crate::shims::unix::foreign_items::EvalContextExt as _;
@@ -128,7 +130,7 @@ fn emulate_foreign_item_inner(
                 let result = this.linux_statx(dirfd, pathname, flags, mask, statxbuf)?;
                 this.write_scalar(result, dest)?;
             }
-            // epoll, eventfd
+            // epoll, eventfd, poll, timerfd, signalfd
             "epoll_create1" => {
                 let [flag] = this.check_shim_sig_lenient(abi, CanonAbi::C, link_name, args)?;
                 let result = this.epoll_create1(flag)?;
@@ -150,6 +152,35 @@ fn emulate_foreign_item_inner(
                 let result = this.eventfd(val, flag)?;
                 this.write_scalar(result, dest)?;
             }
+            "poll" => {
+                let [fds, nfds, timeout] =
+                    this.check_shim_sig_lenient(abi, CanonAbi::C, link_name, args)?;
+                this.poll(fds, nfds, timeout, dest)?;
+            }
+            "timerfd_create" => {
+                let [clockid, flags] =
+                    this.check_shim_sig_lenient(abi, CanonAbi::C, link_name, args)?;
+                let result = this.timerfd_create(clockid, flags)?;
+                this.write_scalar(result, dest)?;
+            }
+            "timerfd_settime" => {
+                let [fd, flags, new_value, old_value] =
+                    this.check_shim_sig_lenient(abi, CanonAbi::C, link_name, args)?;
+                let result = this.timerfd_settime(fd, flags, new_value, old_value)?;
+                this.write_scalar(result, dest)?;
+            }
+            "timerfd_gettime" => {
+                let [fd, curr_value] =
+                    this.check_shim_sig_lenient(abi, CanonAbi::C, link_name, args)?;
+                let result = this.timerfd_gettime(fd, curr_value)?;
+                this.write_scalar(result, dest)?;
+            }
+            "signalfd" => {
+                let [fd, mask, flags] =
+                    this.check_shim_sig_lenient(abi, CanonAbi::C, link_name, args)?;
+                let result = this.signalfd(fd, mask, flags)?;
+                this.write_scalar(result, dest)?;
+            }
 
             // Threading
             "pthread_setname_np" => {
@@ -11,6 +11,7 @@
     DynFileDescriptionRef, FdId, FdNum, FileDescription, FileDescriptionRef, WeakFileDescriptionRef,
 };
 use crate::shims::unix::UnixFileDescription;
+use crate::shims::unix::linux_like::timerfd::EvalContextExt as _;
 use crate::*;
 
 type EpollEventKey = (FdId, FdNum);
@@ -192,6 +193,15 @@ fn get_epolls(&self, id: FdId) -> Option<impl Iterator<Item = &WeakFileDescripti
         self.0.get(&id).map(|epolls| epolls.iter().map(|(_id, epoll)| epoll))
     }
 
+    /// Returns whether a thread is blocked on an epoll instance that is interested in the
+    /// file description `id`.
+    pub fn has_blocked_epolls(&self, id: FdId) -> bool {
+        self.get_epolls(id).is_some_and(|mut epolls| {
+            epolls
+                .any(|epoll| epoll.upgrade().is_some_and(|epoll| !epoll.queue.borrow().is_empty()))
+        })
+    }
+
     pub fn remove_epolls(&mut self, id: FdId) {
         if let Some(epolls) = self.0.remove(&id) {
             for epoll in epolls.iter().filter_map(|(_id, epoll)| epoll.upgrade()) {
@@ -469,6 +479,9 @@ fn epoll_wait(
             return this.set_last_error_and_return(LibcError("EBADF"), dest);
         };
 
+        // Account for the time that passed since the timers were last checked.
+        this.fire_expired_timerfds()?;
+
         if timeout == 0 || !epfd.ready_set.borrow().is_empty() {
             // If the timeout is 0 or there is a ready event, we can return immediately.
             return_ready_list(&epfd, dest, &event, this)?;
@@ -563,6 +576,90 @@ fn update_epoll_active_events(
         interp_ok(())
     }
 
+    /// Register the interest of `epfd` in the events `relevant_events` of the file description
+    /// `fd_ref`, which is open as the file descriptor `fd`.
+    ///
+    /// Unlike `epoll_ctl`, this doesn't require the interest to be edge-triggered. It is meant for
+    /// epoll instances which are only used internally, and whose ready list is never returned.
+    fn epoll_add_interest(
+        &mut self,
+        epfd: &FileDescriptionRef<Epoll>,
+        fd_ref: DynFileDescriptionRef,
+        fd: FdNum,
+        relevant_events: u32,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+        let id = fd_ref.id();
+        let epoll_key = (id, fd);
+
+        let mut interest_list = epfd.interest_list.borrow_mut();
+        if interest_list.range(range_for_id(id)).next().is_none() {
+            this.machine.epoll_interests.insert(id, epfd);
+        }
+        let new_interest = EpollEventInterest {
+            relevant_events,
+            data: 0,
+            active_events: 0,
+            clock: VClock::default(),
+        };
+        assert!(
+            interest_list.insert(epoll_key, new_interest).is_none(),
+            "trying to add an interest that's already in the list"
+        );
+
+        let active_events = fd_ref.as_unix(this).epoll_active_events()?.get_event_bitmask(this);
+        update_readiness(this, epfd, active_events, /* force_edge */ true, move |callback| {
+            callback(epoll_key, interest_list.get_mut(&epoll_key).unwrap())
+        })
+    }
+
+    /// Remove all interests of `epfd`, synchronizing with the events of the interests that
+    /// are ready.
+    fn epoll_clear_interests(&mut self, epfd: &FileDescriptionRef<Epoll>) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+        let interest_list = std::mem::take(&mut *epfd.interest_list.borrow_mut());
+        let ready_set = std::mem::take(&mut *epfd.ready_set.borrow_mut());
+
+        for key in ready_set {
+            this.acquire_clock(&interest_list[&key].clock)?;
+        }
+        let mut ids = interest_list.keys().map(|(id, _num)| *id).collect::<Vec<_>>();
+        ids.dedup(); // they come out of the map sorted
+        for id in ids {
+            this.machine.epoll_interests.remove(id, epfd.id());
+        }
+        interp_ok(())
+    }
+
+    /// Block the current thread on `epfd` until one of its interests is ready or the timeout
+    /// expires. Once the thread is unblocked, `callback` is called with the unblock reason.
+    fn epoll_block_thread(
+        &mut self,
+        epfd: FileDescriptionRef<Epoll>,
+        timeout: Option<(TimeoutClock, TimeoutAnchor, Duration)>,
+        callback: DynUnblockCallback<'tcx>,
+    ) {
+        let this = self.eval_context_mut();
+        epfd.queue.borrow_mut().push_back(this.active_thread());
+        this.block_thread(
+            BlockReason::Epoll { epfd: epfd.clone() },
+            timeout,
+            callback!(
+                @capture<'tcx> {
+                    epfd: FileDescriptionRef<Epoll>,
+                    callback: DynUnblockCallback<'tcx>,
+                }
+                |this, unblock: UnblockKind| {
+                    if unblock == UnblockKind::TimedOut {
+                        // Remove the current active thread_id from the blocked thread_id list.
+                        epfd.queue.borrow_mut().retain(|&id| id != this.active_thread());
+                    }
+                    callback.call(this, unblock)
+                }
+            ),
+        );
+    }
+
     /// Recursively check whether the [`Epoll`] file description contains
     /// interests which are host I/O source file descriptions.
     fn has_epoll_host_interests(&self, epfd: &FileDescriptionRef<Epoll>) -> bool {
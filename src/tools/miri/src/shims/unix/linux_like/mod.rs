@@ -1,4 +1,7 @@
 pub mod epoll;
 pub mod eventfd;
+pub mod poll;
+pub mod signalfd;
 pub mod sync;
 pub mod syscall;
+pub mod timerfd;
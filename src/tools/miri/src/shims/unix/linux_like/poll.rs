@@ -0,0 +1,174 @@
+//! Linux `poll` implementation, built on top of the epoll implementation.
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use crate::shims::files::{FdNum, FileDescriptionRef};
+use crate::shims::unix::linux_like::epoll::{Epoll, EvalContextExt as _};
+use crate::shims::unix::linux_like::timerfd::EvalContextExt as _;
+use crate::*;
+
+/// The `poll` events that can be reported, and the epoll events they correspond to.
+const EVENTS: [(&str, &str); 5] = [
+    ("POLLIN", "EPOLLIN"),
+    ("POLLOUT", "EPOLLOUT"),
+    ("POLLRDHUP", "EPOLLRDHUP"),
+    ("POLLHUP", "EPOLLHUP"),
+    ("POLLERR", "EPOLLERR"),
+];
+
+impl<'tcx> EvalContextExt<'tcx> for crate::MiriInterpCx<'tcx> {}
+pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
+    /// The `poll()` system call waits for one of the file descriptors in the `fds` array of
+    /// `nfds` elements to become ready to perform I/O. For every file descriptor, the `events`
+    /// field of its `pollfd` specifies the events it is polled for, and the `revents` field is
+    /// set to the events that occurred. `POLLHUP` and `POLLERR` are always reported, and
+    /// `POLLNVAL` is reported for file descriptors that are not open. Negative file
+    /// descriptors are ignored.
+    ///
+    /// The `timeout` argument specifies the number of milliseconds that `poll()` will block,
+    /// measured against the CLOCK_MONOTONIC clock. A negative timeout blocks indefinitely,
+    /// while a timeout of zero returns immediately.
+    ///
+    /// On success, `poll()` returns the number of `pollfd`s with a nonzero `revents` field,
+    /// or zero if the timeout expired.
+    ///
+    /// Unlike epoll, `poll()` is level-triggered. We implement it by checking the readiness of
+    /// all file descriptors, and if none is ready, blocking on an internal epoll instance with
+    /// an interest in each of them.
+    ///
+    /// <https://man7.org/linux/man-pages/man2/poll.2.html>
+    fn poll(
+        &mut self,
+        fds: &OpTy<'tcx>,
+        nfds: &OpTy<'tcx>,
+        timeout: &OpTy<'tcx>,
+        dest: &MPlaceTy<'tcx>,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        let nfds_t_size = this.libc_ty_layout("nfds_t").size;
+        let nfds: u64 = this.read_scalar(nfds)?.to_uint(nfds_t_size)?.try_into().unwrap();
+        let timeout = this.read_scalar(timeout)?.to_i32()?;
+        let fds = this.deref_pointer_as(fds, this.libc_array_ty_layout("pollfd", nfds))?;
+
+        // Check the requested events, and collect the epoll events every file descriptor
+        // has to be watched for.
+        let pollpri = this.eval_libc("POLLPRI").to_i16()?;
+        let pollnval = this.eval_libc("POLLNVAL").to_i16()?;
+        let mut supported_events = pollpri | pollnval;
+        for (poll_event, _) in EVENTS {
+            supported_events |= this.eval_libc(poll_event).to_i16()?;
+        }
+        let mut interests: BTreeMap<FdNum, u32> = BTreeMap::new();
+        let mut array_iter = this.project_array_fields(&fds)?;
+        while let Some((_, pollfd)) = array_iter.next(this)? {
+            let fd = this.read_scalar(&this.project_field_named(&pollfd, "fd")?)?.to_i32()?;
+            let events =
+                this.read_scalar(&this.project_field_named(&pollfd, "events")?)?.to_i16()?;
+            if events & !supported_events != 0 {
+                throw_unsup_format!(
+                    "poll: encountered unknown unsupported events {:#x}",
+                    events & !supported_events
+                );
+            }
+            if fd >= 0 {
+                *interests.entry(fd).or_default() |= epoll_events(this, events)?;
+            }
+        }
+
+        // Account for the time that passed since the timers were last checked.
+        this.fire_expired_timerfds()?;
+
+        let ready = write_revents(this, &fds)?;
+        if ready > 0 || timeout == 0 {
+            this.write_int(ready, dest)?;
+            return interp_ok(());
+        }
+
+        // Nothing is ready, so we have to block. Any file descriptor that isn't open would have
+        // been reported with `POLLNVAL`, so all of them are open.
+        let epfd = this.machine.fds.new_ref(Epoll::default());
+        for (fd, events) in interests {
+            let fd_ref = this.machine.fds.get(fd).unwrap();
+            this.epoll_add_interest(&epfd, fd_ref, fd, events)?;
+        }
+
+        let timeout = (timeout > 0).then(|| {
+            let duration = Duration::from_millis(timeout.try_into().unwrap());
+            (TimeoutClock::Monotonic, TimeoutAnchor::Relative, duration)
+        });
+        let dest = dest.clone();
+        this.epoll_block_thread(
+            epfd.clone(),
+            timeout,
+            callback!(
+                @capture<'tcx> {
+                    epfd: FileDescriptionRef<Epoll>,
+                    fds: MPlaceTy<'tcx>,
+                    dest: MPlaceTy<'tcx>,
+                }
+                |this, unblock: UnblockKind| {
+                    this.epoll_clear_interests(&epfd)?;
+                    let ready = write_revents(this, &fds)?;
+                    if unblock == UnblockKind::Ready {
+                        assert!(ready > 0, "we got woken up with no events to deliver");
+                    }
+                    this.write_int(ready, &dest)?;
+                    interp_ok(())
+                }
+            ),
+        );
+        interp_ok(())
+    }
+}
+
+/// Returns the epoll events corresponding to the `poll` events `events`. `EPOLLHUP` and
+/// `EPOLLERR` are always included.
+fn epoll_events<'tcx>(ecx: &MiriInterpCx<'tcx>, events: i16) -> InterpResult<'tcx, u32> {
+    let mut epoll_events = ecx.eval_libc_u32("EPOLLHUP") | ecx.eval_libc_u32("EPOLLERR");
+    for (poll_event, epoll_event) in EVENTS {
+        if events & ecx.eval_libc(poll_event).to_i16()? != 0 {
+            epoll_events |= ecx.eval_libc_u32(epoll_event);
+        }
+    }
+    interp_ok(epoll_events)
+}
+
+/// Sets the `revents` field of every `pollfd` in the array `fds` to the events that are currently
+/// ready, and returns the number of `pollfd`s with some event.
+fn write_revents<'tcx>(
+    ecx: &mut MiriInterpCx<'tcx>,
+    fds: &MPlaceTy<'tcx>,
+) -> InterpResult<'tcx, i32> {
+    let pollhup = ecx.eval_libc("POLLHUP").to_i16()?;
+    let pollerr = ecx.eval_libc("POLLERR").to_i16()?;
+    let pollnval = ecx.eval_libc("POLLNVAL").to_i16()?;
+
+    let mut ready: i32 = 0;
+    let mut array_iter = ecx.project_array_fields(fds)?;
+    while let Some((_, pollfd)) = array_iter.next(ecx)? {
+        let fd = ecx.read_scalar(&ecx.project_field_named(&pollfd, "fd")?)?.to_i32()?;
+        let events = ecx.read_scalar(&ecx.project_field_named(&pollfd, "events")?)?.to_i16()?;
+
+        let revents = if fd < 0 {
+            0
+        } else if let Some(fd_ref) = ecx.machine.fds.get(fd) {
+            let active_events = fd_ref.as_unix(ecx).epoll_active_events()?.get_event_bitmask(ecx);
+            let mut revents = 0;
+            for (poll_event, epoll_event) in EVENTS {
+                if active_events & ecx.eval_libc_u32(epoll_event) != 0 {
+                    revents |= ecx.eval_libc(poll_event).to_i16()?;
+                }
+            }
+            revents & (events | pollhup | pollerr)
+        } else {
+            pollnval
+        };
+
+        ecx.write_int(revents, &ecx.project_field_named(&pollfd, "revents")?)?;
+        if revents != 0 {
+            ready = ready.strict_add(1);
+        }
+    }
+    interp_ok(ready)
+}
@@ -0,0 +1,142 @@
+//! Linux `signalfd` implementation.
+use std::io;
+use std::io::ErrorKind;
+
+use crate::shims::files::{FdId, FileDescription, FileDescriptionRef};
+use crate::shims::unix::UnixFileDescription;
+use crate::shims::unix::linux_like::epoll::EpollReadiness;
+use crate::*;
+
+/// A file descriptor created by `signalfd`, which would allow reading the signals in its mask
+/// that are pending. Miri never delivers signals, so it never becomes readable: reading it
+/// blocks forever, or fails with `EAGAIN` if it is nonblocking.
+///
+/// <https://man7.org/linux/man-pages/man2/signalfd.2.html>
+#[derive(Debug)]
+struct SignalFd {
+    is_nonblock: bool,
+}
+
+impl FileDescription for SignalFd {
+    fn name(&self) -> &'static str {
+        "signalfd"
+    }
+
+    fn metadata<'tcx>(
+        &self,
+    ) -> InterpResult<'tcx, Either<io::Result<std::fs::Metadata>, &'static str>> {
+        // On Linux, signalfd is an "anonymous inode" reported as S_IFREG.
+        interp_ok(Either::Right("S_IFREG"))
+    }
+
+    fn destroy<'tcx>(
+        self,
+        _self_id: FdId,
+        _communicate_allowed: bool,
+        _ecx: &mut MiriInterpCx<'tcx>,
+    ) -> InterpResult<'tcx, io::Result<()>> {
+        interp_ok(Ok(()))
+    }
+
+    fn read<'tcx>(
+        self: FileDescriptionRef<Self>,
+        _communicate_allowed: bool,
+        _ptr: Pointer,
+        len: usize,
+        ecx: &mut MiriInterpCx<'tcx>,
+        finish: DynMachineCallback<'tcx, Result<usize, IoError>>,
+    ) -> InterpResult<'tcx> {
+        // The buffer must be able to hold at least one `signalfd_siginfo`.
+        if len < ecx.libc_ty_layout("signalfd_siginfo").size.bytes_usize() {
+            return finish.call(ecx, Err(ErrorKind::InvalidInput.into()));
+        }
+
+        if self.is_nonblock {
+            return finish.call(ecx, Err(ErrorKind::WouldBlock.into()));
+        }
+
+        // No signal will ever arrive, so this thread is blocked forever.
+        ecx.block_thread(
+            BlockReason::Signalfd,
+            None,
+            callback!(
+                @capture<'tcx> {}
+                |_this, _unblock: UnblockKind| {
+                    unreachable!("threads blocked on a signalfd are never unblocked")
+                }
+            ),
+        );
+        interp_ok(())
+    }
+
+    fn as_unix<'tcx>(&self, _ecx: &MiriInterpCx<'tcx>) -> &dyn UnixFileDescription {
+        self
+    }
+}
+
+impl UnixFileDescription for SignalFd {
+    fn epoll_active_events<'tcx>(&self) -> InterpResult<'tcx, EpollReadiness> {
+        // There is never a signal to read.
+        interp_ok(EpollReadiness::empty())
+    }
+}
+
+impl<'tcx> EvalContextExt<'tcx> for crate::MiriInterpCx<'tcx> {}
+pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
+    /// This function creates a file descriptor that accepts the signals in `mask`, or changes the
+    /// mask of the existing signalfd `fd` unless that is -1. Since Miri never delivers signals,
+    /// the mask only has to be readable.
+    ///
+    /// The `flags` may be bitwise ORed to change the behavior of a new signalfd:
+    /// `SFD_CLOEXEC` - Set the close-on-exec (`FD_CLOEXEC`) flag on the new file descriptor.
+    /// `SFD_NONBLOCK` - Set the `O_NONBLOCK` file status flag on the new open file description.
+    ///
+    /// <https://man7.org/linux/man-pages/man2/signalfd.2.html>
+    fn signalfd(
+        &mut self,
+        fd: &OpTy<'tcx>,
+        mask: &OpTy<'tcx>,
+        flags: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let fd = this.read_scalar(fd)?.to_i32()?;
+        let mask = this.deref_pointer_as(mask, this.libc_ty_layout("sigset_t"))?;
+        let mut flags = this.read_scalar(flags)?.to_i32()?;
+
+        let sfd_cloexec = this.eval_libc_i32("SFD_CLOEXEC");
+        let sfd_nonblock = this.eval_libc_i32("SFD_NONBLOCK");
+
+        // Make sure the mask is initialized, even though no signal will ever match it.
+        this.read_bytes_ptr_strip_provenance(mask.ptr(), mask.layout.size)?;
+
+        let mut is_nonblock = false;
+        // Unset the flag that we support.
+        // After unloading, flags != 0 means other flags are used.
+        if flags & sfd_cloexec == sfd_cloexec {
+            // cloexec is ignored because Miri does not support exec.
+            flags &= !sfd_cloexec;
+        }
+        if flags & sfd_nonblock == sfd_nonblock {
+            flags &= !sfd_nonblock;
+            is_nonblock = true;
+        }
+        if flags != 0 {
+            throw_unsup_format!("signalfd: encountered unknown unsupported flags {:#x}", flags);
+        }
+
+        if fd != -1 {
+            // Changing the mask of an existing signalfd has no effect.
+            let Some(signalfd) = this.machine.fds.get(fd) else {
+                return this.set_last_error_and_return_i32(LibcError("EBADF"));
+            };
+            if signalfd.downcast::<SignalFd>().is_none() {
+                return this.set_last_error_and_return_i32(LibcError("EINVAL"));
+            }
+            return interp_ok(Scalar::from_i32(fd));
+        }
+
+        let fd_value = this.machine.fds.insert_new(SignalFd { is_nonblock });
+        interp_ok(Scalar::from_i32(fd_value))
+    }
+}
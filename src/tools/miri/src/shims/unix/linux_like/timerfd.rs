@@ -0,0 +1,428 @@
+//! Linux `timerfd` implementation.
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeMap;
+use std::io;
+use std::io::ErrorKind;
+use std::time::Duration;
+
+use crate::shims::files::{FdId, FileDescription, FileDescriptionRef, WeakFileDescriptionRef};
+use crate::shims::unix::UnixFileDescription;
+use crate::shims::unix::linux_like::epoll::{EpollReadiness, EvalContextExt as _};
+use crate::*;
+
+/// A file descriptor created by `timerfd_create`. It counts how often the timer expired since
+/// the last `read`, and is readable whenever that count is not zero.
+///
+/// Only timers measured against `CLOCK_MONOTONIC` are supported. Times are represented as
+/// durations since the epoch of Miri's monotone clock.
+///
+/// <https://man7.org/linux/man-pages/man2/timerfd_create.2.html>
+#[derive(Debug)]
+pub struct TimerFd {
+    is_nonblock: bool,
+    /// The time of the next expiration, or `None` if the timer is disarmed.
+    deadline: Cell<Option<Duration>>,
+    /// The period of the timer after its first expiration, or zero for a one-shot timer.
+    interval: Cell<Duration>,
+    /// The number of expirations since the timer was last read or set.
+    expirations: Cell<u64>,
+    /// A list of thread ids blocked on `read`.
+    blocked_read_tid: RefCell<Vec<ThreadId>>,
+}
+
+impl FileDescription for TimerFd {
+    fn name(&self) -> &'static str {
+        "timerfd"
+    }
+
+    fn metadata<'tcx>(
+        &self,
+    ) -> InterpResult<'tcx, Either<io::Result<std::fs::Metadata>, &'static str>> {
+        // On Linux, timerfd is an "anonymous inode" reported as S_IFREG.
+        interp_ok(Either::Right("S_IFREG"))
+    }
+
+    fn destroy<'tcx>(
+        self,
+        self_id: FdId,
+        _communicate_allowed: bool,
+        ecx: &mut MiriInterpCx<'tcx>,
+    ) -> InterpResult<'tcx, io::Result<()>> {
+        ecx.machine.timerfds.remove(self_id);
+        interp_ok(Ok(()))
+    }
+
+    /// Read the number of expirations into the buffer, and reset it to zero.
+    fn read<'tcx>(
+        self: FileDescriptionRef<Self>,
+        _communicate_allowed: bool,
+        ptr: Pointer,
+        len: usize,
+        ecx: &mut MiriInterpCx<'tcx>,
+        finish: DynMachineCallback<'tcx, Result<usize, IoError>>,
+    ) -> InterpResult<'tcx> {
+        // We're treating the buffer as a `u64`.
+        let ty = ecx.machine.layouts.u64;
+        // Check the size of slice, and return error only if the size of the slice < 8.
+        if len < ty.size.bytes_usize() {
+            return finish.call(ecx, Err(ErrorKind::InvalidInput.into()));
+        }
+
+        // Turn the pointer into a place at the right type.
+        let buf_place = ecx.ptr_to_mplace_unaligned(ptr, ty);
+
+        // Account for the time that passed since the timers were last checked.
+        ecx.fire_expired_timerfds()?;
+        timerfd_read(buf_place, self, ecx, finish)
+    }
+
+    fn as_unix<'tcx>(&self, _ecx: &MiriInterpCx<'tcx>) -> &dyn UnixFileDescription {
+        self
+    }
+}
+
+impl UnixFileDescription for TimerFd {
+    fn epoll_active_events<'tcx>(&self) -> InterpResult<'tcx, EpollReadiness> {
+        interp_ok(EpollReadiness {
+            epollin: self.expirations.get() != 0,
+            ..EpollReadiness::empty()
+        })
+    }
+}
+
+/// The table of all timerfds, which lets them expire while no thread is using them.
+pub struct TimerFdTable(BTreeMap<FdId, WeakFileDescriptionRef<TimerFd>>);
+
+impl TimerFdTable {
+    pub(crate) fn new() -> Self {
+        TimerFdTable(BTreeMap::new())
+    }
+
+    fn insert(&mut self, timerfd: &FileDescriptionRef<TimerFd>) {
+        self.0
+            .try_insert(timerfd.id(), FileDescriptionRef::downgrade(timerfd))
+            .unwrap_or_else(|_| panic!("trying to add a timerfd that's already in the table"));
+    }
+
+    fn remove(&mut self, id: FdId) {
+        self.0.remove(&id).expect("trying to remove a timerfd that's not in the table");
+    }
+
+    fn timerfds(&self) -> Vec<FileDescriptionRef<TimerFd>> {
+        self.0
+            .values()
+            .map(|weak| {
+                weak.upgrade()
+                    .expect("someone forgot to remove the garbage from `machine.timerfds`")
+            })
+            .collect()
+    }
+}
+
+impl<'tcx> EvalContextExt<'tcx> for crate::MiriInterpCx<'tcx> {}
+pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
+    /// This function creates a new timer, which is initially disarmed, and returns a file
+    /// descriptor referring to it.
+    ///
+    /// Only `CLOCK_MONOTONIC` (and its "coarse" variant) is supported as `clockid`.
+    /// The `flags` may be bitwise ORed to change the behavior of `timerfd_create`:
+    /// `TFD_CLOEXEC` - Set the close-on-exec (`FD_CLOEXEC`) flag on the new file descriptor.
+    /// `TFD_NONBLOCK` - Set the `O_NONBLOCK` file status flag on the new open file description.
+    ///
+    /// <https://man7.org/linux/man-pages/man2/timerfd_create.2.html>
+    fn timerfd_create(
+        &mut self,
+        clockid: &OpTy<'tcx>,
+        flags: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let clockid = this.read_scalar(clockid)?;
+        let mut flags = this.read_scalar(flags)?.to_i32()?;
+
+        let tfd_cloexec = this.eval_libc_i32("TFD_CLOEXEC");
+        let tfd_nonblock = this.eval_libc_i32("TFD_NONBLOCK");
+
+        match this.parse_clockid(clockid) {
+            Some(TimeoutClock::Monotonic) => {}
+            _ => throw_unsup_format!("timerfd_create: only CLOCK_MONOTONIC is supported"),
+        }
+
+        let mut is_nonblock = false;
+        // Unset the flag that we support.
+        // After unloading, flags != 0 means other flags are used.
+        if flags & tfd_cloexec == tfd_cloexec {
+            // cloexec is ignored because Miri does not support exec.
+            flags &= !tfd_cloexec;
+        }
+        if flags & tfd_nonblock == tfd_nonblock {
+            flags &= !tfd_nonblock;
+            is_nonblock = true;
+        }
+        if flags != 0 {
+            throw_unsup_format!(
+                "timerfd_create: encountered unknown unsupported flags {:#x}",
+                flags
+            );
+        }
+
+        let timerfd = this.machine.fds.new_ref(TimerFd {
+            is_nonblock,
+            deadline: Cell::new(None),
+            interval: Cell::new(Duration::ZERO),
+            expirations: Cell::new(0),
+            blocked_read_tid: RefCell::new(Vec::new()),
+        });
+        this.machine.timerfds.insert(&timerfd);
+        let fd_value = this.machine.fds.insert(timerfd);
+
+        interp_ok(Scalar::from_i32(fd_value))
+    }
+
+    /// This function arms or disarms the timer referred to by the file descriptor `fd`, and
+    /// resets its number of expirations. If `old_value` is not null, the previous setting of
+    /// the timer is stored in it, as `timerfd_gettime` would.
+    ///
+    /// The timer is disarmed if `new_value.it_value` is zero. Otherwise, it first expires after
+    /// `new_value.it_value`, and then periodically every `new_value.it_interval`, unless that is
+    /// zero. With `TFD_TIMER_ABSTIME` in `flags`, `new_value.it_value` is an absolute time.
+    ///
+    /// <https://man7.org/linux/man-pages/man2/timerfd_settime.2.html>
+    fn timerfd_settime(
+        &mut self,
+        fd: &OpTy<'tcx>,
+        flags: &OpTy<'tcx>,
+        new_value: &OpTy<'tcx>,
+        old_value: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let fd = this.read_scalar(fd)?.to_i32()?;
+        let flags = this.read_scalar(flags)?.to_i32()?;
+        let new_value = this.deref_pointer_as(new_value, this.libc_ty_layout("itimerspec"))?;
+        let old_value_ptr = this.read_pointer(old_value)?;
+
+        let tfd_timer_abstime = this.eval_libc_i32("TFD_TIMER_ABSTIME");
+        let tfd_timer_cancel_on_set = this.eval_libc_i32("TFD_TIMER_CANCEL_ON_SET");
+
+        let Some(timerfd) = this.machine.fds.get(fd) else {
+            return this.set_last_error_and_return_i32(LibcError("EBADF"));
+        };
+        let Some(timerfd) = timerfd.downcast::<TimerFd>() else {
+            return this.set_last_error_and_return_i32(LibcError("EINVAL"));
+        };
+
+        if flags & tfd_timer_cancel_on_set != 0 {
+            throw_unsup_format!("timerfd_settime: TFD_TIMER_CANCEL_ON_SET is unsupported");
+        }
+        if flags & !tfd_timer_abstime != 0 {
+            return this.set_last_error_and_return_i32(LibcError("EINVAL"));
+        }
+
+        let interval = this.project_field_named(&new_value, "it_interval")?;
+        let value = this.project_field_named(&new_value, "it_value")?;
+        let (Some(interval), Some(value)) =
+            (this.read_timespec(&interval)?, this.read_timespec(&value)?)
+        else {
+            return this.set_last_error_and_return_i32(LibcError("EINVAL"));
+        };
+
+        if !this.ptr_is_null(old_value_ptr)? {
+            let old_value = this.deref_pointer_as(old_value, this.libc_ty_layout("itimerspec"))?;
+            write_itimerspec(this, &timerfd, &old_value)?;
+        }
+
+        let now = monotonic_now(this);
+        let deadline = if value.is_zero() {
+            None
+        } else if flags & tfd_timer_abstime != 0 {
+            Some(value)
+        } else {
+            Some(now.saturating_add(value))
+        };
+        timerfd.deadline.set(deadline);
+        timerfd.interval.set(interval);
+        timerfd.expirations.set(0);
+
+        // The timer may not be readable anymore.
+        this.update_epoll_active_events(timerfd, /* force_edge */ false)?;
+        // An absolute time in the past lets the timer expire immediately.
+        this.fire_expired_timerfds()?;
+
+        interp_ok(Scalar::from_i32(0))
+    }
+
+    /// This function stores the setting of the timer referred to by the file descriptor `fd`
+    /// in `curr_value`. Its `it_value` is the time until the next expiration, or zero if the
+    /// timer is disarmed.
+    ///
+    /// <https://man7.org/linux/man-pages/man2/timerfd_settime.2.html>
+    fn timerfd_gettime(
+        &mut self,
+        fd: &OpTy<'tcx>,
+        curr_value: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let fd = this.read_scalar(fd)?.to_i32()?;
+        let curr_value = this.deref_pointer_as(curr_value, this.libc_ty_layout("itimerspec"))?;
+
+        let Some(timerfd) = this.machine.fds.get(fd) else {
+            return this.set_last_error_and_return_i32(LibcError("EBADF"));
+        };
+        let Some(timerfd) = timerfd.downcast::<TimerFd>() else {
+            return this.set_last_error_and_return_i32(LibcError("EINVAL"));
+        };
+
+        this.fire_expired_timerfds()?;
+        write_itimerspec(this, &timerfd, &curr_value)?;
+
+        interp_ok(Scalar::from_i32(0))
+    }
+
+    /// Let all timers whose next expiration has passed expire. This must be called whenever
+    /// a thread might observe a timer, and by the scheduler before it lets time pass.
+    fn fire_expired_timerfds(&mut self) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+        let now = monotonic_now(this);
+
+        for timerfd in this.machine.timerfds.timerfds() {
+            let Some(deadline) = timerfd.deadline.get() else { continue };
+            if deadline > now {
+                continue;
+            }
+
+            // Figure out how many periods have passed since the deadline.
+            let interval = timerfd.interval.get();
+            let (expirations, next_deadline) = if interval.is_zero() {
+                (1, None)
+            } else {
+                let periods = (now - deadline).as_nanos() / interval.as_nanos() + 1;
+                let next_deadline = deadline.as_nanos() + periods * interval.as_nanos();
+                (periods, Some(Duration::from_nanos_u128(next_deadline)))
+            };
+            timerfd.deadline.set(next_deadline);
+            timerfd.expirations.set(
+                timerfd
+                    .expirations
+                    .get()
+                    .saturating_add(expirations.try_into().unwrap_or(u64::MAX)),
+            );
+
+            // Unblock *all* threads previously blocked on `read`.
+            // We need to take out the blocked thread ids and unblock them together,
+            // because `unblock_threads` may block them again and end up re-adding the
+            // thread to the blocked list.
+            let waiting_threads = std::mem::take(&mut *timerfd.blocked_read_tid.borrow_mut());
+            for thread_id in waiting_threads {
+                this.unblock_thread(thread_id, BlockReason::Timerfd)?;
+            }
+
+            // Like for eventfd, every expiration notifies edge-triggered epoll interests.
+            this.update_epoll_active_events(timerfd, /* force_edge */ true)?;
+        }
+
+        interp_ok(())
+    }
+
+    /// Returns the time until the next expiration of a timer that a thread is blocked on, either
+    /// by reading it or through epoll. If there is no such timer, [`None`] is returned.
+    ///
+    /// Timers that no thread is waiting for don't count, so that the program can still be
+    /// reported as deadlocked if it has a periodic timer that no thread will ever wait for.
+    fn next_timerfd_expiration(&self) -> Option<Duration> {
+        let this = self.eval_context_ref();
+        let now = monotonic_now(this);
+
+        this.machine
+            .timerfds
+            .timerfds()
+            .iter()
+            .filter(|timerfd| {
+                !timerfd.blocked_read_tid.borrow().is_empty()
+                    || this.machine.epoll_interests.has_blocked_epolls(timerfd.id())
+            })
+            .filter_map(|timerfd| timerfd.deadline.get())
+            .map(|deadline| deadline.saturating_sub(now))
+            .min()
+    }
+}
+
+/// Returns the current time of the monotone clock, as the duration since its epoch.
+fn monotonic_now(ecx: &MiriInterpCx<'_>) -> Duration {
+    ecx.machine.monotonic_clock.now().duration_since(ecx.machine.monotonic_clock.epoch())
+}
+
+/// Store the current setting of `timerfd` in the `itimerspec` place `dest`.
+fn write_itimerspec<'tcx>(
+    ecx: &mut MiriInterpCx<'tcx>,
+    timerfd: &TimerFd,
+    dest: &MPlaceTy<'tcx>,
+) -> InterpResult<'tcx> {
+    let value = match timerfd.deadline.get() {
+        // An armed timer always has some time left, even if its deadline just passed.
+        Some(deadline) => deadline.saturating_sub(monotonic_now(ecx)).max(Duration::from_nanos(1)),
+        None => Duration::ZERO,
+    };
+    let interval = timerfd.interval.get();
+
+    for (name, duration) in [("it_interval", interval), ("it_value", value)] {
+        let timespec = ecx.project_field_named(dest, name)?;
+        ecx.write_int_fields(
+            &[duration.as_secs().into(), duration.subsec_nanos().into()],
+            &timespec,
+        )?;
+    }
+    interp_ok(())
+}
+
+/// Block thread if the timer has not expired since it was last read,
+/// else return the number of expirations to the caller and reset it to 0.
+fn timerfd_read<'tcx>(
+    buf_place: MPlaceTy<'tcx>,
+    timerfd: FileDescriptionRef<TimerFd>,
+    ecx: &mut MiriInterpCx<'tcx>,
+    finish: DynMachineCallback<'tcx, Result<usize, IoError>>,
+) -> InterpResult<'tcx> {
+    // Set the number of expirations to 0, get old value.
+    let expirations = timerfd.expirations.replace(0);
+
+    // Block when the timer has not expired.
+    if expirations == 0 {
+        if timerfd.is_nonblock {
+            return finish.call(ecx, Err(ErrorKind::WouldBlock.into()));
+        }
+
+        timerfd.blocked_read_tid.borrow_mut().push(ecx.active_thread());
+
+        let weak_timerfd = FileDescriptionRef::downgrade(&timerfd);
+        ecx.block_thread(
+            BlockReason::Timerfd,
+            None,
+            callback!(
+                @capture<'tcx> {
+                    buf_place: MPlaceTy<'tcx>,
+                    finish: DynMachineCallback<'tcx, Result<usize, IoError>>,
+                    weak_timerfd: WeakFileDescriptionRef<TimerFd>,
+                }
+                |this, unblock: UnblockKind| {
+                    assert_eq!(unblock, UnblockKind::Ready);
+                    // When we get unblocked, try again. We know the ref is still valid,
+                    // otherwise the timer couldn't have expired.
+                    let timerfd_ref = weak_timerfd.upgrade().unwrap();
+                    timerfd_read(buf_place, timerfd_ref, this, finish)
+                }
+            ),
+        );
+    } else {
+        // Return the number of expirations into user-space buffer.
+        ecx.write_int(expirations, &buf_place)?;
+
+        // The timer is no longer readable.
+        ecx.update_epoll_active_events(timerfd, /* force_edge */ false)?;
+
+        // Tell userspace how many bytes we put into the buffer.
+        return finish.call(ecx, Ok(buf_place.layout.size.bytes_usize()));
+    }
+    interp_ok(())
+}
@@ -3,6 +3,7 @@
 mod env;
 mod fd;
 mod fs;
+mod host_socket;
 mod mem;
 mod socket;
 mod socket_address;
@@ -20,13 +21,14 @@
 // All the Unix-specific extension traits
 pub use self::env::{EvalContextExt as _, UnixEnvVars};
 pub use self::fd::{EvalContextExt as _, UnixFileDescription};
-pub use self::fs::{DirTable, EvalContextExt as _};
+pub use self::fs::{DirTable, EvalContextExt as _, FileMetadata};
 pub use self::linux_like::epoll::{
     Epoll, EpollInterestTable, EvalContextExt as EpollEvalContextExt,
 };
+pub use self::linux_like::timerfd::{EvalContextExt as TimerFdEvalContextExt, TimerFdTable};
 pub use self::mem::EvalContextExt as _;
 pub use self::socket::EvalContextExt as _;
-pub use self::socket_address::EvalContextExt as _;
+pub use self::socket_address::{EvalContextExt as _, SocketAddress};
 pub use self::sync::EvalContextExt as _;
 pub use self::thread::{EvalContextExt as _, ThreadNameResult};
 pub use self::virtual_socket::EvalContextExt as _;
@@ -1,12 +1,11 @@
 use std::cell::{Cell, RefCell, RefMut};
 use std::io;
 use std::io::Read;
-use std::net::{Ipv4Addr, Shutdown, SocketAddr, SocketAddrV4};
+use std::net::{Ipv4Addr, Ipv6Addr, Shutdown, SocketAddr, SocketAddrV4, SocketAddrV6};
 use std::sync::atomic::AtomicBool;
 use std::time::Duration;
 
 use mio::event::Source;
-use mio::net::{TcpListener, TcpStream};
 use rustc_abi::Size;
 use rustc_const_eval::interpret::{InterpResult, interp_ok};
 use rustc_middle::throw_unsup_format;
@@ -14,38 +13,65 @@
 
 use crate::shims::files::{EvalContextExt as _, FdId, FileDescription, FileDescriptionRef};
 use crate::shims::unix::UnixFileDescription;
+use crate::shims::unix::host_socket::{Datagram, Listener, Stream};
 use crate::shims::unix::linux_like::epoll::{EpollReadiness, EvalContextExt as _};
-use crate::shims::unix::socket_address::EvalContextExt as _;
+use crate::shims::unix::socket_address::{EvalContextExt as _, SocketAddress};
 use crate::*;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Copy)]
 enum SocketFamily {
     // IPv4 internet protocols
     IPv4,
     // IPv6 internet protocols
     IPv6,
+    // Local communication via Unix-domain sockets
+    Unix,
+}
+
+impl SocketFamily {
+    /// Get the family of a socket address.
+    fn of(address: &SocketAddress) -> SocketFamily {
+        match address {
+            SocketAddress::Inet(SocketAddr::V4(_)) => SocketFamily::IPv4,
+            SocketAddress::Inet(SocketAddr::V6(_)) => SocketFamily::IPv6,
+            SocketAddress::Unix(_) => SocketFamily::Unix,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum SocketType {
+    /// A connection-based byte stream (`SOCK_STREAM`).
+    Stream,
+    /// Connectionless datagrams (`SOCK_DGRAM`).
+    Datagram,
 }
 
 #[derive(Debug)]
 enum SocketState {
     /// No syscall after `socket` has been made.
     Initial,
-    /// The `bind` syscall has been called on the socket.
+    /// The `bind` syscall has been called on the stream socket.
     /// This is only reachable from the [`SocketState::Initial`] state.
-    Bound(SocketAddr),
+    Bound(SocketAddress),
     /// The `listen` syscall has been called on the socket.
     /// This is only reachable from the [`SocketState::Bound`] state.
-    Listening(TcpListener),
+    Listening(Listener),
     /// The `connect` syscall has been called and we weren't yet able
     /// to ensure the connection is established. This is only reachable
     /// from the [`SocketState::Initial`] state.
-    Connecting(TcpStream),
+    Connecting(Stream),
     /// The `connect` syscall has been called on the socket and
     /// we ensured that the connection is established, or
     /// the socket was created by the `accept` syscall.
     /// For a socket created using the `connect` syscall, this is
     /// only reachable from the [`SocketState::Connecting`] state.
-    Connected(TcpStream),
+    Connected(Stream),
+    /// The datagram socket is backed by a host socket, which happens as soon as
+    /// it is bound, connected or used to send a datagram. Datagram sockets don't
+    /// go through any of the other states.
+    /// This is only reachable from the [`SocketState::Initial`] state.
+    Datagram(Datagram),
 }
 
 #[derive(Debug)]
@@ -53,6 +79,8 @@ struct Socket {
     /// Family of the socket, used to ensure socket only binds/connects to address of
     /// same family.
     family: SocketFamily,
+    /// Type of the socket, which determines whether it is connection-based.
+    socket_type: SocketType,
     /// Current state of the inner socket.
     state: RefCell<SocketState>,
     /// Whether this fd is non-blocking or not.
@@ -78,7 +106,10 @@ fn destroy<'tcx>(
 
         if matches!(
             &*self.state.borrow(),
-            SocketState::Listening(_) | SocketState::Connecting(_) | SocketState::Connected(_)
+            SocketState::Listening(_)
+                | SocketState::Connecting(_)
+                | SocketState::Connected(_)
+                | SocketState::Datagram(_)
         ) {
             // There exists an associated host socket so we need to deregister it
             // from the blocking I/O manager.
@@ -121,12 +152,12 @@ fn read<'tcx>(
                     if socket.is_non_block.get() {
                         // We have a non-blocking socket and thus don't want to block until
                         // we can read.
-                        let result = this.try_non_block_recv(&socket, ptr, len, /* should_peek */ false)?;
+                        let result = this.try_non_block_recv(&socket, ptr, len, /* should_peek */ false, None)?;
                         finish.call(this, result)
                     } else {
                         // The socket is in blocking mode and thus the read call should block
                         // until we can read some bytes from the socket.
-                        this.block_for_recv(socket, ptr, len, /* should_peek */ false, finish)
+                        this.block_for_recv(socket, ptr, len, /* should_peek */ false, None, finish)
                     }
                 }
             ),
@@ -166,12 +197,12 @@ fn write<'tcx>(
                     if socket.is_non_block.get() {
                         // We have a non-blocking socket and thus don't want to block until
                         // we can write.
-                        let result = this.try_non_block_send(&socket, ptr, len)?;
+                        let result = this.try_non_block_send(&socket, ptr, len, None)?;
                         return finish.call(this, result)
                     } else {
                         // The socket is in blocking mode and thus the write call should block
                         // until we can write some bytes into the socket.
-                        this.block_for_send(socket, ptr, len, finish)
+                        this.block_for_send(socket, ptr, len, None, finish)
                     }
                 }
             ),
@@ -315,31 +346,49 @@ fn socket(
             SocketFamily::IPv4
         } else if domain == this.eval_libc_i32("AF_INET6") {
             SocketFamily::IPv6
+        } else if domain == this.eval_libc_i32("AF_UNIX") {
+            if !cfg!(unix) {
+                throw_unsup_format!("socket: AF_UNIX sockets are only supported on Unix hosts");
+            }
+            SocketFamily::Unix
         } else {
             throw_unsup_format!(
-                "socket: domain {:#x} is unsupported, only AF_INET and \
-                AF_INET6 are allowed.",
+                "socket: domain {:#x} is unsupported, only AF_INET, \
+                AF_INET6 and AF_UNIX are allowed.",
                 domain
             );
         };
 
-        if flags != this.eval_libc_i32("SOCK_STREAM") {
+        let socket_type = if flags == this.eval_libc_i32("SOCK_STREAM") {
+            SocketType::Stream
+        } else if flags == this.eval_libc_i32("SOCK_DGRAM") {
+            SocketType::Datagram
+        } else {
             throw_unsup_format!(
                 "socket: type {:#x} is unsupported, only SOCK_STREAM, \
-                SOCK_CLOEXEC and SOCK_NONBLOCK are allowed",
+                SOCK_DGRAM, SOCK_CLOEXEC and SOCK_NONBLOCK are allowed",
                 flags
             );
-        }
-        if protocol != 0 && protocol != this.eval_libc_i32("IPPROTO_TCP") {
+        };
+        // Unix-domain sockets only have the default protocol.
+        let inet_protocol = match socket_type {
+            SocketType::Stream => "IPPROTO_TCP",
+            SocketType::Datagram => "IPPROTO_UDP",
+        };
+        if protocol != 0
+            && (family == SocketFamily::Unix || protocol != this.eval_libc_i32(inet_protocol))
+        {
             throw_unsup_format!(
                 "socket: socket protocol {protocol} is unsupported, \
-                only IPPROTO_TCP and 0 are allowed"
+                only IPPROTO_TCP for SOCK_STREAM, IPPROTO_UDP for SOCK_DGRAM \
+                and 0 are allowed"
             );
         }
 
         let fds = &mut this.machine.fds;
         let fd = fds.new_ref(Socket {
             family,
+            socket_type,
             state: RefCell::new(SocketState::Initial),
             is_non_block: Cell::new(is_sock_nonblock),
             io_readiness: RefCell::new(BlockingIoSourceReadiness::empty()),
@@ -379,12 +428,7 @@ fn bind(
 
         match *state {
             SocketState::Initial => {
-                let address_family = match &address {
-                    SocketAddr::V4(_) => SocketFamily::IPv4,
-                    SocketAddr::V6(_) => SocketFamily::IPv6,
-                };
-
-                if socket.family != address_family {
+                if socket.family != SocketFamily::of(&address) {
                     // Attempted to bind an address from a family that doesn't match
                     // the family of the socket.
                     let err = if matches!(this.tcx.sess.target.os, Os::Linux | Os::Android) {
@@ -400,14 +444,35 @@ fn bind(
                     return this.set_last_error_and_return_i32(err);
                 }
 
-                *state = SocketState::Bound(address);
+                match socket.socket_type {
+                    SocketType::Stream => *state = SocketState::Bound(address),
+                    SocketType::Datagram => {
+                        // Datagram sockets can be used right after binding them,
+                        // so we immediately create the host socket.
+                        let datagram = this.create_host_socket(
+                            "bind",
+                            || Datagram::bind(&address),
+                            Datagram::Replayed,
+                        );
+                        match datagram {
+                            Ok(datagram) => {
+                                *state = SocketState::Datagram(datagram);
+                                drop(state);
+                                // Register the socket to the blocking I/O manager because
+                                // we now have an associated host socket.
+                                this.machine.blocking_io.register(socket);
+                            }
+                            Err(e) => return this.set_last_error_and_return_i32(e),
+                        }
+                    }
+                }
             }
             SocketState::Connecting(_) | SocketState::Connected(_) =>
                 throw_unsup_format!(
                     "bind: socket is already connected and binding a
                     connected socket is unsupported"
                 ),
-            SocketState::Bound(_) | SocketState::Listening(_) =>
+            SocketState::Bound(_) | SocketState::Listening(_) | SocketState::Datagram(_) =>
                 throw_unsup_format!(
                     "bind: socket is already bound and binding a socket \
                     multiple times is unsupported"
@@ -436,11 +501,21 @@ fn listen(&mut self, socket: &OpTy<'tcx>, backlog: &OpTy<'tcx>) -> InterpResult<
 
         assert!(this.machine.communicate(), "cannot have `Socket` with isolation enabled!");
 
+        if socket.socket_type == SocketType::Datagram {
+            // Only connection-based sockets can listen for connections.
+            return this.set_last_error_and_return_i32(LibcError("EOPNOTSUPP"));
+        }
+
         let mut state = socket.state.borrow_mut();
 
-        match *state {
-            SocketState::Bound(socket_addr) =>
-                match TcpListener::bind(socket_addr) {
+        match &*state {
+            SocketState::Bound(address) => {
+                let listener = this.create_host_socket(
+                    "listen",
+                    || Listener::bind(address),
+                    Listener::Replayed,
+                );
+                match listener {
                     Ok(listener) => {
                         *state = SocketState::Listening(listener);
                         drop(state);
@@ -449,7 +524,8 @@ fn listen(&mut self, socket: &OpTy<'tcx>, backlog: &OpTy<'tcx>) -> InterpResult<
                         this.machine.blocking_io.register(socket);
                     }
                     Err(e) => return this.set_last_error_and_return_i32(e),
-                },
+                }
+            }
             SocketState::Initial => {
                 throw_unsup_format!(
                     "listen: listening on a socket which isn't bound is unsupported"
@@ -461,6 +537,8 @@ fn listen(&mut self, socket: &OpTy<'tcx>, backlog: &OpTy<'tcx>) -> InterpResult<
             SocketState::Connecting(_) | SocketState::Connected(_) => {
                 throw_unsup_format!("listen: listening on a connected socket is unsupported")
             }
+            SocketState::Datagram(_) =>
+                unreachable!("stream sockets are never in the datagram state"),
         }
 
         interp_ok(Scalar::from_i32(0))
@@ -497,6 +575,11 @@ fn accept4(
 
         assert!(this.machine.communicate(), "cannot have `Socket` with isolation enabled!");
 
+        if socket.socket_type == SocketType::Datagram {
+            // Only connection-based sockets can accept connections.
+            return this.set_last_error_and_return(LibcError("EOPNOTSUPP"), dest);
+        }
+
         if !matches!(*socket.state.borrow(), SocketState::Listening(_)) {
             throw_unsup_format!(
                 "accept4: accepting incoming connections is only allowed when socket is listening"
@@ -591,6 +674,27 @@ fn connect(
 
         assert!(this.machine.communicate(), "cannot have `Socket` with isolation enabled!");
 
+        if socket.family != SocketFamily::of(&address) {
+            // Attempted to connect to an address from a family that doesn't match
+            // the family of the socket.
+            return this.set_last_error_and_return(LibcError("EAFNOSUPPORT"), dest);
+        }
+
+        if socket.socket_type == SocketType::Datagram {
+            // Connecting a datagram socket only sets the default destination of the
+            // datagrams and thus completes immediately.
+            if let Err(e) = this.autobind_datagram(&socket)? {
+                return this.set_last_error_and_return(e, dest);
+            }
+            let SocketState::Datagram(datagram) = &*socket.state.borrow() else {
+                unreachable!("datagram sockets are in the datagram state after autobinding")
+            };
+            return match this.host_op("connect", || datagram.connect(&address)) {
+                Ok(()) => this.write_scalar(Scalar::from_i32(0), dest),
+                Err(e) => this.set_last_error_and_return(e, dest),
+            };
+        }
+
         match &*socket.state.borrow() {
             SocketState::Initial => { /* fall-through to below */ }
             // The socket is already in a connecting state.
@@ -608,10 +712,10 @@ fn connect(
 
         // Mio returns a potentially unconnected stream.
         // We can be ensured that the connection is established when
-        // [`TcpStream::take_err`] and [`TcpStream::peer_addr`] both
+        // [`Stream::take_error`] and [`Stream::peer_addr`] both
         // don't return an error after receiving an [`Interest::WRITEABLE`]
         // event on the stream.
-        match TcpStream::connect(address) {
+        match this.create_host_socket("connect", || Stream::connect(&address), Stream::Replayed) {
             Ok(stream) => {
                 *socket.state.borrow_mut() = SocketState::Connecting(stream);
                 // Register the socket to the blocking I/O manager because
@@ -625,7 +729,7 @@ fn connect(
             // We have a non-blocking socket and thus don't want to block until
             // the connection is established.
 
-            // Since the [`TcpStream::connect`] function of mio hides the EINPROGRESS
+            // Since the `connect` functions of mio hide the EINPROGRESS
             // we just always return EINPROGRESS and check whether the connection succeeded
             // once we want to use the connected socket.
             this.set_last_error_and_return(LibcError("EINPROGRESS"), dest)
@@ -654,23 +758,40 @@ fn connect(
         }
     }
 
+    /// Implements both `send` and, when the destination `address` and its length
+    /// are provided, `sendto`.
     fn send(
         &mut self,
         socket: &OpTy<'tcx>,
         buffer: &OpTy<'tcx>,
         length: &OpTy<'tcx>,
         flags: &OpTy<'tcx>,
+        address: Option<(&OpTy<'tcx>, &OpTy<'tcx>)>,
         // Location where the output scalar is written to.
         dest: &MPlaceTy<'tcx>,
     ) -> InterpResult<'tcx> {
         let this = self.eval_context_mut();
 
+        let foreign_name = if address.is_some() { "sendto" } else { "send" };
+
         let socket = this.read_scalar(socket)?.to_i32()?;
         let buffer_ptr = this.read_pointer(buffer)?;
         let size_layout = this.libc_ty_layout("size_t");
         let length: usize =
             this.read_scalar(length)?.to_uint(size_layout.size)?.try_into().unwrap();
         let mut flags = this.read_scalar(flags)?.to_i32()?;
+        // A null destination address means that the data is sent to the
+        // address the socket is connected to, just like with `send`.
+        let target = if let Some((address, address_len)) = address
+            && this.read_pointer(address)? != Pointer::null()
+        {
+            match this.read_socket_address(address, address_len, foreign_name)? {
+                Ok(address) => Some(address),
+                Err(e) => return this.set_last_error_and_return(e, dest),
+            }
+        } else {
+            None
+        };
 
         // Get the file handle
         let Some(fd) = this.machine.fds.get(socket) else {
@@ -708,10 +829,28 @@ fn send(
 
         if flags != 0 {
             throw_unsup_format!(
-                "send: flag {flags:#x} is unsupported, only MSG_NOSIGNAL and MSG_DONTWAIT are allowed",
+                "{foreign_name}: flag {flags:#x} is unsupported, only MSG_NOSIGNAL and \
+                MSG_DONTWAIT are allowed",
             );
         }
 
+        if let Some(target) = &target {
+            if socket.socket_type == SocketType::Stream {
+                throw_unsup_format!(
+                    "sendto: providing a destination address is only supported for datagram sockets"
+                );
+            }
+            if socket.family != SocketFamily::of(target) {
+                // Attempted to send to an address from a family that doesn't match
+                // the family of the socket.
+                return this.set_last_error_and_return(LibcError("EAFNOSUPPORT"), dest);
+            }
+            // Sending from an unbound datagram socket binds it to an unspecified address.
+            if let Err(e) = this.autobind_datagram(&socket)? {
+                return this.set_last_error_and_return(e, dest);
+            }
+        }
+
         // If either the operation or the socket is non-blocking, we don't want
         // to wait until the connection is established.
         let should_wait = !is_op_non_block && !socket.is_non_block.get();
@@ -720,24 +859,31 @@ fn send(
         this.ensure_connected(
             socket.clone(),
             should_wait,
-            "send",
+            foreign_name,
             callback!(
                 @capture<'tcx> {
                     socket: FileDescriptionRef<Socket>,
                     flags: i32,
                     buffer_ptr: Pointer,
                     length: usize,
+                    target: Option<SocketAddress>,
                     is_op_non_block: bool,
                     dest: MPlaceTy<'tcx>,
                 } |this, result: Result<(), ()>| {
                     if result.is_err() {
-                        return this.set_last_error_and_return(LibcError("ENOTCONN"), &dest)
+                        // A datagram socket without a host socket has neither been
+                        // connected nor been given a destination address.
+                        let err = match socket.socket_type {
+                            SocketType::Stream => LibcError("ENOTCONN"),
+                            SocketType::Datagram => LibcError("EDESTADDRREQ"),
+                        };
+                        return this.set_last_error_and_return(err, &dest)
                     }
 
                     if is_op_non_block || socket.is_non_block.get() {
                         // We have a non-blocking operation or a non-blocking socket and
                         // thus don't want to block until we can send.
-                        match this.try_non_block_send(&socket, buffer_ptr, length)? {
+                        match this.try_non_block_send(&socket, buffer_ptr, length, target.as_ref())? {
                             Ok(size) => this.write_scalar(Scalar::from_target_isize(size.try_into().unwrap(), this), &dest),
                             Err(e) => this.set_last_error_and_return(e, &dest),
                         }
@@ -748,6 +894,7 @@ fn send(
                             socket,
                             buffer_ptr,
                             length,
+                            target,
                             callback!(@capture<'tcx> {
                                 dest: MPlaceTy<'tcx>
                             } |this, result: Result<usize, IoError>| {
@@ -763,23 +910,38 @@ fn send(
         )
     }
 
+    /// Implements both `recv` and, when the buffers for the source `address` and its
+    /// length are provided, `recvfrom`.
     fn recv(
         &mut self,
         socket: &OpTy<'tcx>,
         buffer: &OpTy<'tcx>,
         length: &OpTy<'tcx>,
         flags: &OpTy<'tcx>,
+        address: Option<(&OpTy<'tcx>, &OpTy<'tcx>)>,
         // Location where the output scalar is written to.
         dest: &MPlaceTy<'tcx>,
     ) -> InterpResult<'tcx> {
         let this = self.eval_context_mut();
 
+        let foreign_name = if address.is_some() { "recvfrom" } else { "recv" };
+
         let socket = this.read_scalar(socket)?.to_i32()?;
         let buffer_ptr = this.read_pointer(buffer)?;
         let size_layout = this.libc_ty_layout("size_t");
         let length: usize =
             this.read_scalar(length)?.to_uint(size_layout.size)?.try_into().unwrap();
         let mut flags = this.read_scalar(flags)?.to_i32()?;
+        // If the address pointer is a null pointer, the user isn't interested
+        // in the source address, just like with `recv`.
+        let source = match address {
+            Some((address, address_len)) => {
+                let address_ptr = this.read_pointer(address)?;
+                let address_len_ptr = this.read_pointer(address_len)?;
+                (address_ptr != Pointer::null()).then_some((address_ptr, address_len_ptr))
+            }
+            None => None,
+        };
 
         // Get the file handle
         let Some(fd) = this.machine.fds.get(socket) else {
@@ -828,11 +990,30 @@ fn recv(
 
         if flags != 0 {
             throw_unsup_format!(
-                "recv: flag {flags:#x} is unsupported, only MSG_PEEK, MSG_DONTWAIT \
+                "{foreign_name}: flag {flags:#x} is unsupported, only MSG_PEEK, MSG_DONTWAIT \
                 and MSG_CMSG_CLOEXEC are allowed",
             );
         }
 
+        if should_peek && socket.family == SocketFamily::Unix {
+            throw_unsup_format!(
+                "{foreign_name}: flag MSG_PEEK is unsupported for Unix-domain sockets"
+            );
+        }
+        if source.is_some() && socket.socket_type == SocketType::Stream {
+            throw_unsup_format!(
+                "recvfrom: getting the source address is only supported for datagram sockets"
+            );
+        }
+        if socket.socket_type == SocketType::Datagram
+            && matches!(*socket.state.borrow(), SocketState::Initial)
+        {
+            throw_unsup_format!(
+                "{foreign_name}: receiving from a datagram socket which is neither bound \
+                nor connected is unsupported"
+            );
+        }
+
         // If either the operation or the socket is non-blocking, we don't want
         // to wait until the connection is established.
         let should_wait = !is_op_non_block && !socket.is_non_block.get();
@@ -841,13 +1022,14 @@ fn recv(
         this.ensure_connected(
             socket.clone(),
             should_wait,
-            "recv",
+            foreign_name,
             callback!(
                 @capture<'tcx> {
                     socket: FileDescriptionRef<Socket>,
                     buffer_ptr: Pointer,
                     length: usize,
                     should_peek: bool,
+                    source: Option<(Pointer, Pointer)>,
                     is_op_non_block: bool,
                     dest: MPlaceTy<'tcx>,
                 } |this, result: Result<(), ()>| {
@@ -858,7 +1040,7 @@ fn recv(
                     if is_op_non_block || socket.is_non_block.get() {
                         // We have a non-blocking operation or a non-blocking socket and
                         // thus don't want to block until we can receive.
-                        match this.try_non_block_recv(&socket, buffer_ptr, length, should_peek)? {
+                        match this.try_non_block_recv(&socket, buffer_ptr, length, should_peek, source)? {
                             Ok(size) => this.write_scalar(Scalar::from_target_isize(size.try_into().unwrap(), this), &dest),
                             Err(e) => this.set_last_error_and_return(e, &dest),
                         }
@@ -870,6 +1052,7 @@ fn recv(
                             buffer_ptr,
                             length,
                             should_peek,
+                            source,
                             callback!(@capture<'tcx> {
                                 dest: MPlaceTy<'tcx>
                             } |this, result: Result<usize, IoError>| {
@@ -1011,10 +1194,15 @@ fn getsockopt(
                 // If they return `Ok(None)`, then we fall back to the stored `socket.error`.
                 let error = match &*socket.state.borrow() {
                     SocketState::Initial | SocketState::Bound(_) => socket.error.take(),
-                    SocketState::Listening(listener) =>
-                        listener.take_error().unwrap_or(socket.error.take()),
-                    SocketState::Connecting(stream) | SocketState::Connected(stream) =>
-                        stream.take_error().unwrap_or(socket.error.take()),
+                    SocketState::Listening(listener) => this
+                        .host_op("take_error", || listener.take_error())
+                        .unwrap_or(socket.error.take()),
+                    SocketState::Connecting(stream) | SocketState::Connected(stream) => this
+                        .host_op("take_error", || stream.take_error())
+                        .unwrap_or(socket.error.take()),
+                    SocketState::Datagram(datagram) => this
+                        .host_op("take_error", || datagram.take_error())
+                        .unwrap_or(socket.error.take()),
                 };
                 // Clear our own stored error -- it was either `take`n above or it is outdated.
                 socket.error.replace(None);
@@ -1040,6 +1228,11 @@ fn getsockopt(
                 );
             }
         } else if level == this.eval_libc_i32("IPPROTO_IP") {
+            if socket.family == SocketFamily::Unix {
+                // Unix-domain sockets don't have any options on the IP level.
+                return this.set_last_error_and_return_i32(LibcError("EOPNOTSUPP"));
+            }
+
             let opt_ip_ttl = this.eval_libc_i32("IP_TTL");
 
             if option_name == opt_ip_ttl {
@@ -1047,11 +1240,12 @@ fn getsockopt(
                     SocketState::Initial | SocketState::Bound(_) =>
                         throw_unsup_format!(
                             "getsockopt: reading option IP_TTL on level IPPROTO_IP is only supported \
-                            on connected and listening sockets"
+                            on connected, listening and bound datagram sockets"
                         ),
-                    SocketState::Listening(listener) => listener.ttl(),
+                    SocketState::Listening(listener) => this.host_op("ttl", || listener.ttl()),
                     SocketState::Connecting(stream) | SocketState::Connected(stream) =>
-                        stream.ttl(),
+                        this.host_op("ttl", || stream.ttl()),
+                    SocketState::Datagram(datagram) => this.host_op("ttl", || datagram.ttl()),
                 };
 
                 let ttl = match ttl {
@@ -1128,7 +1322,9 @@ fn getsockname(
 
         let address = match &*state {
             SocketState::Bound(address) => {
-                if address.port() == 0 {
+                if let SocketAddress::Inet(inet_address) = address
+                    && inet_address.port() == 0
+                {
                     // The socket is bound to a zero-port which means it gets assigned a random
                     // port. Since we don't yet have an underlying socket, we don't know what this
                     // random port will be and thus this is unsupported.
@@ -1138,10 +1334,10 @@ fn getsockname(
                     )
                 }
 
-                *address
+                address.clone()
             }
             SocketState::Listening(listener) =>
-                match listener.local_addr() {
+                match this.host_op("local_addr", || listener.local_addr()) {
                     Ok(address) => address,
                     Err(e) => return this.set_last_error_and_return_i32(e),
                 },
@@ -1158,14 +1354,22 @@ fn getsockname(
                         this.emit_diagnostic(NonHaltingDiagnostic::ConnectingSocketGetsockname);
                     }
                 }
-                match stream.local_addr() {
+                match this.host_op("local_addr", || stream.local_addr()) {
                     Ok(address) => address,
                     Err(e) => return this.set_last_error_and_return_i32(e),
                 }
             }
+            SocketState::Datagram(datagram) =>
+                match this.host_op("local_addr", || datagram.local_addr()) {
+                    Ok(address) => address,
+                    Err(e) => return this.set_last_error_and_return_i32(e),
+                },
             // For non-bound sockets the POSIX manual says the returned address is unspecified.
             // Often this is 0.0.0.0:0 and thus we set it to this value.
-            SocketState::Initial => SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0)),
+            SocketState::Initial if socket.family != SocketFamily::Unix =>
+                SocketAddress::Inet(SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0))),
+            // Unix-domain sockets which aren't bound are unnamed.
+            SocketState::Initial => SocketAddress::Unix(None),
         };
 
         this.write_socket_address(&address, address_ptr, address_len_ptr, "getsockname")
@@ -1200,7 +1404,7 @@ fn getpeername(
 
         let dest = dest.clone();
 
-        // It's only safe to call [`TcpStream::peer_addr`] after the socket is connected since
+        // It's only safe to call [`Stream::peer_addr`] after the socket is connected since
         // UNIX targets should return ENOTCONN when the connection is not yet established.
         this.ensure_connected(
             socket.clone(),
@@ -1217,11 +1421,15 @@ fn getpeername(
                         return this.set_last_error_and_return(LibcError("ENOTCONN"), &dest)
                     };
 
-                    let SocketState::Connected(stream) = &*socket.state.borrow() else {
-                        unreachable!()
+                    let address = match &*socket.state.borrow() {
+                        SocketState::Connected(stream) =>
+                            this.host_op("peer_addr", || stream.peer_addr()),
+                        // The host returns ENOTCONN if the datagram socket isn't connected.
+                        SocketState::Datagram(datagram) =>
+                            this.host_op("peer_addr", || datagram.peer_addr()),
+                        _ => unreachable!(),
                     };
-
-                    let address = match stream.peer_addr() {
+                    let address = match address {
                         Ok(address) => address,
                         Err(e) => return this.set_last_error_and_return(e, &dest),
                     };
@@ -1256,6 +1464,10 @@ fn shutdown(&mut self, socket: &OpTy<'tcx>, how: &OpTy<'tcx>) -> InterpResult<'t
 
         assert!(this.machine.communicate(), "cannot have `Socket` with isolation enabled!");
 
+        if socket.socket_type == SocketType::Datagram {
+            throw_unsup_format!("shutdown: shutting down datagram sockets is unsupported");
+        }
+
         let state = socket.state.borrow();
 
         let (SocketState::Connecting(stream) | SocketState::Connected(stream)) = &*state else {
@@ -1274,7 +1486,7 @@ fn shutdown(&mut self, socket: &OpTy<'tcx>, how: &OpTy<'tcx>) -> InterpResult<'t
             _ => return this.set_last_error_and_return_i32(LibcError("EINVAL")),
         };
 
-        match stream.shutdown(how) {
+        match this.host_op("shutdown", || stream.shutdown(how)) {
             Ok(_) => interp_ok(Scalar::from_i32(0)),
             Err(e) => this.set_last_error_and_return_i32(e),
         }
@@ -1283,6 +1495,18 @@ fn shutdown(&mut self, socket: &OpTy<'tcx>, how: &OpTy<'tcx>) -> InterpResult<'t
 
 impl<'tcx> EvalContextPrivExt<'tcx> for crate::MiriInterpCx<'tcx> {}
 trait EvalContextPrivExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
+    /// Create a host socket using `live`. When replaying the host log, `stand_in` is used instead.
+    fn create_host_socket<S>(
+        &self,
+        what: &'static str,
+        live: impl FnOnce() -> io::Result<S>,
+        stand_in: S,
+    ) -> io::Result<S> {
+        let this = self.eval_context_ref();
+        this.create_host_object(what, || live().map(|socket| (socket, ())), || stand_in)
+            .map(|(socket, ())| socket)
+    }
+
     /// Block the thread until there's an incoming connection or an error occurred.
     ///
     /// This recursively calls itself should the operation still block for some reason.
@@ -1353,7 +1577,8 @@ fn try_non_block_accept(
             )
         };
 
-        let (stream, addr) = match listener.accept() {
+        let accepted = this.create_host_object("accept", || listener.accept(), || Stream::Replayed);
+        let (stream, addr) = match accepted {
             Ok(peer) => peer,
             Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
                 // We know that the source is not readable so we need to update its readiness.
@@ -1365,11 +1590,6 @@ fn try_non_block_accept(
             Err(e) => return interp_ok(Err(IoError::HostError(e))),
         };
 
-        let family = match addr {
-            SocketAddr::V4(_) => SocketFamily::IPv4,
-            SocketAddr::V6(_) => SocketFamily::IPv6,
-        };
-
         if address_ptr != Pointer::null() {
             // We only attempt a write if the address pointer is not a null pointer.
             // If the address pointer is a null pointer the user isn't interested in the
@@ -1378,7 +1598,9 @@ fn try_non_block_accept(
         }
 
         let fd = this.machine.fds.new_ref(Socket {
-            family,
+            // The connection is of the same family as the listening socket.
+            family: socket.family,
+            socket_type: SocketType::Stream,
             state: RefCell::new(SocketState::Connected(stream)),
             is_non_block: Cell::new(is_client_sock_nonblock),
             io_readiness: RefCell::new(BlockingIoSourceReadiness::empty()),
@@ -1397,12 +1619,13 @@ fn try_non_block_accept(
     /// This recursively calls itself should the operation still block for some reason.
     ///
     /// **Note**: This function is only safe to call when having previously ensured
-    /// that the socket is in [`SocketState::Connected`].
+    /// that the socket is in [`SocketState::Connected`] or [`SocketState::Datagram`].
     fn block_for_send(
         &mut self,
         socket: FileDescriptionRef<Socket>,
         buffer_ptr: Pointer,
         length: usize,
+        target: Option<SocketAddress>,
         finish: DynMachineCallback<'tcx, Result<usize, IoError>>,
     ) -> InterpResult<'tcx> {
         let this = self.eval_context_mut();
@@ -1414,6 +1637,7 @@ fn block_for_send(
                 socket: FileDescriptionRef<Socket>,
                 buffer_ptr: Pointer,
                 length: usize,
+                target: Option<SocketAddress>,
                 finish: DynMachineCallback<'tcx, Result<usize, IoError>>,
             } |this, kind: UnblockKind| {
                 assert_eq!(kind, UnblockKind::Ready);
@@ -1421,10 +1645,10 @@ fn block_for_send(
                 // Remove the blocking I/O interest for unblocking this thread.
                 this.machine.blocking_io.remove_blocked_thread(socket.id(), this.machine.threads.active_thread());
 
-                match this.try_non_block_send(&socket, buffer_ptr, length)? {
+                match this.try_non_block_send(&socket, buffer_ptr, length, target.as_ref())? {
                     Err(IoError::HostError(e)) if e.kind() == io::ErrorKind::WouldBlock => {
                         // We need to block the thread again as it would still block.
-                        this.block_for_send(socket, buffer_ptr, length, finish)
+                        this.block_for_send(socket, buffer_ptr, length, target, finish)
                     },
                     result => finish.call(this, result)
                 }
@@ -1433,28 +1657,47 @@ fn block_for_send(
     }
 
     /// Attempt to send bytes into the connected socket in a non-blocking manner.
+    /// Datagram sockets send the bytes as a single datagram to `target`, or to the
+    /// address they are connected to if there is no `target`.
     ///
     /// **Note**: This function is only safe to call when having previously ensured
-    /// that the socket is in [`SocketState::Connected`].
+    /// that the socket is in [`SocketState::Connected`] or [`SocketState::Datagram`].
     fn try_non_block_send(
         &mut self,
         socket: &FileDescriptionRef<Socket>,
         buffer_ptr: Pointer,
         length: usize,
+        target: Option<&SocketAddress>,
     ) -> InterpResult<'tcx, Result<usize, IoError>> {
         let this = self.eval_context_mut();
 
-        let SocketState::Connected(stream) = &mut *socket.state.borrow_mut() else {
-            panic!("try_non_block_send must only be called when the socket is connected")
-        };
-
         // This is a *non-blocking* write.
-        let result = this.write_to_host(stream, length, buffer_ptr)?;
+        let result = match &mut *socket.state.borrow_mut() {
+            SocketState::Connected(stream) =>
+                this.write_to_host_logged(stream, length, buffer_ptr)?,
+            SocketState::Datagram(datagram) => {
+                let bytes =
+                    this.read_bytes_ptr_strip_provenance(buffer_ptr, Size::from_bytes(length))?;
+                let send = |bytes: &[u8]| match target {
+                    Some(target) => datagram.send_to(bytes, target),
+                    None => datagram.send(bytes),
+                };
+                let result = match this.machine.host_log.as_deref() {
+                    Some(host_log) => host_log.write(bytes, send),
+                    None => send(bytes),
+                };
+                result.map_err(IoError::HostError)
+            }
+            _ => panic!("try_non_block_send must only be called when the socket is connected"),
+        };
         // FIXME: When the host does a short write, we should emit an epoll edge -- at least for targets for which tokio assumes no short writes:
         // <https://github.com/tokio-rs/tokio/blob/6c03e03898d71eca976ee1ad8481cf112ae722ba/tokio/src/io/poll_evented.rs#L240>
         match result {
+            // Datagram sockets which aren't connected legitimately return ENOTCONN.
             Err(IoError::HostError(e))
-                if matches!(e.kind(), io::ErrorKind::NotConnected | io::ErrorKind::WouldBlock) =>
+                if e.kind() == io::ErrorKind::WouldBlock
+                    || (e.kind() == io::ErrorKind::NotConnected
+                        && socket.socket_type == SocketType::Stream) =>
             {
                 // We know that the source is not writable so we need to update it's readiness.
                 socket.io_readiness.borrow_mut().writable = false;
@@ -1474,13 +1717,14 @@ fn try_non_block_send(
     /// This recursively calls itself should the operation still block for some reason.
     ///
     /// **Note**: This function is only safe to call when having previously ensured
-    /// that the socket is in [`SocketState::Connected`].
+    /// that the socket is in [`SocketState::Connected`] or [`SocketState::Datagram`].
     fn block_for_recv(
         &mut self,
         socket: FileDescriptionRef<Socket>,
         buffer_ptr: Pointer,
         length: usize,
         should_peek: bool,
+        source: Option<(Pointer, Pointer)>,
         finish: DynMachineCallback<'tcx, Result<usize, IoError>>,
     ) -> InterpResult<'tcx> {
         let this = self.eval_context_mut();
@@ -1493,6 +1737,7 @@ fn block_for_recv(
                 buffer_ptr: Pointer,
                 length: usize,
                 should_peek: bool,
+                source: Option<(Pointer, Pointer)>,
                 finish: DynMachineCallback<'tcx, Result<usize, IoError>>,
             } |this, kind: UnblockKind| {
                 assert_eq!(kind, UnblockKind::Ready);
@@ -1500,10 +1745,10 @@ fn block_for_recv(
                 // Remove the blocking I/O interest for unblocking this thread.
                 this.machine.blocking_io.remove_blocked_thread(socket.id(), this.machine.threads.active_thread());
 
-                match this.try_non_block_recv(&socket, buffer_ptr, length, should_peek)? {
+                match this.try_non_block_recv(&socket, buffer_ptr, length, should_peek, source)? {
                     Err(IoError::HostError(e)) if e.kind() == io::ErrorKind::WouldBlock => {
                         // We need to block the thread again as it would still block.
-                        this.block_for_recv(socket, buffer_ptr, length, should_peek, finish)
+                        this.block_for_recv(socket, buffer_ptr, length, should_peek, source, finish)
                     },
                     result => finish.call(this, result)
                 }
@@ -1512,35 +1757,71 @@ fn block_for_recv(
     }
 
     /// Attempt to receive bytes from the connected socket in a non-blocking manner.
+    /// Datagram sockets receive a single datagram, whose source address is written
+    /// into the `source` address and address length pointers if provided.
     ///
     /// **Note**: This function is only safe to call when having previously ensured
-    /// that the socket is in [`SocketState::Connected`].
+    /// that the socket is in [`SocketState::Connected`] or [`SocketState::Datagram`].
     fn try_non_block_recv(
         &mut self,
         socket: &FileDescriptionRef<Socket>,
         buffer_ptr: Pointer,
         length: usize,
         should_peek: bool,
+        source: Option<(Pointer, Pointer)>,
     ) -> InterpResult<'tcx, Result<usize, IoError>> {
         let this = self.eval_context_mut();
 
-        let SocketState::Connected(stream) = &mut *socket.state.borrow_mut() else {
-            panic!("try_non_block_recv must only be called when the socket is connected")
+        let mut source_address = None;
+        // This is a *non-blocking* read/peek.
+        let result = match &mut *socket.state.borrow_mut() {
+            SocketState::Connected(stream) =>
+                this.read_from_host_logged(
+                    |buf| {
+                        if should_peek { stream.peek(buf) } else { stream.read(buf) }
+                    },
+                    length,
+                    buffer_ptr,
+                )?,
+            SocketState::Datagram(datagram) => {
+                let host_log = this.machine.host_log.clone();
+                this.read_from_host(
+                    |buf| {
+                        let recv_from = |buf: &mut [u8]| {
+                            if should_peek {
+                                datagram.peek_from(buf)
+                            } else {
+                                datagram.recv_from(buf)
+                            }
+                        };
+                        let (size, address) = match &host_log {
+                            Some(host_log) => host_log.recv_from(buf, recv_from)?,
+                            None => recv_from(buf)?,
+                        };
+                        source_address = Some(address);
+                        Ok(size)
+                    },
+                    length,
+                    buffer_ptr,
+                )?
+            }
+            _ => panic!("try_non_block_recv must only be called when the socket is connected"),
         };
 
-        // This is a *non-blocking* read/peek.
-        let result = this.read_from_host(
-            |buf| {
-                if should_peek { stream.peek(buf) } else { stream.read(buf) }
-            },
-            length,
-            buffer_ptr,
-        )?;
+        if let Some((address_ptr, address_len_ptr)) = source
+            && let Some(address) = source_address
+        {
+            this.write_socket_address(&address, address_ptr, address_len_ptr, "recvfrom")?;
+        }
+
         // FIXME: When the host does a short read, we should emit an epoll edge -- at least for targets for which tokio assumes no short reads:
         // <https://github.com/tokio-rs/tokio/blob/6c03e03898d71eca976ee1ad8481cf112ae722ba/tokio/src/io/poll_evented.rs#L182>
         match result {
+            // Datagram sockets which aren't connected legitimately return ENOTCONN.
             Err(IoError::HostError(e))
-                if matches!(e.kind(), io::ErrorKind::NotConnected | io::ErrorKind::WouldBlock) =>
+                if e.kind() == io::ErrorKind::WouldBlock
+                    || (e.kind() == io::ErrorKind::NotConnected
+                        && socket.socket_type == SocketType::Stream) =>
             {
                 // We know that the source is not readable so we need to update it's readiness.
                 socket.io_readiness.borrow_mut().readable = false;
@@ -1558,8 +1839,10 @@ fn try_non_block_recv(
     // [`SocketState::Connected`] or an error occurred.
     /// If the socket is currently neither in the [`SocketState::Connecting`] nor
     /// the [`SocketState::Connecting`] state, an ENOTCONN error is returned.
+    /// Datagram sockets don't establish connections, so those in the
+    /// [`SocketState::Datagram`] state count as connected.
     /// When the callback function is called with `Ok(_)`, then we're guaranteed
-    /// that the socket is in the [`SocketState::Connected`] state.
+    /// that the socket is in the [`SocketState::Connected`] or [`SocketState::Datagram`] state.
     ///
     /// This function can optionally also block until either an error occurred or
     /// the socket reached the [`SocketState::Connected`] state.
@@ -1575,7 +1858,7 @@ fn ensure_connected(
         let state = socket.state.borrow();
         match &*state {
             SocketState::Connecting(_) => { /* fall-through to below */ }
-            SocketState::Connected(_) => {
+            SocketState::Connected(_) | SocketState::Datagram(_) => {
                 drop(state);
                 return action.call(this, Ok(()));
             }
@@ -1641,7 +1924,7 @@ fn ensure_connected(
                     };
 
                     // Manually check whether there were any errors since calling `connect`.
-                    if let Ok(Some(err)) = stream.take_error() {
+                    if let Ok(Some(err)) = this.host_op("take_error", || stream.take_error()) {
                         // There was an error during connecting and thus we
                         // return ENOTCONN. It's the program's responsibility
                         // to read SO_ERROR itself.
@@ -1663,7 +1946,7 @@ fn ensure_connected(
                     // peer address of the socket (following the advice given by mio):
                     // <https://docs.rs/mio/latest/mio/net/struct.TcpStream.html#notes>
 
-                    match stream.peer_addr() {
+                    match this.host_op("peer_addr", || stream.peer_addr()) {
                         Ok(_) => { /* fall-through to below */},
                         Err(e) if matches!(e.kind(), io::ErrorKind::NotConnected | io::ErrorKind::InProgress) => {
                             // We received a spurious wakeup from the OS. This should be considered an OS bug:
@@ -1691,6 +1974,55 @@ fn ensure_connected(
             ),
         )
     }
+
+    /// Bind the datagram socket to an unspecified address, unless it is already backed
+    /// by a host socket. This is what happens when connecting or sending from a datagram
+    /// socket which isn't bound.
+    fn autobind_datagram(
+        &mut self,
+        socket: &FileDescriptionRef<Socket>,
+    ) -> InterpResult<'tcx, Result<(), IoError>> {
+        let this = self.eval_context_mut();
+
+        let mut state = socket.state.borrow_mut();
+        if !matches!(*state, SocketState::Initial) {
+            return interp_ok(Ok(()));
+        }
+
+        // The host picks the port for internet sockets, and Unix-domain sockets stay unnamed.
+        let bind = || match socket.family {
+            SocketFamily::IPv4 =>
+                Datagram::bind(&SocketAddress::Inet(SocketAddr::V4(SocketAddrV4::new(
+                    Ipv4Addr::UNSPECIFIED,
+                    0,
+                )))),
+            SocketFamily::IPv6 =>
+                Datagram::bind(&SocketAddress::Inet(SocketAddr::V6(SocketAddrV6::new(
+                    Ipv6Addr::UNSPECIFIED,
+                    0,
+                    0,
+                    0,
+                )))),
+            #[cfg(unix)]
+            SocketFamily::Unix => Datagram::unbound_unix(),
+            #[cfg(not(unix))]
+            SocketFamily::Unix =>
+                unreachable!("Unix-domain sockets are only created on Unix hosts"),
+        };
+        let datagram = this.create_host_socket("autobind", bind, Datagram::Replayed);
+
+        match datagram {
+            Ok(datagram) => {
+                *state = SocketState::Datagram(datagram);
+                drop(state);
+                // Register the socket to the blocking I/O manager because
+                // we now have an associated host socket.
+                this.machine.blocking_io.register(socket.clone());
+                interp_ok(Ok(()))
+            }
+            Err(e) => interp_ok(Err(IoError::HostError(e))),
+        }
+    }
 }
 
 impl VisitProvenance for FileDescriptionRef<Socket> {
@@ -1699,15 +2031,25 @@ impl VisitProvenance for FileDescriptionRef<Socket> {
     fn visit_provenance(&self, _visit: &mut VisitWith<'_>) {}
 }
 
+impl VisitProvenance for SocketAddress {
+    // A socket address is a host value and thus doesn't contain any
+    // references to machine memory.
+    fn visit_provenance(&self, _visit: &mut VisitWith<'_>) {}
+}
+
 impl SourceFileDescription for Socket {
     fn with_source(&self, f: &mut dyn FnMut(&mut dyn Source) -> io::Result<()>) -> io::Result<()> {
         let mut state = self.state.borrow_mut();
-        match &mut *state {
-            SocketState::Listening(listener) => f(listener),
-            SocketState::Connecting(stream) | SocketState::Connected(stream) => f(stream),
+        let source = match &mut *state {
+            SocketState::Listening(listener) => listener.source(),
+            SocketState::Connecting(stream) | SocketState::Connected(stream) => stream.source(),
+            SocketState::Datagram(datagram) => datagram.source(),
             // We never try adding a socket which is not backed by a real socket to the poll registry.
             _ => unreachable!(),
-        }
+        };
+        // Stand-ins for host sockets while replaying the host log are not polled: their
+        // readiness comes from the log.
+        source.map_or(Ok(()), |source| f(source))
     }
 
     fn get_readiness_mut(&self) -> RefMut<'_, BlockingIoSourceReadiness> {
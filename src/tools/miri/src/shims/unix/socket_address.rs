@@ -1,11 +1,22 @@
 use std::iter;
 use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6, ToSocketAddrs};
+use std::path::PathBuf;
 
 use rustc_abi::Size;
 use rustc_target::spec::Env;
 
+use crate::shims::os_str::bytes_to_os_str;
 use crate::*;
 
+/// A socket address of one of the supported address families.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SocketAddress {
+    /// An IPv4 or IPv6 address.
+    Inet(SocketAddr),
+    /// The path of a Unix-domain socket, or [`None`] for an unnamed socket.
+    Unix(Option<PathBuf>),
+}
+
 impl<'tcx> EvalContextExt<'tcx> for crate::MiriInterpCx<'tcx> {}
 pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
     fn getaddrinfo(
@@ -123,7 +134,7 @@ fn freeaddrinfo(&mut self, res: &OpTy<'tcx>) -> InterpResult<'tcx> {
         this.free_address_infos(res_ptr)
     }
 
-    /// Attempt to turn an address and length operand into a standard library socket address.
+    /// Attempt to turn an address and length operand into a [`SocketAddress`].
     ///
     /// Returns an IO error should the address length not match the address family length.
     fn read_socket_address(
@@ -131,7 +142,7 @@ fn read_socket_address(
         address: &OpTy<'tcx>,
         address_len: &OpTy<'tcx>,
         foreign_name: &'static str,
-    ) -> InterpResult<'tcx, Result<SocketAddr, IoError>> {
+    ) -> InterpResult<'tcx, Result<SocketAddress, IoError>> {
         let this = self.eval_context_ref();
 
         let socklen_layout = this.libc_ty_layout("socklen_t");
@@ -142,13 +153,16 @@ fn read_socket_address(
 
         // Initially, treat address as generic sockaddr just to extract the family field.
         let sockaddr_layout = this.libc_ty_layout("sockaddr");
-        if address_len < sockaddr_layout.size.bytes() {
-            // Address length should be at least as big as the generic sockaddr
-            return interp_ok(Err(LibcError("EINVAL")));
-        }
         let address = this.deref_pointer_as(address, sockaddr_layout)?;
 
         let family_field = this.project_field_named(&address, "sa_family")?;
+        // Unix-domain addresses can be shorter than the generic sockaddr, so we only
+        // require the address to be long enough to contain the family field.
+        let family_end =
+            family_field.ptr().addr() - address.ptr().addr() + family_field.layout.size;
+        if address_len < family_end.bytes() {
+            return interp_ok(Err(LibcError("EINVAL")));
+        }
         let family_layout = this.libc_ty_layout("sa_family_t");
         let family = this.read_scalar(&family_field)?.to_int(family_layout.size)?;
 
@@ -179,7 +193,10 @@ fn read_socket_address(
                 .unwrap();
             let addr_bits = u32::from_be_bytes(addr_bytes);
 
-            SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::from_bits(addr_bits), port))
+            SocketAddress::Inet(SocketAddr::V4(SocketAddrV4::new(
+                Ipv4Addr::from_bits(addr_bits),
+                port,
+            )))
         } else if family == this.eval_libc_i32("AF_INET6").into() {
             let sockaddr_in6_layout = this.libc_ty_layout("sockaddr_in6");
             if address_len != sockaddr_in6_layout.size.bytes() {
@@ -218,25 +235,56 @@ fn read_socket_address(
             // and not in network byte order.
             let scope_id = this.read_scalar(&scope_id_field)?.to_u32()?;
 
-            SocketAddr::V6(SocketAddrV6::new(
+            SocketAddress::Inet(SocketAddr::V6(SocketAddrV6::new(
                 Ipv6Addr::from_bits(addr_bits),
                 port,
                 flowinfo,
                 scope_id,
-            ))
+            )))
+        } else if family == this.eval_libc_i32("AF_UNIX").into() {
+            let sockaddr_un_layout = this.libc_ty_layout("sockaddr_un");
+            if address_len > sockaddr_un_layout.size.bytes() {
+                // Address length should be at most the length of a Unix-domain address.
+                return interp_ok(Err(LibcError("EINVAL")));
+            }
+            // We cannot transmute since the `sockaddr_un` layout is bigger than the `sockaddr` layout.
+            let address = address.offset(Size::ZERO, sockaddr_un_layout, this)?;
+
+            let sun_path_field = this.project_field_named(&address, "sun_path")?;
+            // The path consists of the remaining bytes of the address, up to the first null byte.
+            let path_offset = sun_path_field.ptr().addr() - address.ptr().addr();
+            let path_len = Size::from_bytes(address_len) - path_offset;
+            let path_bytes =
+                this.read_bytes_ptr_strip_provenance(sun_path_field.ptr(), path_len)?;
+            match path_bytes.first() {
+                None =>
+                    throw_unsup_format!(
+                        "{foreign_name}: unnamed Unix-domain socket addresses are unsupported"
+                    ),
+                Some(0) =>
+                    throw_unsup_format!(
+                        "{foreign_name}: abstract Unix-domain socket addresses are unsupported"
+                    ),
+                Some(_) => {}
+            }
+            let path_bytes = path_bytes.split(|&byte| byte == 0).next().unwrap();
+
+            // Unix-domain sockets are only supported on Unix hosts, where target paths
+            // are also host paths.
+            SocketAddress::Unix(Some(PathBuf::from(bytes_to_os_str(path_bytes)?)))
         } else {
             // Socket of other types shouldn't be created in a first place and
             // thus also no address family of another type should be supported.
             throw_unsup_format!(
                 "{foreign_name}: address family {family:#x} is unsupported, \
-                only AF_INET and AF_INET6 are allowed"
+                only AF_INET, AF_INET6 and AF_UNIX are allowed"
             );
         };
 
         interp_ok(Ok(socket_addr))
     }
 
-    /// Attempt to write a standard library socket address into a pointer.
+    /// Attempt to write a [`SocketAddress`] into a pointer.
     ///
     /// The `address_len_ptr` parameter serves both as input and output parameter.
     /// On input, it points to the size of the buffer `address_ptr` points to, and
@@ -247,7 +295,7 @@ fn read_socket_address(
     /// overflow the buffer.
     fn write_socket_address(
         &mut self,
-        address: &SocketAddr,
+        address: &SocketAddress,
         address_ptr: Pointer,
         address_len_ptr: Pointer,
         foreign_name: &'static str,
@@ -272,8 +320,9 @@ fn write_socket_address(
             .try_into()
             .unwrap();
 
-        let address_buffer = match address {
-            SocketAddr::V4(address) => {
+        // The buffer containing the address, and the length of the address in that buffer.
+        let (address_buffer, address_len) = match address {
+            SocketAddress::Inet(SocketAddr::V4(address)) => {
                 // IPv4 address bytes; already stored in network byte order.
                 let address_bytes = address.ip().octets();
                 // Port needs to be manually turned into network byte order.
@@ -310,9 +359,10 @@ fn write_socket_address(
                 let s_addr_field = this.project_field_named(&sin_addr_field, "s_addr")?;
                 this.write_bytes_ptr(s_addr_field.ptr(), address_bytes)?;
 
-                address_buffer
+                let address_len = address_buffer.layout.size.bytes();
+                (address_buffer, address_len)
             }
-            SocketAddr::V6(address) => {
+            SocketAddress::Inet(SocketAddr::V6(address)) => {
                 // IPv6 address bytes; already stored in network byte order.
                 let address_bytes = address.ip().octets();
                 // Port needs to be manually turned into network byte order.
@@ -363,7 +413,53 @@ fn write_socket_address(
                 let s6_addr_field = this.project_field_named(&sin6_addr_field, "s6_addr")?;
                 this.write_bytes_ptr(s6_addr_field.ptr(), address_bytes)?;
 
-                address_buffer
+                let address_len = address_buffer.layout.size.bytes();
+                (address_buffer, address_len)
+            }
+            SocketAddress::Unix(path) => {
+                let sockaddr_un_layout = this.libc_ty_layout("sockaddr_un");
+                // Allocate new buffer on the stack with the `sockaddr_un` layout.
+                // We need a temporary buffer as `address_ptr` might not point to a large enough
+                // buffer, in which case we have to truncate.
+                let address_buffer = this.allocate(sockaddr_un_layout, MemoryKind::Stack)?;
+                // Zero the whole buffer as some libc targets have additional fields which we fill
+                // with zero bytes (just like the standard library does it).
+                this.write_bytes_ptr(
+                    address_buffer.ptr(),
+                    iter::repeat_n(0, address_buffer.layout.size.bytes_usize()),
+                )?;
+
+                let sun_family_field = this.project_field_named(&address_buffer, "sun_family")?;
+                // See the `AF_INET` case above for why we cannot write the scalar directly.
+                let af_unix = this.eval_libc("AF_UNIX");
+                let address_family =
+                    Scalar::from_int(af_unix.to_int(af_unix.size())?, sun_family_field.layout.size);
+                this.write_scalar(address_family, &sun_family_field)?;
+
+                let sun_path_field = this.project_field_named(&address_buffer, "sun_path")?;
+                let path_offset =
+                    (sun_path_field.ptr().addr() - address_buffer.ptr().addr()).bytes();
+                // The address of an unnamed socket only consists of the family. Otherwise, the
+                // address contains the null-terminated path.
+                let address_len = match path {
+                    None => path_offset,
+                    Some(path) => {
+                        let (written, path_len) = this.write_os_str_to_c_str(
+                            path.as_os_str(),
+                            sun_path_field.ptr(),
+                            sun_path_field.layout.size.bytes(),
+                        )?;
+                        if !written {
+                            throw_unsup_format!(
+                                "{foreign_name}: the Unix-domain socket path {} is too long \
+                                for the target",
+                                path.display()
+                            );
+                        }
+                        path_offset + path_len
+                    }
+                };
+                (address_buffer, address_len)
             }
         };
 
@@ -372,7 +468,7 @@ fn write_socket_address(
             address_buffer.ptr(),
             address_ptr,
             // Truncate the address to fit the provided buffer.
-            address_buffer.layout.size.min(Size::from_bytes(address_buffer_len)),
+            Size::from_bytes(address_len.min(address_buffer_len)),
             // The buffers are guaranteed to not overlap since the `address_buffer`
             // was just newly allocated on the stack.
             true,
@@ -380,9 +476,8 @@ fn write_socket_address(
         // Deallocate the address buffer as it was only needed to construct the address and
         // copy it into the buffer pointed to by `address_ptr`.
         this.deallocate_ptr(address_buffer.ptr(), None, MemoryKind::Stack)?;
-        // Size of the non-truncated address.
-        let address_len = address_buffer.layout.size.bytes();
 
+        // On output, the length pointer contains the size of the non-truncated address.
         this.write_scalar(
             Scalar::from_uint(address_len, socklen_layout.size),
             &address_buffer_len_place,
@@ -453,7 +548,7 @@ fn allocate_address_infos(
             iter::repeat_n(0, sockaddr_mplace.layout.size.bytes_usize()),
         )?;
         this.write_socket_address(
-            &address,
+            &SocketAddress::Inet(address),
             sockaddr_mplace.ptr(),
             addrlen_mplace.ptr(),
             "getaddrinfo",
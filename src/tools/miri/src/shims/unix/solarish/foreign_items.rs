@@ -141,6 +141,24 @@ fn emulate_foreign_item_inner(
                 )?;
                 this.connect(socket, address, address_len, dest)?;
             }
+            "__xnet_sendto" => {
+                let [socket, buffer, length, flags, address, address_len] = this.check_shim_sig(
+                    shim_sig!(
+                        extern "C" fn(
+                            i32,
+                            *const _,
+                            libc::size_t,
+                            i32,
+                            *const _,
+                            libc::socklen_t,
+                        ) -> libc::ssize_t
+                    ),
+                    link_name,
+                    abi,
+                    args,
+                )?;
+                this.send(socket, buffer, length, flags, Some((address, address_len)), dest)?;
+            }
             "__xnet_getaddrinfo" => {
                 let [node, service, hints, res] = this.check_shim_sig(
                     shim_sig!(extern "C" fn(*const _, *const _, *const _, *mut _) -> i32),
@@ -0,0 +1,22 @@
+//@compile-flags: -Zmiri-leak-graph=/dev/stdout
+//@ignore-host: windows # the graph is written to `/dev/stdout`
+//@error-in-other-file: memory leaked
+//@normalize-stderr-test: ".*│.*" -> "$$stripped$$"
+//@normalize-stderr-test: "Rust heap, size: [0-9]+, align: [0-9]+" -> "Rust heap, SIZE, ALIGN"
+//@normalize-stdout-test: "alloc[0-9]+" -> "ALLOC"
+//@normalize-stdout-test: "size: [0-9]+, align: [0-9]+" -> "SIZE, ALIGN"
+//@normalize-stdout-test: "\.rs:[0-9]+:[0-9]+: [0-9]+:[0-9]+" -> ".rs:LL:CC"
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+struct Node(RefCell<Option<Rc<Node>>>);
+
+fn main() {
+    // Two `Rc`s that keep each other alive. The first one is the head of the cycle.
+    let first = Rc::new(Node(RefCell::new(None)));
+    let second = Rc::new(Node(RefCell::new(Some(first.clone()))));
+    *first.0.borrow_mut() = Some(second);
+    // A leak that is not part of any cycle.
+    let _ = Box::leak(Box::new(42)); //~ERROR: memory leaked
+}
@@ -0,0 +1,130 @@
+//@only-target: linux
+// test_poll_block_then_unblock depends on a deterministic schedule.
+//@compile-flags: -Zmiri-deterministic-concurrency
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[path = "../../utils/libc.rs"]
+mod libc_utils;
+use libc_utils::*;
+
+fn main() {
+    test_poll_eventfd();
+    test_poll_socketpair();
+    test_poll_invalid_fds();
+    test_poll_same_fd_twice();
+    test_poll_timeout();
+    test_poll_without_fds();
+    test_poll_block_then_unblock();
+}
+
+fn pollfd(fd: i32, events: i16) -> libc::pollfd {
+    libc::pollfd { fd, events, revents: 0 }
+}
+
+/// Call `poll` on `fds` and return the number of ready file descriptors.
+#[track_caller]
+fn poll(fds: &mut [libc::pollfd], timeout: i32) -> i32 {
+    errno_result(unsafe { libc::poll(fds.as_mut_ptr(), fds.len().try_into().unwrap(), timeout) })
+        .expect("poll returned an error")
+}
+
+fn test_poll_eventfd() {
+    let fd = errno_result(unsafe { libc::eventfd(0, libc::EFD_NONBLOCK) }).unwrap();
+
+    // The counter is zero, so the eventfd is writable but not readable.
+    let mut fds = [pollfd(fd, libc::POLLIN)];
+    assert_eq!(poll(&mut fds, 0), 0);
+    assert_eq!(fds[0].revents, 0);
+    let mut fds = [pollfd(fd, libc::POLLIN | libc::POLLOUT)];
+    assert_eq!(poll(&mut fds, 0), 1);
+    assert_eq!(fds[0].revents, libc::POLLOUT);
+
+    // Unlike epoll, poll keeps reporting the same events.
+    assert_eq!(poll(&mut fds, 0), 1);
+    assert_eq!(fds[0].revents, libc::POLLOUT);
+
+    write_all(fd, &1_u64.to_ne_bytes()).unwrap();
+    assert_eq!(poll(&mut fds, 0), 1);
+    assert_eq!(fds[0].revents, libc::POLLIN | libc::POLLOUT);
+}
+
+fn test_poll_socketpair() {
+    let mut sv = [-1, -1];
+    errno_check(unsafe { libc::socketpair(libc::AF_UNIX, libc::SOCK_STREAM, 0, sv.as_mut_ptr()) });
+
+    let mut fds = [pollfd(sv[0], libc::POLLIN | libc::POLLRDHUP)];
+    assert_eq!(poll(&mut fds, 0), 0);
+
+    write_all(sv[1], b"abcde").unwrap();
+    assert_eq!(poll(&mut fds, 0), 1);
+    assert_eq!(fds[0].revents, libc::POLLIN);
+
+    // Closing the peer is reported with `POLLHUP`, even though it wasn't requested.
+    errno_check(unsafe { libc::close(sv[1]) });
+    assert_eq!(poll(&mut fds, 0), 1);
+    assert_eq!(fds[0].revents, libc::POLLIN | libc::POLLRDHUP | libc::POLLHUP);
+}
+
+fn test_poll_invalid_fds() {
+    let fd = errno_result(unsafe { libc::eventfd(0, libc::EFD_NONBLOCK) }).unwrap();
+    errno_check(unsafe { libc::close(fd) });
+
+    // Closed file descriptors are reported with `POLLNVAL`, and negative ones are ignored.
+    let mut fds = [pollfd(fd, libc::POLLIN), pollfd(-1, libc::POLLIN)];
+    assert_eq!(poll(&mut fds, 0), 1);
+    assert_eq!(fds[0].revents, libc::POLLNVAL);
+    assert_eq!(fds[1].revents, 0);
+}
+
+fn test_poll_same_fd_twice() {
+    let fd = errno_result(unsafe { libc::eventfd(0, libc::EFD_NONBLOCK) }).unwrap();
+
+    let mut fds = [pollfd(fd, libc::POLLIN), pollfd(fd, libc::POLLOUT)];
+    assert_eq!(poll(&mut fds, 0), 1);
+    assert_eq!(fds[0].revents, 0);
+    assert_eq!(fds[1].revents, libc::POLLOUT);
+
+    // Block until the first entry becomes ready.
+    let thread = thread::spawn(move || {
+        write_all(fd, &1_u64.to_ne_bytes()).unwrap();
+    });
+    let mut fds = [pollfd(fd, libc::POLLIN), pollfd(fd, libc::POLLIN)];
+    assert_eq!(poll(&mut fds, -1), 2);
+    assert_eq!(fds[0].revents, libc::POLLIN);
+    assert_eq!(fds[1].revents, libc::POLLIN);
+    thread.join().unwrap();
+}
+
+fn test_poll_timeout() {
+    let fd = errno_result(unsafe { libc::eventfd(0, libc::EFD_NONBLOCK) }).unwrap();
+
+    let start = Instant::now();
+    let mut fds = [pollfd(fd, libc::POLLIN)];
+    assert_eq!(poll(&mut fds, 10), 0);
+    assert_eq!(fds[0].revents, 0);
+    assert!(start.elapsed() >= Duration::from_millis(10));
+}
+
+fn test_poll_without_fds() {
+    // Without file descriptors, `poll` just sleeps.
+    let start = Instant::now();
+    assert_eq!(poll(&mut [], 10), 0);
+    assert!(start.elapsed() >= Duration::from_millis(10));
+}
+
+fn test_poll_block_then_unblock() {
+    let mut sv = [-1, -1];
+    errno_check(unsafe { libc::socketpair(libc::AF_UNIX, libc::SOCK_STREAM, 0, sv.as_mut_ptr()) });
+
+    // The poll blocks until the other thread writes to the socket.
+    let thread = thread::spawn(move || {
+        thread::yield_now();
+        write_all(sv[1], b"abcde").unwrap();
+    });
+    let mut fds = [pollfd(sv[0], libc::POLLIN)];
+    assert_eq!(poll(&mut fds, -1), 1);
+    assert_eq!(fds[0].revents, libc::POLLIN);
+    thread.join().unwrap();
+}
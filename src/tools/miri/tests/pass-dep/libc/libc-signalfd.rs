@@ -0,0 +1,54 @@
+//@only-target: linux
+
+#[path = "../../utils/libc.rs"]
+mod libc_utils;
+use libc_utils::*;
+
+fn main() {
+    test_signalfd();
+    test_signalfd_change_mask();
+}
+
+fn signalfd(fd: i32, flags: i32) -> i32 {
+    // Miri doesn't deliver signals, so the mask doesn't matter.
+    let mask: libc::sigset_t = unsafe { std::mem::zeroed() };
+    unsafe { libc::signalfd(fd, &mask, flags) }
+}
+
+fn test_signalfd() {
+    let fd = errno_result(signalfd(-1, libc::SFD_NONBLOCK | libc::SFD_CLOEXEC)).unwrap();
+
+    // There is never a signal to read.
+    let mut buf = [0u8; size_of::<libc::signalfd_siginfo>()];
+    let res = unsafe { libc::read(fd, buf.as_mut_ptr().cast(), buf.len()) };
+    assert_eq!(res, -1);
+    assert_eq!(errno(), libc::EAGAIN);
+
+    // The buffer must be able to hold at least one `signalfd_siginfo`.
+    let res = unsafe { libc::read(fd, buf.as_mut_ptr().cast(), buf.len() - 1) };
+    assert_eq!(res, -1);
+    assert_eq!(errno(), libc::EINVAL);
+
+    let mut fds = [libc::pollfd { fd, events: libc::POLLIN, revents: 0 }];
+    let res = errno_result(unsafe { libc::poll(fds.as_mut_ptr(), 1, 0) }).unwrap();
+    assert_eq!(res, 0);
+    assert_eq!(fds[0].revents, 0);
+
+    errno_check(unsafe { libc::close(fd) });
+}
+
+fn test_signalfd_change_mask() {
+    let fd = errno_result(signalfd(-1, 0)).unwrap();
+    // Passing an existing signalfd changes its mask instead of creating a new one.
+    assert_eq!(signalfd(fd, 0), fd);
+
+    // Other file descriptors are rejected.
+    let eventfd = errno_result(unsafe { libc::eventfd(0, 0) }).unwrap();
+    assert_eq!(signalfd(eventfd, 0), -1);
+    assert_eq!(errno(), libc::EINVAL);
+    errno_check(unsafe { libc::close(eventfd) });
+
+    errno_check(unsafe { libc::close(fd) });
+    assert_eq!(signalfd(fd, 0), -1);
+    assert_eq!(errno(), libc::EBADF);
+}
@@ -0,0 +1,124 @@
+//@only-target: linux android illumos
+//@ignore-host: windows # Unix-domain sockets are only supported on Unix hosts
+//@compile-flags: -Zmiri-disable-isolation
+
+use std::fs::remove_file;
+use std::io::{self, ErrorKind, Read, Write};
+use std::net::UdpSocket;
+use std::os::fd::{AsRawFd, RawFd};
+use std::os::unix::net::{UnixDatagram, UnixListener, UnixStream};
+use std::thread;
+
+#[path = "../../utils/libc.rs"]
+mod libc_utils;
+#[path = "../../utils/mod.rs"]
+mod utils;
+use libc_utils::epoll::*;
+use libc_utils::*;
+
+const TEST_BYTES: &[u8] = b"these are some test bytes!";
+
+fn main() {
+    test_udp();
+    test_unix_datagram();
+    test_unix_stream();
+}
+
+/// Test the epoll readiness of a pair of connected UDP sockets.
+fn test_udp() {
+    let receiver = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+    sender.connect(receiver.local_addr().unwrap()).unwrap();
+    receiver.set_nonblocking(true).unwrap();
+    sender.set_nonblocking(true).unwrap();
+
+    check_readiness(
+        sender.as_raw_fd(),
+        receiver.as_raw_fd(),
+        || sender.send(TEST_BYTES),
+        |buffer| receiver.recv(buffer),
+    );
+}
+
+/// Test the epoll readiness of an unbound Unix datagram socket connected
+/// to a socket bound to a path.
+fn test_unix_datagram() {
+    let path = utils::prepare("miri_test_socket_epoll_unix_datagram.sock");
+    let receiver = UnixDatagram::bind(&path).unwrap();
+    let sender = UnixDatagram::unbound().unwrap();
+    sender.connect(&path).unwrap();
+    receiver.set_nonblocking(true).unwrap();
+    sender.set_nonblocking(true).unwrap();
+
+    check_readiness(
+        sender.as_raw_fd(),
+        receiver.as_raw_fd(),
+        || sender.send(TEST_BYTES),
+        |buffer| receiver.recv(buffer),
+    );
+
+    remove_file(&path).unwrap();
+}
+
+/// Test the epoll readiness of both ends of a Unix stream connection.
+fn test_unix_stream() {
+    let path = utils::prepare("miri_test_socket_epoll_unix_stream.sock");
+    let listener = UnixListener::bind(&path).unwrap();
+    let handle = thread::spawn(move || listener.accept().unwrap().0);
+
+    let mut sender = UnixStream::connect(&path).unwrap();
+    let mut receiver = handle.join().unwrap();
+    receiver.set_nonblocking(true).unwrap();
+    sender.set_nonblocking(true).unwrap();
+
+    check_readiness(
+        sender.as_raw_fd(),
+        receiver.as_raw_fd(),
+        || sender.write(TEST_BYTES),
+        |buffer| receiver.read(buffer),
+    );
+
+    remove_file(&path).unwrap();
+}
+
+/// Check that `sender` becomes writable, and that `receiver` becomes readable
+/// once `send` was called and stops being readable after draining it with `recv`.
+/// Each socket gets its own epoll instance so that the notifications of one socket
+/// don't depend on the other one.
+fn check_readiness(
+    sender: RawFd,
+    receiver: RawFd,
+    send: impl FnOnce() -> io::Result<usize>,
+    mut recv: impl FnMut(&mut [u8]) -> io::Result<usize>,
+) {
+    let sender_epfd = errno_result(unsafe { libc::epoll_create1(0) }).unwrap();
+    let receiver_epfd = errno_result(unsafe { libc::epoll_create1(0) }).unwrap();
+
+    // A fresh socket can be written to right away.
+    epoll_ctl_add(sender_epfd, sender, EPOLLOUT | EPOLLET).unwrap();
+    check_epoll_wait::<8>(sender_epfd, &[Ev { events: EPOLLOUT, data: sender }], -1);
+
+    // Nothing was sent yet, so the receiver is not readable.
+    let mut buffer = [0; TEST_BYTES.len()];
+    let err = recv(&mut buffer).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::WouldBlock);
+    assert_eq!(current_epoll_readiness::<8>(receiver, EPOLLIN | EPOLLET), 0);
+
+    epoll_ctl_add(receiver_epfd, receiver, EPOLLIN | EPOLLET).unwrap();
+    check_epoll_wait_noblock::<8>(receiver_epfd, &[]);
+
+    // Sending makes the receiver readable.
+    assert_eq!(send().unwrap(), TEST_BYTES.len());
+    check_epoll_wait::<8>(receiver_epfd, &[Ev { events: EPOLLIN, data: receiver }], -1);
+
+    assert_eq!(recv(&mut buffer).unwrap(), TEST_BYTES.len());
+    assert_eq!(&buffer, TEST_BYTES);
+
+    // After draining the receiver, it is no longer readable.
+    let err = recv(&mut buffer).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::WouldBlock);
+    assert_eq!(current_epoll_readiness::<8>(receiver, EPOLLIN | EPOLLET), 0);
+
+    errno_check(unsafe { libc::close(sender_epfd) });
+    errno_check(unsafe { libc::close(receiver_epfd) });
+}
@@ -0,0 +1,179 @@
+//@only-target: linux
+
+use std::time::{Duration, Instant};
+use std::{io, thread};
+
+#[path = "../../utils/libc.rs"]
+mod libc_utils;
+use libc_utils::epoll::*;
+use libc_utils::*;
+
+fn main() {
+    test_disarmed();
+    test_one_shot();
+    test_periodic();
+    test_absolute_time();
+    test_rearm_and_disarm();
+    test_blocking_read();
+    test_epoll_wakeup();
+    test_poll_wakeup();
+}
+
+fn timespec(duration: Duration) -> libc::timespec {
+    libc::timespec {
+        tv_sec: duration.as_secs().try_into().unwrap(),
+        tv_nsec: duration.subsec_nanos().try_into().unwrap(),
+    }
+}
+
+fn duration(timespec: libc::timespec) -> Duration {
+    Duration::new(timespec.tv_sec.try_into().unwrap(), timespec.tv_nsec.try_into().unwrap())
+}
+
+fn timerfd_create(flags: i32) -> i32 {
+    errno_result(unsafe { libc::timerfd_create(libc::CLOCK_MONOTONIC, flags) }).unwrap()
+}
+
+/// Arm the timer to expire after `value`, and then every `interval`. Returns the previous setting.
+fn timerfd_settime(fd: i32, value: Duration, interval: Duration) -> (Duration, Duration) {
+    let new_value = libc::itimerspec { it_interval: timespec(interval), it_value: timespec(value) };
+    let mut old_value = libc::itimerspec {
+        it_interval: timespec(Duration::ZERO),
+        it_value: timespec(Duration::ZERO),
+    };
+    errno_check(unsafe { libc::timerfd_settime(fd, 0, &new_value, &mut old_value) });
+    (duration(old_value.it_value), duration(old_value.it_interval))
+}
+
+/// Returns the time until the next expiration and the interval of the timer.
+fn timerfd_gettime(fd: i32) -> (Duration, Duration) {
+    let mut curr_value = libc::itimerspec {
+        it_interval: timespec(Duration::ZERO),
+        it_value: timespec(Duration::ZERO),
+    };
+    errno_check(unsafe { libc::timerfd_gettime(fd, &mut curr_value) });
+    (duration(curr_value.it_value), duration(curr_value.it_interval))
+}
+
+/// Read the number of expirations of the timer.
+fn read_expirations(fd: i32) -> io::Result<u64> {
+    let mut buf = [0; 8];
+    let res = errno_result(unsafe { libc::read(fd, buf.as_mut_ptr().cast(), buf.len()) })?;
+    assert_eq!(res, 8);
+    Ok(u64::from_ne_bytes(buf))
+}
+
+fn test_disarmed() {
+    let fd = timerfd_create(libc::TFD_NONBLOCK | libc::TFD_CLOEXEC);
+    assert_eq!(timerfd_gettime(fd), (Duration::ZERO, Duration::ZERO));
+
+    // A disarmed timer never expires.
+    let err = read_expirations(fd).unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(libc::EAGAIN));
+
+    // The buffer must be able to hold the number of expirations.
+    let mut buf = [0; 4];
+    let res = unsafe { libc::read(fd, buf.as_mut_ptr().cast(), buf.len()) };
+    assert_eq!(res, -1);
+    assert_eq!(errno(), libc::EINVAL);
+}
+
+fn test_one_shot() {
+    let fd = timerfd_create(libc::TFD_NONBLOCK);
+    timerfd_settime(fd, Duration::from_millis(10), Duration::ZERO);
+
+    let (value, interval) = timerfd_gettime(fd);
+    assert!(value > Duration::ZERO && value <= Duration::from_millis(10));
+    assert_eq!(interval, Duration::ZERO);
+    let err = read_expirations(fd).unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(libc::EAGAIN));
+
+    thread::sleep(Duration::from_millis(20));
+    assert_eq!(read_expirations(fd).unwrap(), 1);
+    // The timer is disarmed after its only expiration.
+    assert_eq!(timerfd_gettime(fd), (Duration::ZERO, Duration::ZERO));
+    let err = read_expirations(fd).unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(libc::EAGAIN));
+}
+
+fn test_periodic() {
+    let fd = timerfd_create(libc::TFD_NONBLOCK);
+    timerfd_settime(fd, Duration::from_millis(5), Duration::from_millis(5));
+
+    thread::sleep(Duration::from_millis(22));
+    assert!(read_expirations(fd).unwrap() >= 4);
+    let (value, interval) = timerfd_gettime(fd);
+    assert!(value > Duration::ZERO && value <= Duration::from_millis(5));
+    assert_eq!(interval, Duration::from_millis(5));
+}
+
+fn test_absolute_time() {
+    let fd = timerfd_create(libc::TFD_NONBLOCK);
+
+    // An absolute time in the past lets the timer expire immediately.
+    let new_value = libc::itimerspec {
+        it_interval: timespec(Duration::ZERO),
+        it_value: timespec(Duration::from_nanos(1)),
+    };
+    errno_check(unsafe {
+        libc::timerfd_settime(fd, libc::TFD_TIMER_ABSTIME, &new_value, std::ptr::null_mut())
+    });
+    assert_eq!(read_expirations(fd).unwrap(), 1);
+}
+
+fn test_rearm_and_disarm() {
+    let fd = timerfd_create(libc::TFD_NONBLOCK);
+    timerfd_settime(fd, Duration::from_millis(1), Duration::ZERO);
+    thread::sleep(Duration::from_millis(5));
+
+    // Setting the timer again discards the expirations that were not read yet.
+    let (old_value, old_interval) =
+        timerfd_settime(fd, Duration::from_secs(10), Duration::from_secs(1));
+    assert_eq!((old_value, old_interval), (Duration::ZERO, Duration::ZERO));
+    let err = read_expirations(fd).unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(libc::EAGAIN));
+
+    let (old_value, old_interval) = timerfd_settime(fd, Duration::ZERO, Duration::ZERO);
+    assert!(old_value > Duration::from_secs(9));
+    assert_eq!(old_interval, Duration::from_secs(1));
+    assert_eq!(timerfd_gettime(fd), (Duration::ZERO, Duration::ZERO));
+}
+
+fn test_blocking_read() {
+    let fd = timerfd_create(0);
+    let start = Instant::now();
+    timerfd_settime(fd, Duration::from_millis(10), Duration::ZERO);
+
+    // The read blocks until the timer expires.
+    assert_eq!(read_expirations(fd).unwrap(), 1);
+    assert!(start.elapsed() >= Duration::from_millis(10));
+}
+
+fn test_epoll_wakeup() {
+    let epfd = errno_result(unsafe { libc::epoll_create1(0) }).unwrap();
+    let fd = timerfd_create(libc::TFD_NONBLOCK);
+    epoll_ctl_add(epfd, fd, EPOLLIN | EPOLLET).unwrap();
+    check_epoll_wait::<1>(epfd, &[], 0);
+
+    let start = Instant::now();
+    timerfd_settime(fd, Duration::from_millis(10), Duration::ZERO);
+
+    // The epoll_wait blocks until the timer expires.
+    check_epoll_wait::<1>(epfd, &[Ev { events: EPOLLIN, data: fd }], -1);
+    assert!(start.elapsed() >= Duration::from_millis(10));
+    assert_eq!(read_expirations(fd).unwrap(), 1);
+}
+
+fn test_poll_wakeup() {
+    let fd = timerfd_create(libc::TFD_NONBLOCK);
+    let start = Instant::now();
+    timerfd_settime(fd, Duration::from_millis(10), Duration::ZERO);
+
+    // The poll blocks until the timer expires.
+    let mut fds = [libc::pollfd { fd, events: libc::POLLIN, revents: 0 }];
+    let res = errno_result(unsafe { libc::poll(fds.as_mut_ptr(), 1, -1) }).unwrap();
+    assert_eq!(res, 1);
+    assert_eq!(fds[0].revents, libc::POLLIN);
+    assert!(start.elapsed() >= Duration::from_millis(10));
+    assert_eq!(read_expirations(fd).unwrap(), 1);
+}
@@ -0,0 +1,14 @@
+//@compile-flags: -Zmiri-coverage=/dev/stdout
+//@ignore-host: windows # the report is written to `/dev/stdout`
+//@normalize-stdout-test: "SF:.*" -> "SF:$$FILE"
+
+fn never_called() {
+    std::process::abort();
+}
+
+fn main() {
+    let x = std::hint::black_box(3);
+    if x > 5 {
+        never_called();
+    }
+}
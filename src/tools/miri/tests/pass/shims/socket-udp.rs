@@ -0,0 +1,114 @@
+//@ignore-target: windows # No socket support on Windows
+//@compile-flags: -Zmiri-disable-isolation
+
+use std::io::ErrorKind;
+use std::net::UdpSocket;
+
+const TEST_BYTES: &[u8] = b"these are some test bytes!";
+
+fn main() {
+    test_create_ipv4_socket();
+    test_create_ipv6_socket();
+    test_send_to_recv_from();
+    test_connect_send_recv();
+    test_peek_from();
+    test_recv_truncated();
+    test_recv_nonblock();
+}
+
+fn test_create_ipv4_socket() {
+    let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let address = socket.local_addr().unwrap();
+    assert!(address.is_ipv4());
+    assert_ne!(address.port(), 0);
+}
+
+fn test_create_ipv6_socket() {
+    let socket = UdpSocket::bind("[::1]:0").unwrap();
+    let address = socket.local_addr().unwrap();
+    assert!(address.is_ipv6());
+    assert_ne!(address.port(), 0);
+}
+
+/// Send a datagram to a socket and check that it is received together with
+/// the address of the sender.
+fn test_send_to_recv_from() {
+    let receiver = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+
+    let bytes_written = sender.send_to(TEST_BYTES, receiver.local_addr().unwrap()).unwrap();
+    assert_eq!(bytes_written, TEST_BYTES.len());
+
+    let mut buffer = [0; TEST_BYTES.len()];
+    let (bytes_read, source) = receiver.recv_from(&mut buffer).unwrap();
+    assert_eq!(bytes_read, TEST_BYTES.len());
+    assert_eq!(&buffer, TEST_BYTES);
+    assert_eq!(source, sender.local_addr().unwrap());
+}
+
+/// Test sending and receiving on sockets connected to each other.
+fn test_connect_send_recv() {
+    let socket1 = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let socket2 = UdpSocket::bind("127.0.0.1:0").unwrap();
+    socket1.connect(socket2.local_addr().unwrap()).unwrap();
+    socket2.connect(socket1.local_addr().unwrap()).unwrap();
+    assert_eq!(socket1.peer_addr().unwrap(), socket2.local_addr().unwrap());
+
+    socket1.send(TEST_BYTES).unwrap();
+    let mut buffer = [0; TEST_BYTES.len()];
+    let bytes_read = socket2.recv(&mut buffer).unwrap();
+    assert_eq!(bytes_read, TEST_BYTES.len());
+    assert_eq!(&buffer, TEST_BYTES);
+
+    socket2.send(b"reply").unwrap();
+    let mut buffer = [0; 5];
+    socket1.recv(&mut buffer).unwrap();
+    assert_eq!(&buffer, b"reply");
+}
+
+/// Test that peeking at a datagram doesn't remove it from the socket.
+fn test_peek_from() {
+    let receiver = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+    sender.send_to(TEST_BYTES, receiver.local_addr().unwrap()).unwrap();
+
+    let mut buffer = [0; TEST_BYTES.len()];
+    let (bytes_read, source) = receiver.peek_from(&mut buffer).unwrap();
+    assert_eq!(bytes_read, TEST_BYTES.len());
+    assert_eq!(&buffer, TEST_BYTES);
+    assert_eq!(source, sender.local_addr().unwrap());
+
+    let mut buffer = [0; TEST_BYTES.len()];
+    let (bytes_read, _source) = receiver.recv_from(&mut buffer).unwrap();
+    assert_eq!(bytes_read, TEST_BYTES.len());
+    assert_eq!(&buffer, TEST_BYTES);
+}
+
+/// Test that receiving into a buffer which is too small discards the rest of
+/// the datagram.
+fn test_recv_truncated() {
+    let receiver = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+    sender.send_to(TEST_BYTES, receiver.local_addr().unwrap()).unwrap();
+    sender.send_to(b"second", receiver.local_addr().unwrap()).unwrap();
+
+    let mut buffer = [0; 5];
+    let (bytes_read, _source) = receiver.recv_from(&mut buffer).unwrap();
+    assert_eq!(bytes_read, 5);
+    assert_eq!(&buffer, &TEST_BYTES[..5]);
+
+    let mut buffer = [0; TEST_BYTES.len()];
+    let (bytes_read, _source) = receiver.recv_from(&mut buffer).unwrap();
+    assert_eq!(&buffer[..bytes_read], b"second");
+}
+
+/// Test that receiving on a nonblocking socket without pending datagrams
+/// returns [`ErrorKind::WouldBlock`].
+fn test_recv_nonblock() {
+    let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    socket.set_nonblocking(true).unwrap();
+
+    let mut buffer = [0; TEST_BYTES.len()];
+    let err = socket.recv_from(&mut buffer).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::WouldBlock);
+}
@@ -0,0 +1,102 @@
+//@ignore-target: windows # No socket support on Windows
+//@ignore-host: windows # Unix-domain sockets are only supported on Unix hosts
+//@compile-flags: -Zmiri-disable-isolation
+
+use std::fs::remove_file;
+use std::io::{Read, Write};
+use std::os::unix::net::{UnixDatagram, UnixListener, UnixStream};
+use std::thread;
+
+#[path = "../../utils/mod.rs"]
+mod utils;
+
+const TEST_BYTES: &[u8] = b"these are some test bytes!";
+
+fn main() {
+    test_stream();
+    test_datagram_send_to_recv_from();
+    test_datagram_unbound_sender();
+    test_datagram_connected();
+}
+
+/// Connect to a listener accepting connections in a separate thread and
+/// exchange some bytes with it.
+fn test_stream() {
+    let path = utils::prepare("miri_test_socket_unix_stream.sock");
+    let listener = UnixListener::bind(&path).unwrap();
+    assert_eq!(listener.local_addr().unwrap().as_pathname(), Some(path.as_path()));
+
+    let handle = thread::spawn(move || {
+        let (mut stream, _address) = listener.accept().unwrap();
+        let mut buffer = [0; TEST_BYTES.len()];
+        stream.read_exact(&mut buffer).unwrap();
+        assert_eq!(&buffer, TEST_BYTES);
+        stream.write_all(b"reply").unwrap();
+    });
+
+    let mut stream = UnixStream::connect(&path).unwrap();
+    assert_eq!(stream.peer_addr().unwrap().as_pathname(), Some(path.as_path()));
+    stream.write_all(TEST_BYTES).unwrap();
+    let mut buffer = [0; 5];
+    stream.read_exact(&mut buffer).unwrap();
+    assert_eq!(&buffer, b"reply");
+
+    handle.join().unwrap();
+    remove_file(&path).unwrap();
+}
+
+/// Send a datagram between two sockets bound to a path and check that it is
+/// received together with the path of the sender.
+fn test_datagram_send_to_recv_from() {
+    let receiver_path = utils::prepare("miri_test_socket_unix_receiver.sock");
+    let sender_path = utils::prepare("miri_test_socket_unix_sender.sock");
+    let receiver = UnixDatagram::bind(&receiver_path).unwrap();
+    let sender = UnixDatagram::bind(&sender_path).unwrap();
+    assert_eq!(receiver.local_addr().unwrap().as_pathname(), Some(receiver_path.as_path()));
+
+    let bytes_written = sender.send_to(TEST_BYTES, &receiver_path).unwrap();
+    assert_eq!(bytes_written, TEST_BYTES.len());
+
+    let mut buffer = [0; TEST_BYTES.len()];
+    let (bytes_read, source) = receiver.recv_from(&mut buffer).unwrap();
+    assert_eq!(bytes_read, TEST_BYTES.len());
+    assert_eq!(&buffer, TEST_BYTES);
+    assert_eq!(source.as_pathname(), Some(sender_path.as_path()));
+
+    remove_file(&receiver_path).unwrap();
+    remove_file(&sender_path).unwrap();
+}
+
+/// Test that datagrams sent by a socket which isn't bound to a path come from
+/// an unnamed address.
+fn test_datagram_unbound_sender() {
+    let path = utils::prepare("miri_test_socket_unix_unbound.sock");
+    let receiver = UnixDatagram::bind(&path).unwrap();
+    let sender = UnixDatagram::unbound().unwrap();
+
+    sender.send_to(TEST_BYTES, &path).unwrap();
+    let mut buffer = [0; TEST_BYTES.len()];
+    let (bytes_read, source) = receiver.recv_from(&mut buffer).unwrap();
+    assert_eq!(bytes_read, TEST_BYTES.len());
+    assert_eq!(&buffer, TEST_BYTES);
+    assert!(source.is_unnamed());
+
+    remove_file(&path).unwrap();
+}
+
+/// Test sending and receiving on a socket connected to a path.
+fn test_datagram_connected() {
+    let path = utils::prepare("miri_test_socket_unix_connected.sock");
+    let receiver = UnixDatagram::bind(&path).unwrap();
+    let sender = UnixDatagram::unbound().unwrap();
+    sender.connect(&path).unwrap();
+    assert_eq!(sender.peer_addr().unwrap().as_pathname(), Some(path.as_path()));
+
+    sender.send(TEST_BYTES).unwrap();
+    let mut buffer = [0; TEST_BYTES.len()];
+    let bytes_read = receiver.recv(&mut buffer).unwrap();
+    assert_eq!(bytes_read, TEST_BYTES.len());
+    assert_eq!(&buffer, TEST_BYTES);
+
+    remove_file(&path).unwrap();
+}
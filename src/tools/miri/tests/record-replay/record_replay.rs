@@ -0,0 +1,61 @@
+//@ignore-target: windows # The file operations of Windows targets are not replayed
+//@compile-flags: -Zmiri-disable-isolation
+// `tests/ui.rs` runs this test twice: first recording the interactions with the host, then
+// replaying them with a different `MIRI_RECORD_REPLAY`. The replay must produce the output of the
+// recording, even though the host now has a different environment, clock, and randomness, and
+// without touching the host's files and sockets.
+
+use std::collections::hash_map::RandomState;
+use std::fs;
+use std::hash::BuildHasher;
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+use std::time::{Instant, SystemTime};
+
+#[path = "../utils/mod.rs"]
+mod utils;
+
+const TEST_BYTES: &[u8] = b"these are some test bytes!";
+
+fn main() {
+    println!("{}", std::env::var("MIRI_RECORD_REPLAY").unwrap());
+    test_files();
+    test_sockets();
+}
+
+fn test_files() {
+    let start = Instant::now();
+    let now = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap();
+    let hash = RandomState::new().hash_one(0u8);
+    let values = format!("{} {hash} {}", now.as_nanos(), start.elapsed().as_nanos());
+
+    // The recording leaves the directory behind, so creating it only succeeds during the replay
+    // if that is answered from the log.
+    let dir = utils::tmp().join("miri_test_record_replay");
+    fs::remove_dir(&dir).ok();
+    fs::create_dir(&dir).unwrap();
+
+    // The read returns what was written during the recording, which only matches `values` if the
+    // clock and randomness are replayed.
+    let path = dir.join("values.txt");
+    fs::write(&path, &values).unwrap();
+    println!("{}", fs::read_to_string(&path).unwrap() == values);
+    println!("{}", fs::metadata(&path).unwrap().len() == values.len() as u64);
+    fs::remove_file(&path).unwrap();
+}
+
+fn test_sockets() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let handle = thread::spawn(move || {
+        let (mut stream, _address) = listener.accept().unwrap();
+        stream.write_all(TEST_BYTES).unwrap();
+    });
+
+    let mut stream = TcpStream::connect(address).unwrap();
+    let mut buffer = Vec::new();
+    stream.read_to_end(&mut buffer).unwrap();
+    println!("{}", buffer == TEST_BYTES);
+    handle.join().unwrap();
+}
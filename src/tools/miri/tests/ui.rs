@@ -157,12 +157,20 @@ fn miri_config(
     config
 }
 
+/// Whether the tests record the interactions with the host or replay them.
+#[derive(Copy, Clone, Debug)]
+enum HostLog {
+    Record,
+    Replay,
+}
+
 fn run_tests(
     mode: Mode,
     path: &str,
     target: &str,
     with_dependencies: bool,
     tmpdir: &Path,
+    host_log: Option<HostLog>,
 ) -> Result<()> {
     // Handle command-line arguments.
     let mut args = ui_test::Args::test()?;
@@ -218,6 +226,17 @@ fn run_tests(
         // FIXME(genmc): remove this when GenMC and SB can be used together.
         config.program.args.push("-Zmiri-disable-stacked-borrows".into());
     }
+    // The record-replay tests keep their logs in the temp dir. The environment differs between
+    // recording and replaying, so the tests can check that the replay ignores it.
+    if let Some(host_log) = host_log {
+        let (mut flag, env) = match host_log {
+            HostLog::Record => (OsString::from("-Zmiri-record="), "recorded"),
+            HostLog::Replay => (OsString::from("-Zmiri-replay="), "replayed"),
+        };
+        flag.push(tmpdir.join("record-replay"));
+        config.program.args.push(flag);
+        config.program.envs.push(("MIRI_RECORD_REPLAY".into(), Some(env.into())));
+    }
 
     eprintln!("   Compiler: {}", config.program.display());
     ui_test::run_tests_generic(
@@ -309,10 +328,23 @@ fn ui(
         WithDependencies => true,
         WithoutDependencies => false,
     };
-    run_tests(mode, path, target, with_dependencies, tmpdir)
+    run_tests(mode, path, target, with_dependencies, tmpdir, None)
         .with_context(|| format!("ui tests in {path} for {target} failed"))
 }
 
+/// Run the tests in `path` recording the interactions with the host, and then again replaying
+/// them. Both runs have to produce the same output.
+fn record_replay(path: &str, target: &str, tmpdir: &Path) -> Result<()> {
+    for host_log in [HostLog::Record, HostLog::Replay] {
+        let msg = format!("## Running ui tests in {path} for {target} ({host_log:?})");
+        eprintln!("{}", msg.green().bold());
+
+        run_tests(Mode::Pass, path, target, false, tmpdir, Some(host_log))
+            .with_context(|| format!("ui tests in {path} for {target} ({host_log:?}) failed"))?;
+    }
+    Ok(())
+}
+
 fn get_host() -> String {
     rustc_version::VersionMeta::for_command(std::process::Command::new(miri_path()))
         .expect("failed to parse rustc version info")
@@ -344,6 +376,7 @@ fn main() -> Result<()> {
     ui(Mode::Panic, "tests/panic", &target, WithDependencies, tmpdir.path())?;
     ui(Mode::Fail, "tests/fail", &target, WithoutDependencies, tmpdir.path())?;
     ui(Mode::Fail, "tests/fail-dep", &target, WithDependencies, tmpdir.path())?;
+    record_replay("tests/record-replay", &target, tmpdir.path())?;
     if cfg!(all(unix, feature = "native-lib")) && target == host {
         ui(Mode::Pass, "tests/native-lib/pass", &target, WithoutDependencies, tmpdir.path())?;
         ui(Mode::Fail, "tests/native-lib/fail", &target, WithoutDependencies, tmpdir.path())?;
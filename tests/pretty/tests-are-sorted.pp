@@ -25,6 +25,7 @@ pub const m_test: test::TestDescAndFn =
             compile_fail: false,
             no_run: false,
             should_panic: test::ShouldPanic::No,
+            timeout_secs: ::core::option::Option::None,
             test_type: test::TestType::Unknown,
         },
         testfn: test::StaticTestFn(#[coverage(off)] ||
@@ -49,6 +50,7 @@ pub const z_test: test::TestDescAndFn =
             compile_fail: false,
             no_run: false,
             should_panic: test::ShouldPanic::No,
+            timeout_secs: ::core::option::Option::None,
             test_type: test::TestType::Unknown,
         },
         testfn: test::StaticTestFn(#[coverage(off)] ||
@@ -74,6 +76,7 @@ pub const a_test: test::TestDescAndFn =
             compile_fail: false,
             no_run: false,
             should_panic: test::ShouldPanic::No,
+            timeout_secs: ::core::option::Option::None,
             test_type: test::TestType::Unknown,
         },
         testfn: test::StaticTestFn(#[coverage(off)] ||
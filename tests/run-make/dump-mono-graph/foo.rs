@@ -0,0 +1,11 @@
+pub fn parse<T: Default>() -> T {
+    T::default()
+}
+
+fn helper() -> u32 {
+    parse::<u32>()
+}
+
+pub fn entry() -> u32 {
+    helper() + parse::<u8>() as u32
+}
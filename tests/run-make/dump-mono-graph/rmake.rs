@@ -0,0 +1,50 @@
+// This test checks that `-Zdump-mono-graph` writes the mono item graph, and that each instance
+// of a generic function is attributed to the non-generic item that caused it to be instantiated.
+
+//@ needs-target-std
+
+use run_make_support::{rfs, rustc, serde_json};
+
+fn main() {
+    rustc().crate_type("lib").input("foo.rs").arg("-Zdump-mono-graph=graph.json").run();
+
+    let graph: serde_json::Value =
+        serde_json::from_str(&rfs::read_to_string("graph.json")).expect("graph should be json");
+    assert_eq!(graph["crate_name"], "foo");
+    let items = graph["items"].as_array().unwrap();
+    let index_of = |name: &str| {
+        items
+            .iter()
+            .position(|item| item["name"] == name)
+            .unwrap_or_else(|| panic!("no mono item named `{name}`"))
+    };
+    let root_cause = |name: &str| -> Vec<usize> {
+        items[index_of(name)]["root_cause"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|user| user.as_u64().unwrap() as usize)
+            .collect()
+    };
+
+    // Non-generic local items are roots.
+    assert_eq!(items[index_of("fn entry")]["generic"], false);
+    assert!(root_cause("fn entry").is_empty());
+    assert!(root_cause("fn helper").is_empty());
+
+    assert_eq!(items[index_of("fn parse::<u32>")]["generic"], true);
+    assert_eq!(items[index_of("fn parse::<u32>")]["def_path"], "parse");
+    assert_eq!(root_cause("fn parse::<u32>"), [index_of("fn helper")]);
+    assert_eq!(root_cause("fn parse::<u8>"), [index_of("fn entry")]);
+    assert!(
+        items[index_of("fn entry")]["uses"]
+            .as_array()
+            .unwrap()
+            .contains(&serde_json::Value::from(index_of("fn parse::<u8>")),)
+    );
+
+    for item in items {
+        assert!(item["size_estimate"].as_u64().is_some());
+        assert!(!item["cgus"].as_array().unwrap().is_empty());
+    }
+}
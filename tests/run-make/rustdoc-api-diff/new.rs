@@ -0,0 +1,23 @@
+pub struct Plain {
+    pub a: u32,
+    pub b: u8,
+}
+
+pub struct Token(pub std::rc::Rc<u8>);
+
+#[non_exhaustive]
+pub enum Kind {
+    A,
+}
+
+pub fn parse<T: AsRef<str>>(input: T) -> u32 {
+    input.as_ref().len() as u32
+}
+
+pub fn added() {}
+
+pub mod shared {}
+
+pub fn shared(a: u8, b: u8) -> u8 {
+    a + b
+}
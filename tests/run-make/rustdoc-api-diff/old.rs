@@ -0,0 +1,26 @@
+pub struct Plain {
+    pub a: u32,
+}
+
+pub struct Token(pub u8);
+
+pub enum Kind {
+    A,
+}
+
+pub fn parse(input: &str) -> u32 {
+    input.len() as u32
+}
+
+pub fn removed() {}
+
+pub mod shared {}
+
+pub fn shared(a: u8) -> u8 {
+    a
+}
+
+#[macro_export]
+macro_rules! shared {
+    () => {};
+}
@@ -0,0 +1,76 @@
+// This test checks that `--output-format=api-diff` compares a crate against the JSON output of
+// its previous version and classifies the changes.
+
+//@ needs-target-std
+
+use run_make_support::{rfs, rustdoc, serde_json};
+
+fn main() {
+    rustdoc()
+        .input("old.rs")
+        .crate_name("foo")
+        .out_dir("old")
+        .arg("-Zunstable-options")
+        .output_format("json")
+        .run();
+
+    let diff = rustdoc()
+        .input("new.rs")
+        .crate_name("foo")
+        .out_dir("-")
+        .arg("-Zunstable-options")
+        .output_format("api-diff")
+        .arg("--baseline=old/foo.json")
+        .run()
+        .stdout_utf8();
+    let diff: serde_json::Value = serde_json::from_str(&diff).expect("stdout should be valid json");
+
+    assert_eq!(diff["required_bump"], "major");
+    let changes: Vec<(&str, &str, &str, &str)> = diff["changes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|change| {
+            (
+                change["path"].as_str().unwrap(),
+                change["item_kind"].as_str().unwrap(),
+                change["change"].as_str().unwrap(),
+                change["semver"].as_str().unwrap(),
+            )
+        })
+        .collect();
+    for expected in [
+        ("foo::Plain::b", "field", "added", "major"),
+        ("<foo::Token as core::marker::Send>", "auto trait impl", "removed", "major"),
+        ("<foo::Token as core::marker::Sync>", "auto trait impl", "removed", "major"),
+        ("foo::Token::0", "field", "changed", "major"),
+        ("foo::Kind", "enum", "changed", "major"),
+        ("foo::parse", "function", "changed", "major"),
+        ("foo::removed", "function", "removed", "major"),
+        ("foo::added", "function", "added", "minor"),
+        // Items of different namespaces with the same path are compared separately.
+        ("foo::shared", "function", "changed", "major"),
+        ("foo::shared", "macro", "removed", "major"),
+    ] {
+        assert!(changes.contains(&expected), "missing {expected:?} in {changes:#?}");
+    }
+    assert!(!changes.iter().any(|&(path, kind, ..)| path == "foo::shared" && kind == "module"));
+
+    // Without `-o -`, the report is written next to where the JSON output would be.
+    rustdoc()
+        .input("new.rs")
+        .crate_name("foo")
+        .out_dir("new")
+        .arg("-Zunstable-options")
+        .output_format("api-diff")
+        .arg("--baseline=old/foo.json")
+        .run();
+    assert!(rfs::read_to_string("new/foo.api-diff.json").contains("\"required_bump\":\"major\""));
+
+    rustdoc()
+        .input("new.rs")
+        .arg("-Zunstable-options")
+        .output_format("api-diff")
+        .run_fail()
+        .assert_stderr_contains("`--output-format=api-diff` requires a `--baseline`");
+}
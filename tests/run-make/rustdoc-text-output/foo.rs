@@ -0,0 +1,26 @@
+//! A crate with a [`Bar`] struct.
+
+pub mod inner {
+    /// Does nothing.
+    pub fn noop() {}
+}
+
+/// A bar, made with [`Bar::new`] and used by [`inner::noop`].
+///
+/// # Examples
+///
+/// ```
+/// # let hidden = 1;
+/// let bar = foo::Bar::new();
+/// ```
+pub struct Bar {
+    /// The size of the bar.
+    pub size: usize,
+}
+
+impl Bar {
+    /// Makes a new bar.
+    pub fn new() -> Bar {
+        Bar { size: 0 }
+    }
+}
@@ -0,0 +1,3 @@
+//! A command.
+
+fn main() {}
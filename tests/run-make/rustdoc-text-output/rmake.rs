@@ -0,0 +1,59 @@
+// This test checks the pages written by `--output-format=markdown` and `--output-format=man`.
+
+//@ needs-target-std
+
+use run_make_support::{rfs, rustdoc};
+
+fn main() {
+    rustdoc()
+        .input("foo.rs")
+        .out_dir("markdown")
+        .arg("-Zunstable-options")
+        .output_format("markdown")
+        .run();
+
+    let index = rfs::read_to_string("markdown/foo/index.md");
+    assert!(index.starts_with("# Crate `foo`\n"), "{index}");
+    assert!(index.contains("- [`inner`](inner/index.md)"), "{index}");
+    assert!(
+        index.contains(
+            "- [`Bar`](struct.Bar.md): A bar, made with `Bar::new` and used by `inner::noop`.\n"
+        ),
+        "{index}"
+    );
+    assert!(rfs::read_to_string("markdown/foo/inner/fn.noop.md").contains("pub fn noop()"));
+
+    let bar = rfs::read_to_string("markdown/foo/struct.Bar.md");
+    assert!(bar.starts_with("# Struct `foo::Bar`\n"), "{bar}");
+    assert!(bar.contains("```rust\npub struct Bar {\n    pub size: usize,\n}\n```"), "{bar}");
+    // Intra-doc links are relative to the page.
+    assert!(bar.contains("[`Bar::new`]: struct.Bar.md#method.new\n"), "{bar}");
+    assert!(bar.contains("[`inner::noop`]: inner/fn.noop.md\n"), "{bar}");
+    // Headings are nested under the page title, and hidden lines are removed.
+    assert!(bar.contains("## Examples"), "{bar}");
+    assert!(bar.contains("```rust\nlet bar = foo::Bar::new();\n```"), "{bar}");
+    assert!(!bar.contains("hidden"), "{bar}");
+    assert!(bar.contains("<a id=\"structfield.size\"></a>`pub size: usize`"), "{bar}");
+    assert!(bar.contains("<a id=\"method.new\"></a>`pub fn new() -> Bar`"), "{bar}");
+
+    rustdoc().input("foo.rs").out_dir("man").arg("-Zunstable-options").output_format("man").run();
+
+    let index = rfs::read_to_string("man/man3/foo.3");
+    assert!(index.starts_with(".TH \"foo\" 3 \"\" \"foo\" \"Library Functions Manual\"\n"));
+    assert!(index.contains("\\fBfoo.Bar\\fP(3)"), "{index}");
+    let bar = rfs::read_to_string("man/man3/foo.Bar.3");
+    assert!(bar.starts_with(".TH \"foo.Bar\" 3 "), "{bar}");
+    assert!(bar.contains(".SH NAME\nfoo::Bar \\- A bar, made with `Bar::new`"), "{bar}");
+    assert!(bar.contains(".nf\nlet bar = foo::Bar::new();\n.fi"), "{bar}");
+    assert!(!bar.contains("hidden"), "{bar}");
+    assert!(rfs::read_to_string("man/man3/foo.inner.noop.3").contains("pub fn noop()"));
+
+    rustdoc()
+        .input("main.rs")
+        .crate_type("bin")
+        .out_dir("bin")
+        .arg("-Zunstable-options")
+        .output_format("man")
+        .run();
+    assert!(rfs::read_to_string("bin/man1/main.1").contains("\"General Commands Manual\""));
+}
@@ -9,4 +9,13 @@
 fn main() {
     let out = rustdoc().output_format("json").input("x.html").run_fail().stderr_utf8();
     diff().expected_file("output-format-json.stderr").actual_text("actual-json", out).run();
+
+    let out = rustdoc().output_format("api-diff").input("x.html").run_fail().stderr_utf8();
+    diff().expected_file("output-format-api-diff.stderr").actual_text("actual-api-diff", out).run();
+
+    let out = rustdoc().output_format("markdown").input("x.html").run_fail().stderr_utf8();
+    diff().expected_file("output-format-markdown.stderr").actual_text("actual-markdown", out).run();
+
+    let out = rustdoc().output_format("man").input("x.html").run_fail().stderr_utf8();
+    diff().expected_file("output-format-man.stderr").actual_text("actual-man", out).run();
 }
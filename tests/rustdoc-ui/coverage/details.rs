@@ -0,0 +1,19 @@
+//@ compile-flags:-Z unstable-options --show-coverage --coverage-details
+//@ check-pass
+
+//! Make sure to have some docs on your crate root
+
+pub mod inner {
+    //! This module is documented
+
+    /// This function is documented
+    pub fn documented() {}
+
+    pub fn undocumented() {}
+}
+
+pub struct Foo {
+    /// This field is documented
+    pub a: u32,
+    pub b: u32,
+}
@@ -0,0 +1,11 @@
+//@ compile-flags:-Z unstable-options --show-coverage --coverage-min=80 --coverage-min-examples=50%
+
+//! Make sure to have some docs on your crate root
+
+/// This function is documented, but has no example
+pub fn documented() {}
+
+pub fn undocumented() {}
+
+//~? ERROR 66.7% of the items are documented, below the minimum of 80%
+//~? ERROR 0.0% of the items that should have examples have one
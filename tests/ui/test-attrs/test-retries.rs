@@ -0,0 +1,25 @@
+//@ compile-flags: --test
+//@ run-flags: --test-threads=1 -Zunstable-options --retries=1
+//@ run-pass
+//@ check-run-results
+//@ only-nightly
+//@ exec-env:RUST_BACKTRACE=0
+//@ normalize-stdout: "finished in \d+\.\d+s" -> "finished in $$TIME"
+//@ needs-threads
+//@ needs-unwind
+
+// Checks that a test that fails and then passes when retried is reported as flaky.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static ATTEMPTS: AtomicUsize = AtomicUsize::new(0);
+
+#[test]
+fn fails_once() {
+    if ATTEMPTS.fetch_add(1, Ordering::SeqCst) == 0 {
+        panic!("first attempt");
+    }
+}
+
+#[test]
+fn passes() {}
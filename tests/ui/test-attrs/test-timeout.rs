@@ -0,0 +1,30 @@
+//@ no-prefer-dynamic
+//@ compile-flags: --test
+//@ run-flags: --test-threads=1
+//@ run-fail
+//@ check-run-results
+//@ normalize-stdout: "finished in \d+\.\d+s" -> "finished in $$TIME"
+//@ needs-threads
+
+// Tests that a test exceeding its `#[test_timeout]` is reported as failed
+// instead of hanging the test run.
+
+#![cfg(test)]
+#![feature(test_timeout)]
+
+use std::thread;
+use std::time::Duration;
+
+#[test]
+#[test_timeout = "1"]
+fn it_hangs() {
+    loop {
+        thread::sleep(Duration::from_secs(60));
+    }
+}
+
+#[test]
+#[test_timeout = "60"]
+fn it_works() {
+    assert_eq!(1 + 1, 2);
+}